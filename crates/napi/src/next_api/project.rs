@@ -52,10 +52,12 @@ use turbopack_core::{
     error::PrettyPrintError,
     issue::PlainIssue,
     output::{OutputAsset, OutputAssets},
+    resolve::trace::ResolveTrace,
     source_map::{SourceMap, Token},
     version::{PartialUpdate, TotalUpdate, Update, VersionState},
 };
 use turbopack_ecmascript_hmr_protocol::{ClientUpdateInstruction, Issue, ResourceIdentifier};
+use turbopack_node::pool_policy::{NodeJsPoolPolicy, set_pool_policy};
 use turbopack_trace_utils::{
     exit::{ExitHandler, ExitReceiver},
     filter_layer::FilterLayer,
@@ -254,6 +256,34 @@ pub struct NapiTurboEngineOptions {
     pub is_ci: Option<bool>,
     /// Whether the project is running in a short session.
     pub is_short_session: Option<bool>,
+    /// The maximum number of Node.js workers (e.g. for webpack loaders and PostCSS) that are
+    /// alive at the same time. Derived from the available memory by default.
+    pub node_worker_max_count: Option<u32>,
+    /// Node.js workers that use more memory than this number of bytes are restarted.
+    pub node_worker_memory_limit: Option<f64>,
+    /// Node.js workers that have been idle for this number of milliseconds are stopped.
+    pub node_worker_idle_timeout: Option<f64>,
+}
+
+impl NapiTurboEngineOptions {
+    /// The limits of the Node.js worker pools, if any of them is configured.
+    fn node_pool_policy(&self) -> Option<NodeJsPoolPolicy> {
+        if self.node_worker_max_count.is_none()
+            && self.node_worker_memory_limit.is_none()
+            && self.node_worker_idle_timeout.is_none()
+        {
+            return None;
+        }
+        Some(NodeJsPoolPolicy {
+            max_workers: self.node_worker_max_count.map(|count| count as usize),
+            max_worker_rss: self.node_worker_memory_limit.map(|limit| limit as u64),
+            idle_timeout: self
+                .node_worker_idle_timeout
+                .filter(|timeout| timeout.is_finite() && *timeout > 0.0)
+                .map(|timeout| Duration::from_secs_f64(timeout / 1000.0)),
+            ..Default::default()
+        })
+    }
 }
 
 impl From<NapiWatchOptions> for WatchOptions {
@@ -496,6 +526,9 @@ pub fn project_new(
             let dependency_tracking = turbo_engine_options.dependency_tracking.unwrap_or(true);
             let is_ci = turbo_engine_options.is_ci.unwrap_or(false);
             let is_short_session = turbo_engine_options.is_short_session.unwrap_or(false);
+            if let Some(policy) = turbo_engine_options.node_pool_policy() {
+                set_pool_policy(policy)?;
+            }
             let turbo_tasks = create_turbo_tasks(
                 PathBuf::from(&options.dist_dir),
                 persistent_caching,
@@ -1807,6 +1840,84 @@ pub fn project_get_source_map_sync(
     })
 }
 
+#[napi(object)]
+pub struct NapiResolveTraceStep {
+    pub kind: String,
+    pub message: RcStr,
+}
+
+#[napi(object)]
+pub struct NapiResolveTraceGroup {
+    pub lookup_path: RcStr,
+    pub request: RcStr,
+    pub steps: Vec<NapiResolveTraceStep>,
+}
+
+#[napi(object)]
+pub struct NapiResolveTrace {
+    /// The steps of resolving each request, starting with the explained one. Aliases, exports
+    /// fields and packages lead to nested requests.
+    pub groups: Vec<NapiResolveTraceGroup>,
+    pub result: RcStr,
+}
+
+impl From<&ResolveTrace> for NapiResolveTrace {
+    fn from(trace: &ResolveTrace) -> Self {
+        Self {
+            groups: trace
+                .groups
+                .iter()
+                .map(|group| NapiResolveTraceGroup {
+                    lookup_path: group.lookup_path.clone(),
+                    request: group.request.clone(),
+                    steps: group
+                        .steps
+                        .iter()
+                        .map(|(kind, message)| NapiResolveTraceStep {
+                            kind: kind.as_str().to_string(),
+                            message: message.clone(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            result: trace.result.clone(),
+        }
+    }
+}
+
+#[turbo_tasks::function(operation)]
+fn explain_resolve_operation(
+    container: ResolvedVc<ProjectContainer>,
+    specifier: RcStr,
+    from: RcStr,
+    server: bool,
+) -> Vc<ResolveTrace> {
+    container.project().explain_resolve(specifier, from, server)
+}
+
+/// Explains step by step how `specifier` is resolved when it's imported from `from`, a path
+/// relative to the project, in the Pages Router client or server context.
+#[tracing::instrument(level = "info", name = "explain resolve", skip_all)]
+#[napi]
+pub async fn project_explain_resolve(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    specifier: RcStr,
+    from: RcStr,
+    server: bool,
+) -> napi::Result<NapiResolveTrace> {
+    let container = project.container;
+    let ctx = &project.turbopack_ctx;
+    ctx.turbo_tasks()
+        .run(async move {
+            let trace = explain_resolve_operation(container, specifier, from, server)
+                .read_strongly_consistent()
+                .await?;
+            Ok(NapiResolveTrace::from(&*trace))
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e.into()).to_string()))
+}
+
 #[napi]
 pub async fn project_write_analyze_data(
     #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
@@ -28,6 +28,8 @@ use turbo_tasks_backend::{
     noop_backing_storage,
 };
 use turbopack_core::error::PrettyPrintError;
+use turbopack_image::process::persistent_cache::init_persistent_image_cache;
+use turbopack_node::transforms::persistent_cache::init_persistent_loader_cache;
 
 pub type NextTurboTasks =
     Arc<TurboTasks<TurboTasksBackend<Either<DefaultBackingStorage, NoopBackingStorage>>>>;
@@ -188,7 +190,7 @@ pub fn create_turbo_tasks(
     is_ci: bool,
     is_short_session: bool,
 ) -> Result<NextTurboTasks> {
-    Ok(if persistent_caching {
+    let turbo_tasks = if persistent_caching {
         let version_info = GitVersionInfo {
             describe: env!("VERGEN_GIT_DESCRIBE"),
             dirty: option_env!("CI").is_none_or(|value| value.is_empty())
@@ -228,7 +230,18 @@ pub fn create_turbo_tasks(
             },
             Either::Right(noop_backing_storage()),
         ))
-    })
+    };
+    if persistent_caching {
+        // Loader and image processing results are cached independently of the turbo-tasks cache,
+        // so they survive compiler upgrades and cache invalidations.
+        let turbo_tasks_api: Arc<dyn TurboTasksApi> = turbo_tasks.clone();
+        init_persistent_loader_cache(
+            &turbo_tasks_api,
+            output_path.join("cache/turbopack-loaders"),
+        );
+        init_persistent_image_cache(&turbo_tasks_api, output_path.join("cache/turbopack-images"));
+    }
+    Ok(turbo_tasks)
 }
 
 #[derive(Serialize)]
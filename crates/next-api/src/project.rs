@@ -73,7 +73,12 @@ use turbopack_core::{
         expand_output_assets,
     },
     reference::all_assets_from_entries,
-    resolve::{FindContextFileResult, find_context_file},
+    reference_type::{EcmaScriptModulesReferenceSubType, ReferenceType},
+    resolve::{
+        FindContextFileResult, find_context_file,
+        parse::Request,
+        trace::{ResolveTrace, explain_resolve},
+    },
     version::{
         NotFoundVersion, OptionVersionedContent, Update, Version, VersionState, VersionedContent,
     },
@@ -993,7 +998,8 @@ impl Project {
             self.project_path().owned().await?,
             node_execution_chunking_context,
             self.env(),
-        ))
+        )
+        .with_backend(*self.next_config().node_backend().await?))
     }
 
     #[turbo_tasks::function]
@@ -2081,6 +2087,33 @@ impl Project {
         }
     }
 
+    /// Explains step by step how `specifier` is resolved when it's imported from `from`, a path
+    /// relative to the project. Uses the resolve options of the Pages Router client or server
+    /// context.
+    #[turbo_tasks::function]
+    pub async fn explain_resolve(
+        self: Vc<Self>,
+        specifier: RcStr,
+        from: RcStr,
+        server: bool,
+    ) -> Result<Vc<ResolveTrace>> {
+        let from_path = self.project_path().await?.join(&from)?;
+        let asset_context = if server {
+            self.pages_project().ssr_module_context()
+        } else {
+            self.pages_project().client_module_context()
+        };
+        let reference_type =
+            ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::Import);
+        let options = asset_context.resolve_options(from_path.clone(), reference_type.clone());
+        Ok(explain_resolve(
+            from_path.parent(),
+            reference_type,
+            Request::parse_string(specifier).to_resolved().await?,
+            options.to_resolved().await?,
+        ))
+    }
+
     #[turbo_tasks::function]
     pub async fn with_next_config(&self, next_config: Vc<NextConfig>) -> Result<Vc<Self>> {
         Ok(Self {
@@ -40,7 +40,7 @@ use crate::{
         runtime_entry::{RuntimeEntries, RuntimeEntry},
         transforms::get_next_client_transforms_rules,
     },
-    next_config::NextConfig,
+    next_config::{NextConfig, ResolveConditionsLayer},
     next_font::local::NextFontLocalResolvePlugin,
     next_import_map::{
         get_next_client_fallback_import_map, get_next_client_import_map,
@@ -157,8 +157,17 @@ pub async fn get_client_resolve_options_context(
         custom_conditions.push(rcstr!("next-js"));
     };
 
+    custom_conditions.extend(
+        next_config
+            .resolve_conditions(ResolveConditionsLayer::Client, None)
+            .await?
+            .iter()
+            .cloned(),
+    );
+
     let resolve_options_context = ResolveOptionsContext {
         enable_node_modules: Some(project_path.root().owned().await?),
+        enable_pnp: Some(project_path.root().owned().await?),
         custom_conditions,
         import_map: Some(next_client_import_map),
         fallback_import_map: Some(next_client_fallback_import_map),
@@ -327,6 +336,9 @@ pub async fn get_client_module_options_context(
             enable_typeof_window_inlining: Some(TypeofWindow::Object),
             source_maps,
             infer_module_side_effects: *next_config.turbopack_infer_module_side_effects().await?,
+            enable_coverage: *next_config.coverage_options().await?,
+            // Inlining changes the exports of modules, which would invalidate more during HMR
+            enable_constant_export_inlining: next_mode.is_production(),
             ..Default::default()
         },
         css: CssOptionsContext {
@@ -12,7 +12,10 @@ use turbo_tasks::{
 };
 use turbo_tasks_env::EnvMap;
 use turbo_tasks_fetch::FetchClientConfig;
-use turbo_tasks_fs::FileSystemPath;
+use turbo_tasks_fs::{
+    FileSystemPath,
+    glob::{Glob, GlobOptions},
+};
 use turbopack::module_options::{
     ConditionItem, ConditionPath, LoaderRuleItem, WebpackRules,
     module_options_context::MdxTransformOptions,
@@ -22,12 +25,15 @@ use turbopack_core::{
     issue::{Issue, IssueExt, IssueStage, OptionStyledString, StyledString},
     resolve::ResolveAliasMap,
 };
-use turbopack_ecmascript::{OptionTreeShaking, TreeShakingMode};
+use turbopack_ecmascript::{CoverageOptions, OptionTreeShaking, TreeShakingMode};
 use turbopack_ecmascript_plugins::transform::{
     emotion::EmotionTransformConfig, relay::RelayConfig,
     styled_components::StyledComponentsTransformConfig,
 };
-use turbopack_node::transforms::webpack::{WebpackLoaderItem, WebpackLoaderItems};
+use turbopack_node::{
+    evaluate::NodeJsBackend,
+    transforms::webpack::{WebpackLoaderItem, WebpackLoaderItems},
+};
 
 use crate::{
     app_structure::FileSystemPathVec,
@@ -36,6 +42,7 @@ use crate::{
     next_shared::{
         transforms::ModularizeImportPackageConfig, webpack_rules::WebpackLoaderBuiltinCondition,
     },
+    util::NextRuntime,
 };
 
 #[turbo_tasks::value(transparent)]
@@ -604,9 +611,86 @@ pub struct TurbopackConfig {
     #[bincode(with = "turbo_bincode::serde_self_describing")]
     pub resolve_alias: Option<FxIndexMap<RcStr, JsonValue>>,
     pub resolve_extensions: Option<Vec<RcStr>>,
+    pub resolve_conditions: Option<ResolveConditionsConfig>,
     pub debug_ids: Option<bool>,
 }
 
+/// Custom conditions for resolving the `exports` and `imports` fields of packages, by the layers
+/// they apply to. The conditions of all keys matching a layer are combined.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    TraceRawVcs,
+    NonLocalValue,
+    OperationValue,
+    Encode,
+    Decode,
+)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResolveConditionsConfig {
+    /// Applies to all layers.
+    #[serde(rename = "*")]
+    pub all: Vec<RcStr>,
+    /// Browser code of the Pages and App Router.
+    pub client: Vec<RcStr>,
+    /// Server-side rendering of pages and Client Components.
+    pub ssr: Vec<RcStr>,
+    /// Server Components.
+    pub rsc: Vec<RcStr>,
+    /// API routes and App Router route handlers.
+    pub route: Vec<RcStr>,
+    pub middleware: Vec<RcStr>,
+    pub instrumentation: Vec<RcStr>,
+    /// All server layers using the Node.js runtime.
+    pub nodejs: Vec<RcStr>,
+    /// All server layers using the Edge runtime.
+    pub edge: Vec<RcStr>,
+}
+
+/// The layers `turbopack.resolveConditions` can configure conditions for.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, TaskInput, TraceRawVcs, NonLocalValue, Encode, Decode,
+)]
+pub enum ResolveConditionsLayer {
+    Client,
+    Ssr,
+    Rsc,
+    Route,
+    Middleware,
+    Instrumentation,
+}
+
+impl ResolveConditionsConfig {
+    /// Returns the conditions for a layer, or for a server layer if `runtime` is set.
+    fn conditions(
+        &self,
+        layer: ResolveConditionsLayer,
+        runtime: Option<NextRuntime>,
+    ) -> impl Iterator<Item = &RcStr> {
+        let layer_conditions = match layer {
+            ResolveConditionsLayer::Client => &self.client,
+            ResolveConditionsLayer::Ssr => &self.ssr,
+            ResolveConditionsLayer::Rsc => &self.rsc,
+            ResolveConditionsLayer::Route => &self.route,
+            ResolveConditionsLayer::Middleware => &self.middleware,
+            ResolveConditionsLayer::Instrumentation => &self.instrumentation,
+        };
+        let runtime_conditions = match runtime {
+            Some(NextRuntime::NodeJs) => &self.nodejs[..],
+            Some(NextRuntime::Edge) => &self.edge[..],
+            None => &[],
+        };
+        self.all
+            .iter()
+            .chain(layer_conditions)
+            .chain(runtime_conditions)
+    }
+}
+
 #[derive(
     Deserialize,
     Clone,
@@ -876,6 +960,45 @@ pub enum ReactCompilerOptionsOrBoolean {
 #[turbo_tasks::value(transparent)]
 pub struct OptionalReactCompilerOptions(Option<ResolvedVc<ReactCompilerOptions>>);
 
+/// Code coverage instrumentation of the modules, see [`CoverageOptions`].
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Deserialize,
+    TraceRawVcs,
+    NonLocalValue,
+    OperationValue,
+    Encode,
+    Decode,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TurbopackCoverageConfig {
+    /// Glob of the instrumented module paths, relative to the root directory (`turbopack.root`).
+    #[serde(default)]
+    include: Option<RcStr>,
+    /// Glob of the module paths that are not instrumented, relative to the root directory.
+    /// Defaults to the modules in `node_modules`.
+    #[serde(default)]
+    exclude: Option<RcStr>,
+    /// The global variable the coverage data is collected in, `__coverage__` by default.
+    #[serde(default)]
+    coverage_variable: Option<RcStr>,
+}
+
+#[derive(
+    Clone, Debug, PartialEq, Deserialize, TraceRawVcs, NonLocalValue, OperationValue, Encode, Decode,
+)]
+#[serde(untagged)]
+pub enum TurbopackCoverageConfigOrBoolean {
+    Boolean(bool),
+    Config(TurbopackCoverageConfig),
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionalCoverageOptions(Option<ResolvedVc<CoverageOptions>>);
+
 #[derive(
     Clone,
     Debug,
@@ -989,6 +1112,11 @@ pub struct ExperimentalConfig {
     turbopack_client_side_nested_async_chunking: Option<bool>,
     turbopack_server_side_nested_async_chunking: Option<bool>,
     turbopack_import_type_bytes: Option<bool>,
+    /// Run webpack loaders and PostCSS in worker threads of a shared Node.js process instead of a
+    /// process per worker.
+    turbopack_node_worker_threads: Option<bool>,
+    /// Instrument the modules with Istanbul compatible code coverage counters.
+    turbopack_coverage: Option<TurbopackCoverageConfigOrBoolean>,
     /// Disable automatic configuration of the sass loader.
     #[serde(default)]
     turbopack_use_builtin_sass: Option<bool>,
@@ -1548,6 +1676,48 @@ impl NextConfig {
         ))
     }
 
+    #[turbo_tasks::function]
+    pub fn node_backend(&self) -> Vc<NodeJsBackend> {
+        if self
+            .experimental
+            .turbopack_node_worker_threads
+            .unwrap_or_default()
+        {
+            NodeJsBackend::WorkerThreads.cell()
+        } else {
+            NodeJsBackend::ChildProcess.cell()
+        }
+    }
+
+    #[turbo_tasks::function]
+    pub async fn coverage_options(&self) -> Result<Vc<OptionalCoverageOptions>> {
+        let config = match &self.experimental.turbopack_coverage {
+            Some(TurbopackCoverageConfigOrBoolean::Boolean(true)) => {
+                TurbopackCoverageConfig::default()
+            }
+            Some(TurbopackCoverageConfigOrBoolean::Config(config)) => config.clone(),
+            _ => return Ok(Vc::cell(None)),
+        };
+        let glob = async |glob: Option<RcStr>| {
+            anyhow::Ok(match glob {
+                Some(glob) => Some(
+                    Glob::new(glob, GlobOptions::default())
+                        .to_resolved()
+                        .await?,
+                ),
+                None => None,
+            })
+        };
+        Ok(Vc::cell(Some(
+            CoverageOptions {
+                include: glob(config.include).await?,
+                exclude: glob(config.exclude).await?,
+                coverage_variable: config.coverage_variable,
+            }
+            .resolved_cell(),
+        )))
+    }
+
     #[turbo_tasks::function]
     pub fn resolve_alias_options(&self) -> Result<Vc<ResolveAliasMap>> {
         let Some(resolve_alias) = self
@@ -1749,6 +1919,29 @@ impl NextConfig {
         Vc::cell(self.experimental.transition_indicator.unwrap_or(false))
     }
 
+    /// Returns the custom resolve conditions configured with `turbopack.resolveConditions` for a
+    /// layer. `runtime` is the runtime of server layers.
+    #[turbo_tasks::function]
+    pub fn resolve_conditions(
+        &self,
+        layer: ResolveConditionsLayer,
+        runtime: Option<NextRuntime>,
+    ) -> Vc<Vec<RcStr>> {
+        let Some(resolve_conditions) = self
+            .turbopack
+            .as_ref()
+            .and_then(|t| t.resolve_conditions.as_ref())
+        else {
+            return Vc::cell(vec![]);
+        };
+        Vc::cell(
+            resolve_conditions
+                .conditions(layer, runtime)
+                .cloned()
+                .collect(),
+        )
+    }
+
     #[turbo_tasks::function]
     pub fn enable_cache_components(&self) -> Vc<bool> {
         Vc::cell(self.cache_components.unwrap_or(false))
@@ -2100,4 +2293,37 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_resolve_conditions() {
+        let config: ResolveConditionsConfig = serde_json::from_value(serde_json::json!({
+            "*": ["source"],
+            "rsc": ["my-server"],
+            "edge": ["my-edge"],
+        }))
+        .unwrap();
+
+        let conditions = |layer, runtime| {
+            config
+                .conditions(layer, runtime)
+                .map(|c| c.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(conditions(ResolveConditionsLayer::Client, None), ["source"]);
+        assert_eq!(
+            conditions(ResolveConditionsLayer::Rsc, Some(NextRuntime::NodeJs)),
+            ["source", "my-server"]
+        );
+        assert_eq!(
+            conditions(ResolveConditionsLayer::Rsc, Some(NextRuntime::Edge)),
+            ["source", "my-server", "my-edge"]
+        );
+
+        assert!(
+            serde_json::from_value::<ResolveConditionsConfig>(serde_json::json!({
+                "server": ["source"],
+            }))
+            .is_err()
+        );
+    }
 }
@@ -158,8 +158,17 @@ pub async fn get_edge_resolve_options_context(
     // but Middleware doesn't have all Next.js APIs so we omit the "next-js" condition for all edge
     // entrypoints
 
+    custom_conditions.extend(
+        next_config
+            .resolve_conditions(ty.resolve_conditions_layer(), Some(NextRuntime::Edge))
+            .await?
+            .iter()
+            .cloned(),
+    );
+
     let resolve_options_context = ResolveOptionsContext {
         enable_node_modules: Some(project_path.root().owned().await?),
+        enable_pnp: Some(project_path.root().owned().await?),
         enable_edge_node_externals: true,
         custom_conditions,
         import_map: Some(next_edge_import_map),
@@ -33,7 +33,7 @@ use turbopack_core::{
 };
 use turbopack_node::{
     debug::should_debug,
-    evaluate::{evaluate, get_evaluate_entries},
+    evaluate::{NodeJsBackend, evaluate, get_evaluate_entries},
     execution_context::ExecutionContext,
 };
 
@@ -730,6 +730,7 @@ async fn get_mock_stylesheet(
         env,
         project_path: _,
         chunking_context,
+        backend: _,
     } = *execution_context.await?;
     let asset_context = node_evaluate_asset_context(
         execution_context,
@@ -773,6 +774,7 @@ async fn get_mock_stylesheet(
         vec![],
         Completion::immutable(),
         should_debug("next_font::google"),
+        NodeJsBackend::ChildProcess,
     )
     .await?;
 
@@ -43,7 +43,7 @@ use crate::{
     app_structure::CollectedRootParams,
     mode::NextMode,
     next_build::get_postcss_package_mapping,
-    next_config::NextConfig,
+    next_config::{NextConfig, ResolveConditionsLayer},
     next_font::local::NextFontLocalResolvePlugin,
     next_import_map::{get_next_edge_and_server_fallback_import_map, get_next_server_import_map},
     next_server::{
@@ -110,6 +110,21 @@ pub enum ServerContextType {
 }
 
 impl ServerContextType {
+    /// The layer the custom resolve conditions of `turbopack.resolveConditions` are selected by.
+    pub fn resolve_conditions_layer(&self) -> ResolveConditionsLayer {
+        match self {
+            ServerContextType::Pages { .. } | ServerContextType::AppSSR { .. } => {
+                ResolveConditionsLayer::Ssr
+            }
+            ServerContextType::AppRSC { .. } => ResolveConditionsLayer::Rsc,
+            ServerContextType::PagesApi { .. } | ServerContextType::AppRoute { .. } => {
+                ResolveConditionsLayer::Route
+            }
+            ServerContextType::Middleware { .. } => ResolveConditionsLayer::Middleware,
+            ServerContextType::Instrumentation { .. } => ResolveConditionsLayer::Instrumentation,
+        }
+    }
+
     pub fn should_use_react_server_condition(&self) -> bool {
         matches!(
             self,
@@ -220,6 +235,14 @@ pub async fn get_server_resolve_options_context(
         custom_conditions.push(rcstr!("next-js"));
     };
 
+    custom_conditions.extend(
+        next_config
+            .resolve_conditions(ty.resolve_conditions_layer(), Some(NextRuntime::NodeJs))
+            .await?
+            .iter()
+            .cloned(),
+    );
+
     let external_cjs_modules_plugin = if *next_config.bundle_pages_router_dependencies().await? {
         server_external_packages_plugin
     } else {
@@ -315,6 +338,7 @@ pub async fn get_server_resolve_options_context(
 
     let resolve_options_context = ResolveOptionsContext {
         enable_node_modules: Some(root_dir.clone()),
+        enable_pnp: Some(root_dir.clone()),
         enable_node_externals: true,
         enable_node_native_modules: true,
         module: true,
@@ -583,6 +607,9 @@ pub async fn get_server_module_options_context(
             ignore_dynamic_requests: true,
             source_maps,
             infer_module_side_effects: *next_config.turbopack_infer_module_side_effects().await?,
+            enable_coverage: *next_config.coverage_options().await?,
+            // Inlining changes the exports of modules, which would invalidate more during HMR
+            enable_constant_export_inlining: next_mode.is_production(),
             ..Default::default()
         },
         execution_context: Some(execution_context),
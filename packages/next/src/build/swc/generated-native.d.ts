@@ -220,6 +220,15 @@ export interface NapiTurboEngineOptions {
   isCi?: boolean
   /** Whether the project is running in a short session. */
   isShortSession?: boolean
+  /**
+   * The maximum number of Node.js workers (e.g. for webpack loaders and PostCSS) that are
+   * alive at the same time. Derived from the available memory by default.
+   */
+  nodeWorkerMaxCount?: number
+  /** Node.js workers that use more memory than this number of bytes are restarted. */
+  nodeWorkerMemoryLimit?: number
+  /** Node.js workers that have been idle for this number of milliseconds are stopped. */
+  nodeWorkerIdleTimeout?: number
 }
 export declare function projectNew(
   options: NapiProjectOptions,
@@ -374,6 +383,33 @@ export declare function projectGetSourceMapSync(
   project: { __napiType: 'Project' },
   filePath: RcStr
 ): string | null
+export interface NapiResolveTraceStep {
+  kind: string
+  message: RcStr
+}
+export interface NapiResolveTraceGroup {
+  lookupPath: RcStr
+  request: RcStr
+  steps: Array<NapiResolveTraceStep>
+}
+export interface NapiResolveTrace {
+  /**
+   * The steps of resolving each request, starting with the explained one. Aliases, exports
+   * fields and packages lead to nested requests.
+   */
+  groups: Array<NapiResolveTraceGroup>
+  result: RcStr
+}
+/**
+ * Explains step by step how `specifier` is resolved when it's imported from `from`, a path
+ * relative to the project, in the Pages Router client or server context.
+ */
+export declare function projectExplainResolve(
+  project: { __napiType: 'Project' },
+  specifier: RcStr,
+  from: RcStr,
+  server: boolean
+): Promise<NapiResolveTrace>
 export declare function projectWriteAnalyzeData(
   project: { __napiType: 'Project' },
   appDirOnly: boolean
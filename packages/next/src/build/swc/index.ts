@@ -20,6 +20,7 @@ import { type DefineEnvOptions, getDefineEnv } from '../define-env'
 import type {
   NapiPartialProjectOptions,
   NapiProjectOptions,
+  NapiResolveTrace,
   NapiSourceDiagnostic,
 } from './generated-native'
 import type {
@@ -760,6 +761,19 @@ function bindingToApi(
       )
     }
 
+    explainResolve(
+      specifier: string,
+      from: string,
+      server: boolean
+    ): Promise<NapiResolveTrace> {
+      return binding.projectExplainResolve(
+        this._nativeProject,
+        specifier,
+        from,
+        server
+      )
+    }
+
     getSourceForAsset(filePath: string): Promise<string | null> {
       return binding.projectGetSourceForAsset(this._nativeProject, filePath)
     }
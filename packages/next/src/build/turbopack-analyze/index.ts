@@ -83,6 +83,9 @@ export async function turbopackAnalyze(
     {
       persistentCaching,
       memoryLimit: config.experimental?.turbopackMemoryLimit,
+      nodeWorkerMaxCount: config.experimental?.turbopackMaxNodeWorkers,
+      nodeWorkerMemoryLimit: config.experimental?.turbopackNodeWorkerMemoryLimit,
+      nodeWorkerIdleTimeout: config.experimental?.turbopackNodeWorkerIdleTimeout,
       dependencyTracking: persistentCaching,
       isCi: isCI,
       isShortSession: true,
@@ -88,6 +88,9 @@ export async function turbopackBuild(): Promise<{
     {
       persistentCaching,
       memoryLimit: config.experimental?.turbopackMemoryLimit,
+      nodeWorkerMaxCount: config.experimental?.turbopackMaxNodeWorkers,
+      nodeWorkerMemoryLimit: config.experimental?.turbopackNodeWorkerMemoryLimit,
+      nodeWorkerIdleTimeout: config.experimental?.turbopackNodeWorkerIdleTimeout,
       dependencyTracking: persistentCaching,
       isCi: isCI,
       isShortSession: true,
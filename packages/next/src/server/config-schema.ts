@@ -158,6 +158,19 @@ const zTurbopackConfig: zod.ZodType<TurbopackOptions> = z.strictObject({
     )
     .optional(),
   resolveExtensions: z.array(z.string()).optional(),
+  resolveConditions: z
+    .strictObject({
+      '*': z.array(z.string()).optional(),
+      client: z.array(z.string()).optional(),
+      ssr: z.array(z.string()).optional(),
+      rsc: z.array(z.string()).optional(),
+      route: z.array(z.string()).optional(),
+      middleware: z.array(z.string()).optional(),
+      instrumentation: z.array(z.string()).optional(),
+      nodejs: z.array(z.string()).optional(),
+      edge: z.array(z.string()).optional(),
+    })
+    .optional(),
   root: z.string().optional(),
   debugIds: z.boolean().optional(),
 })
@@ -287,6 +300,9 @@ export const experimentalSchema = {
         jsxImportSource: z.string().optional(),
         providerImportSource: z.string().optional(),
         mdxType: z.enum(['gfm', 'commonmark']).optional(),
+        frontmatter: z.boolean().optional(),
+        headingIds: z.boolean().optional(),
+        tableOfContents: z.boolean().optional(),
       }),
     ])
     .optional(),
@@ -295,6 +311,20 @@ export const experimentalSchema = {
   webpackBuildWorker: z.boolean().optional(),
   webpackMemoryOptimizations: z.boolean().optional(),
   turbopackMemoryLimit: z.number().optional(),
+  turbopackMaxNodeWorkers: z.number().int().positive().optional(),
+  turbopackNodeWorkerMemoryLimit: z.number().positive().optional(),
+  turbopackNodeWorkerIdleTimeout: z.number().positive().optional(),
+  turbopackNodeWorkerThreads: z.boolean().optional(),
+  turbopackCoverage: z
+    .union([
+      z.boolean(),
+      z.strictObject({
+        include: z.string().optional(),
+        exclude: z.string().optional(),
+        coverageVariable: z.string().optional(),
+      }),
+    ])
+    .optional(),
   turbopackMinify: z.boolean().optional(),
   turbopackFileSystemCacheForDev: z.boolean().optional(),
   turbopackFileSystemCacheForBuild: z.boolean().optional(),
@@ -139,6 +139,19 @@ export type TurbopackRuleConfigCollection =
   | TurbopackRuleConfigItem
   | (TurbopackLoaderItem | TurbopackRuleConfigItem)[]
 
+export type TurbopackResolveConditions = {
+  [layer in
+    | '*'
+    | 'client'
+    | 'ssr'
+    | 'rsc'
+    | 'route'
+    | 'middleware'
+    | 'instrumentation'
+    | 'nodejs'
+    | 'edge']?: string[]
+}
+
 export interface TurbopackOptions {
   /**
    * (`next --turbopack` only) A mapping of aliased imports to modules to load in their place.
@@ -157,6 +170,16 @@ export interface TurbopackOptions {
    */
   resolveExtensions?: string[]
 
+  /**
+   * (`next --turbopack` only) Custom conditions used when resolving the
+   * `exports` and `imports` fields of packages, by the layers they apply to.
+   * `*` applies to all layers, `nodejs` and `edge` to all server layers of the
+   * runtime. The conditions of all matching keys are combined.
+   *
+   * @example { '*': ['source'], rsc: ['my-server-condition'] }
+   */
+  resolveConditions?: TurbopackResolveConditions
+
   /**
    * (`next --turbopack` only) A list of webpack loaders to apply when running with Turbopack.
    *
@@ -404,6 +427,41 @@ export interface ExperimentalConfig {
    */
   turbopackMemoryLimit?: number
 
+  /**
+   * The maximum number of Node.js workers that turbo runs webpack loaders and PostCSS in at the
+   * same time. Derived from the available memory by default.
+   */
+  turbopackMaxNodeWorkers?: number
+
+  /**
+   * Node.js workers of turbo that use more memory than this number of bytes are restarted.
+   */
+  turbopackNodeWorkerMemoryLimit?: number
+
+  /**
+   * Node.js workers of turbo that have been idle for this number of milliseconds are stopped.
+   */
+  turbopackNodeWorkerIdleTimeout?: number
+
+  /**
+   * Run webpack loaders and PostCSS in worker threads of a shared Node.js process instead of a
+   * process per worker.
+   */
+  turbopackNodeWorkerThreads?: boolean
+
+  /**
+   * Instrument the modules with Istanbul compatible code coverage counters, collected in
+   * `globalThis.__coverage__`. Modules in `node_modules` are not instrumented, unless `exclude`
+   * is set. The globs are relative to `turbopack.root`.
+   */
+  turbopackCoverage?:
+    | boolean
+    | {
+        include?: string
+        exclude?: string
+        coverageVariable?: string
+      }
+
   /**
    * Enable minification. Defaults to true in build mode and false in dev mode.
    */
@@ -516,6 +574,18 @@ export interface ExperimentalConfig {
         jsxImportSource?: string
         providerImportSource?: string
         mdxType?: 'gfm' | 'commonmark'
+        /**
+         * Parse YAML frontmatter and expose it as the `frontmatter` named export.
+         */
+        frontmatter?: boolean
+        /**
+         * Add `id` attributes with GitHub-style slugs to all headings.
+         */
+        headingIds?: boolean
+        /**
+         * Expose the headings as the `tableOfContents` named export.
+         */
+        tableOfContents?: boolean
       }
 
   /**
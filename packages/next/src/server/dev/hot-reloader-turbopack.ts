@@ -285,6 +285,9 @@ export async function createHotReloaderTurbopack(
     {
       persistentCaching: isFileSystemCacheEnabledForDev(opts.nextConfig),
       memoryLimit: opts.nextConfig.experimental?.turbopackMemoryLimit,
+      nodeWorkerMaxCount: opts.nextConfig.experimental?.turbopackMaxNodeWorkers,
+      nodeWorkerMemoryLimit: opts.nextConfig.experimental?.turbopackNodeWorkerMemoryLimit,
+      nodeWorkerIdleTimeout: opts.nextConfig.experimental?.turbopackNodeWorkerIdleTimeout,
       isShortSession: false,
     }
   )
@@ -0,0 +1,169 @@
+/* eslint-env jest */
+import { readFileSync } from 'fs'
+import { join } from 'path'
+import vm from 'vm'
+import ts from 'typescript'
+
+const federationRuntimePath = join(
+  __dirname,
+  '../../turbopack/crates/turbopack-ecmascript-runtime/js/src/browser/runtime/base/federation.ts'
+)
+
+type SharedConfig = {
+  from: string
+  version: string | null
+  requiredVersion: string | null
+  singleton: boolean
+}
+
+type FederationRuntime = {
+  satisfiesVersion(version: string, range: string): boolean
+  getFederationShared(
+    name: string,
+    config: SharedConfig,
+    getLocal: () => any
+  ): any
+  console: { warn: jest.Mock }
+}
+
+// The runtime is a script that is appended to the base runtime, so it's evaluated with a stub of
+// the context prototype it extends.
+function loadFederationRuntime(): FederationRuntime {
+  const { outputText } = ts.transpileModule(
+    readFileSync(federationRuntimePath, 'utf8'),
+    { compilerOptions: { target: ts.ScriptTarget.ES2020 } }
+  )
+  const context = vm.createContext({
+    browserContextPrototype: {},
+    console: { warn: jest.fn() },
+  })
+  vm.runInContext(outputText, context)
+  return context as FederationRuntime
+}
+
+function shared(
+  from: string,
+  version: string | null,
+  options: Partial<SharedConfig> = {}
+): SharedConfig {
+  return {
+    from,
+    version,
+    requiredVersion: version != null ? `^${version}` : null,
+    singleton: false,
+    ...options,
+  }
+}
+
+describe('federation runtime', () => {
+  describe('satisfiesVersion', () => {
+    const { satisfiesVersion } = loadFederationRuntime()
+
+    it.each([
+      ['1.2.3', '^1.0.0', true],
+      ['2.0.0', '^1.0.0', false],
+      ['0.2.5', '^0.2.1', true],
+      ['0.3.0', '^0.2.1', false],
+      ['0.0.3', '^0.0.3', true],
+      ['0.0.4', '^0.0.3', false],
+      ['1.2.9', '~1.2.3', true],
+      ['1.3.0', '~1.2.3', false],
+      ['1.9.0', '~1', true],
+      ['1.2.3', '>=1.2.3', true],
+      ['1.2.2', '>=1.2.3', false],
+      ['1.3.0', '>1.2', true],
+      ['1.2.9', '>1.2', false],
+      ['1.2.9', '<=1.2', true],
+      ['1.3.0', '<=1.2', false],
+      ['1.2.2', '<1.2.3', true],
+      ['1.2.5', '1.2.x', true],
+      ['1.3.0', '1.2.x', false],
+      ['1.2.3', '=1.2.3', true],
+      ['5.0.0', '*', true],
+      ['1.5.0', '>=1.2.0 <2.0.0', true],
+      ['2.0.0', '>=1.2.0 <2.0.0', false],
+      ['3.1.0', '^1.0.0 || ^3.0.0', true],
+      ['2.1.0', '^1.0.0 || ^3.0.0', false],
+      ['v1.2.3', '^1.2.0', true],
+      ['1.2.3-beta.1', '^1.2.0', false],
+      ['1.2.3-beta.1', '1.2.3-beta.1', true],
+      ['not-a-version', '*', false],
+    ])('%s satisfies %s: %s', (version, range, expected) => {
+      expect(satisfiesVersion(version, range)).toBe(expected)
+    })
+  })
+
+  describe('getFederationShared', () => {
+    it('uses the first loaded version of singletons', () => {
+      const runtime = loadFederationRuntime()
+      const { getFederationShared } = runtime
+      const host = { name: 'host' }
+      const remote = { name: 'remote' }
+
+      expect(
+        getFederationShared(
+          'react',
+          shared('host', '18.2.0', { singleton: true }),
+          () => host
+        )
+      ).toBe(host)
+      expect(
+        getFederationShared(
+          'react',
+          shared('remote', '18.3.1', {
+            singleton: true,
+            requiredVersion: '^18.0.0',
+          }),
+          () => remote
+        )
+      ).toBe(host)
+      expect(runtime.console.warn).not.toHaveBeenCalled()
+
+      // An unsatisfied singleton is still used, but with a warning.
+      expect(
+        getFederationShared(
+          'react',
+          shared('remote', '19.0.0', { singleton: true }),
+          () => remote
+        )
+      ).toBe(host)
+      expect(runtime.console.warn).toHaveBeenCalledTimes(1)
+      expect(runtime.console.warn.mock.calls[0][0]).toContain(
+        "Shared singleton react 18.2.0 loaded by host doesn't satisfy ^19.0.0 required by remote"
+      )
+    })
+
+    it('uses the highest loaded version satisfying the required version', () => {
+      const { getFederationShared } = loadFederationRuntime()
+      const v1 = { version: '1.1.0' }
+      const v2 = { version: '1.4.0' }
+      const v3 = { version: '2.0.0' }
+
+      expect(getFederationShared('lib', shared('a', '1.1.0'), () => v1)).toBe(
+        v1
+      )
+      expect(getFederationShared('lib', shared('b', '1.4.0'), () => v2)).toBe(
+        v2
+      )
+      // 2.0.0 doesn't satisfy ^1.4.0 or ^1.1.0, so the build loads its own copy.
+      expect(getFederationShared('lib', shared('c', '2.0.0'), () => v3)).toBe(
+        v3
+      )
+      expect(
+        getFederationShared('lib', shared('d', '1.0.0'), () => {
+          throw new Error('the local copy should not be loaded')
+        })
+      ).toBe(v2)
+    })
+
+    it('only uses its own copy without a required version', () => {
+      const { getFederationShared } = loadFederationRuntime()
+      const a = { from: 'a' }
+      const b = { from: 'b' }
+
+      expect(getFederationShared('lib', shared('a', null), () => a)).toBe(a)
+      expect(getFederationShared('lib', shared('b', null), () => b)).toBe(b)
+      expect(getFederationShared('lib', shared('a', null), () => b)).toBe(a)
+    })
+  })
+})
@@ -24,6 +24,7 @@ mod invalidator_map;
 pub mod json;
 mod mutex_map;
 mod path_map;
+pub mod persistent_cache;
 mod read_glob;
 mod retry;
 pub mod rope;
@@ -31,6 +32,7 @@ pub mod source_context;
 pub mod util;
 pub(crate) mod virtual_fs;
 mod watcher;
+mod zip;
 
 use std::{
     borrow::Cow,
@@ -85,7 +87,7 @@ use crate::{
     util::extract_disk_access,
     watcher::DiskWatcher,
 };
-pub use crate::{read_glob::ReadGlobResult, virtual_fs::VirtualFileSystem};
+pub use crate::{read_glob::ReadGlobResult, virtual_fs::VirtualFileSystem, zip::ZipFileSystem};
 
 /// A (somewhat arbitrary) filename limit that we should try to keep output file names below.
 ///
@@ -0,0 +1,366 @@
+//! Caches that persist across builds in their own directory, outside of the turbo-tasks cache.
+//!
+//! They are meant for expensive results that only depend on their inputs, e.g. image metadata or
+//! loader outputs, and should survive compiler upgrades. Entries are stored keyed by a hash of the
+//! inputs computed with [CacheKeyHasher]. Reading the cache isn't tracked by turbo-tasks, so a
+//! cache hit must always yield the same result as recomputing it.
+//!
+//! The size of a cache is bounded. Reading an entry marks it as recently used, and when the entries
+//! grow beyond the limit the least recently used ones are removed.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+
+use sha2::{Digest, Sha256};
+use turbo_tasks::{TurboTasksApi, spawn_blocking, turbo_tasks_weak};
+
+/// Makes temporary file names unique within the process.
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Temporary files older than this are left over from writes that were interrupted.
+const STALE_TEMP_FILE_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// A cache directory per turbo-tasks instance, meant to be stored in a static.
+pub struct PersistentCache {
+    /// Needs to be increased when the format of entries or the way results are computed changes.
+    version: u32,
+    /// The maximum size of all entries in bytes.
+    max_size: u64,
+    /// The cache directory of every turbo-tasks instance that enabled the cache. Each project has
+    /// its own instance, so projects in the same process can use different directories.
+    directories: Mutex<Vec<(Weak<dyn TurboTasksApi>, PathBuf)>>,
+    /// The number of bytes written since the size of the cache was last checked.
+    written: AtomicU64,
+}
+
+impl PersistentCache {
+    pub const fn new(version: u32, max_size: u64) -> Self {
+        PersistentCache {
+            version,
+            max_size,
+            directories: Mutex::new(Vec::new()),
+            written: AtomicU64::new(0),
+        }
+    }
+
+    /// Enables the cache in the given directory for the tasks executed by `turbo_tasks`. Entries
+    /// are stored in a subdirectory for the version, the entries of other versions are removed.
+    pub fn init(&self, turbo_tasks: &Arc<dyn TurboTasksApi>, directory: impl Into<PathBuf>) {
+        let root = directory.into();
+        let directory = root.join(format!("v{}", self.version));
+        {
+            let mut directories = self.directories.lock().unwrap();
+            directories.retain(|(instance, _)| {
+                instance.strong_count() > 0 && !Weak::ptr_eq(instance, &Arc::downgrade(turbo_tasks))
+            });
+            directories.push((Arc::downgrade(turbo_tasks), directory.clone()));
+        }
+
+        // Cleaning up a big cache takes a while, it shouldn't delay the build.
+        let max_size = self.max_size;
+        std::thread::spawn(move || {
+            remove_other_versions(&root, &directory);
+            let _ = evict(&directory, max_size);
+        });
+    }
+
+    /// The cache directory of the turbo-tasks instance executing the current task.
+    fn directory(&self) -> Option<PathBuf> {
+        let current = turbo_tasks_weak();
+        self.directories
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(instance, _)| Weak::ptr_eq(instance, &current))
+            .map(|(_, directory)| directory.clone())
+    }
+
+    /// Whether the cache is enabled for the current task.
+    pub fn is_enabled(&self) -> bool {
+        self.directory().is_some()
+    }
+
+    /// Reads an entry and marks it as recently used. Missing and unreadable entries are treated as
+    /// cache misses.
+    pub async fn read(&self, key: &str) -> Option<Vec<u8>> {
+        let directory = self.directory()?;
+        let key = key.to_string();
+        spawn_blocking(move || read_entry(&directory, &key)).await
+    }
+
+    /// Stores an entry. Writing is best effort, failures are ignored since the result can always be
+    /// recomputed.
+    pub async fn write(&self, key: &str, content: Vec<u8>) {
+        let Some(directory) = self.directory() else {
+            return;
+        };
+        let size = content.len() as u64;
+        // Check the size of the cache whenever a quarter of the limit has been written.
+        let check_size =
+            self.written.fetch_add(size, Ordering::Relaxed) + size >= self.max_size / 4;
+        if check_size {
+            self.written.store(0, Ordering::Relaxed);
+        }
+        let key = key.to_string();
+        let max_size = self.max_size;
+        spawn_blocking(move || {
+            let _ = write_entry(&directory, &key, &content);
+            if check_size {
+                let _ = evict(&directory, max_size);
+            }
+        })
+        .await;
+    }
+}
+
+/// Computes cache keys. They are sha256 hashes, since a collision would silently return the result
+/// for other inputs.
+#[derive(Default)]
+pub struct CacheKeyHasher(Sha256);
+
+impl CacheKeyHasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value to the key. It is prefixed with its length, so that consecutive values can't
+    /// run into each other.
+    pub fn write(&mut self, bytes: impl AsRef<[u8]>) {
+        let bytes = bytes.as_ref();
+        self.0.update((bytes.len() as u64).to_le_bytes());
+        self.0.update(bytes);
+    }
+
+    /// Adds an optional value to the key.
+    pub fn write_option(&mut self, bytes: Option<impl AsRef<[u8]>>) {
+        match bytes {
+            Some(bytes) => {
+                self.0.update([1]);
+                self.write(bytes);
+            }
+            None => self.0.update([0]),
+        }
+    }
+
+    /// Returns the key as a hex string.
+    pub fn finish(self) -> String {
+        format!("{:x}", self.0.finalize())
+    }
+}
+
+fn entry_path(directory: &Path, key: &str) -> PathBuf {
+    // Shard entries into subdirectories to avoid huge directories.
+    directory.join(&key[..2]).join(key)
+}
+
+fn read_entry(directory: &Path, key: &str) -> Option<Vec<u8>> {
+    let path = entry_path(directory, key);
+    let content = fs::read(&path).ok()?;
+    // The modification time orders the entries for eviction.
+    let _ = fs::File::options()
+        .write(true)
+        .open(&path)
+        .and_then(|file| file.set_modified(SystemTime::now()));
+    Some(content)
+}
+
+fn write_entry(directory: &Path, key: &str, content: &[u8]) -> io::Result<()> {
+    let path = entry_path(directory, key);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Write to a temporary file first, so concurrent readers never see partial entries.
+    let temp_path = path.with_extension(format!(
+        "{}.{}.tmp",
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, &path)
+}
+
+/// Removes the least recently used entries when the entries are bigger than `max_size`. It removes
+/// more than needed, so that this doesn't happen again after the next write.
+fn evict(directory: &Path, max_size: u64) -> io::Result<()> {
+    let shards = match fs::read_dir(directory) {
+        Ok(shards) => shards,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    let mut entries = Vec::new();
+    let mut size = 0;
+    for shard in shards {
+        let shard = shard?;
+        if !shard.file_type()?.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(shard.path())? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let modified = metadata.modified()?;
+            if entry.path().extension().is_some_and(|ext| ext == "tmp") {
+                if modified
+                    .elapsed()
+                    .is_ok_and(|age| age > STALE_TEMP_FILE_AGE)
+                {
+                    let _ = fs::remove_file(entry.path());
+                }
+                continue;
+            }
+            size += metadata.len();
+            entries.push((modified, metadata.len(), entry.path()));
+        }
+    }
+    if size <= max_size {
+        return Ok(());
+    }
+
+    entries.sort();
+    let target_size = max_size / 4 * 3;
+    for (_, len, path) in entries {
+        if size <= target_size {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            size -= len;
+        }
+    }
+    Ok(())
+}
+
+/// Removes the directories of other versions of the cache next to `current`.
+fn remove_other_versions(root: &Path, current: &Path) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_version = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix('v'))
+            .is_some_and(|version| version.parse::<u32>().is_ok());
+        if is_version && path != current {
+            let _ = fs::remove_dir_all(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_modified(directory: &Path, key: &str, time: SystemTime) {
+        fs::File::options()
+            .write(true)
+            .open(entry_path(directory, key))
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let directory = tempfile::tempdir().unwrap();
+        let key = "ab12";
+
+        assert_eq!(read_entry(directory.path(), key), None);
+        write_entry(directory.path(), key, b"content").unwrap();
+        assert_eq!(
+            read_entry(directory.path(), key).as_deref(),
+            Some(&b"content"[..])
+        );
+        write_entry(directory.path(), key, b"replaced").unwrap();
+        assert_eq!(
+            read_entry(directory.path(), key).as_deref(),
+            Some(&b"replaced"[..])
+        );
+    }
+
+    #[test]
+    fn test_evict_least_recently_used() {
+        let directory = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        for (index, key) in ["aa01", "bb02", "cc03", "dd04"].into_iter().enumerate() {
+            write_entry(directory.path(), key, &[0; 100]).unwrap();
+            set_modified(
+                directory.path(),
+                key,
+                now - Duration::from_secs(100 - index as u64),
+            );
+        }
+        // Reading the oldest entry keeps it.
+        read_entry(directory.path(), "aa01").unwrap();
+
+        evict(directory.path(), 400).unwrap();
+        assert!(read_entry(directory.path(), "bb02").is_some());
+
+        evict(directory.path(), 300).unwrap();
+        assert!(entry_path(directory.path(), "aa01").exists());
+        assert!(!entry_path(directory.path(), "bb02").exists());
+        assert!(!entry_path(directory.path(), "cc03").exists());
+        assert!(entry_path(directory.path(), "dd04").exists());
+    }
+
+    #[test]
+    fn test_evict_stale_temp_files() {
+        let directory = tempfile::tempdir().unwrap();
+        let shard = directory.path().join("ab");
+        fs::create_dir_all(&shard).unwrap();
+        let stale = shard.join("ab12.1.0.tmp");
+        let fresh = shard.join("ab12.1.1.tmp");
+        fs::write(&stale, b"partial").unwrap();
+        fs::write(&fresh, b"partial").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&stale)
+            .unwrap()
+            .set_modified(SystemTime::now() - STALE_TEMP_FILE_AGE * 2)
+            .unwrap();
+
+        evict(directory.path(), u64::MAX).unwrap();
+        assert!(!stale.exists());
+        assert!(fresh.exists());
+    }
+
+    #[test]
+    fn test_remove_other_versions() {
+        let root = tempfile::tempdir().unwrap();
+        for name in ["v1", "v2", "other"] {
+            fs::create_dir_all(root.path().join(name)).unwrap();
+        }
+
+        remove_other_versions(root.path(), &root.path().join("v2"));
+        assert!(!root.path().join("v1").exists());
+        assert!(root.path().join("v2").exists());
+        assert!(root.path().join("other").exists());
+    }
+
+    #[test]
+    fn test_cache_key_hasher() {
+        let key = |values: &[&str]| {
+            let mut hasher = CacheKeyHasher::new();
+            for value in values {
+                hasher.write(value);
+            }
+            hasher.finish()
+        };
+        assert_eq!(key(&["a", "bc"]).len(), 64);
+        assert_eq!(key(&["a", "bc"]), key(&["a", "bc"]));
+        assert_ne!(key(&["a", "bc"]), key(&["ab", "c"]));
+
+        let option = |value: Option<&str>| {
+            let mut hasher = CacheKeyHasher::new();
+            hasher.write_option(value);
+            hasher.finish()
+        };
+        assert_ne!(option(None), option(Some("")));
+    }
+}
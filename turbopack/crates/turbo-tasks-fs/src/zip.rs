@@ -0,0 +1,369 @@
+use std::io::Read;
+
+use anyhow::{Context, Result, bail};
+use auto_hash_map::AutoMap;
+use flate2::read::DeflateDecoder;
+use rustc_hash::FxHashMap;
+use turbo_rcstr::RcStr;
+use turbo_tasks::{ValueToString, Vc};
+
+use crate::{
+    File, FileContent, FileMeta, FileSystem, FileSystemPath, LinkContent, RawDirectoryContent,
+    RawDirectoryEntry,
+};
+
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+const CENTRAL_DIRECTORY_HEADER_SIGNATURE: u32 = 0x02014b50;
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+const CENTRAL_DIRECTORY_HEADER_SIZE: usize = 46;
+const LOCAL_FILE_HEADER_SIZE: usize = 30;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+
+/// A read-only filesystem exposing the entries of a zip archive, e.g. a package in the Yarn
+/// cache.
+///
+/// Only stored and deflated entries are supported, which covers the archives written by Yarn.
+/// Symbolic links inside of the archive are not followed. An archive that can't be read is
+/// treated as empty.
+#[turbo_tasks::value]
+pub struct ZipFileSystem {
+    pub archive: FileSystemPath,
+}
+
+#[turbo_tasks::value_impl]
+impl ZipFileSystem {
+    /// Creates a filesystem for the zip `archive`. The archive is only read when an entry is
+    /// accessed.
+    #[turbo_tasks::function]
+    pub fn new(archive: FileSystemPath) -> Vc<Self> {
+        ZipFileSystem { archive }.cell()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ZipEntry {
+    /// Offset of the local file header in the archive.
+    header_offset: usize,
+    compressed_size: usize,
+    uncompressed_size: usize,
+    method: u16,
+}
+
+#[turbo_tasks::value(serialization = "none", eq = "manual", cell = "new")]
+struct ZipIndex {
+    #[turbo_tasks(trace_ignore)]
+    files: FxHashMap<RcStr, ZipEntry>,
+    #[turbo_tasks(trace_ignore)]
+    directories: FxHashMap<RcStr, AutoMap<RcStr, RawDirectoryEntry>>,
+}
+
+#[turbo_tasks::function]
+async fn zip_index(archive: FileSystemPath) -> Result<Vc<ZipIndex>> {
+    let mut index = ZipIndex {
+        files: FxHashMap::default(),
+        directories: FxHashMap::default(),
+    };
+    index.directories.insert(RcStr::default(), AutoMap::new());
+    let FileContent::Content(file) = &*archive.read().await? else {
+        return Ok(index.cell());
+    };
+    let Ok(entries) = parse_entries(&file.content().to_bytes()) else {
+        return Ok(index.cell());
+    };
+
+    for (path, entry) in entries {
+        let path = path.trim_end_matches('/');
+        if path.is_empty()
+            || path
+                .split('/')
+                .any(|segment| matches!(segment, "" | "." | ".."))
+        {
+            continue;
+        }
+        let mut kind = match entry {
+            Some(entry) => {
+                index.files.insert(path.into(), entry);
+                RawDirectoryEntry::File
+            }
+            None => {
+                index.directories.entry(path.into()).or_default();
+                RawDirectoryEntry::Directory
+            }
+        };
+        // Archives don't have to contain entries for all directories, so they are inferred from
+        // the paths of their children.
+        let mut child = path;
+        loop {
+            let (parent, name) = child.rsplit_once('/').unwrap_or(("", child));
+            let listing = index.directories.entry(parent.into()).or_default();
+            let existed = listing.contains_key(name);
+            listing.insert(name.into(), kind);
+            if existed || parent.is_empty() {
+                break;
+            }
+            child = parent;
+            kind = RawDirectoryEntry::Directory;
+        }
+    }
+    Ok(index.cell())
+}
+
+/// Parses the central directory of a zip archive. Directories have no entry.
+fn parse_entries(data: &[u8]) -> Result<Vec<(String, Option<ZipEntry>)>> {
+    let end = find_end_of_central_directory(data)
+        .context("not a zip archive, the end of the central directory is missing")?;
+    let count = read_u16(data, end + 10)? as usize;
+    let mut offset = read_u32(data, end + 16)? as usize;
+    if count == 0xffff || offset == 0xffffffff {
+        bail!("zip64 archives are not supported");
+    }
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if read_u32(data, offset)? != CENTRAL_DIRECTORY_HEADER_SIGNATURE {
+            bail!("invalid central directory header at {offset}");
+        }
+        let method = read_u16(data, offset + 10)?;
+        let compressed_size = read_u32(data, offset + 20)? as usize;
+        let uncompressed_size = read_u32(data, offset + 24)? as usize;
+        let name_length = read_u16(data, offset + 28)? as usize;
+        let extra_length = read_u16(data, offset + 30)? as usize;
+        let comment_length = read_u16(data, offset + 32)? as usize;
+        let header_offset = read_u32(data, offset + 42)? as usize;
+        let name_start = offset + CENTRAL_DIRECTORY_HEADER_SIZE;
+        let name = data
+            .get(name_start..name_start + name_length)
+            .context("truncated central directory")?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        offset = name_start + name_length + extra_length + comment_length;
+
+        if name.ends_with('/') {
+            entries.push((name, None));
+        } else {
+            entries.push((
+                name,
+                Some(ZipEntry {
+                    header_offset,
+                    compressed_size,
+                    uncompressed_size,
+                    method,
+                }),
+            ));
+        }
+    }
+    Ok(entries)
+}
+
+fn find_end_of_central_directory(data: &[u8]) -> Option<usize> {
+    let last = data.len().checked_sub(END_OF_CENTRAL_DIRECTORY_SIZE)?;
+    // The record is followed by a comment of at most 64KiB.
+    let first = last.saturating_sub(u16::MAX as usize);
+    (first..=last)
+        .rev()
+        .find(|&offset| read_u32(data, offset).ok() == Some(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+}
+
+/// Reads and decompresses the content of an entry.
+fn read_entry(data: &[u8], entry: &ZipEntry) -> Result<Vec<u8>> {
+    let offset = entry.header_offset;
+    if read_u32(data, offset)? != LOCAL_FILE_HEADER_SIGNATURE {
+        bail!("invalid local file header at {offset}");
+    }
+    let name_length = read_u16(data, offset + 26)? as usize;
+    let extra_length = read_u16(data, offset + 28)? as usize;
+    let start = offset + LOCAL_FILE_HEADER_SIZE + name_length + extra_length;
+    let compressed = data
+        .get(start..start + entry.compressed_size)
+        .context("truncated zip entry")?;
+    match entry.method {
+        METHOD_STORED => Ok(compressed.to_vec()),
+        METHOD_DEFLATED => {
+            let mut content = Vec::with_capacity(entry.uncompressed_size);
+            DeflateDecoder::new(compressed).read_to_end(&mut content)?;
+            Ok(content)
+        }
+        method => bail!("unsupported zip compression method {method}"),
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .context("unexpected end of zip archive")?;
+    Ok(u16::from_le_bytes(bytes.try_into()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = data
+        .get(offset..offset + 4)
+        .context("unexpected end of zip archive")?;
+    Ok(u32::from_le_bytes(bytes.try_into()?))
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for ZipFileSystem {
+    #[turbo_tasks::function]
+    async fn read(&self, path: FileSystemPath) -> Result<Vc<FileContent>> {
+        let index = zip_index(self.archive.clone()).await?;
+        let Some(entry) = index.files.get(&path.path) else {
+            return Ok(FileContent::NotFound.cell());
+        };
+        let FileContent::Content(archive) = &*self.archive.read().await? else {
+            return Ok(FileContent::NotFound.cell());
+        };
+        let content = read_entry(&archive.content().to_bytes(), entry).with_context(|| {
+            format!(
+                "failed to read {} from the zip archive {}",
+                path.path, self.archive.path
+            )
+        })?;
+        Ok(FileContent::Content(File::from(content)).cell())
+    }
+
+    #[turbo_tasks::function]
+    fn read_link(&self, _path: FileSystemPath) -> Vc<LinkContent> {
+        LinkContent::NotFound.cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn raw_read_dir(&self, path: FileSystemPath) -> Result<Vc<RawDirectoryContent>> {
+        let index = zip_index(self.archive.clone()).await?;
+        Ok(match index.directories.get(&path.path) {
+            Some(entries) => RawDirectoryContent::new(entries.clone()),
+            None => RawDirectoryContent::not_found(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn write(&self, _path: FileSystemPath, _content: Vc<FileContent>) -> Result<Vc<()>> {
+        bail!("Writing is not possible to a zip archive")
+    }
+
+    #[turbo_tasks::function]
+    fn write_link(&self, _path: FileSystemPath, _target: Vc<LinkContent>) -> Result<Vc<()>> {
+        bail!("Writing is not possible to a zip archive")
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(&self, path: FileSystemPath) -> Result<Vc<FileMeta>> {
+        let index = zip_index(self.archive.clone()).await?;
+        if !index.files.contains_key(&path.path) && !index.directories.contains_key(&path.path) {
+            bail!("path not found, can't read metadata");
+        }
+
+        Ok(FileMeta::default().cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ZipFileSystem {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> Vc<RcStr> {
+        self.archive.value_to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{Compression, write::DeflateEncoder};
+
+    use super::{ZipEntry, parse_entries, read_entry};
+
+    /// Writes a zip archive with the given entries, deflating the ones marked as compressed.
+    fn write_zip(entries: &[(&str, &[u8], bool)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut central_directory = Vec::new();
+        for (name, content, compressed) in entries {
+            let (method, stored) = if *compressed {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(content).unwrap();
+                (8u16, encoder.finish().unwrap())
+            } else {
+                (0u16, content.to_vec())
+            };
+            let header_offset = data.len() as u32;
+            data.extend(0x04034b50u32.to_le_bytes());
+            data.extend([20, 0, 0, 0]);
+            data.extend(method.to_le_bytes());
+            data.extend([0; 8]);
+            data.extend((stored.len() as u32).to_le_bytes());
+            data.extend((content.len() as u32).to_le_bytes());
+            data.extend((name.len() as u16).to_le_bytes());
+            data.extend([0, 0]);
+            data.extend(name.as_bytes());
+            data.extend(&stored);
+
+            central_directory.extend(0x02014b50u32.to_le_bytes());
+            central_directory.extend([20, 0, 20, 0, 0, 0]);
+            central_directory.extend(method.to_le_bytes());
+            central_directory.extend([0; 8]);
+            central_directory.extend((stored.len() as u32).to_le_bytes());
+            central_directory.extend((content.len() as u32).to_le_bytes());
+            central_directory.extend((name.len() as u16).to_le_bytes());
+            central_directory.extend([0; 12]);
+            central_directory.extend(header_offset.to_le_bytes());
+            central_directory.extend(name.as_bytes());
+        }
+        let central_directory_offset = data.len() as u32;
+        data.extend(&central_directory);
+        data.extend(0x06054b50u32.to_le_bytes());
+        data.extend([0; 4]);
+        data.extend((entries.len() as u16).to_le_bytes());
+        data.extend((entries.len() as u16).to_le_bytes());
+        data.extend((central_directory.len() as u32).to_le_bytes());
+        data.extend(central_directory_offset.to_le_bytes());
+        data.extend([0, 0]);
+        data
+    }
+
+    #[test]
+    fn test_read_entries() {
+        let index = b"module.exports = 'index';";
+        let data = write_zip(&[
+            ("node_modules/", b"", false),
+            (
+                "node_modules/pkg/package.json",
+                b"{\"name\":\"pkg\"}",
+                false,
+            ),
+            ("node_modules/pkg/index.js", index, true),
+        ]);
+        let entries = parse_entries(&data).unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|(name, entry)| (name.as_str(), entry.is_some()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("node_modules/", false),
+                ("node_modules/pkg/package.json", true),
+                ("node_modules/pkg/index.js", true),
+            ]
+        );
+
+        let package_json: &ZipEntry = entries[1].1.as_ref().unwrap();
+        assert_eq!(
+            read_entry(&data, package_json).unwrap(),
+            b"{\"name\":\"pkg\"}"
+        );
+        let index_js = entries[2].1.as_ref().unwrap();
+        assert_eq!(index_js.method, 8);
+        assert_eq!(read_entry(&data, index_js).unwrap(), index);
+    }
+
+    #[test]
+    fn test_invalid_archive() {
+        assert!(parse_entries(b"not a zip archive").is_err());
+
+        let mut data = write_zip(&[("index.js", b"content", false)]);
+        // Corrupt the local file header
+        data[0] = 0;
+        let entries = parse_entries(&data).unwrap();
+        assert!(read_entry(&data, entries[0].1.as_ref().unwrap()).is_err());
+    }
+}
@@ -6,14 +6,15 @@ use turbo_tasks::{
     FxIndexMap, NonLocalValue, ResolvedVc, TaskInput, TryJoinIterExt, Upcast, ValueToString, Vc,
     trace::TraceRawVcs,
 };
-use turbo_tasks_fs::FileSystemPath;
+use turbo_tasks_fs::{FileSystemPath, FileSystemPathOption};
 use turbo_tasks_hash::{DeterministicHash, hash_xxh3_hash64};
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{
-        Chunk, ChunkGroupResult, ChunkItem, ChunkType, ChunkableModule, ChunkingConfig,
-        ChunkingConfigs, ChunkingContext, EntryChunkGroupResult, EvaluatableAsset,
-        EvaluatableAssets, MinifyType, ModuleId, SourceMapSourceType, SourceMapsType,
+        Chunk, ChunkFormat, ChunkGroupResult, ChunkItem, ChunkType, ChunkableModule,
+        ChunkingConfig, ChunkingConfigs, ChunkingContext, EntryChunkGroupResult, EvaluatableAsset,
+        EvaluatableAssets, MinifyType, ModuleId, OptionPropertyMangling, PropertyMangling,
+        SourceMapSourceType, SourceMapsType,
         availability_info::AvailabilityInfo,
         chunk_group::{MakeChunkGroupResult, make_chunk_group},
         module_id_strategies::{DevModuleIdStrategy, ModuleIdStrategy},
@@ -32,6 +33,7 @@ use turbopack_core::{
 use turbopack_ecmascript::{
     async_chunk::module::AsyncLoaderModule,
     chunk::EcmascriptChunk,
+    federation::federation_chunk_loading_global,
     manifest::{chunk_asset::ManifestAsyncModule, loader_item::ManifestLoaderChunkItem},
 };
 use turbopack_ecmascript_runtime::{ChunkSuffix, RuntimeType};
@@ -52,6 +54,9 @@ pub enum CurrentChunkMethod {
 pub const CURRENT_CHUNK_METHOD_DOCUMENT_CURRENT_SCRIPT_EXPR: &str =
     "typeof document === \"object\" ? document.currentScript : undefined";
 
+/// Used instead of the [`CurrentChunkMethod`] for ES module chunks.
+pub const CURRENT_CHUNK_METHOD_IMPORT_META_URL_EXPR: &str = "import.meta.url";
+
 #[derive(
     Debug,
     TaskInput,
@@ -131,6 +136,16 @@ impl BrowserChunkingContextBuilder {
         self
     }
 
+    pub fn federation(mut self, federation_name: Option<RcStr>) -> Self {
+        self.chunking_context.federation_name = federation_name;
+        self
+    }
+
+    pub fn chunk_format(mut self, chunk_format: ChunkFormat) -> Self {
+        self.chunking_context.chunk_format = chunk_format;
+        self
+    }
+
     pub fn chunk_suffix(mut self, chunk_suffix: ResolvedVc<ChunkSuffix>) -> Self {
         self.chunking_context.chunk_suffix = Some(chunk_suffix);
         self
@@ -151,6 +166,15 @@ impl BrowserChunkingContextBuilder {
         self
     }
 
+    /// Renames object properties consistently in all chunks when minifying.
+    pub fn property_mangling(
+        mut self,
+        property_mangling: Option<ResolvedVc<PropertyMangling>>,
+    ) -> Self {
+        self.chunking_context.property_mangling = property_mangling;
+        self
+    }
+
     pub fn source_maps(mut self, source_maps: SourceMapsType) -> Self {
         self.chunking_context.source_maps_type = source_maps;
         self
@@ -226,6 +250,14 @@ impl BrowserChunkingContextBuilder {
         self
     }
 
+    /// Keeps the mangled names of chunk items in the directory, one file per chunk, and reuses
+    /// them in later builds. Modules that didn't change then produce the same minified code, so
+    /// with content hashing, chunks only get a new hash when their modules changed.
+    pub fn minify_name_cache(mut self, minify_name_cache: Option<FileSystemPath>) -> Self {
+        self.chunking_context.minify_name_cache = minify_name_cache;
+        self
+    }
+
     pub fn build(self) -> Vc<BrowserChunkingContext> {
         BrowserChunkingContext::cell(self.chunking_context)
     }
@@ -267,6 +299,12 @@ pub struct BrowserChunkingContext {
     /// Suffix that will be appended to all chunk URLs when loading them.
     /// This path will not appear in chunk paths or chunk data.
     chunk_suffix: Option<ResolvedVc<ChunkSuffix>>,
+    /// The name of the build when module federation is enabled. This includes the federation
+    /// runtime and registers chunks with a global of their own, as other builds are loaded into
+    /// the same page.
+    federation_name: Option<RcStr>,
+    /// The format JavaScript chunks are emitted in.
+    chunk_format: ChunkFormat,
     /// URL prefix that will be prepended to all static asset URLs when loading
     /// them.
     asset_base_path: Option<RcStr>,
@@ -292,8 +330,12 @@ pub struct BrowserChunkingContext {
     runtime_type: RuntimeType,
     /// Whether to minify resulting chunks
     minify_type: MinifyType,
+    /// Object properties to rename when minifying
+    property_mangling: Option<ResolvedVc<PropertyMangling>>,
     /// Whether content hashing is enabled.
     content_hashing: Option<ContentHashing>,
+    /// The directory mangled names are kept in across builds
+    minify_name_cache: Option<FileSystemPath>,
     /// Whether to generate source maps
     source_maps_type: SourceMapsType,
     /// Method to use when figuring out the current chunk src
@@ -337,6 +379,8 @@ impl BrowserChunkingContext {
                 asset_root_paths: Default::default(),
                 chunk_base_path: None,
                 chunk_suffix: None,
+                federation_name: None,
+                chunk_format: ChunkFormat::Script,
                 asset_base_path: None,
                 asset_base_paths: Default::default(),
                 enable_hot_module_replacement: false,
@@ -348,7 +392,9 @@ impl BrowserChunkingContext {
                 environment,
                 runtime_type,
                 minify_type: MinifyType::NoMinify,
+                property_mangling: None,
                 content_hashing: None,
+                minify_name_cache: None,
                 source_maps_type: SourceMapsType::Full,
                 current_chunk_method: CurrentChunkMethod::StringLiteral,
                 manifest_chunks: false,
@@ -448,6 +494,21 @@ impl BrowserChunkingContext {
         }
     }
 
+    /// Returns the name of the global variable chunks are registered with.
+    #[turbo_tasks::function]
+    pub fn chunk_loading_global(&self) -> Vc<RcStr> {
+        Vc::cell(match &self.federation_name {
+            Some(name) => federation_chunk_loading_global(name),
+            None => rcstr!("TURBOPACK"),
+        })
+    }
+
+    /// Returns whether module federation is enabled.
+    #[turbo_tasks::function]
+    pub fn federation_enabled(&self) -> Vc<bool> {
+        Vc::cell(self.federation_name.is_some())
+    }
+
     /// Returns the source map type.
     #[turbo_tasks::function]
     pub fn source_maps_type(&self) -> Vc<SourceMapsType> {
@@ -460,6 +521,21 @@ impl BrowserChunkingContext {
         self.minify_type.cell()
     }
 
+    /// Returns the file the mangled names of the chunk with the `ident` are kept in, if any.
+    #[turbo_tasks::function]
+    pub async fn minify_name_cache_path(
+        &self,
+        ident: Vc<AssetIdent>,
+    ) -> Result<Vc<FileSystemPathOption>> {
+        let Some(minify_name_cache) = &self.minify_name_cache else {
+            return Ok(Vc::cell(None));
+        };
+        let name = ident
+            .output_name(self.root_path.clone(), None, rcstr!(".json"))
+            .await?;
+        Ok(Vc::cell(Some(minify_name_cache.join(&name)?)))
+    }
+
     /// Returns the chunk path information.
     #[turbo_tasks::function]
     fn chunk_path_info(&self) -> Vc<ChunkPathInfo> {
@@ -671,11 +747,21 @@ impl ChunkingContext for BrowserChunkingContext {
         self.minify_type.cell()
     }
 
+    #[turbo_tasks::function]
+    fn property_mangling(&self) -> Vc<OptionPropertyMangling> {
+        Vc::cell(self.property_mangling)
+    }
+
     #[turbo_tasks::function]
     fn should_use_absolute_url_references(&self) -> Vc<bool> {
         Vc::cell(self.should_use_absolute_url_references)
     }
 
+    #[turbo_tasks::function]
+    fn chunk_format(&self) -> Vc<ChunkFormat> {
+        self.chunk_format.cell()
+    }
+
     #[turbo_tasks::function]
     async fn chunk_group(
         self: ResolvedVc<Self>,
@@ -49,7 +49,7 @@ impl EcmascriptBrowserChunk {
 }
 
 impl EcmascriptBrowserChunk {
-    fn ident_for_path(&self) -> Vc<AssetIdent> {
+    pub(super) fn ident_for_path(&self) -> Vc<AssetIdent> {
         self.chunk
             .ident()
             .with_modifier(rcstr!("ecmascript dev chunk"))
@@ -7,13 +7,18 @@ use turbo_tasks::{ResolvedVc, Vc};
 use turbo_tasks_fs::{File, FileContent};
 use turbopack_core::{
     asset::AssetContent,
-    chunk::{ChunkingContext, MinifyType, ModuleId},
+    chunk::{ChunkFormat, ChunkingContext, MinifyType, ModuleId},
     code_builder::{Code, CodeBuilder},
     output::OutputAsset,
     source_map::{GenerateSourceMap, SourceMapAsset},
     version::{MergeableVersionedContent, Version, VersionedContent, VersionedContentMerger},
 };
-use turbopack_ecmascript::{chunk::EcmascriptChunkContent, minify::minify, utils::StringifyJs};
+use turbopack_ecmascript::{
+    chunk::EcmascriptChunkContent,
+    minify::minify,
+    minify_name_cache::{read_minify_name_cache, write_minify_name_cache},
+    utils::StringifyJs,
+};
 
 use super::{
     chunk::EcmascriptBrowserChunk, content_entry::EcmascriptBrowserChunkContentEntries,
@@ -21,7 +26,10 @@ use super::{
 };
 use crate::{
     BrowserChunkingContext,
-    chunking_context::{CURRENT_CHUNK_METHOD_DOCUMENT_CURRENT_SCRIPT_EXPR, CurrentChunkMethod},
+    chunking_context::{
+        CURRENT_CHUNK_METHOD_DOCUMENT_CURRENT_SCRIPT_EXPR,
+        CURRENT_CHUNK_METHOD_IMPORT_META_URL_EXPR, CurrentChunkMethod,
+    },
 };
 
 #[turbo_tasks::value(serialization = "none")]
@@ -74,10 +82,14 @@ impl EcmascriptBrowserChunkContent {
             .chunking_context
             .reference_chunk_source_maps(*ResolvedVc::upcast(this.chunk))
             .await?;
+        let chunk_format = *this.chunking_context.chunk_format().await?;
         // Lifetime hack to pull out the var into this scope
         let chunk_path;
-        let script_or_path = match *this.chunking_context.current_chunk_method().await? {
-            CurrentChunkMethod::StringLiteral => {
+        let script_or_path = match (
+            chunk_format,
+            *this.chunking_context.current_chunk_method().await?,
+        ) {
+            (ChunkFormat::Script, CurrentChunkMethod::StringLiteral) => {
                 let output_root = this.chunking_context.output_root().await?;
                 let chunk_path_vc = this.chunk.path();
                 chunk_path = chunk_path_vc.await?;
@@ -88,28 +100,43 @@ impl EcmascriptBrowserChunkContent {
                 };
                 Either::Left(StringifyJs(chunk_server_path))
             }
-            CurrentChunkMethod::DocumentCurrentScript => {
+            (ChunkFormat::Script, CurrentChunkMethod::DocumentCurrentScript) => {
                 Either::Right(CURRENT_CHUNK_METHOD_DOCUMENT_CURRENT_SCRIPT_EXPR)
             }
+            // ES module chunks aren't loaded via script tags, they identify themselves by their
+            // URL instead.
+            (ChunkFormat::EsModule, _) => Either::Right(CURRENT_CHUNK_METHOD_IMPORT_META_URL_EXPR),
         };
         let mut code = CodeBuilder::new(
             source_maps,
             *this.chunking_context.debug_ids_enabled().await?,
         );
 
-        // When a chunk is executed, it will either register itself with the current
-        // instance of the runtime, or it will push itself onto the list of pending
-        // chunks (`self.TURBOPACK`).
-        //
-        // When the runtime executes (see the `evaluate` module), it will pick up and
-        // register all pending chunks, and replace the list of pending chunks
-        // with itself so later chunks can register directly with it.
-        write!(
-            code,
-            // `||=` would be better but we need to be es2020 compatible
-            //`x || (x = default)` is better than `x = x || default` simply because we avoid _writing_ the property in the common case.
-            "(globalThis.TURBOPACK || (globalThis.TURBOPACK = [])).push([{script_or_path},"
-        )?;
+        match chunk_format {
+            ChunkFormat::Script => {
+                // When a chunk is executed, it will either register itself with the current
+                // instance of the runtime, or it will push itself onto the list of pending
+                // chunks (`self.TURBOPACK`).
+                //
+                // When the runtime executes (see the `evaluate` module), it will pick up and
+                // register all pending chunks, and replace the list of pending chunks
+                // with itself so later chunks can register directly with it.
+                let chunk_loading_global = this.chunking_context.chunk_loading_global().await?;
+                write!(
+                    code,
+                    // `||=` would be better but we need to be es2020 compatible
+                    //`x || (x = default)` is better than `x = x || default` simply because we avoid _writing_ the property in the common case.
+                    "(globalThis.{chunk_loading_global} || (globalThis.{chunk_loading_global} = \
+                     [])).push([{script_or_path},"
+                )?;
+            }
+            ChunkFormat::EsModule => {
+                // ES module chunks export their registration instead. It is registered by the
+                // evaluate chunk that statically imports it, or by the runtime after loading it
+                // with `import()`.
+                write!(code, "export default [{script_or_path},")?;
+            }
+        }
 
         let content = this.content.await?;
         let chunk_items = content.chunk_item_code_and_ids().await?;
@@ -121,12 +148,39 @@ impl EcmascriptBrowserChunkContent {
             }
         }
 
-        write!(code, "\n]);")?;
+        match chunk_format {
+            ChunkFormat::Script => write!(code, "\n]);")?,
+            ChunkFormat::EsModule => write!(code, "\n];")?,
+        }
 
         let mut code = code.build();
 
         if let MinifyType::Minify { mangle } = *this.chunking_context.minify_type().await? {
-            code = minify(code, source_maps, mangle)?;
+            let property_mangling = match *this.chunking_context.property_mangling().await? {
+                Some(property_mangling) => Some(property_mangling.await?),
+                None => None,
+            };
+            let name_cache_path = this
+                .chunking_context
+                .minify_name_cache_path(this.chunk.await?.ident_for_path())
+                .owned()
+                .await?;
+            let mut name_cache = match &name_cache_path {
+                Some(path) => Some(read_minify_name_cache(path.clone()).owned().await?),
+                None => None,
+            };
+            code = minify(
+                code,
+                source_maps,
+                mangle,
+                property_mangling.as_deref(),
+                name_cache.as_mut(),
+            )?;
+            if let (Some(path), Some(name_cache)) = (name_cache_path, name_cache) {
+                write_minify_name_cache(Vc::cell(name_cache), path)
+                    .as_side_effect()
+                    .await?;
+            }
         }
 
         Ok(code.cell())
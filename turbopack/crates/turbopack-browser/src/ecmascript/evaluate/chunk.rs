@@ -10,7 +10,7 @@ use turbo_tasks_fs::{File, FileContent, FileSystemPath};
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{
-        ChunkData, ChunkingContext, ChunksData, EvaluatableAssets, MinifyType,
+        ChunkData, ChunkFormat, ChunkingContext, ChunksData, EvaluatableAssets, MinifyType,
         ModuleChunkItemIdExt, ModuleId,
     },
     code_builder::{Code, CodeBuilder},
@@ -29,7 +29,10 @@ use turbopack_ecmascript_runtime::RuntimeType;
 
 use crate::{
     BrowserChunkingContext,
-    chunking_context::{CURRENT_CHUNK_METHOD_DOCUMENT_CURRENT_SCRIPT_EXPR, CurrentChunkMethod},
+    chunking_context::{
+        CURRENT_CHUNK_METHOD_DOCUMENT_CURRENT_SCRIPT_EXPR,
+        CURRENT_CHUNK_METHOD_IMPORT_META_URL_EXPR, CurrentChunkMethod,
+    },
 };
 
 /// An Ecmascript chunk that:
@@ -89,10 +92,20 @@ impl EcmascriptBrowserEvaluateChunk {
             .chunking_context
             .reference_chunk_source_maps(Vc::upcast(self))
             .await?;
+        let runtime_type = *this.chunking_context.runtime_type().await?;
+        let chunk_format = *this.chunking_context.chunk_format().await?;
+        if matches!(chunk_format, ChunkFormat::EsModule)
+            && matches!(runtime_type, RuntimeType::Development)
+        {
+            bail!("ES module chunk output is not supported with the development runtime");
+        }
         // Lifetime hack to pull out the var into this scope
         let chunk_path;
-        let script_or_path = match *this.chunking_context.current_chunk_method().await? {
-            CurrentChunkMethod::StringLiteral => {
+        let script_or_path = match (
+            chunk_format,
+            *this.chunking_context.current_chunk_method().await?,
+        ) {
+            (ChunkFormat::Script, CurrentChunkMethod::StringLiteral) => {
                 let output_root = this.chunking_context.output_root().await?;
                 let chunk_path_vc = self.path();
                 chunk_path = chunk_path_vc.await?;
@@ -103,9 +116,12 @@ impl EcmascriptBrowserEvaluateChunk {
                 };
                 Either::Left(StringifyJs(chunk_server_path))
             }
-            CurrentChunkMethod::DocumentCurrentScript => {
+            (ChunkFormat::Script, CurrentChunkMethod::DocumentCurrentScript) => {
                 Either::Right(CURRENT_CHUNK_METHOD_DOCUMENT_CURRENT_SCRIPT_EXPR)
             }
+            // ES module chunks aren't loaded via script tags, they identify themselves by their
+            // URL instead.
+            (ChunkFormat::EsModule, _) => Either::Right(CURRENT_CHUNK_METHOD_IMPORT_META_URL_EXPR),
         };
 
         let other_chunks_data = self.chunks_data().await?;
@@ -154,12 +170,61 @@ impl EcmascriptBrowserEvaluateChunk {
         // We still use the `TURBOPACK` global variable to store the chunk here,
         // as there may be another runtime already loaded in the page.
         // This is the case in integration tests.
+        let chunk_loading_global = this.chunking_context.chunk_loading_global().await?;
+
+        // The relative path from this chunk to the output root, used to compute the chunk base URL
+        // from `import.meta.url` in ES module output. Chunks are always placed in the chunk root,
+        // using it instead of the chunk's own path avoids a cycle when content hashing is enabled.
+        let mut chunk_to_output_root = RcStr::default();
+        if matches!(chunk_format, ChunkFormat::EsModule) {
+            let output_root = this.chunking_context.output_root().await?;
+            let chunk_directory = this.chunking_context.chunk_root_path().await?;
+            let Some(path) = chunk_directory.get_relative_path_to(&output_root) else {
+                bail!("chunk directory {chunk_directory} is not in output root {output_root}");
+            };
+            chunk_to_output_root = format!("{path}/").into();
+
+            // Statically import the other JS chunks of this chunk group and register them before
+            // the runtime starts evaluating entries.
+            let other_chunks = this.other_chunks.await?;
+            let mut imported_chunks = 0;
+            for other_chunk in &*other_chunks {
+                let other_chunk_path = other_chunk.path().await?;
+                if !other_chunk_path.has_extension(".js") {
+                    continue;
+                }
+                let Some(specifier) = chunk_directory.get_relative_path_to(&other_chunk_path)
+                else {
+                    bail!(
+                        "chunk {other_chunk_path} is not in the same filesystem as \
+                         {chunk_directory}"
+                    );
+                };
+                writedoc!(
+                    code,
+                    r#"
+                        import __turbopack_chunk_{imported_chunks}__ from {};
+                    "#,
+                    StringifyJs(&specifier),
+                )?;
+                imported_chunks += 1;
+            }
+            for i in 0..imported_chunks {
+                writedoc!(
+                    code,
+                    r#"
+                        (globalThis.{chunk_loading_global} || (globalThis.{chunk_loading_global} = [])).push(__turbopack_chunk_{i}__);
+                    "#,
+                )?;
+            }
+        }
+
         writedoc!(
             code,
             // `||=` would be better but we need to be es2020 compatible
             //`x || (x = default)` is better than `x = x || default` simply because we avoid _writing_ the property in the common case.
             r#"
-                (globalThis.TURBOPACK || (globalThis.TURBOPACK = [])).push([
+                (globalThis.{chunk_loading_global} || (globalThis.{chunk_loading_global} = [])).push([
                     {script_or_path},
                     {}
                 ]);
@@ -167,7 +232,6 @@ impl EcmascriptBrowserEvaluateChunk {
             StringifyJs(&params),
         )?;
 
-        let runtime_type = *this.chunking_context.runtime_type().await?;
         match runtime_type {
             RuntimeType::Production | RuntimeType::Development => {
                 let runtime_code = turbopack_ecmascript_runtime::get_browser_runtime_code(
@@ -177,6 +241,10 @@ impl EcmascriptBrowserEvaluateChunk {
                     runtime_type,
                     output_root_to_root_path,
                     source_maps,
+                    (*chunk_loading_global).clone(),
+                    *this.chunking_context.federation_enabled().await?,
+                    chunk_format,
+                    chunk_to_output_root,
                 );
                 code.push_code(&*runtime_code.await?);
             }
@@ -190,7 +258,17 @@ impl EcmascriptBrowserEvaluateChunk {
         let mut code = code.build();
 
         if let MinifyType::Minify { mangle } = *this.chunking_context.minify_type().await? {
-            code = minify(code, source_maps, mangle)?;
+            let property_mangling = match *this.chunking_context.property_mangling().await? {
+                Some(property_mangling) => Some(property_mangling.await?),
+                None => None,
+            };
+            code = minify(
+                code,
+                source_maps,
+                mangle,
+                property_mangling.as_deref(),
+                None,
+            )?;
         }
 
         Ok(code.cell())
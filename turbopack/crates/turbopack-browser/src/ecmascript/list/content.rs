@@ -45,6 +45,7 @@ pub(super) struct EcmascriptDevChunkListContent {
     #[bincode(with = "turbo_bincode::indexmap")]
     pub(super) chunks_contents: FxIndexMap<String, ResolvedVc<Box<dyn VersionedContent>>>,
     source: EcmascriptDevChunkListSource,
+    chunk_loading_global: RcStr,
 }
 
 #[turbo_tasks::value_impl]
@@ -90,6 +91,11 @@ impl EcmascriptDevChunkListContent {
                 .filter_map(|(path, content)| path.map(|path| (path, content)))
                 .collect(),
             source: chunk_list_ref.source,
+            chunk_loading_global: chunk_list_ref
+                .chunking_context
+                .chunk_loading_global()
+                .owned()
+                .await?,
         }
         .cell())
     }
@@ -156,12 +162,13 @@ impl EcmascriptDevChunkListContent {
         // When loaded, JS chunks must register themselves with the `TURBOPACK` global
         // variable. Similarly, we register the chunk list with the
         // `TURBOPACK_CHUNK_LISTS` global variable.
+        let chunk_loading_global = &this.chunk_loading_global;
         writedoc!(
             code,
             // `||=` would be better but we need to be es2020 compatible
             //`x || (x = default)` is better than `x = x || default` simply because we avoid _writing_ the property in the common case.
             r#"
-                (globalThis.TURBOPACK_CHUNK_LISTS || (globalThis.TURBOPACK_CHUNK_LISTS = [])).push({{
+                (globalThis.{chunk_loading_global}_CHUNK_LISTS || (globalThis.{chunk_loading_global}_CHUNK_LISTS = [])).push({{
                     script: {script_or_path},
                     chunks: {:#},
                     source: {:#}
@@ -75,11 +75,20 @@ fn bench_small_apps(c: &mut Criterion) {
                                 full_stats: false,
                                 target: None,
                                 worker_threads: None,
+                                max_node_workers: None,
+                                node_worker_memory_limit: None,
+                                node_worker_idle_timeout: None,
                             },
                             no_sourcemap: false,
                             no_minify: false,
                             force_memory_cleanup: true,
                             no_scope_hoist: false,
+                            watch: false,
+                            singleton_packages: vec![],
+                            url_import_prefixes: vec![],
+                            frozen_url_imports: false,
+                            explain_resolve: None,
+                            from: None,
                         })
                         .await
                     })
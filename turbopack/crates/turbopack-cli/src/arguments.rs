@@ -2,13 +2,16 @@ use std::{
     net::IpAddr,
     path::{Path, PathBuf},
     str::FromStr,
+    time::Duration,
 };
 
 use anyhow::anyhow;
 use bincode::{Decode, Encode};
 use clap::{Args, Parser, ValueEnum};
 use turbo_tasks::{NonLocalValue, TaskInput, trace::TraceRawVcs};
+use turbopack::module_options::SvgImportMode;
 use turbopack_core::issue::IssueSeverity;
+use turbopack_node::{evaluate::NodeJsBackend, pool_policy::NodeJsPoolPolicy};
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -33,6 +36,14 @@ impl Arguments {
             Arguments::Dev(args) => args.common.worker_threads,
         }
     }
+
+    /// The limits of the Node.js worker pools. see [CommonArguments]::node_pool_policy
+    pub fn node_pool_policy(&self) -> NodeJsPoolPolicy {
+        match self {
+            Arguments::Build(args) => args.common.node_pool_policy(),
+            Arguments::Dev(args) => args.common.node_pool_policy(),
+        }
+    }
 }
 
 #[derive(
@@ -54,10 +65,30 @@ pub enum Target {
     Node,
 }
 
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq, Eq)]
+pub enum SvgMode {
+    Url,
+    DataUrl,
+    Component,
+}
+
+impl SvgMode {
+    pub fn import_mode(self) -> SvgImportMode {
+        match self {
+            SvgMode::Url => SvgImportMode::Url,
+            SvgMode::DataUrl => SvgImportMode::DataUrl { max_size: 4096 },
+            SvgMode::Component => SvgImportMode::Component,
+        }
+    }
+}
+
 #[derive(Debug, Args, Clone)]
 pub struct CommonArguments {
     /// The entrypoints of the project. Resolved relative to the project's
     /// directory (`--dir`).
+    /// When building for the browser, `.html` files can be used as entrypoints.
+    /// Their local scripts and stylesheets become entries and the HTML is
+    /// emitted with references to the built chunks.
     #[clap(value_parser)]
     pub entries: Option<Vec<String>>,
 
@@ -95,12 +126,52 @@ pub struct CommonArguments {
     /// Number of worker threads to use for parallel processing
     #[clap(long)]
     pub worker_threads: Option<usize>,
+
+    /// The maximum number of Node.js workers (e.g. running webpack loaders and PostCSS) that are
+    /// alive at the same time. Defaults to the number that fits into the available memory.
+    #[clap(long)]
+    pub max_node_workers: Option<usize>,
+
+    /// Stop Node.js workers whose memory usage exceeds this number of megabytes after an
+    /// operation. Only supported on Linux.
+    #[clap(long, value_name = "MB")]
+    pub node_worker_memory_limit: Option<u64>,
+
+    /// Stop Node.js workers that haven't been used for this number of seconds.
+    #[clap(long, value_name = "SECONDS")]
+    pub node_worker_idle_timeout: Option<u64>,
+
+    /// Run webpack loaders and PostCSS in worker threads of a shared Node.js process instead of a
+    /// process per worker.
+    #[clap(long)]
+    pub node_worker_threads: bool,
     // Enable experimental garbage collection with the provided memory limit in
     // MB.
     // #[clap(long)]
     // pub memory_limit: Option<usize>,
 }
 
+impl CommonArguments {
+    /// The limits of the Node.js worker pools configured by the `--*node-worker*` arguments.
+    pub fn node_pool_policy(&self) -> NodeJsPoolPolicy {
+        NodeJsPoolPolicy {
+            max_workers: self.max_node_workers,
+            max_worker_rss: self.node_worker_memory_limit.map(|mb| mb * 1024 * 1024),
+            idle_timeout: self.node_worker_idle_timeout.map(Duration::from_secs),
+            ..Default::default()
+        }
+    }
+
+    /// How webpack loaders and PostCSS are evaluated.
+    pub fn node_backend(&self) -> NodeJsBackend {
+        if self.node_worker_threads {
+            NodeJsBackend::WorkerThreads
+        } else {
+            NodeJsBackend::ChildProcess
+        }
+    }
+}
+
 #[derive(Debug, Args)]
 #[clap(author, version, about, long_about = None)]
 pub struct DevArguments {
@@ -154,6 +225,80 @@ pub struct BuildArguments {
     #[clap(long)]
     pub no_scope_hoist: bool,
 
+    /// Emit chunks as ES modules that import each other and load async chunks with `import()`,
+    /// instead of scripts that register with the Turbopack runtime. Node.js chunks use the `.mjs`
+    /// extension.
+    #[clap(long)]
+    pub esm: bool,
+
+    /// Build the entries as a library: dependencies and peer dependencies from `package.json`
+    /// aren't bundled, and every entry is emitted as an ES module (`dist/esm`) and a CommonJS
+    /// module (`dist/cjs`), together with a `dist/package.json` exporting them. Requires
+    /// `--target node`.
+    #[clap(long, conflicts_with = "esm")]
+    pub library: bool,
+
+    /// Keep running and rebuild whenever a file changes. Only changed output assets are written
+    /// again.
+    #[clap(short, long)]
+    pub watch: bool,
+
+    /// A package that must only be included once, e.g. `react`. It's resolved to the outermost
+    /// installed copy and duplicates are reported as errors. Can be passed multiple times.
+    #[clap(long = "singleton", value_name = "PACKAGE")]
+    pub singleton_packages: Vec<String>,
+
+    /// A URL prefix, e.g. `https://esm.sh/`, from which `https://` imports are fetched at build
+    /// time. Responses are cached in `turbopack.lock/` with a lockfile of their hashes, and the
+    /// build fails if a locked URL returns different content. Can be passed multiple times.
+    #[clap(long = "url-import", value_name = "PREFIX")]
+    pub url_import_prefixes: Vec<String>,
+
+    /// Fail instead of fetching URL imports that are not in the lockfile yet.
+    #[clap(long)]
+    pub frozen_url_imports: bool,
+
+    /// How `.svg` imports are exposed: as the url of the minified file, as a data url when the
+    /// minified file is at most 4 KiB large, or as a React component.
+    #[clap(long, value_name = "MODE")]
+    pub svg: Option<SvgMode>,
+
+    /// Rename object properties matching the regular expression (e.g. `^_`) to short names when
+    /// minifying. The names are kept in `turbopack.props.json` in the project directory, so that
+    /// they stay the same across builds.
+    #[clap(long, value_name = "REGEX", conflicts_with = "no_minify")]
+    pub mangle_props: Option<String>,
+
+    /// Keep mangled names stable across builds, so that chunks of modules that didn't change keep
+    /// their content and content hash. The names are kept in `turbopack.names/` in the project
+    /// directory. Only used with `--target browser`.
+    #[clap(long, conflicts_with = "no_minify")]
+    pub minify_name_cache: bool,
+
+    /// A JSON file configuring module federation, relative to the project directory. It names the
+    /// build and lists the modules it `exposes`, the `remotes` it consumes by the URL of their
+    /// remote entry and the `shared` packages. Requires `--target browser`.
+    #[clap(long, value_name = "FILE")]
+    pub federation: Option<String>,
+
+    /// A JSON file with dependency rules, relative to the project directory. Each rule is either
+    /// `{ "type": "forbidden", "name", "from", "to", "package", "layer" }`, forbidding imports of
+    /// modules matching `to` (or of the npm `package`) from modules matching `from`, or
+    /// `{ "type": "noCycles", "name", "path" }`, forbidding import cycles through `path`. Paths
+    /// are globs relative to the project directory. Violations fail the build.
+    #[clap(long, value_name = "FILE")]
+    pub boundary_rules: Option<String>,
+
+    /// Instead of building, print every step of resolving the specifier (import map lookups,
+    /// `exports` field conditions, probed files, ...) when it's imported from `--from`.
+    #[clap(long, value_name = "SPECIFIER", requires = "from")]
+    pub explain_resolve: Option<String>,
+
+    /// The file the `--explain-resolve` specifier is imported from, relative to the project
+    /// directory.
+    #[clap(long, value_name = "FILE", requires = "explain_resolve")]
+    pub from: Option<String>,
+
     /// Drop the `TurboTasks` object upon exit. By default we intentionally leak this memory, as
     /// we're about to exit the process anyways, but that can cause issues with valgrind or other
     /// leak detectors.
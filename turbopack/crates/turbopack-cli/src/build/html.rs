@@ -0,0 +1,627 @@
+use std::ops::Range;
+
+use anyhow::{Context, Result, bail, ensure};
+use data_encoding::BASE64;
+use rustc_hash::FxHashSet;
+use sha2::{Digest, Sha384};
+use turbo_rcstr::RcStr;
+use turbo_tasks::{ResolvedVc, TryJoinIterExt, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    context::AssetContext,
+    file_source::FileSource,
+    module::Module,
+    output::{OutputAsset, OutputAssets, OutputAssetsReference, OutputAssetsWithReferenced},
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    resolve::{
+        origin::{PlainResolveOrigin, ResolveOrigin, ResolveOriginExt},
+        parse::Request,
+    },
+    source::Source,
+};
+
+/// An HTML entry with its references resolved to modules.
+pub struct HtmlEntry {
+    pub source: ResolvedVc<Box<dyn Source>>,
+    /// The referenced modules in document order.
+    pub modules: Vec<ResolvedVc<Box<dyn Module>>>,
+}
+
+/// Reads an HTML entry and resolves all of its local script and stylesheet references. Urls are
+/// resolved relative to the HTML file, or to the project directory when they start with `/`.
+pub async fn resolve_html_entry(
+    project_path: FileSystemPath,
+    html_path: FileSystemPath,
+    asset_context: Vc<Box<dyn AssetContext>>,
+) -> Result<HtmlEntry> {
+    let source: ResolvedVc<Box<dyn Source>> =
+        ResolvedVc::upcast(FileSource::new(html_path.clone()).to_resolved().await?);
+    let content = source.content().file_content().await?;
+    let FileContent::Content(file) = &*content else {
+        bail!("HTML entry {} doesn't exist", html_path.path);
+    };
+    let html = file.content().to_str()?;
+
+    let modules = parse_html_references(&html)
+        .into_iter()
+        .map(|reference| {
+            let project_path = project_path.clone();
+            let html_path = html_path.clone();
+            async move {
+                let (origin_path, request) = match reference.url.strip_prefix('/') {
+                    Some(url) => (project_path.join("_")?, format!("./{url}")),
+                    None if reference.url.starts_with('.') => {
+                        (html_path.clone(), reference.url.clone())
+                    }
+                    None => (html_path.clone(), format!("./{}", reference.url)),
+                };
+                let origin = PlainResolveOrigin::new(asset_context, origin_path);
+                let ty = ReferenceType::Entry(EntryReferenceSubType::Undefined);
+                origin
+                    .resolve_asset(
+                        Request::relative(
+                            RcStr::from(request).into(),
+                            Default::default(),
+                            Default::default(),
+                            false,
+                        ),
+                        origin.resolve_options(ty.clone()),
+                        ty,
+                    )
+                    .await?
+                    .first_module()
+                    .await?
+                    .with_context(|| {
+                        format!(
+                            "Unable to resolve {} referenced from {}.",
+                            reference.url, html_path.path
+                        )
+                    })
+            }
+        })
+        .try_join()
+        .await?;
+
+    Ok(HtmlEntry { source, modules })
+}
+
+/// The HTML output of an HTML entry.
+///
+/// The content is the source HTML, with every local script and stylesheet reference replaced by
+/// the chunks of the referenced module. Chunk tags carry subresource integrity attributes and
+/// scripts are preloaded from the `<head>`. ES module chunks are included as module scripts.
+#[turbo_tasks::value]
+pub struct HtmlEntryAsset {
+    path: FileSystemPath,
+    source: ResolvedVc<Box<dyn Source>>,
+    /// One chunk group for each reference of the source HTML, in document order.
+    chunk_groups: Vec<ResolvedVc<OutputAssetsWithReferenced>>,
+    /// Whether the chunks are ES modules.
+    module_scripts: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl HtmlEntryAsset {
+    #[turbo_tasks::function]
+    pub fn new(
+        path: FileSystemPath,
+        source: ResolvedVc<Box<dyn Source>>,
+        chunk_groups: Vec<ResolvedVc<OutputAssetsWithReferenced>>,
+        module_scripts: bool,
+    ) -> Vc<Self> {
+        HtmlEntryAsset {
+            path,
+            source,
+            chunk_groups,
+            module_scripts,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAssetsReference for HtmlEntryAsset {
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<OutputAssetsWithReferenced> {
+        self.chunk_groups.iter().fold(
+            OutputAssetsWithReferenced::from_assets(OutputAssets::empty_resolved()),
+            |references, chunk_group| references.concatenate(**chunk_group),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for HtmlEntryAsset {
+    #[turbo_tasks::function]
+    fn path(&self) -> Vc<FileSystemPath> {
+        self.path.clone().cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for HtmlEntryAsset {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let content = self.source.content().file_content().await?;
+        let FileContent::Content(file) = &*content else {
+            bail!("HTML entry {} doesn't exist", self.path.path);
+        };
+        let html = file.content().to_str()?;
+        let references = parse_html_references(&html);
+        ensure!(
+            references.len() == self.chunk_groups.len(),
+            "HTML entry {} changed while it was processed",
+            self.path.path
+        );
+
+        let context_path = self.path.parent();
+        let chunks = self
+            .chunk_groups
+            .iter()
+            .map(|chunk_group| {
+                let context_path = &context_path;
+                async move {
+                    chunk_group
+                        .await?
+                        .assets
+                        .await?
+                        .iter()
+                        .map(|&chunk| async move {
+                            let path = chunk.path().await?;
+                            Ok(HtmlChunk {
+                                url: context_path
+                                    .get_relative_path_to(&path)
+                                    .context("Chunks must be on the output filesystem")?,
+                                integrity: subresource_integrity(*chunk).owned().await?,
+                            })
+                        })
+                        .try_join()
+                        .await
+                }
+            })
+            .try_join()
+            .await?;
+
+        Ok(AssetContent::file(
+            FileContent::Content(File::from(rewrite_html(
+                &html,
+                &references,
+                &chunks,
+                self.module_scripts,
+            )?))
+            .cell(),
+        ))
+    }
+}
+
+/// Computes the value of the `integrity` attribute for an output asset.
+#[turbo_tasks::function]
+async fn subresource_integrity(asset: Vc<Box<dyn OutputAsset>>) -> Result<Vc<RcStr>> {
+    let content = asset.content().file_content().await?;
+    let FileContent::Content(file) = &*content else {
+        bail!("Chunk {} has no content", asset.path().await?.path);
+    };
+    let digest = Sha384::digest(file.content().to_bytes());
+    Ok(Vc::cell(
+        format!("sha384-{}", BASE64.encode(&digest)).into(),
+    ))
+}
+
+/// A `<script src>` or `<link rel="stylesheet">` tag in an HTML entry that references a module of
+/// the project.
+#[derive(Debug, PartialEq, Eq)]
+struct HtmlReference {
+    /// The byte range of the whole tag, including the closing `</script>` tag.
+    range: Range<usize>,
+    kind: HtmlReferenceKind,
+    /// The url of the referenced module, without query and fragment.
+    url: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum HtmlReferenceKind {
+    Script { defer: bool },
+    Stylesheet,
+}
+
+/// A chunk that replaces a reference in the HTML.
+struct HtmlChunk {
+    /// The url of the chunk, relative to the HTML file.
+    url: RcStr,
+    integrity: RcStr,
+}
+
+/// Finds all script and stylesheet tags that reference local files. Tags referencing external
+/// urls are left alone.
+fn parse_html_references(html: &str) -> Vec<HtmlReference> {
+    // ASCII lowercasing keeps byte offsets intact.
+    let lowercase = html.to_ascii_lowercase();
+    let mut references = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = lowercase[pos..].find('<') {
+        let start = pos + offset;
+        if lowercase[start..].starts_with("<!--") {
+            pos = lowercase[start..]
+                .find("-->")
+                .map_or(html.len(), |end| start + end + 3);
+            continue;
+        }
+        let Some(tag) = parse_tag(html, start) else {
+            pos = start + 1;
+            continue;
+        };
+        pos = tag.end;
+        match &*tag.name {
+            "script" => {
+                let Some(close) = lowercase[tag.end..].find("</script") else {
+                    break;
+                };
+                let close = tag.end + close;
+                pos = lowercase[close..]
+                    .find('>')
+                    .map_or(html.len(), |end| close + end + 1);
+                if let Some(url) = tag.attribute("src").and_then(local_url) {
+                    references.push(HtmlReference {
+                        range: start..pos,
+                        kind: HtmlReferenceKind::Script {
+                            defer: tag.attribute("defer").is_some()
+                                || tag.attribute("type") == Some("module"),
+                        },
+                        url,
+                    });
+                }
+            }
+            "style" => {
+                // Skip the content, it's not HTML.
+                if let Some(close) = lowercase[tag.end..].find("</style") {
+                    pos = tag.end + close;
+                }
+            }
+            "link" => {
+                let is_stylesheet = tag.attribute("rel").is_some_and(|rel| {
+                    rel.split_ascii_whitespace()
+                        .any(|rel| rel.eq_ignore_ascii_case("stylesheet"))
+                });
+                if is_stylesheet && let Some(url) = tag.attribute("href").and_then(local_url) {
+                    references.push(HtmlReference {
+                        range: start..tag.end,
+                        kind: HtmlReferenceKind::Stylesheet,
+                        url,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    references
+}
+
+struct Tag<'a> {
+    /// The lowercase tag name.
+    name: String,
+    /// Attributes with lowercase names.
+    attributes: Vec<(String, &'a str)>,
+    /// The offset after the closing `>`.
+    end: usize,
+}
+
+impl<'a> Tag<'a> {
+    fn attribute(&self, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// Parses the start tag at `start`, which points to a `<`.
+fn parse_tag(html: &str, start: usize) -> Option<Tag<'_>> {
+    let bytes = html.as_bytes();
+    let mut pos = start + 1;
+    if !bytes.get(pos)?.is_ascii_alphabetic() {
+        return None;
+    }
+    while bytes
+        .get(pos)
+        .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'-')
+    {
+        pos += 1;
+    }
+    let name = html[start + 1..pos].to_ascii_lowercase();
+
+    let mut attributes = Vec::new();
+    loop {
+        while bytes
+            .get(pos)
+            .is_some_and(|b| b.is_ascii_whitespace() || *b == b'/')
+        {
+            pos += 1;
+        }
+        match bytes.get(pos)? {
+            b'>' => break,
+            _ => {
+                let name_start = pos;
+                while bytes
+                    .get(pos)
+                    .is_some_and(|b| !b.is_ascii_whitespace() && !b"/>=".contains(b))
+                {
+                    pos += 1;
+                }
+                let attribute_name = html[name_start..pos].to_ascii_lowercase();
+                while bytes.get(pos).is_some_and(|b| b.is_ascii_whitespace()) {
+                    pos += 1;
+                }
+                let mut value = "";
+                if bytes.get(pos) == Some(&b'=') {
+                    pos += 1;
+                    while bytes.get(pos).is_some_and(|b| b.is_ascii_whitespace()) {
+                        pos += 1;
+                    }
+                    match bytes.get(pos)? {
+                        quote @ (b'"' | b'\'') => {
+                            let value_start = pos + 1;
+                            let value_end =
+                                value_start + html[value_start..].find(char::from(*quote))?;
+                            value = &html[value_start..value_end];
+                            pos = value_end + 1;
+                        }
+                        _ => {
+                            let value_start = pos;
+                            while bytes
+                                .get(pos)
+                                .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'>')
+                            {
+                                pos += 1;
+                            }
+                            value = &html[value_start..pos];
+                        }
+                    }
+                }
+                attributes.push((attribute_name, value));
+            }
+        }
+    }
+
+    Some(Tag {
+        name,
+        attributes,
+        end: pos + 1,
+    })
+}
+
+/// Returns the path of a url that points to a local file, without query and fragment.
+fn local_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    if path.is_empty() || path.starts_with("//") {
+        return None;
+    }
+    // Urls with a scheme, like `https:` or `data:`, are external.
+    if let Some((scheme, _)) = path.split_once(':')
+        && !scheme.contains('/')
+    {
+        return None;
+    }
+    Some(path.to_string())
+}
+
+/// Replaces the references with tags for the given chunks. `chunks` contains the chunks of each
+/// reference. Chunks that were already included by a previous reference are skipped. Stylesheets
+/// of scripts and preload hints are added to the `<head>`. With `module_scripts`, scripts are
+/// included as module scripts, which are always deferred, and preloaded with `modulepreload`.
+fn rewrite_html(
+    html: &str,
+    references: &[HtmlReference],
+    chunks: &[Vec<HtmlChunk>],
+    module_scripts: bool,
+) -> Result<String> {
+    let mut included = FxHashSet::default();
+    let mut head = String::new();
+    let mut edits = Vec::with_capacity(references.len() + 1);
+    for (reference, chunks) in references.iter().zip(chunks) {
+        let mut replacement = String::new();
+        for chunk in chunks {
+            if !included.insert(&chunk.url) {
+                continue;
+            }
+            let url = escape_attribute(&chunk.url);
+            let integrity = &chunk.integrity;
+            if chunk.url.ends_with(".js") && module_scripts {
+                replacement.push_str(&format!(
+                    "<script type=\"module\" src=\"{url}\" integrity=\"{integrity}\"></script>"
+                ));
+                head.push_str(&format!(
+                    "<link rel=\"modulepreload\" href=\"{url}\" integrity=\"{integrity}\">"
+                ));
+            } else if chunk.url.ends_with(".js") {
+                let defer = match reference.kind {
+                    HtmlReferenceKind::Script { defer: true } => " defer",
+                    _ => "",
+                };
+                replacement.push_str(&format!(
+                    "<script src=\"{url}\" integrity=\"{integrity}\"{defer}></script>"
+                ));
+                head.push_str(&format!(
+                    "<link rel=\"preload\" as=\"script\" href=\"{url}\" integrity=\"{integrity}\">"
+                ));
+            } else if chunk.url.ends_with(".css") {
+                let tag =
+                    format!("<link rel=\"stylesheet\" href=\"{url}\" integrity=\"{integrity}\">");
+                match reference.kind {
+                    HtmlReferenceKind::Stylesheet => replacement.push_str(&tag),
+                    HtmlReferenceKind::Script { .. } => head.push_str(&tag),
+                }
+            } else {
+                bail!("chunk with unknown asset type: {}", chunk.url)
+            }
+        }
+        edits.push((reference.range.clone(), replacement));
+    }
+
+    if !head.is_empty() {
+        let lowercase = html.to_ascii_lowercase();
+        let position = lowercase
+            .find("</head")
+            .or_else(|| lowercase.find("<body"))
+            .unwrap_or_default();
+        edits.push((position..position, head));
+        edits.sort_by_key(|(range, _)| (range.start, range.end));
+    }
+
+    let mut result = String::with_capacity(html.len());
+    let mut pos = 0;
+    for (range, replacement) in edits {
+        result.push_str(&html[pos..range.start]);
+        result.push_str(&replacement);
+        pos = range.end;
+    }
+    result.push_str(&html[pos..]);
+    Ok(result)
+}
+
+fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        HtmlChunk, HtmlReference, HtmlReferenceKind, local_url, parse_html_references, rewrite_html,
+    };
+
+    const HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<LINK rel=stylesheet href="./styles.css?v=1">
+<link rel="icon" href="favicon.ico">
+<link rel="stylesheet" href="https://example.com/font.css">
+<!-- <script src="commented.js"></script> -->
+</head>
+<body>
+<script type="module" src="/src/main.ts"></script>
+<script>console.log("<script src='inline.js'></script>")</script>
+</body>
+</html>"#;
+
+    #[test]
+    fn test_local_url() {
+        assert_eq!(local_url("./a.js?x#y").as_deref(), Some("./a.js"));
+        assert_eq!(local_url("/src/a.js").as_deref(), Some("/src/a.js"));
+        assert_eq!(local_url("a:b/c.js"), None);
+        assert_eq!(local_url("https://example.com/a.js"), None);
+        assert_eq!(local_url("//example.com/a.js"), None);
+        assert_eq!(local_url("data:text/javascript,1"), None);
+        assert_eq!(local_url(""), None);
+    }
+
+    #[test]
+    fn test_parse_html_references() {
+        let references = parse_html_references(HTML);
+        assert_eq!(
+            references
+                .iter()
+                .map(|reference| (
+                    &HTML[reference.range.clone()],
+                    &reference.kind,
+                    &*reference.url
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    r#"<LINK rel=stylesheet href="./styles.css?v=1">"#,
+                    &HtmlReferenceKind::Stylesheet,
+                    "./styles.css"
+                ),
+                (
+                    r#"<script type="module" src="/src/main.ts"></script>"#,
+                    &HtmlReferenceKind::Script { defer: true },
+                    "/src/main.ts"
+                ),
+            ]
+        );
+    }
+
+    fn chunk(url: &str) -> HtmlChunk {
+        HtmlChunk {
+            url: url.into(),
+            integrity: format!("sha384-{url}").into(),
+        }
+    }
+
+    #[test]
+    fn test_rewrite_html() {
+        let references = parse_html_references(HTML);
+        let html = rewrite_html(
+            HTML,
+            &references,
+            &[
+                vec![chunk("./styles.css")],
+                vec![
+                    chunk("./runtime.js"),
+                    chunk("./main.css"),
+                    chunk("./main.js"),
+                    chunk("./styles.css"),
+                ],
+            ],
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            html,
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<link rel="stylesheet" href="./styles.css" integrity="sha384-./styles.css">
+<link rel="icon" href="favicon.ico">
+<link rel="stylesheet" href="https://example.com/font.css">
+<!-- <script src="commented.js"></script> -->
+<link rel="preload" as="script" href="./runtime.js" integrity="sha384-./runtime.js"><link rel="stylesheet" href="./main.css" integrity="sha384-./main.css"><link rel="preload" as="script" href="./main.js" integrity="sha384-./main.js"></head>
+<body>
+<script src="./runtime.js" integrity="sha384-./runtime.js" defer></script><script src="./main.js" integrity="sha384-./main.js" defer></script>
+<script>console.log("<script src='inline.js'></script>")</script>
+</body>
+</html>"#
+        );
+    }
+
+    #[test]
+    fn test_rewrite_html_without_head() {
+        let html = "<script src=\"a.js\"></script>";
+        let references = parse_html_references(html);
+        assert_eq!(
+            references,
+            vec![HtmlReference {
+                range: 0..html.len(),
+                kind: HtmlReferenceKind::Script { defer: false },
+                url: "a.js".to_string(),
+            }]
+        );
+        assert_eq!(
+            rewrite_html(html, &references, &[vec![chunk("./a.js")]], false).unwrap(),
+            "<link rel=\"preload\" as=\"script\" href=\"./a.js\" \
+             integrity=\"sha384-./a.js\"><script src=\"./a.js\" \
+             integrity=\"sha384-./a.js\"></script>"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_html_module_scripts() {
+        let html = "<head></head><script type=\"module\" src=\"a.js\"></script>";
+        let references = parse_html_references(html);
+        assert_eq!(
+            rewrite_html(
+                html,
+                &references,
+                &[vec![chunk("./a.js"), chunk("./entry.js")]],
+                true
+            )
+            .unwrap(),
+            "<head><link rel=\"modulepreload\" href=\"./a.js\" integrity=\"sha384-./a.js\"><link \
+             rel=\"modulepreload\" href=\"./entry.js\" \
+             integrity=\"sha384-./entry.js\"></head><script type=\"module\" src=\"./a.js\" \
+             integrity=\"sha384-./a.js\"></script><script type=\"module\" src=\"./entry.js\" \
+             integrity=\"sha384-./entry.js\"></script>"
+        );
+    }
+}
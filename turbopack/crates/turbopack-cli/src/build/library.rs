@@ -0,0 +1,370 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Map, Value, json};
+use turbo_rcstr::RcStr;
+use turbo_tasks::{ResolvedVc, Vc};
+use turbo_tasks_fs::{File, FileContent, FileJsonContent, FileSystemPath};
+use turbopack::{global_module_ids::get_global_module_id_strategy, module_options::SvgImportMode};
+use turbopack_core::{
+    asset::AssetContent,
+    chunk::{
+        ChunkFormat, ChunkingContext, EvaluatableAsset, EvaluatableAssets, MinifyType,
+        SourceMapsType, availability_info::AvailabilityInfo,
+    },
+    compile_time_info::CompileTimeInfo,
+    environment::{Environment, ExecutionEnvironment, NodeJsEnvironment},
+    module::Module,
+    module_graph::{
+        ModuleGraph,
+        binding_usage_info::compute_binding_usage_info,
+        boundary_rules::{BoundaryRules, check_boundary_rules},
+        chunk_group_info::ChunkGroupEntry,
+        duplicate_packages::detect_duplicate_packages,
+    },
+    output::{OutputAssets, OutputAssetsWithReferenced},
+    resolve::{ExternalType, parse::Request},
+    virtual_output::VirtualOutputAsset,
+};
+use turbopack_ecmascript_runtime::RuntimeType;
+use turbopack_node::execution_context::ExecutionContext;
+use turbopack_nodejs::NodeJsChunkingContext;
+use turbopack_resolve::url_imports::{UrlImportsOptions, write_url_imports_lock};
+
+use super::{OUTPUT_DIR, resolve_entries};
+use crate::contexts::{NodeEnv, get_client_asset_context};
+
+/// An output format of a library build.
+struct LibraryFormat {
+    chunk_format: ChunkFormat,
+    /// How the dependencies of the library are imported.
+    external_type: ExternalType,
+    /// The directory in the output directory the format is emitted to.
+    directory: &'static str,
+    extension: &'static str,
+}
+
+const LIBRARY_FORMATS: [LibraryFormat; 2] = [
+    LibraryFormat {
+        chunk_format: ChunkFormat::EsModule,
+        external_type: ExternalType::EcmaScriptModule,
+        directory: "esm",
+        extension: "mjs",
+    },
+    LibraryFormat {
+        chunk_format: ChunkFormat::Script,
+        external_type: ExternalType::CommonJs,
+        directory: "cjs",
+        extension: "js",
+    },
+];
+
+/// The packages a library doesn't bundle, i.e. the `dependencies` and `peerDependencies` of the
+/// project's `package.json`.
+pub(crate) async fn library_dependencies(project_path: &FileSystemPath) -> Result<Vec<RcStr>> {
+    let package_json = project_path.join("package.json")?.read_json().await?;
+    let FileJsonContent::Content(package_json) = &*package_json else {
+        return Ok(vec![]);
+    };
+    Ok(dependency_names(package_json))
+}
+
+fn dependency_names(package_json: &Value) -> Vec<RcStr> {
+    let mut names = BTreeSet::new();
+    for field in ["dependencies", "peerDependencies"] {
+        if let Some(dependencies) = package_json[field].as_object() {
+            names.extend(dependencies.keys().map(|name| RcStr::from(name.as_str())));
+        }
+    }
+    names.into_iter().collect()
+}
+
+/// An entry point of a library, i.e. a subpath of the `exports` map of its `package.json`.
+#[derive(Debug, Default)]
+struct LibraryExport {
+    subpath: RcStr,
+    /// The ES module of the entry, relative to the output directory.
+    import: RcStr,
+    /// The CommonJS module of the entry, relative to the output directory.
+    require: RcStr,
+}
+
+/// The `exports` subpath of an entry. The `index` entry is the package itself.
+fn export_subpath(stem: &str) -> RcStr {
+    if stem == "index" {
+        ".".into()
+    } else {
+        format!("./{stem}").into()
+    }
+}
+
+/// Returns the `package.json` of the built library. Its metadata and dependencies are copied from
+/// the project's `package.json`, and its `exports` map points to the outputs of the entries.
+fn library_package_json(package_json: &Value, exports: &[LibraryExport]) -> Value {
+    let mut result = Map::new();
+    for field in [
+        "name",
+        "version",
+        "description",
+        "license",
+        "dependencies",
+        "peerDependencies",
+    ] {
+        if let Some(value) = package_json.get(field) {
+            result.insert(field.to_string(), value.clone());
+        }
+    }
+    // The outputs are `.mjs` and `.js` files, so `.js` must mean CommonJS.
+    result.insert("type".to_string(), json!("commonjs"));
+    if let Some(root) = exports.iter().find(|export| export.subpath == ".") {
+        result.insert("main".to_string(), json!(format!("./{}", root.require)));
+        result.insert("module".to_string(), json!(format!("./{}", root.import)));
+    }
+    let mut exports_map = Map::new();
+    for export in exports {
+        exports_map.insert(
+            export.subpath.to_string(),
+            json!({
+                "import": format!("./{}", export.import),
+                "require": format!("./{}", export.require),
+            }),
+        );
+    }
+    exports_map.insert("./package.json".to_string(), json!("./package.json"));
+    result.insert("exports".to_string(), Value::Object(exports_map));
+    Value::Object(result)
+}
+
+/// Builds the entries as a library, once per [`LIBRARY_FORMATS`]. Every format has its own module
+/// graph, as the dependencies are referenced differently.
+pub(super) async fn build_library(
+    project_path: FileSystemPath,
+    root_path: FileSystemPath,
+    build_output_root: FileSystemPath,
+    project_dir: &str,
+    entry_requests: &[Vc<Request>],
+    execution_context: Vc<ExecutionContext>,
+    compile_time_info: Vc<CompileTimeInfo>,
+    runtime_type: RuntimeType,
+    source_maps_type: SourceMapsType,
+    minify_type: MinifyType,
+    scope_hoist: bool,
+    singleton_packages: Vec<RcStr>,
+    url_imports: Option<ResolvedVc<UrlImportsOptions>>,
+    svg: Option<SvgImportMode>,
+    boundary_rules: ResolvedVc<BoundaryRules>,
+) -> Result<Vec<Vc<OutputAssetsWithReferenced>>> {
+    let mut outputs = vec![];
+    let mut exports: Vec<LibraryExport> = vec![];
+
+    for (i, format) in LIBRARY_FORMATS.iter().enumerate() {
+        let asset_context = get_client_asset_context(
+            project_path.clone(),
+            execution_context,
+            compile_time_info,
+            NodeEnv::Production.cell(),
+            source_maps_type,
+            singleton_packages.clone(),
+            url_imports,
+            svg,
+            None,
+            Some(format.external_type),
+        );
+        let entries = resolve_entries(
+            asset_context,
+            root_path.join("_")?,
+            entry_requests,
+            project_dir,
+        )
+        .await?;
+
+        let mut module_graph = ModuleGraph::from_modules(
+            Vc::cell(vec![ChunkGroupEntry::Entry(entries.clone())]),
+            false,
+            true,
+        );
+        // The graphs of the formats only differ in how the externals are referenced.
+        if i == 0 {
+            detect_duplicate_packages(module_graph, singleton_packages.clone())
+                .as_side_effect()
+                .await?;
+            check_boundary_rules(module_graph, project_path.clone(), *boundary_rules)
+                .as_side_effect()
+                .await?;
+            if let Some(url_imports) = url_imports {
+                write_url_imports_lock(module_graph, *url_imports)
+                    .as_side_effect()
+                    .await?;
+            }
+        }
+        let module_id_strategy = ResolvedVc::upcast(
+            get_global_module_id_strategy(module_graph)
+                .to_resolved()
+                .await?,
+        );
+        let binding_usage = compute_binding_usage_info(module_graph.to_resolved().await?, true)
+            .resolve_strongly_consistent()
+            .await?;
+        module_graph = module_graph.without_unused_references(*binding_usage);
+
+        let output_root = build_output_root.join(format.directory)?;
+        let output_root_to_root_path = project_path
+            .join(OUTPUT_DIR)?
+            .join(format.directory)?
+            .get_relative_path_to(&root_path)
+            .context("Project path is in root path")?;
+        let chunking_context: Vc<Box<dyn ChunkingContext>> = Vc::upcast(
+            NodeJsChunkingContext::builder(
+                project_path.clone(),
+                output_root.clone(),
+                output_root_to_root_path,
+                output_root.clone(),
+                output_root.clone(),
+                output_root.clone(),
+                Environment::new(ExecutionEnvironment::NodeJsLambda(
+                    NodeJsEnvironment::default().resolved_cell(),
+                ))
+                .to_resolved()
+                .await?,
+                runtime_type,
+            )
+            .source_maps(source_maps_type)
+            .module_id_strategy(module_id_strategy)
+            .export_usage(Some(binding_usage))
+            .unused_references(Some(binding_usage))
+            .chunk_format(format.chunk_format)
+            .minify_type(minify_type)
+            .module_merging(scope_hoist)
+            .build(),
+        );
+
+        for (j, entry) in entries.into_iter().enumerate() {
+            let Some(evaluatable) = ResolvedVc::try_sidecast::<Box<dyn EvaluatableAsset>>(entry)
+            else {
+                bail!("Library entry module is not chunkable, so it can't be exported");
+            };
+            let stem = entry.ident().path().await?.file_stem().unwrap().to_string();
+            let path = output_root.join(&format!("{stem}.{}", format.extension))?;
+            let output_path: RcStr = build_output_root
+                .get_path_to(&path)
+                .context("Library output is not in the output directory")?
+                .into();
+            if i == 0 {
+                let subpath = export_subpath(&stem);
+                if exports.iter().any(|export| export.subpath == subpath) {
+                    bail!("Multiple library entries are named {stem}");
+                }
+                exports.push(LibraryExport {
+                    subpath,
+                    import: output_path,
+                    ..Default::default()
+                });
+            } else {
+                exports[j].require = output_path;
+            }
+
+            let asset = chunking_context
+                .entry_chunk_group(
+                    path,
+                    EvaluatableAssets::one(*evaluatable),
+                    module_graph,
+                    OutputAssets::empty(),
+                    OutputAssets::empty(),
+                    AvailabilityInfo::root(),
+                )
+                .await?
+                .asset;
+            outputs.push(OutputAssetsWithReferenced::from_assets(Vc::cell(vec![
+                asset,
+            ])));
+        }
+    }
+
+    let package_json = match &*project_path.join("package.json")?.read_json().await? {
+        FileJsonContent::Content(package_json) => package_json.clone(),
+        _ => Value::Object(Map::new()),
+    };
+    let package_json = library_package_json(&package_json, &exports);
+    outputs.push(OutputAssetsWithReferenced::from_assets(Vc::cell(vec![
+        ResolvedVc::upcast(
+            VirtualOutputAsset::new(
+                build_output_root.join("package.json")?,
+                AssetContent::file(
+                    FileContent::Content(File::from(serde_json::to_string_pretty(&package_json)?))
+                        .cell(),
+                ),
+            )
+            .to_resolved()
+            .await?,
+        ),
+    ])));
+
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{LibraryExport, dependency_names, export_subpath, library_package_json};
+
+    #[test]
+    fn test_dependency_names() {
+        let package_json = json!({
+            "name": "lib",
+            "dependencies": { "react-dom": "^19.0.0", "lodash": "^4.0.0" },
+            "peerDependencies": { "react": "^19.0.0", "react-dom": "^19.0.0" },
+            "devDependencies": { "typescript": "^5.0.0" },
+        });
+        assert_eq!(
+            dependency_names(&package_json),
+            vec!["lodash", "react", "react-dom"]
+        );
+        assert!(dependency_names(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_library_package_json() {
+        let package_json = json!({
+            "name": "lib",
+            "version": "1.2.3",
+            "type": "module",
+            "scripts": { "build": "turbopack build --library" },
+            "peerDependencies": { "react": "^19.0.0" },
+        });
+        let exports = [
+            LibraryExport {
+                subpath: export_subpath("index"),
+                import: "esm/index.mjs".into(),
+                require: "cjs/index.js".into(),
+            },
+            LibraryExport {
+                subpath: export_subpath("server"),
+                import: "esm/server.mjs".into(),
+                require: "cjs/server.js".into(),
+            },
+        ];
+        assert_eq!(
+            library_package_json(&package_json, &exports),
+            json!({
+                "name": "lib",
+                "version": "1.2.3",
+                "type": "commonjs",
+                "peerDependencies": { "react": "^19.0.0" },
+                "main": "./cjs/index.js",
+                "module": "./esm/index.mjs",
+                "exports": {
+                    ".": {
+                        "import": "./esm/index.mjs",
+                        "require": "./cjs/index.js",
+                    },
+                    "./server": {
+                        "import": "./esm/server.mjs",
+                        "require": "./cjs/server.js",
+                    },
+                    "./package.json": "./package.json",
+                },
+            })
+        );
+    }
+}
@@ -1,60 +1,91 @@
 use std::{
     env::current_dir,
     mem::forget,
-    path::{MAIN_SEPARATOR, PathBuf},
-    sync::Arc,
+    path::{MAIN_SEPARATOR, Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context, Result, bail};
-use rustc_hash::FxHashSet;
+use owo_colors::OwoColorize;
+use rustc_hash::{FxHashMap, FxHashSet};
+use tokio::sync::mpsc::UnboundedReceiver;
 use tracing::Instrument;
 use turbo_rcstr::RcStr;
-use turbo_tasks::{ResolvedVc, TransientInstance, TryJoinIterExt, TurboTasks, Vc, apply_effects};
+use turbo_tasks::{
+    OperationVc, ReadRef, ResolvedVc, TaskId, TransientInstance, TryJoinIterExt, TurboTasks, Vc,
+    apply_effects, util::FormatDuration,
+};
 use turbo_tasks_backend::{
     BackendOptions, NoopBackingStorage, TurboTasksBackend, noop_backing_storage,
 };
-use turbo_tasks_fs::FileSystem;
+use turbo_tasks_fs::{FileSystem, FileSystemPath};
 use turbo_unix_path::join_path;
-use turbopack::global_module_ids::get_global_module_id_strategy;
+use turbopack::{global_module_ids::get_global_module_id_strategy, module_options::SvgImportMode};
 use turbopack_browser::{BrowserChunkingContext, ContentHashing, CurrentChunkMethod};
 use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
 use turbopack_core::{
     asset::Asset,
     chunk::{
-        ChunkingConfig, ChunkingContext, ChunkingContextExt, EvaluatableAsset, EvaluatableAssets,
-        MangleType, MinifyType, SourceMapsType, availability_info::AvailabilityInfo,
+        ChunkFormat, ChunkingConfig, ChunkingContext, ChunkingContextExt, EvaluatableAsset,
+        EvaluatableAssets, MangleType, MinifyType, SourceMapsType,
+        availability_info::AvailabilityInfo,
     },
+    context::AssetContext,
     environment::{BrowserEnvironment, Environment, ExecutionEnvironment, NodeJsEnvironment},
     ident::AssetIdent,
-    issue::{IssueReporter, IssueSeverity, handle_issues},
+    issue::{CollectibleIssuesExt, IssueReporter, IssueSeverity, handle_issues},
     module::Module,
     module_graph::{
         ModuleGraph,
         binding_usage_info::compute_binding_usage_info,
+        boundary_rules::{BoundaryRule, BoundaryRules, check_boundary_rules},
         chunk_group_info::{ChunkGroup, ChunkGroupEntry},
+        duplicate_packages::detect_duplicate_packages,
     },
     output::{OutputAsset, OutputAssets, OutputAssetsWithReferenced},
-    reference_type::{EntryReferenceSubType, ReferenceType},
+    reference_type::{EcmaScriptModulesReferenceSubType, EntryReferenceSubType, ReferenceType},
     resolve::{
         origin::{PlainResolveOrigin, ResolveOrigin, ResolveOriginExt},
         parse::Request,
+        trace::{ResolveTrace, explain_resolve},
     },
 };
 use turbopack_css::chunk::CssChunkType;
-use turbopack_ecmascript::chunk::EcmascriptChunkType;
+use turbopack_ecmascript::{
+    chunk::EcmascriptChunkType,
+    federation::{FederationOptions, federation_container_source, federation_manifest},
+    property_mangling::{
+        PropertyManglingOptions, compute_property_mangling, write_property_name_cache,
+    },
+};
 use turbopack_ecmascript_runtime::RuntimeType;
 use turbopack_env::dotenv::load_env;
-use turbopack_node::execution_context::ExecutionContext;
+use turbopack_node::{evaluate::NodeJsBackend, execution_context::ExecutionContext};
 use turbopack_nodejs::NodeJsChunkingContext;
+use turbopack_resolve::{
+    resolve::resolve_options,
+    url_imports::{UrlImportsOptions, write_url_imports_lock},
+};
 
+use self::{
+    html::{HtmlEntryAsset, resolve_html_entry},
+    library::build_library,
+};
 use crate::{
-    arguments::{BuildArguments, Target},
-    contexts::{NodeEnv, get_client_asset_context, get_client_compile_time_info},
+    arguments::{BuildArguments, SvgMode, Target},
+    contexts::{
+        NodeEnv, get_client_asset_context, get_client_compile_time_info,
+        get_client_resolve_options_context,
+    },
     util::{
         EntryRequest, NormalizedDirs, normalize_dirs, normalize_entries, output_fs, project_fs,
     },
 };
 
+pub(crate) mod html;
+pub(crate) mod library;
+
 type Backend = TurboTasksBackend<NoopBackingStorage>;
 
 pub struct TurbopackBuildBuilder {
@@ -68,8 +99,20 @@ pub struct TurbopackBuildBuilder {
     log_detail: bool,
     source_maps_type: SourceMapsType,
     minify_type: MinifyType,
+    chunk_format: ChunkFormat,
     target: Target,
     scope_hoist: bool,
+    watch: bool,
+    singleton_packages: Vec<RcStr>,
+    url_import_prefixes: Vec<RcStr>,
+    frozen_url_imports: bool,
+    svg: Option<SvgImportMode>,
+    mangle_properties: Option<RcStr>,
+    minify_name_cache: bool,
+    federation: Option<FederationOptions>,
+    library: bool,
+    boundary_rules: Vec<BoundaryRule>,
+    node_backend: NodeJsBackend,
 }
 
 impl TurbopackBuildBuilder {
@@ -89,8 +132,20 @@ impl TurbopackBuildBuilder {
             minify_type: MinifyType::Minify {
                 mangle: Some(MangleType::OptimalSize),
             },
+            chunk_format: ChunkFormat::Script,
             target: Target::Node,
             scope_hoist: true,
+            watch: false,
+            singleton_packages: vec![],
+            url_import_prefixes: vec![],
+            frozen_url_imports: false,
+            svg: None,
+            mangle_properties: None,
+            minify_name_cache: false,
+            federation: None,
+            library: false,
+            boundary_rules: vec![],
+            node_backend: NodeJsBackend::ChildProcess,
         }
     }
 
@@ -129,6 +184,11 @@ impl TurbopackBuildBuilder {
         self
     }
 
+    pub fn chunk_format(mut self, chunk_format: ChunkFormat) -> Self {
+        self.chunk_format = chunk_format;
+        self
+    }
+
     pub fn scope_hoist(mut self, scope_hoist: bool) -> Self {
         self.scope_hoist = scope_hoist;
         self
@@ -139,47 +199,403 @@ impl TurbopackBuildBuilder {
         self
     }
 
-    pub async fn build(self) -> Result<()> {
-        self.turbo_tasks
-            .run_once(async move {
-                let build_result_op = build_internal(
-                    self.project_dir.clone(),
-                    self.root_dir,
-                    self.entry_requests.clone(),
-                    self.browserslist_query,
-                    self.source_maps_type,
-                    self.minify_type,
-                    self.target,
-                    self.scope_hoist,
-                );
+    /// Watches the project's files for changes. Requires a `TurboTasks` instance with dependency
+    /// tracking enabled.
+    pub fn watch(mut self, watch: bool) -> Self {
+        self.watch = watch;
+        self
+    }
 
-                // Await the result to propagate any errors.
-                build_result_op.read_strongly_consistent().await?;
+    /// Only allows a single copy of the package. It's always resolved to the outermost
+    /// `node_modules` copy, and any other copy in the module graph is reported as an error.
+    pub fn singleton_package(mut self, name: RcStr) -> Self {
+        self.singleton_packages.push(name);
+        self
+    }
 
-                apply_effects(build_result_op).await?;
+    /// Fetches `https://` imports starting with the prefix at build time. They are cached in the
+    /// project's `turbopack.lock` directory, together with a lockfile of their hashes.
+    pub fn url_import_prefix(mut self, prefix: RcStr) -> Self {
+        self.url_import_prefixes.push(prefix);
+        self
+    }
+
+    /// Fails the build for URL imports that are not in the lockfile, instead of fetching them.
+    pub fn frozen_url_imports(mut self, frozen_url_imports: bool) -> Self {
+        self.frozen_url_imports = frozen_url_imports;
+        self
+    }
+
+    /// Minifies imported `.svg` files and exposes them according to the mode. By default they are
+    /// plain static assets.
+    pub fn svg(mut self, svg: Option<SvgImportMode>) -> Self {
+        self.svg = svg;
+        self
+    }
+
+    /// Renames object properties matching the regular expression to short names when minifying.
+    /// The names are kept in the project's `turbopack.props.json`, so that they stay the same
+    /// across builds.
+    pub fn mangle_properties(mut self, pattern: Option<RcStr>) -> Self {
+        self.mangle_properties = pattern;
+        self
+    }
 
-                let issue_reporter: Vc<Box<dyn IssueReporter>> =
-                    Vc::upcast(ConsoleUi::new(TransientInstance::new(LogOptions {
-                        project_dir: PathBuf::from(self.project_dir),
-                        current_dir: current_dir().unwrap(),
-                        show_all: self.show_all,
-                        log_detail: self.log_detail,
-                        log_level: self.log_level,
-                    })));
+    /// Keeps mangled names stable across builds, so that modules that didn't change produce the
+    /// same minified code and their chunks keep their content hash. The names are kept in the
+    /// project's `turbopack.names` directory. Only used when building for the browser.
+    pub fn minify_name_cache(mut self, minify_name_cache: bool) -> Self {
+        self.minify_name_cache = minify_name_cache;
+        self
+    }
 
+    /// Enables module federation, i.e. exposing modules to other builds and consuming the
+    /// modules of other builds at runtime. Only supported when building for the browser.
+    pub fn federation(mut self, federation: Option<FederationOptions>) -> Self {
+        self.federation = federation;
+        self
+    }
+
+    /// Builds the entries as a library instead of an application: the `dependencies` and
+    /// `peerDependencies` of the project's `package.json` stay external, and every entry is
+    /// emitted as an ES module and a CommonJS module exporting what the entry module exports.
+    /// A `package.json` with the matching `exports` map is written next to them.
+    pub fn library(mut self, library: bool) -> Self {
+        self.library = library;
+        self
+    }
+
+    /// Dependency rules that are checked against the module graph, e.g. forbidden imports between
+    /// directories or cycles. Violations are reported as errors.
+    pub fn boundary_rules(mut self, boundary_rules: Vec<BoundaryRule>) -> Self {
+        self.boundary_rules = boundary_rules;
+        self
+    }
+
+    /// How webpack loaders and PostCSS are evaluated.
+    pub fn node_backend(mut self, node_backend: NodeJsBackend) -> Self {
+        self.node_backend = node_backend;
+        self
+    }
+
+    fn log_options(&self) -> TransientInstance<LogOptions> {
+        TransientInstance::new(LogOptions {
+            project_dir: PathBuf::from(self.project_dir.clone()),
+            current_dir: current_dir().unwrap(),
+            show_all: self.show_all,
+            log_detail: self.log_detail,
+            log_level: self.log_level,
+        })
+    }
+
+    fn build_operation(self) -> OperationVc<OutputAssets> {
+        build_internal(
+            self.project_dir,
+            self.root_dir,
+            self.entry_requests,
+            self.browserslist_query,
+            self.source_maps_type,
+            self.minify_type,
+            self.chunk_format,
+            self.target,
+            self.scope_hoist,
+            self.watch,
+            self.singleton_packages,
+            self.url_import_prefixes,
+            self.frozen_url_imports,
+            self.svg,
+            self.mangle_properties,
+            self.minify_name_cache,
+            self.federation.map(FederationOptions::resolved_cell),
+            self.library,
+            ResolvedVc::cell(self.boundary_rules),
+            self.node_backend,
+        )
+    }
+
+    /// Prints every step of resolving `specifier` from the file `from` (relative to the project
+    /// directory), instead of building.
+    pub async fn explain_resolve(self, specifier: RcStr, from: RcStr) -> Result<()> {
+        let log_options = self.log_options();
+        self.turbo_tasks
+            .clone()
+            .run_once(async move {
+                let explain_op = explain_resolve_operation(
+                    self.project_dir,
+                    self.root_dir,
+                    specifier.clone(),
+                    from.clone(),
+                    self.singleton_packages,
+                    self.url_import_prefixes,
+                    self.frozen_url_imports,
+                    self.federation.map(FederationOptions::resolved_cell),
+                );
+                let trace = explain_op.read_strongly_consistent().await?;
                 handle_issues(
-                    build_result_op,
-                    issue_reporter,
-                    IssueSeverity::Error,
+                    explain_op,
+                    Vc::upcast(ConsoleUi::new(log_options)),
+                    IssueSeverity::Fatal,
                     None,
                     None,
                 )
                 .await?;
+                print_resolve_trace(&specifier, &from, &trace);
+                Ok(())
+            })
+            .await
+    }
 
+    pub async fn build(self) -> Result<()> {
+        let log_options = self.log_options();
+        self.turbo_tasks
+            .clone()
+            .run_once(async move {
+                let build_result_op = self.build_operation();
+                emit_build(build_result_op, log_options).await?;
                 Ok(())
             })
             .await
     }
+
+    /// Builds the project and rebuilds it whenever one of its input files changes. Only output
+    /// assets with changed content are written to disk again. This never returns unless the
+    /// initial setup fails.
+    pub async fn build_and_watch(self) -> Result<()> {
+        let turbo_tasks = self.turbo_tasks.clone();
+        let (root_task, mut update_rx) = self.watch_builds().await?;
+
+        let mut initial = true;
+        while let Some((duration, update)) = update_rx.recv().await {
+            let duration = FormatDuration(duration);
+            match update {
+                Ok(stats) => println!(
+                    "{event_type} - build {duration} ({stats})",
+                    event_type = "event".purple(),
+                ),
+                Err(err) => println!(
+                    "{event_type} - build failed after {duration}\n{err:?}",
+                    event_type = "error".red(),
+                ),
+            }
+            if initial {
+                println!("{} - watching for file changes", "ready".green());
+                initial = false;
+            }
+        }
+
+        turbo_tasks.dispose_root_task(root_task);
+        Ok(())
+    }
+
+    /// Starts building the project in watch mode. Every build, the initial one included, sends its
+    /// duration and result to the returned receiver. The returned root task keeps rebuilding until
+    /// it is disposed.
+    async fn watch_builds(self) -> Result<(TaskId, UnboundedReceiver<BuildUpdate>)> {
+        let turbo_tasks = self.turbo_tasks.clone();
+        let log_options = self.log_options();
+        let build_result_op = turbo_tasks
+            .run_once(async move { Ok(self.watch(true).build_operation()) })
+            .await?;
+
+        let (update_tx, update_rx) = tokio::sync::mpsc::unbounded_channel();
+        let emitted_hashes: Arc<Mutex<FxHashMap<RcStr, u64>>> = Default::default();
+        // A root task is reexecuted whenever anything it read is invalidated, which makes it
+        // rebuild the project on every file change.
+        let root_task = turbo_tasks.spawn_root_task(move || {
+            let log_options = log_options.clone();
+            let update_tx = update_tx.clone();
+            let emitted_hashes = emitted_hashes.clone();
+            async move {
+                let start = Instant::now();
+                let update = rebuild(build_result_op, log_options, &emitted_hashes).await;
+                let _ = update_tx.send((start.elapsed(), update));
+                Ok::<Vc<()>, anyhow::Error>(Default::default())
+            }
+        });
+
+        Ok((root_task, update_rx))
+    }
+}
+
+/// Reads the build result, writes the output assets and reports issues.
+async fn emit_build(
+    build_result_op: OperationVc<OutputAssets>,
+    log_options: TransientInstance<LogOptions>,
+) -> Result<ReadRef<OutputAssets>> {
+    // Await the result to propagate any errors.
+    let output_assets = build_result_op.read_strongly_consistent().await?;
+
+    apply_effects(build_result_op).await?;
+
+    let issue_reporter: Vc<Box<dyn IssueReporter>> = Vc::upcast(ConsoleUi::new(log_options));
+
+    handle_issues(
+        build_result_op,
+        issue_reporter,
+        IssueSeverity::Error,
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(output_assets)
+}
+
+/// The duration and result of a single build in watch mode.
+type BuildUpdate = (Duration, Result<BuildStats>);
+
+/// Statistics of a single build in watch mode.
+struct BuildStats {
+    output_assets: usize,
+    /// The paths of the output assets whose content changed since the previous build.
+    changed_assets: Vec<RcStr>,
+    errors: usize,
+    warnings: usize,
+}
+
+impl std::fmt::Display for BuildStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} output assets, {} changed, {} errors, {} warnings",
+            self.output_assets,
+            self.changed_assets.len(),
+            self.errors,
+            self.warnings
+        )
+    }
+}
+
+async fn rebuild(
+    build_result_op: OperationVc<OutputAssets>,
+    log_options: TransientInstance<LogOptions>,
+    emitted_hashes: &Mutex<FxHashMap<RcStr, u64>>,
+) -> Result<BuildStats> {
+    let output_assets = emit_build(build_result_op, log_options).await?;
+
+    let issues = build_result_op.peek_issues().get_plain_issues().await?;
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity <= IssueSeverity::Error)
+        .count();
+    let warnings = issues
+        .iter()
+        .filter(|issue| issue.severity == IssueSeverity::Warning)
+        .count();
+
+    let hashes = output_assets
+        .iter()
+        .map(|asset| async move {
+            Ok((
+                asset.path().await?.path.clone(),
+                *asset.content().hash().await?,
+            ))
+        })
+        .try_join()
+        .await?;
+    let mut emitted_hashes = emitted_hashes.lock().unwrap();
+    let changed_assets = hashes
+        .iter()
+        .filter(|(path, hash)| emitted_hashes.get(path) != Some(hash))
+        .map(|(path, _)| path.clone())
+        .collect();
+    *emitted_hashes = hashes.into_iter().collect();
+
+    Ok(BuildStats {
+        output_assets: output_assets.len(),
+        changed_assets,
+        errors,
+        warnings,
+    })
+}
+
+const OUTPUT_DIR: &str = "dist";
+
+/// Returns the project directory relative to the root directory, with `/` as separator.
+fn project_relative_path(project_dir: &str, root_dir: &str) -> RcStr {
+    let project_relative = project_dir.strip_prefix(root_dir).unwrap();
+    project_relative
+        .strip_prefix(MAIN_SEPARATOR)
+        .unwrap_or(project_relative)
+        .replace(MAIN_SEPARATOR, "/")
+        .into()
+}
+
+fn url_imports_options(
+    project_path: &FileSystemPath,
+    url_import_prefixes: Vec<RcStr>,
+    frozen_url_imports: bool,
+) -> Result<Option<ResolvedVc<UrlImportsOptions>>> {
+    if url_import_prefixes.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(
+        UrlImportsOptions {
+            allowed_prefixes: url_import_prefixes,
+            cache_dir: project_path.join("turbopack.lock")?,
+            frozen_lockfile: frozen_url_imports,
+        }
+        .resolved_cell(),
+    ))
+}
+
+#[turbo_tasks::function(operation)]
+async fn explain_resolve_operation(
+    project_dir: RcStr,
+    root_dir: RcStr,
+    specifier: RcStr,
+    from: RcStr,
+    singleton_packages: Vec<RcStr>,
+    url_import_prefixes: Vec<RcStr>,
+    frozen_url_imports: bool,
+    federation: Option<ResolvedVc<FederationOptions>>,
+) -> Result<Vc<ResolveTrace>> {
+    let project_relative = project_relative_path(&project_dir, &root_dir);
+    let project_fs = project_fs(
+        root_dir,
+        false,
+        join_path(project_relative.as_str(), OUTPUT_DIR)
+            .unwrap()
+            .into(),
+    );
+    let project_path = project_fs.root().await?.join(&project_relative)?;
+    let from_path = project_path.join(&from)?;
+    let lookup_path = from_path.parent();
+    let url_imports = url_imports_options(&project_path, url_import_prefixes, frozen_url_imports)?;
+    let resolve_options_context = get_client_resolve_options_context(
+        project_path,
+        NodeEnv::Production.cell(),
+        singleton_packages,
+        url_imports,
+        federation,
+        None,
+    );
+    Ok(explain_resolve(
+        lookup_path.clone(),
+        ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::Import),
+        Request::parse_string(specifier).to_resolved().await?,
+        resolve_options(lookup_path, resolve_options_context)
+            .to_resolved()
+            .await?,
+    ))
+}
+
+fn print_resolve_trace(specifier: &str, from: &str, trace: &ResolveTrace) {
+    println!(
+        "{} - resolving `{specifier}` from `{from}`",
+        "explain".purple()
+    );
+    for group in &trace.groups {
+        println!(
+            "\n  {} in {}",
+            group.request.bold(),
+            group.lookup_path.dimmed()
+        );
+        for (kind, message) in &group.steps {
+            println!("    {}: {message}", kind.as_str().cyan());
+        }
+    }
+    println!("\n{} - {}", "result".green(), trace.result);
 }
 
 #[turbo_tasks::function(operation)]
@@ -190,20 +606,26 @@ async fn build_internal(
     browserslist_query: RcStr,
     source_maps_type: SourceMapsType,
     minify_type: MinifyType,
+    chunk_format: ChunkFormat,
     target: Target,
     scope_hoist: bool,
-) -> Result<Vc<()>> {
+    watch: bool,
+    singleton_packages: Vec<RcStr>,
+    url_import_prefixes: Vec<RcStr>,
+    frozen_url_imports: bool,
+    svg: Option<SvgImportMode>,
+    mangle_properties: Option<RcStr>,
+    minify_name_cache: bool,
+    federation: Option<ResolvedVc<FederationOptions>>,
+    library: bool,
+    boundary_rules: ResolvedVc<BoundaryRules>,
+    node_backend: NodeJsBackend,
+) -> Result<Vc<OutputAssets>> {
     let output_fs = output_fs(project_dir.clone());
-    const OUTPUT_DIR: &str = "dist";
-    let project_relative = project_dir.strip_prefix(&*root_dir).unwrap();
-    let project_relative: RcStr = project_relative
-        .strip_prefix(MAIN_SEPARATOR)
-        .unwrap_or(project_relative)
-        .replace(MAIN_SEPARATOR, "/")
-        .into();
+    let project_relative = project_relative_path(&project_dir, &root_dir);
     let project_fs = project_fs(
         root_dir.clone(),
-        /* watch= */ false,
+        watch,
         join_path(project_relative.as_str(), OUTPUT_DIR)
             .unwrap()
             .into(),
@@ -214,6 +636,16 @@ async fn build_internal(
 
     let node_env = NodeEnv::Production.cell();
 
+    if federation.is_some() && target != Target::Browser {
+        bail!("Module federation is only supported when building for the browser");
+    }
+    if federation.is_some() && matches!(chunk_format, ChunkFormat::EsModule) {
+        bail!("Module federation is not supported with ES module output");
+    }
+    if library && (target != Target::Node || federation.is_some()) {
+        bail!("Library builds are only supported when building for Node.js without federation");
+    }
+
     let build_output_root_to_root_path = project_path
         .join(OUTPUT_DIR)?
         .get_relative_path_to(&root_path)
@@ -245,15 +677,20 @@ async fn build_internal(
             .build(),
         ),
         load_env(root_path.clone()),
-    );
+    )
+    .with_backend(node_backend);
 
-    let asset_context = get_client_asset_context(
-        project_path.clone(),
-        execution_context,
-        compile_time_info,
-        node_env,
-        source_maps_type,
-    );
+    let url_imports = url_imports_options(&project_path, url_import_prefixes, frozen_url_imports)?;
+
+    // HTML entries aren't modules, they reference the actual entry modules with script and link
+    // tags.
+    let (html_entry_requests, entry_requests): (Vec<_>, Vec<_>) =
+        entry_requests.into_iter().partition(
+            |request| matches!(request, EntryRequest::Relative(path) if path.ends_with(".html")),
+        );
+    if !html_entry_requests.is_empty() && target != Target::Browser {
+        bail!("HTML entries are only supported when building for the browser");
+    }
 
     let entry_requests = (*entry_requests
         .into_iter()
@@ -277,38 +714,110 @@ async fn build_internal(
         .await?)
         .to_vec();
 
-    let origin = PlainResolveOrigin::new(asset_context, project_fs.root().await?.join("_")?);
-    let project_dir = &project_dir;
-    let entries = async move {
-        entry_requests
-            .into_iter()
-            .map(|request_vc| async move {
-                let ty = ReferenceType::Entry(EntryReferenceSubType::Undefined);
-                let request = request_vc.await?;
-                origin
-                    .resolve_asset(request_vc, origin.resolve_options(ty.clone()), ty)
-                    .await?
-                    .first_module()
-                    .await?
-                    .with_context(|| {
-                        format!(
-                            "Unable to resolve entry {} from directory {}.",
-                            request.request().unwrap(),
-                            project_dir
-                        )
-                    })
-            })
-            .try_join()
-            .await
+    if library {
+        return write_output_assets(
+            build_library(
+                project_path,
+                root_path,
+                build_output_root,
+                &project_dir,
+                &entry_requests,
+                execution_context,
+                compile_time_info,
+                runtime_type,
+                source_maps_type,
+                minify_type,
+                scope_hoist,
+                singleton_packages,
+                url_imports,
+                svg,
+                boundary_rules,
+            )
+            .await?,
+        )
+        .await;
     }
-    .instrument(tracing::info_span!("resolve entries"))
+
+    let asset_context = get_client_asset_context(
+        project_path.clone(),
+        execution_context,
+        compile_time_info,
+        node_env,
+        source_maps_type,
+        singleton_packages.clone(),
+        url_imports,
+        svg,
+        federation,
+        None,
+    );
+
+    let entries = resolve_entries(
+        asset_context,
+        project_fs.root().await?.join("_")?,
+        &entry_requests,
+        &project_dir,
+    )
     .await?;
 
+    let html_entries = html_entry_requests
+        .into_iter()
+        .map(|request| {
+            let project_path = project_path.clone();
+            async move {
+                let EntryRequest::Relative(path) = request else {
+                    unreachable!("only relative requests are HTML entries")
+                };
+                let html_path = project_path.join(&path)?;
+                let entry = resolve_html_entry(project_path, html_path, asset_context).await?;
+                anyhow::Ok((path, entry))
+            }
+        })
+        .try_join()
+        .instrument(tracing::info_span!("resolve html entries"))
+        .await?;
+
+    // The remote entry registers the modules the build exposes to other builds.
+    let federation_container = match federation {
+        Some(federation) if !federation.await?.exposes.is_empty() => Some(
+            asset_context
+                .process(
+                    federation_container_source(project_path.clone(), *federation),
+                    ReferenceType::Entry(EntryReferenceSubType::Undefined),
+                )
+                .module()
+                .to_resolved()
+                .await?,
+        ),
+        _ => None,
+    };
+
     let mut module_graph = ModuleGraph::from_modules(
-        Vc::cell(vec![ChunkGroupEntry::Entry(entries.clone())]),
+        Vc::cell(vec![ChunkGroupEntry::Entry(
+            entries
+                .iter()
+                .copied()
+                .chain(
+                    html_entries
+                        .iter()
+                        .flat_map(|(_, entry)| entry.modules.iter().copied()),
+                )
+                .chain(federation_container)
+                .collect(),
+        )]),
         false,
         true,
     );
+    detect_duplicate_packages(module_graph, singleton_packages)
+        .as_side_effect()
+        .await?;
+    check_boundary_rules(module_graph, project_path.clone(), *boundary_rules)
+        .as_side_effect()
+        .await?;
+    if let Some(url_imports) = url_imports {
+        write_url_imports_lock(module_graph, *url_imports)
+            .as_side_effect()
+            .await?;
+    }
     let module_id_strategy = ResolvedVc::upcast(
         get_global_module_id_strategy(module_graph)
             .to_resolved()
@@ -318,6 +827,31 @@ async fn build_internal(
         .resolve_strongly_consistent()
         .await?;
     module_graph = module_graph.without_unused_references(*binding_usage);
+    let property_mangling = match mangle_properties {
+        Some(pattern) => {
+            let name_cache = project_path.join("turbopack.props.json")?;
+            let mangling = compute_property_mangling(
+                module_graph,
+                PropertyManglingOptions {
+                    pattern,
+                    name_cache: Some(name_cache.clone()),
+                }
+                .cell(),
+            )
+            .to_resolved()
+            .await?;
+            write_property_name_cache(*mangling, name_cache)
+                .as_side_effect()
+                .await?;
+            Some(mangling)
+        }
+        None => None,
+    };
+    let minify_name_cache = if minify_name_cache {
+        Some(project_path.join("turbopack.names")?)
+    } else {
+        None
+    };
 
     let chunking_context: Vc<Box<dyn ChunkingContext>> = match target {
         Target::Browser => {
@@ -346,7 +880,17 @@ async fn build_internal(
             .export_usage(Some(binding_usage))
             .unused_references(Some(binding_usage))
             .current_chunk_method(CurrentChunkMethod::DocumentCurrentScript)
-            .minify_type(minify_type);
+            .chunk_format(chunk_format)
+            .minify_type(minify_type)
+            .property_mangling(property_mangling)
+            .minify_name_cache(minify_name_cache);
+
+            if let Some(federation) = federation {
+                let federation = federation.await?;
+                builder = builder
+                    .federation(Some(federation.name.clone()))
+                    .chunk_base_path(federation.public_path.clone());
+            }
 
             match *node_env.await? {
                 NodeEnv::Development => {}
@@ -395,7 +939,9 @@ async fn build_internal(
             .module_id_strategy(module_id_strategy)
             .export_usage(Some(binding_usage))
             .unused_references(Some(binding_usage))
-            .minify_type(minify_type);
+            .chunk_format(chunk_format)
+            .minify_type(minify_type)
+            .property_mangling(property_mangling);
 
             match *node_env.await? {
                 NodeEnv::Development => {}
@@ -463,7 +1009,10 @@ async fn build_internal(
                                                         .file_stem()
                                                         .unwrap(),
                                                 )?
-                                                .with_extension("entry.js"),
+                                                .with_extension(match chunk_format {
+                                                    ChunkFormat::Script => "entry.js",
+                                                    ChunkFormat::EsModule => "entry.mjs",
+                                                }),
                                             EvaluatableAssets::one(*ecmascript),
                                             module_graph,
                                             OutputAssets::empty(),
@@ -490,9 +1039,101 @@ async fn build_internal(
         .try_join()
         .await?;
 
+    let html_outputs = html_entries
+        .into_iter()
+        .map(|(path, entry)| {
+            let build_output_root = build_output_root.clone();
+            async move {
+                let chunk_groups = entry
+                    .modules
+                    .iter()
+                    .map(|&module| async move {
+                        let chunk_group = if let Some(evaluatable) =
+                            ResolvedVc::try_sidecast::<Box<dyn EvaluatableAsset>>(module)
+                        {
+                            chunking_context.evaluated_chunk_group_assets(
+                                module.ident(),
+                                ChunkGroup::Entry(vec![ResolvedVc::upcast(evaluatable)]),
+                                module_graph,
+                                AvailabilityInfo::root(),
+                            )
+                        } else {
+                            chunking_context.root_chunk_group_assets(
+                                module.ident(),
+                                ChunkGroup::Entry(vec![module]),
+                                module_graph,
+                            )
+                        };
+                        chunk_group.to_resolved().await
+                    })
+                    .try_join()
+                    .await?;
+                let html = HtmlEntryAsset::new(
+                    build_output_root.join(&path)?,
+                    *entry.source,
+                    chunk_groups,
+                    matches!(chunk_format, ChunkFormat::EsModule),
+                )
+                .to_resolved()
+                .await?;
+                anyhow::Ok(OutputAssetsWithReferenced::from_assets(Vc::cell(vec![
+                    ResolvedVc::upcast(html),
+                ])))
+            }
+        })
+        .try_join()
+        .await?;
+
+    let federation_outputs = match (federation, federation_container) {
+        (Some(federation), Some(container)) => {
+            let Some(container) = ResolvedVc::try_sidecast::<Box<dyn EvaluatableAsset>>(container)
+            else {
+                bail!("The module federation container is not chunkable");
+            };
+            let remote_entry = chunking_context.evaluated_chunk_group_assets(
+                AssetIdent::from_path(build_output_root.join("remoteEntry.js")?),
+                ChunkGroup::Entry(vec![ResolvedVc::upcast(container)]),
+                module_graph,
+                AvailabilityInfo::root(),
+            );
+            // The evaluate chunk, which loads the other chunks of the group, comes last.
+            let remote_entry_asset = *remote_entry
+                .await?
+                .assets
+                .await?
+                .last()
+                .context("The remote entry chunk group is empty")?;
+            let manifest = federation_manifest(
+                build_output_root.join("federation-manifest.json")?,
+                *federation,
+                *remote_entry_asset,
+            )
+            .to_resolved()
+            .await?;
+            vec![
+                remote_entry,
+                OutputAssetsWithReferenced::from_assets(Vc::cell(vec![manifest])),
+            ]
+        }
+        _ => vec![],
+    };
+
+    write_output_assets(
+        entry_chunk_groups
+            .into_iter()
+            .chain(html_outputs)
+            .chain(federation_outputs),
+    )
+    .await
+}
+
+/// Writes all assets of the output groups, including the ones they reference.
+async fn write_output_assets(
+    groups: impl IntoIterator<Item = Vc<OutputAssetsWithReferenced>>,
+) -> Result<Vc<OutputAssets>> {
     let all_assets = async move {
         let mut all_assets: FxHashSet<ResolvedVc<Box<dyn OutputAsset>>> = FxHashSet::default();
-        for group in entry_chunk_groups {
+        for group in groups {
             all_assets.extend(group.expand_all_assets().await?);
         }
         anyhow::Ok(all_assets)
@@ -506,7 +1147,41 @@ async fn build_internal(
         .try_join()
         .await?;
 
-    Ok(Default::default())
+    Ok(Vc::cell(all_assets.into_iter().collect()))
+}
+
+/// Resolves the entry requests to modules, relative to `origin_path`.
+async fn resolve_entries(
+    asset_context: Vc<Box<dyn AssetContext>>,
+    origin_path: FileSystemPath,
+    entry_requests: &[Vc<Request>],
+    project_dir: &str,
+) -> Result<Vec<ResolvedVc<Box<dyn Module>>>> {
+    let origin = PlainResolveOrigin::new(asset_context, origin_path);
+    async move {
+        entry_requests
+            .iter()
+            .map(|&request_vc| async move {
+                let ty = ReferenceType::Entry(EntryReferenceSubType::Undefined);
+                let request = request_vc.await?;
+                origin
+                    .resolve_asset(request_vc, origin.resolve_options(ty.clone()), ty)
+                    .await?
+                    .first_module()
+                    .await?
+                    .with_context(|| {
+                        format!(
+                            "Unable to resolve entry {} from directory {}.",
+                            request.request().unwrap(),
+                            project_dir
+                        )
+                    })
+            })
+            .try_join()
+            .await
+    }
+    .instrument(tracing::info_span!("resolve entries"))
+    .await
 }
 
 pub async fn build(args: &BuildArguments) -> Result<()> {
@@ -517,7 +1192,7 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
 
     let tt = TurboTasks::new(TurboTasksBackend::new(
         BackendOptions {
-            dependency_tracking: false,
+            dependency_tracking: args.watch,
             storage_mode: None,
             ..Default::default()
         },
@@ -543,15 +1218,60 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
                 mangle: Some(MangleType::OptimalSize),
             }
         })
+        .chunk_format(if args.esm {
+            ChunkFormat::EsModule
+        } else {
+            ChunkFormat::Script
+        })
         .scope_hoist(!args.no_scope_hoist)
+        .library(args.library)
         .target(args.common.target.unwrap_or(Target::Node))
-        .show_all(args.common.show_all);
+        .show_all(args.common.show_all)
+        .node_backend(args.common.node_backend());
 
     for entry in normalize_entries(&args.common.entries) {
         builder = builder.entry_request(EntryRequest::Relative(entry));
     }
 
-    builder.build().await?;
+    for name in &args.singleton_packages {
+        builder = builder.singleton_package(name.clone().into());
+    }
+
+    for prefix in &args.url_import_prefixes {
+        builder = builder.url_import_prefix(prefix.clone().into());
+    }
+    builder = builder.frozen_url_imports(args.frozen_url_imports);
+    builder = builder.svg(args.svg.map(SvgMode::import_mode));
+    builder = builder.mangle_properties(args.mangle_props.clone().map(RcStr::from));
+    builder = builder.minify_name_cache(args.minify_name_cache);
+
+    if let Some(federation) = &args.federation {
+        let path = Path::new(&*builder.project_dir).join(federation);
+        let config = std::fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read the federation config {}", path.display()))?;
+        let options: FederationOptions = serde_json::from_str(&config)
+            .with_context(|| format!("Invalid federation config {}", path.display()))?;
+        builder = builder.federation(Some(options));
+    }
+
+    if let Some(boundary_rules) = &args.boundary_rules {
+        let path = Path::new(&*builder.project_dir).join(boundary_rules);
+        let config = std::fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read the boundary rules {}", path.display()))?;
+        let rules: Vec<BoundaryRule> = serde_json::from_str(&config)
+            .with_context(|| format!("Invalid boundary rules {}", path.display()))?;
+        builder = builder.boundary_rules(rules);
+    }
+
+    if let (Some(specifier), Some(from)) = (&args.explain_resolve, &args.from) {
+        builder
+            .explain_resolve(specifier.clone().into(), from.clone().into())
+            .await?;
+    } else if args.watch {
+        builder.build_and_watch().await?;
+    } else {
+        builder.build().await?;
+    }
 
     // Intentionally leak this `Arc`. Otherwise we'll waste time during process exit performing a
     // ton of drop calls.
@@ -561,3 +1281,120 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fs, path::Path, time::Duration};
+
+    use dunce::canonicalize;
+    use tokio::sync::mpsc::UnboundedReceiver;
+    use turbo_rcstr::RcStr;
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_backend::{BackendOptions, TurboTasksBackend, noop_backing_storage};
+
+    use super::{BuildStats, BuildUpdate, OUTPUT_DIR, TurbopackBuildBuilder};
+    use crate::util::EntryRequest;
+
+    async fn next_build(updates: &mut UnboundedReceiver<BuildUpdate>) -> BuildStats {
+        let (_, stats) = tokio::time::timeout(Duration::from_secs(60), updates.recv())
+            .await
+            .expect("timed out waiting for a build")
+            .expect("the watcher stopped");
+        stats.unwrap()
+    }
+
+    /// Reads all emitted files, keyed by their path relative to the project directory.
+    fn read_output(project_dir: &Path) -> BTreeMap<String, String> {
+        fn read(dir: &Path, prefix: &str, files: &mut BTreeMap<String, String>) {
+            for entry in fs::read_dir(dir).unwrap() {
+                let entry = entry.unwrap();
+                let name = format!("{prefix}/{}", entry.file_name().to_str().unwrap());
+                if entry.file_type().unwrap().is_dir() {
+                    read(&entry.path(), &name, files);
+                } else {
+                    files.insert(name, fs::read_to_string(entry.path()).unwrap());
+                }
+            }
+        }
+
+        let mut files = BTreeMap::new();
+        read(&project_dir.join(OUTPUT_DIR), OUTPUT_DIR, &mut files);
+        files
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_build_and_watch_rebuilds_changed_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_dir = canonicalize(dir.path()).unwrap();
+        fs::write(
+            project_dir.join("index.js"),
+            "import(\"./a.js\").then(console.log);\nimport(\"./b.js\").then(console.log);\n",
+        )
+        .unwrap();
+        fs::write(project_dir.join("a.js"), "export default \"a-initial\";\n").unwrap();
+        fs::write(
+            project_dir.join("b.js"),
+            "export default \"b-unchanged\";\n",
+        )
+        .unwrap();
+
+        let tt = TurboTasks::new(TurboTasksBackend::new(
+            BackendOptions {
+                dependency_tracking: true,
+                storage_mode: None,
+                ..Default::default()
+            },
+            noop_backing_storage(),
+        ));
+        let project_dir_str: RcStr = project_dir.to_str().unwrap().into();
+        let (root_task, mut updates) =
+            TurbopackBuildBuilder::new(tt.clone(), project_dir_str.clone(), project_dir_str)
+                .entry_request(EntryRequest::Relative("./index.js".into()))
+                .watch_builds()
+                .await
+                .unwrap();
+
+        let initial = next_build(&mut updates).await;
+        assert_eq!(initial.errors, 0);
+        assert_eq!(initial.changed_assets.len(), initial.output_assets);
+        let before = read_output(&project_dir);
+        let chunk_containing = |output: &BTreeMap<String, String>, text: &str| {
+            output
+                .iter()
+                .find(|(_, content)| content.contains(text))
+                .map(|(path, _)| path.clone())
+                .unwrap_or_else(|| panic!("no chunk contains {text}: {output:#?}"))
+        };
+        let a_chunk = chunk_containing(&before, "a-initial");
+        let b_chunk = chunk_containing(&before, "b-unchanged");
+        assert_ne!(a_chunk, b_chunk);
+
+        fs::write(project_dir.join("a.js"), "export default \"a-changed\";\n").unwrap();
+        let rebuild = next_build(&mut updates).await;
+        let after = read_output(&project_dir);
+
+        assert_eq!(rebuild.errors, 0);
+        assert!(!rebuild.changed_assets.is_empty());
+        assert!(rebuild.changed_assets.len() < rebuild.output_assets);
+        assert!(
+            rebuild.changed_assets.iter().any(|path| **path == *a_chunk),
+            "{a_chunk} is missing from the changed assets {:?}",
+            rebuild.changed_assets
+        );
+        assert!(!rebuild.changed_assets.iter().any(|path| **path == *b_chunk));
+        assert!(after[&a_chunk].contains("a-changed"));
+        assert_eq!(after[&b_chunk], before[&b_chunk]);
+        // Only the chunks that were reported as changed were written again.
+        for (path, content) in &before {
+            if !rebuild
+                .changed_assets
+                .iter()
+                .any(|changed| **changed == **path)
+            {
+                assert_eq!(&after[path], content, "{path} changed");
+            }
+        }
+
+        tt.dispose_root_task(root_task);
+    }
+}
@@ -7,7 +7,7 @@ use turbo_tasks_fs::{FileSystem, FileSystemPath};
 use turbopack::{
     ModuleAssetContext,
     module_options::{
-        EcmascriptOptionsContext, JsxTransformOptions, ModuleOptionsContext,
+        EcmascriptOptionsContext, JsxTransformOptions, ModuleOptionsContext, SvgImportMode,
         TypescriptTransformOptions,
     },
 };
@@ -21,13 +21,24 @@ use turbopack_core::{
     environment::{BrowserEnvironment, Environment, ExecutionEnvironment},
     free_var_references,
     ident::Layer,
-    resolve::options::{ImportMap, ImportMapping},
+    resolve::{
+        ExternalTraced, ExternalType,
+        lockfile::read_locked_versions,
+        options::{ImportMap, ImportMapping},
+    },
+};
+use turbopack_ecmascript::{
+    TreeShakingMode,
+    federation::{FederationOptions, federation_import_map},
 };
-use turbopack_ecmascript::TreeShakingMode;
 use turbopack_node::{
     execution_context::ExecutionContext, transforms::postcss::PostCssTransformOptions,
 };
-use turbopack_resolve::resolve_options_context::ResolveOptionsContext;
+use turbopack_resolve::{
+    resolve_options_context::ResolveOptionsContext, url_imports::UrlImportsOptions,
+};
+
+use crate::build::library::library_dependencies;
 
 #[turbo_tasks::value(shared)]
 pub enum NodeEnv {
@@ -49,7 +60,11 @@ fn foreign_code_context_condition() -> ContextCondition {
 }
 
 #[turbo_tasks::function]
-pub async fn get_client_import_map(project_path: FileSystemPath) -> Result<Vc<ImportMap>> {
+pub async fn get_client_import_map(
+    project_path: FileSystemPath,
+    federation: Option<ResolvedVc<FederationOptions>>,
+    library_externals: Option<ExternalType>,
+) -> Result<Vc<ImportMap>> {
     let mut import_map = ImportMap::empty();
 
     import_map.insert_singleton_alias(rcstr!("@swc/helpers"), project_path.clone());
@@ -71,6 +86,21 @@ pub async fn get_client_import_map(project_path: FileSystemPath) -> Result<Vc<Im
         .resolved_cell(),
     );
 
+    // Shared packages of module federation replace the singleton aliases above.
+    if let Some(federation) = federation {
+        import_map.extend_ref(&*federation_import_map(*federation).await?);
+    }
+
+    // A library doesn't bundle its dependencies, the consumer installs them.
+    if let Some(external_type) = library_externals {
+        let external =
+            ImportMapping::External(None, external_type, ExternalTraced::Untraced).resolved_cell();
+        for package in library_dependencies(&project_path).await? {
+            import_map.insert_exact_alias(package.clone(), external);
+            import_map.insert_wildcard_alias(format!("{package}/"), external);
+        }
+    }
+
     Ok(import_map.cell())
 }
 
@@ -78,12 +108,25 @@ pub async fn get_client_import_map(project_path: FileSystemPath) -> Result<Vc<Im
 pub async fn get_client_resolve_options_context(
     project_path: FileSystemPath,
     node_env: Vc<NodeEnv>,
+    singleton_packages: Vec<RcStr>,
+    url_imports: Option<ResolvedVc<UrlImportsOptions>>,
+    federation: Option<ResolvedVc<FederationOptions>>,
+    library_externals: Option<ExternalType>,
 ) -> Result<Vc<ResolveOptionsContext>> {
-    let next_client_import_map = get_client_import_map(project_path.clone())
-        .to_resolved()
-        .await?;
+    let next_client_import_map =
+        get_client_import_map(project_path.clone(), federation, library_externals)
+            .to_resolved()
+            .await?;
     let module_options_context = ResolveOptionsContext {
         enable_node_modules: Some(project_path.root().owned().await?),
+        enable_pnp: Some(project_path.root().owned().await?),
+        locked_versions: Some(
+            read_locked_versions(project_path.root().owned().await?)
+                .to_resolved()
+                .await?,
+        ),
+        singleton_packages,
+        url_imports,
         custom_conditions: vec![node_env.await?.to_string().into(), rcstr!("browser")],
         import_map: Some(next_client_import_map),
         browser: true,
@@ -109,6 +152,11 @@ async fn get_client_module_options_context(
     env: ResolvedVc<Environment>,
     node_env: Vc<NodeEnv>,
     source_maps_type: SourceMapsType,
+    singleton_packages: Vec<RcStr>,
+    url_imports: Option<ResolvedVc<UrlImportsOptions>>,
+    svg: Option<SvgImportMode>,
+    federation: Option<ResolvedVc<FederationOptions>>,
+    library_externals: Option<ExternalType>,
 ) -> Result<Vc<ModuleOptionsContext>> {
     let is_dev = matches!(*node_env.await?, NodeEnv::Development);
     let module_options_context = ModuleOptionsContext {
@@ -116,11 +164,22 @@ async fn get_client_module_options_context(
         execution_context: Some(execution_context),
         tree_shaking_mode: Some(TreeShakingMode::ReexportsOnly),
         keep_last_successful_parse: is_dev,
+        enable_svg: svg,
+        ecmascript: EcmascriptOptionsContext {
+            enable_constant_export_inlining: !is_dev,
+            ..Default::default()
+        },
         ..Default::default()
     };
 
-    let resolve_options_context =
-        get_client_resolve_options_context(project_path.clone(), node_env);
+    let resolve_options_context = get_client_resolve_options_context(
+        project_path.clone(),
+        node_env,
+        singleton_packages,
+        url_imports,
+        federation,
+        library_externals,
+    );
 
     let enable_react_refresh = is_dev
         && assert_can_resolve_react_refresh(project_path.clone(), resolve_options_context)
@@ -163,15 +222,31 @@ pub fn get_client_asset_context(
     compile_time_info: Vc<CompileTimeInfo>,
     node_env: Vc<NodeEnv>,
     source_maps_type: SourceMapsType,
+    singleton_packages: Vec<RcStr>,
+    url_imports: Option<ResolvedVc<UrlImportsOptions>>,
+    svg: Option<SvgImportMode>,
+    federation: Option<ResolvedVc<FederationOptions>>,
+    library_externals: Option<ExternalType>,
 ) -> Vc<Box<dyn AssetContext>> {
-    let resolve_options_context =
-        get_client_resolve_options_context(project_path.clone(), node_env);
+    let resolve_options_context = get_client_resolve_options_context(
+        project_path.clone(),
+        node_env,
+        singleton_packages.clone(),
+        url_imports,
+        federation,
+        library_externals,
+    );
     let module_options_context = get_client_module_options_context(
         project_path,
         execution_context,
         compile_time_info.environment(),
         node_env,
         source_maps_type,
+        singleton_packages,
+        url_imports,
+        svg,
+        federation,
+        library_externals,
     );
 
     let asset_context: Vc<Box<dyn AssetContext>> = Vc::upcast(ModuleAssetContext::new(
@@ -40,7 +40,7 @@ use turbopack_dev_server::{
 };
 use turbopack_ecmascript_runtime::RuntimeType;
 use turbopack_env::dotenv::load_env;
-use turbopack_node::execution_context::ExecutionContext;
+use turbopack_node::{evaluate::NodeJsBackend, execution_context::ExecutionContext};
 use turbopack_nodejs::NodeJsChunkingContext;
 
 use self::web_entry_source::create_web_entry_source;
@@ -70,6 +70,7 @@ pub struct TurbopackDevServerBuilder {
     show_all: bool,
     log_detail: bool,
     allow_retry: bool,
+    node_backend: NodeJsBackend,
 }
 
 impl TurbopackDevServerBuilder {
@@ -94,6 +95,7 @@ impl TurbopackDevServerBuilder {
             show_all: false,
             log_detail: false,
             allow_retry: false,
+            node_backend: NodeJsBackend::ChildProcess,
         }
     }
 
@@ -142,6 +144,12 @@ impl TurbopackDevServerBuilder {
         self
     }
 
+    /// How webpack loaders and PostCSS are evaluated.
+    pub fn node_backend(mut self, node_backend: NodeJsBackend) -> TurbopackDevServerBuilder {
+        self.node_backend = node_backend;
+        self
+    }
+
     pub fn issue_reporter(
         mut self,
         issue_reporter: Box<dyn IssueReporterProvider>,
@@ -204,6 +212,7 @@ impl TurbopackDevServerBuilder {
         let show_all = self.show_all;
         let log_detail: bool = self.log_detail;
         let browserslist_query: RcStr = self.browserslist_query;
+        let node_backend = self.node_backend;
         let log_args = TransientInstance::new(LogOptions {
             current_dir: current_dir().unwrap(),
             project_dir: PathBuf::from(project_dir.clone()),
@@ -225,6 +234,7 @@ impl TurbopackDevServerBuilder {
             entry_requests: Arc<Vec<EntryRequest>>,
             eager_compile: bool,
             browserslist_query: RcStr,
+            node_backend: NodeJsBackend,
         }
         impl SourceProvider for ServerSourceProvider {
             fn get_source(&self) -> OperationVc<Box<dyn ContentSource>> {
@@ -234,6 +244,7 @@ impl TurbopackDevServerBuilder {
                     self.entry_requests.clone(),
                     self.eager_compile,
                     self.browserslist_query.clone(),
+                    self.node_backend,
                 )
             }
         }
@@ -243,6 +254,7 @@ impl TurbopackDevServerBuilder {
             entry_requests,
             eager_compile,
             browserslist_query,
+            node_backend,
         };
 
         let issue_reporter_arc = Arc::new(move || issue_provider.get_issue_reporter());
@@ -257,6 +269,7 @@ async fn source(
     entry_requests: Arc<Vec<EntryRequest>>,
     eager_compile: bool,
     browserslist_query: RcStr,
+    node_backend: NodeJsBackend,
 ) -> Result<Vc<Box<dyn ContentSource>>> {
     let project_relative = project_dir.strip_prefix(&*root_dir).unwrap();
     let project_relative: RcStr = project_relative
@@ -299,7 +312,8 @@ async fn source(
     .build();
 
     let execution_context =
-        ExecutionContext::new(root_path.clone(), Vc::upcast(build_chunking_context), env);
+        ExecutionContext::new(root_path.clone(), Vc::upcast(build_chunking_context), env)
+            .with_backend(node_backend);
 
     let server_fs = Vc::upcast::<Box<dyn FileSystem>>(ServerFileSystem::new());
     let server_root = server_fs.root().owned().await?;
@@ -385,6 +399,7 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         .port(args.port)
         .log_detail(args.common.log_detail)
         .show_all(args.common.show_all)
+        .node_backend(args.common.node_backend())
         .log_level(
             args.common
                 .log_level
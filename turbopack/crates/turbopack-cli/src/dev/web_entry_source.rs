@@ -66,7 +66,7 @@ pub async fn get_client_runtime_entries(
     node_env: Vc<NodeEnv>,
 ) -> Result<Vc<RuntimeEntries>> {
     let resolve_options_context =
-        get_client_resolve_options_context(project_path.clone(), node_env);
+        get_client_resolve_options_context(project_path.clone(), node_env, vec![], None, None);
 
     let mut runtime_entries = Vec::new();
 
@@ -120,6 +120,11 @@ pub async fn create_web_entry_source(
         compile_time_info,
         node_env,
         source_maps_type,
+        vec![],
+        None,
+        None,
+        None,
+        None,
     );
     let chunking_context = get_client_chunking_context(
         root_path.clone(),
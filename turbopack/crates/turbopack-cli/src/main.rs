@@ -8,6 +8,7 @@ use clap::Parser;
 use tracing_subscriber::{Registry, layer::SubscriberExt, util::SubscriberInitExt};
 use turbo_tasks_malloc::TurboMalloc;
 use turbopack_cli::arguments::Arguments;
+use turbopack_node::pool_policy::set_pool_policy;
 use turbopack_trace_utils::{
     exit::ExitHandler,
     filter_layer::FilterLayer,
@@ -105,6 +106,8 @@ async fn main_inner(args: Arguments) -> Result<()> {
         subscriber.init();
     }
 
+    set_pool_policy(args.node_pool_policy())?;
+
     match args {
         Arguments::Build(args) => turbopack_cli::build::build(&args).await,
         Arguments::Dev(args) => turbopack_cli::dev::start_server(&args).await,
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use anyhow::{Result, bail};
 use bincode::{Decode, Encode};
 use rustc_hash::FxHashMap;
@@ -65,6 +67,14 @@ impl Default for MinifyType {
     }
 }
 
+/// Renames of object properties (original name to mangled name) that are applied when minifying
+/// every chunk, so that a property has the same name in all chunks.
+#[turbo_tasks::value(transparent)]
+pub struct PropertyMangling(BTreeMap<RcStr, RcStr>);
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionPropertyMangling(Option<ResolvedVc<PropertyMangling>>);
+
 #[turbo_tasks::value(shared)]
 #[derive(Debug, Default, TaskInput, Clone, Copy, Hash, DeterministicHash)]
 pub enum SourceMapsType {
@@ -77,6 +87,21 @@ pub enum SourceMapsType {
     None,
 }
 
+/// The format JavaScript chunks are emitted in.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, TaskInput, Clone, Copy, Hash, DeterministicHash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChunkFormat {
+    /// Chunks register themselves with the Turbopack runtime, either via a global (browser) or
+    /// via `module.exports` (Node.js).
+    #[default]
+    Script,
+    /// Chunks are ES modules. Entry chunks statically import the chunks they depend on, async
+    /// chunks are loaded with `import()` and asset URLs are resolved relative to
+    /// `import.meta.url`.
+    EsModule,
+}
+
 #[derive(
     Debug,
     TaskInput,
@@ -377,11 +402,23 @@ pub trait ChunkingContext {
         MinifyType::NoMinify.cell()
     }
 
+    /// Object properties to rename when minifying, see [PropertyMangling].
+    #[turbo_tasks::function]
+    fn property_mangling(self: Vc<Self>) -> Vc<OptionPropertyMangling> {
+        Vc::cell(None)
+    }
+
     #[turbo_tasks::function]
     fn should_use_absolute_url_references(self: Vc<Self>) -> Vc<bool> {
         Vc::cell(false)
     }
 
+    /// The format JavaScript chunks are emitted in.
+    #[turbo_tasks::function]
+    fn chunk_format(self: Vc<Self>) -> Vc<ChunkFormat> {
+        ChunkFormat::Script.cell()
+    }
+
     #[turbo_tasks::function]
     fn async_loader_chunk_item(
         &self,
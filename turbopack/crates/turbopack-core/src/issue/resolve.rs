@@ -10,6 +10,7 @@ use crate::{
     error::PrettyPrintError,
     issue::{IssueSeverity, OptionIssueSource},
     resolve::{
+        find_unmatched_conditions,
         options::{ImportMap, ImportMapResult, ResolveOptions},
         parse::Request,
     },
@@ -80,6 +81,40 @@ impl Issue for ResolvingIssue {
                 }
             }
         }
+
+        // Explain why the package can't be used when none of the conditions of its `exports` or
+        // `imports` entry is active. The error, if any, explains the failure instead.
+        if self.error_message.is_none()
+            && let Ok(Some(unmatched)) = find_unmatched_conditions(
+                self.file_path.clone(),
+                *self.request,
+                *self.resolve_options,
+            )
+            .await
+        {
+            writeln!(
+                description,
+                "No `{}` entry of {} for `{}` matches the active conditions.",
+                unmatched.field,
+                unmatched.package_json_path.value_to_string().await?,
+                unmatched.request
+            )?;
+            writeln!(
+                description,
+                "Active conditions: {}",
+                unmatched.active_conditions.join(", ")
+            )?;
+            writeln!(
+                description,
+                "Conditions of the entry: {}",
+                unmatched.available_conditions.join(", ")
+            )?;
+            writeln!(
+                description,
+                "Add one of the conditions of the entry to the custom conditions of the resolve \
+                 options if the package should be used here."
+            )?;
+        }
         Ok(Vc::cell(Some(
             StyledString::Text(description.into()).resolved_cell(),
         )))
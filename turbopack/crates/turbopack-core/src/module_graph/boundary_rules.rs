@@ -0,0 +1,462 @@
+use anyhow::{Context, Result, bail};
+use auto_hash_map::AutoSet;
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+use turbo_rcstr::{RcStr, rcstr};
+use turbo_tasks::{FxIndexSet, ReadRef, ResolvedVc, TryJoinIterExt, Vc};
+use turbo_tasks_fs::{
+    FileSystemPath,
+    glob::{Glob, GlobOptions},
+};
+
+use crate::{
+    ident::AssetIdent,
+    issue::{
+        ImportTrace, Issue, IssueExt, IssueSeverity, IssueStage, OptionStyledString, StyledString,
+    },
+    module::Module,
+    module_graph::{ModuleGraph, duplicate_packages::package_of_path},
+};
+
+/// A dependency rule of the project's architecture, checked against the module graph by
+/// [`check_boundary_rules`]. Paths are globs relative to the project directory.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BoundaryRule {
+    /// Modules matching `from` (or any module, if it's omitted) must not import modules matching
+    /// `to`, or modules of the npm `package`. Without `from`, the package's imports of its own
+    /// modules are allowed. With a `layer`, only importers in that layer are checked.
+    #[serde(rename_all = "camelCase")]
+    Forbidden {
+        name: RcStr,
+        #[serde(default)]
+        from: Option<RcStr>,
+        #[serde(default)]
+        to: Option<RcStr>,
+        #[serde(default)]
+        package: Option<RcStr>,
+        #[serde(default)]
+        layer: Option<RcStr>,
+    },
+    /// Modules matching `path` must not be part of a cycle of static imports.
+    #[serde(rename_all = "camelCase")]
+    NoCycles { name: RcStr, path: RcStr },
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct BoundaryRules(Vec<BoundaryRule>);
+
+#[turbo_tasks::value(transparent)]
+pub struct BoundaryRuleViolations(Vec<ResolvedVc<BoundaryRuleViolationIssue>>);
+
+/// A [`BoundaryRule`] with its globs parsed.
+enum CompiledRule<'a> {
+    Forbidden {
+        name: &'a RcStr,
+        from: Option<Glob>,
+        to: Option<Glob>,
+        package: Option<&'a RcStr>,
+        layer: Option<&'a RcStr>,
+    },
+    NoCycles {
+        name: &'a RcStr,
+        path: Glob,
+    },
+}
+
+fn parse_glob(name: &RcStr, glob: &RcStr) -> Result<Glob> {
+    Glob::parse(glob.clone(), GlobOptions::default())
+        .with_context(|| format!("Invalid glob {glob} in the boundary rule {name}"))
+}
+
+impl<'a> CompiledRule<'a> {
+    fn new(rule: &'a BoundaryRule) -> Result<Self> {
+        Ok(match rule {
+            BoundaryRule::Forbidden {
+                name,
+                from,
+                to,
+                package,
+                layer,
+            } => {
+                if to.is_none() && package.is_none() {
+                    bail!("The boundary rule {name} needs a `to` glob or a `package`");
+                }
+                CompiledRule::Forbidden {
+                    name,
+                    from: from
+                        .as_ref()
+                        .map(|from| parse_glob(name, from))
+                        .transpose()?,
+                    to: to.as_ref().map(|to| parse_glob(name, to)).transpose()?,
+                    package: package.as_ref(),
+                    layer: layer.as_ref(),
+                }
+            }
+            BoundaryRule::NoCycles { name, path } => CompiledRule::NoCycles {
+                name,
+                path: parse_glob(name, path)?,
+            },
+        })
+    }
+
+    fn name(&self) -> &RcStr {
+        match self {
+            CompiledRule::Forbidden { name, .. } | CompiledRule::NoCycles { name, .. } => name,
+        }
+    }
+
+    /// Whether `from` importing `to` violates this rule.
+    fn forbids_import(
+        &self,
+        project_path: &FileSystemPath,
+        from: &AssetIdent,
+        to: &AssetIdent,
+    ) -> bool {
+        let CompiledRule::Forbidden {
+            from: from_glob,
+            to: to_glob,
+            package,
+            layer,
+            ..
+        } = self
+        else {
+            return false;
+        };
+        if let Some(layer) = layer
+            && from.layer.as_ref().map(|l| l.name()) != Some(*layer)
+        {
+            return false;
+        }
+        if let Some(from_glob) = from_glob
+            && !matches_path(from_glob, project_path, &from.path)
+        {
+            return false;
+        }
+        to_glob
+            .as_ref()
+            .is_some_and(|to_glob| matches_path(to_glob, project_path, &to.path))
+            || package.is_some_and(|package| {
+                imports_package(package, from_glob.is_some(), &from.path.path, &to.path.path)
+            })
+    }
+}
+
+/// Whether `from` importing `to` is an import of the `package`. Imports within the package itself
+/// only count when the rule restricts the importers with a `from` glob, otherwise every package
+/// would violate the rule that forbids it.
+fn imports_package(package: &str, has_from_glob: bool, from: &str, to: &str) -> bool {
+    let in_package = |path: &str| package_of_path(path).is_some_and(|(name, _)| name == package);
+    in_package(to) && (has_from_glob || !in_package(from))
+}
+
+/// Matches the path relative to the project directory. Files outside of it never match.
+fn matches_path(glob: &Glob, project_path: &FileSystemPath, path: &FileSystemPath) -> bool {
+    project_path
+        .get_path_to(path)
+        .is_some_and(|path| glob.matches(path))
+}
+
+/// Checks the imports in the module graph against the `rules` and emits an error for every
+/// violation, with the import traces of the offending module.
+#[turbo_tasks::function]
+pub async fn check_boundary_rules(
+    graph: Vc<ModuleGraph>,
+    project_path: FileSystemPath,
+    rules: Vc<BoundaryRules>,
+) -> Result<Vc<BoundaryRuleViolations>> {
+    let rules = rules.await?;
+    if rules.is_empty() {
+        return Ok(Vc::cell(vec![]));
+    }
+    let rules = rules
+        .iter()
+        .map(CompiledRule::new)
+        .collect::<Result<Vec<_>>>()?;
+
+    let graph = graph.read_graphs().await?;
+    let idents: FxHashMap<ResolvedVc<Box<dyn Module>>, ReadRef<AssetIdent>> = graph
+        .graphs
+        .iter()
+        .flat_map(|graph| graph.iter_nodes())
+        .map(async |module| Ok((module, module.ident().await?)))
+        .try_join()
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut violations = FxIndexSet::default();
+
+    if rules
+        .iter()
+        .any(|rule| matches!(rule, CompiledRule::Forbidden { .. }))
+    {
+        graph.traverse_edges_unordered(|parent, target| {
+            let Some((parent, _)) = parent else {
+                return Ok(());
+            };
+            let from = &idents[&parent];
+            let to = &idents[&target];
+            for (rule_index, rule) in rules.iter().enumerate() {
+                if rule.forbids_import(&project_path, from, to) {
+                    violations.insert((rule_index, vec![parent, target]));
+                }
+            }
+            Ok(())
+        })?;
+    }
+
+    for (rule_index, rule) in rules.iter().enumerate() {
+        let CompiledRule::NoCycles { path, .. } = rule else {
+            continue;
+        };
+        graph.traverse_cycles(
+            |ref_data| ref_data.chunking_type.is_parallel(),
+            |cycle| {
+                let mut modules: Vec<_> = cycle
+                    .iter()
+                    .map(|module| **module)
+                    .filter(|module| matches_path(path, &project_path, &idents[module].path))
+                    .collect();
+                if modules.is_empty() {
+                    return Ok(());
+                }
+                // Report the cycle at the matching module with the smallest path, followed by
+                // the other modules of the cycle.
+                modules.sort_by(|a, b| idents[a].path.path.cmp(&idents[b].path.path));
+                let first = modules[0];
+                let mut rest: Vec<_> = cycle
+                    .iter()
+                    .map(|module| **module)
+                    .filter(|module| *module != first)
+                    .collect();
+                rest.sort_by(|a, b| idents[a].path.path.cmp(&idents[b].path.path));
+                violations.insert((rule_index, [first].into_iter().chain(rest).collect()));
+                Ok(())
+            },
+        )?;
+    }
+
+    let issues = violations
+        .into_iter()
+        .map(|(rule_index, modules)| {
+            let rule = &rules[rule_index];
+            BoundaryRuleViolationIssue {
+                rule: rule.name().clone(),
+                kind: match rule {
+                    CompiledRule::Forbidden { .. } => BoundaryRuleViolationKind::Import,
+                    CompiledRule::NoCycles { .. } => BoundaryRuleViolationKind::Cycle,
+                },
+                modules: modules
+                    .iter()
+                    .map(|module| idents[module].path.clone())
+                    .collect(),
+                import_traces: vec![],
+            }
+            .resolved_cell()
+        })
+        .collect::<Vec<_>>();
+
+    // The traces lead from the importing module, i.e. the issue's file, to the entries.
+    let issue_set: AutoSet<ResolvedVc<Box<dyn Issue>>> = issues
+        .iter()
+        .map(|issue| ResolvedVc::upcast(*issue))
+        .collect();
+    let mut traces: FxHashMap<ResolvedVc<Box<dyn Issue>>, Vec<ImportTrace>> = FxHashMap::default();
+    for graph in &graph.graphs {
+        for (issue, issue_traces) in graph.compute_import_traces_for_issues(&issue_set).await? {
+            traces.entry(issue).or_default().extend(issue_traces);
+        }
+    }
+
+    let issues = issues
+        .into_iter()
+        .map(async |issue| {
+            let import_traces = traces
+                .remove(&ResolvedVc::upcast(issue))
+                .unwrap_or_default();
+            let issue = BoundaryRuleViolationIssue {
+                import_traces,
+                ..(*issue.await?).clone()
+            }
+            .resolved_cell();
+            issue.emit();
+            Ok(issue)
+        })
+        .try_join()
+        .await?;
+    Ok(Vc::cell(issues))
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Copy, Debug)]
+pub enum BoundaryRuleViolationKind {
+    /// `modules[0]` imports `modules[1]`.
+    Import,
+    /// `modules` form a cycle.
+    Cycle,
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+pub struct BoundaryRuleViolationIssue {
+    pub rule: RcStr,
+    pub kind: BoundaryRuleViolationKind,
+    /// The offending module first.
+    pub modules: Vec<FileSystemPath>,
+    pub import_traces: Vec<ImportTrace>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for BoundaryRuleViolationIssue {
+    fn severity(&self) -> IssueSeverity {
+        IssueSeverity::Error
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text(match self.kind {
+                BoundaryRuleViolationKind::Import => rcstr!("Import violates the boundary rule "),
+                BoundaryRuleViolationKind::Cycle => {
+                    rcstr!("Import cycle violates the boundary rule ")
+                }
+            }),
+            StyledString::Code(self.rule.clone()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Analysis.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.modules[0].clone().cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        let description = match self.kind {
+            BoundaryRuleViolationKind::Import => StyledString::Line(vec![
+                StyledString::Code(self.modules[0].to_string().into()),
+                StyledString::Text(rcstr!(" imports ")),
+                StyledString::Code(self.modules[1].to_string().into()),
+            ]),
+            BoundaryRuleViolationKind::Cycle => {
+                let mut lines = vec![StyledString::Text(rcstr!(
+                    "These modules import each other:"
+                ))];
+                lines.extend(
+                    self.modules
+                        .iter()
+                        .map(|path| StyledString::Text(format!("- {path}").into())),
+                );
+                StyledString::Stack(lines)
+            }
+        };
+        Vc::cell(Some(description.resolved_cell()))
+    }
+
+    #[turbo_tasks::function]
+    fn detail(&self) -> Vc<OptionStyledString> {
+        if self.import_traces.is_empty() {
+            return Vc::cell(None);
+        }
+        let mut lines = vec![];
+        for trace in &self.import_traces {
+            lines.push(StyledString::Text(rcstr!("Import trace:")));
+            lines.extend(trace.iter().map(|ident| {
+                StyledString::Text(match &ident.layer {
+                    Some(layer) => {
+                        format!("  {} [{}]", ident.path, layer.user_friendly_name()).into()
+                    }
+                    None => format!("  {}", ident.path).into(),
+                })
+            }));
+        }
+        Vc::cell(Some(StyledString::Stack(lines).resolved_cell()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{BoundaryRule, imports_package};
+
+    #[test]
+    fn test_parse_boundary_rules() {
+        let rules: Vec<BoundaryRule> = serde_json::from_value(json!([
+            {
+                "type": "forbidden",
+                "name": "server-only",
+                "from": "app/**/*.client.tsx",
+                "to": "src/server/**",
+            },
+            {
+                "type": "forbidden",
+                "name": "no-fs-in-edge",
+                "package": "fs-extra",
+                "layer": "edge",
+            },
+            { "type": "noCycles", "name": "core-acyclic", "path": "packages/core/**" },
+        ]))
+        .unwrap();
+        assert!(matches!(
+            &rules[0],
+            BoundaryRule::Forbidden { from: Some(from), to: Some(to), package: None, layer: None, .. }
+                if from == "app/**/*.client.tsx" && to == "src/server/**"
+        ));
+        assert!(matches!(
+            &rules[1],
+            BoundaryRule::Forbidden { from: None, to: None, package: Some(package), layer: Some(layer), .. }
+                if package == "fs-extra" && layer == "edge"
+        ));
+        assert!(matches!(
+            &rules[2],
+            BoundaryRule::NoCycles { name, path } if name == "core-acyclic" && path == "packages/core/**"
+        ));
+    }
+
+    #[test]
+    fn test_imports_package() {
+        let to = "node_modules/fs-extra/lib/index.js";
+        assert!(imports_package("fs-extra", false, "src/index.js", to));
+        // The package's imports of its own modules are allowed
+        assert!(!imports_package(
+            "fs-extra",
+            false,
+            "node_modules/fs-extra/index.js",
+            to
+        ));
+        // unless the rule restricts the importers
+        assert!(imports_package(
+            "fs-extra",
+            true,
+            "node_modules/fs-extra/index.js",
+            to
+        ));
+        assert!(imports_package(
+            "fs-extra",
+            false,
+            "node_modules/other/index.js",
+            to
+        ));
+        assert!(!imports_package(
+            "fs-extra",
+            false,
+            "src/index.js",
+            "node_modules/graceful-fs/index.js"
+        ));
+        assert!(!imports_package(
+            "fs-extra",
+            false,
+            "src/index.js",
+            "src/fs-extra/index.js"
+        ));
+    }
+}
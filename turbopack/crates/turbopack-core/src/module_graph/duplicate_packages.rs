@@ -0,0 +1,201 @@
+use anyhow::Result;
+use bincode::{Decode, Encode};
+use turbo_rcstr::{RcStr, rcstr};
+use turbo_tasks::{FxIndexMap, FxIndexSet, NonLocalValue, TryJoinIterExt, Vc, trace::TraceRawVcs};
+use turbo_tasks_fs::FileSystemPath;
+
+use crate::{
+    file_source::FileSource,
+    issue::{Issue, IssueExt, IssueSeverity, IssueStage, OptionStyledString, StyledString},
+    module_graph::ModuleGraph,
+    package_json::read_package_json,
+};
+
+#[derive(Clone, PartialEq, Eq, TraceRawVcs, Debug, NonLocalValue, Encode, Decode)]
+pub struct PackageCopy {
+    pub dir: FileSystemPath,
+    pub version: Option<RcStr>,
+}
+
+/// All packages that are included from more than one directory, by package name.
+#[turbo_tasks::value(transparent)]
+pub struct DuplicatePackages(
+    #[bincode(with = "turbo_bincode::indexmap")] FxIndexMap<RcStr, Vec<PackageCopy>>,
+);
+
+/// Returns the name of the package containing `path` and the length of the package directory,
+/// based on the last `node_modules` segment.
+pub(super) fn package_of_path(path: &str) -> Option<(&str, usize)> {
+    let start = match path.rfind("/node_modules/") {
+        Some(index) => index + "/node_modules/".len(),
+        None if path.starts_with("node_modules/") => "node_modules/".len(),
+        None => return None,
+    };
+    let rest = &path[start..];
+    let mut segments = rest.split('/');
+    let scope = segments.next().filter(|segment| !segment.is_empty())?;
+    let name_len = if scope.starts_with('@') {
+        scope.len() + 1 + segments.next()?.len()
+    } else if scope.starts_with('.') {
+        // e.g. `node_modules/.pnpm` or `node_modules/.bin`
+        return None;
+    } else {
+        scope.len()
+    };
+    // The path needs to be a file inside of the package
+    segments.next()?;
+    Some((&rest[..name_len], start + name_len))
+}
+
+/// Finds packages that are included multiple times in the module graph, e.g. because different
+/// versions are installed in nested `node_modules` folders, and emits an issue for each of them.
+/// Duplicates of `singleton_packages` are errors, other duplicates are warnings.
+#[turbo_tasks::function]
+pub async fn detect_duplicate_packages(
+    graph: Vc<ModuleGraph>,
+    singleton_packages: Vec<RcStr>,
+) -> Result<Vc<DuplicatePackages>> {
+    let graph = graph.read_graphs().await?;
+    let paths = graph
+        .graphs
+        .iter()
+        .flat_map(|graph| graph.iter_nodes())
+        .map(async |module| Ok(module.ident().await?.path.clone()))
+        .try_join()
+        .await?;
+
+    let mut packages: FxIndexMap<RcStr, FxIndexSet<FileSystemPath>> = FxIndexMap::default();
+    for path in paths {
+        let Some((name, dir_len)) = package_of_path(&path.path) else {
+            continue;
+        };
+        let mut dir = path.parent();
+        while dir.path.len() > dir_len {
+            dir = dir.parent();
+        }
+        packages.entry(name.into()).or_default().insert(dir);
+    }
+
+    let duplicates = packages
+        .into_iter()
+        .filter(|(_, dirs)| dirs.len() > 1)
+        .map(async |(name, dirs)| {
+            let copies = dirs
+                .into_iter()
+                .map(async |dir| {
+                    let package_json =
+                        read_package_json(Vc::upcast(FileSource::new(dir.join("package.json")?)))
+                            .await?;
+                    let version = package_json
+                        .as_ref()
+                        .and_then(|package_json| package_json["version"].as_str())
+                        .map(RcStr::from);
+                    Ok(PackageCopy { dir, version })
+                })
+                .try_join()
+                .await?;
+            DuplicatePackageIssue {
+                singleton: singleton_packages.contains(&name),
+                name: name.clone(),
+                copies: copies.clone(),
+            }
+            .resolved_cell()
+            .emit();
+            Ok((name, copies))
+        })
+        .try_join()
+        .await?;
+    Ok(Vc::cell(duplicates.into_iter().collect()))
+}
+
+#[turbo_tasks::value(shared)]
+pub struct DuplicatePackageIssue {
+    pub name: RcStr,
+    pub copies: Vec<PackageCopy>,
+    pub singleton: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DuplicatePackageIssue {
+    fn severity(&self) -> IssueSeverity {
+        if self.singleton {
+            IssueSeverity::Error
+        } else {
+            IssueSeverity::Warning
+        }
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text(rcstr!("Package ")),
+            StyledString::Code(self.name.clone()),
+            StyledString::Text(format!(" is included {} times", self.copies.len()).into()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Resolve.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.copies[0].dir.clone().cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        let mut lines = vec![StyledString::Text(
+            if self.singleton {
+                "It is configured as a singleton package, but multiple copies are installed:"
+            } else {
+                "Multiple copies are installed, which increases the bundle size and breaks \
+                 packages that rely on module state:"
+            }
+            .into(),
+        )];
+        lines.extend(self.copies.iter().map(|copy| {
+            StyledString::Text(
+                format!(
+                    "- {} ({})",
+                    copy.dir.path,
+                    copy.version.as_deref().unwrap_or("unknown version")
+                )
+                .into(),
+            )
+        }));
+        Vc::cell(Some(StyledString::Stack(lines).resolved_cell()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::package_of_path;
+
+    #[test]
+    fn test_package_of_path() {
+        assert_eq!(
+            package_of_path("node_modules/react/index.js"),
+            Some(("react", "node_modules/react".len()))
+        );
+        assert_eq!(
+            package_of_path("app/node_modules/lib/node_modules/@scope/pkg/dist/index.js"),
+            Some((
+                "@scope/pkg",
+                "app/node_modules/lib/node_modules/@scope/pkg".len()
+            ))
+        );
+        assert_eq!(
+            package_of_path("node_modules/.pnpm/react@18.2.0/node_modules/react/index.js"),
+            Some((
+                "react",
+                "node_modules/.pnpm/react@18.2.0/node_modules/react".len()
+            ))
+        );
+        assert_eq!(package_of_path("node_modules/.bin/next"), None);
+        assert_eq!(package_of_path("src/node_modules_utils/index.js"), None);
+        assert_eq!(package_of_path("src/index.js"), None);
+    }
+}
@@ -44,7 +44,9 @@ use crate::{
 
 pub mod async_module_info;
 pub mod binding_usage_info;
+pub mod boundary_rules;
 pub mod chunk_group_info;
+pub mod duplicate_packages;
 pub mod merged_modules;
 pub mod module_batch;
 pub(crate) mod module_batches;
@@ -75,6 +75,8 @@ pub enum EcmaScriptModulesReferenceSubType {
     ImportPart(ModulePart),
     Import,
     ImportWithType(ImportWithType),
+    /// `import source x from "..."`, imports the source representation of the module.
+    ImportSource,
     DynamicImport,
     Custom(u8),
     #[default]
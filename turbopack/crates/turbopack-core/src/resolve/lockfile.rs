@@ -0,0 +1,412 @@
+//! Reading of the package versions pinned by a lockfile (`pnpm-lock.yaml`, `package-lock.json`
+//! or `yarn.lock`), to detect when `node_modules` is out of sync with it.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context, Result};
+use serde_json::Value as JsonValue;
+use turbo_rcstr::{RcStr, rcstr};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+
+use crate::{
+    file_source::FileSource,
+    issue::{Issue, IssueExt, IssueSeverity, IssueStage, OptionStyledString, StyledString},
+    package_json::read_package_json,
+    resolve::options::LockedVersions,
+};
+
+type PackageVersions = BTreeMap<RcStr, BTreeSet<RcStr>>;
+
+fn insert_version(packages: &mut PackageVersions, name: &str, version: &str) {
+    packages
+        .entry(name.into())
+        .or_default()
+        .insert(version.into());
+}
+
+/// Splits a `pnpm-lock.yaml` package key into name and version. Lockfiles before v6 use
+/// `/name/version_peers`, later versions use `/name@version(peers)` (without the leading slash
+/// since v9).
+fn parse_pnpm_package_key(key: &str, v5: bool) -> Option<(&str, &str)> {
+    let key = key.strip_prefix('/').unwrap_or(key);
+    let scope_len = if key.starts_with('@') {
+        key.find('/')? + 1
+    } else {
+        0
+    };
+    if v5 {
+        let (name, version) = key.split_at(scope_len + key[scope_len..].find('/')?);
+        let version = &version[1..];
+        Some((name, version.split('_').next()?))
+    } else {
+        let key = key.split('(').next()?;
+        let (name, version) = key.split_at(scope_len + key[scope_len..].find('@')?);
+        Some((name, &version[1..]))
+    }
+}
+
+pub fn parse_pnpm_lock(content: &str) -> Result<PackageVersions> {
+    let lockfile: serde_yaml_ng::Value = serde_yaml_ng::from_str(content)?;
+    let v5 = match &lockfile["lockfileVersion"] {
+        serde_yaml_ng::Value::Number(version) => {
+            version.as_f64().is_some_and(|version| version < 6.0)
+        }
+        serde_yaml_ng::Value::String(version) => version.starts_with('5'),
+        _ => false,
+    };
+    let mut packages = PackageVersions::new();
+    let Some(entries) = lockfile["packages"].as_mapping() else {
+        return Ok(packages);
+    };
+    for (key, entry) in entries {
+        let Some((name, version)) = key.as_str().and_then(|key| parse_pnpm_package_key(key, v5))
+        else {
+            continue;
+        };
+        // Tarball and git dependencies list their actual name and version separately.
+        let name = entry["name"].as_str().unwrap_or(name);
+        let version = entry["version"].as_str().unwrap_or(version);
+        insert_version(&mut packages, name, version);
+    }
+    Ok(packages)
+}
+
+pub fn parse_package_lock(content: &str) -> Result<PackageVersions> {
+    fn collect_v1_dependencies(dependencies: &JsonValue, packages: &mut PackageVersions) {
+        let Some(dependencies) = dependencies.as_object() else {
+            return;
+        };
+        for (name, dependency) in dependencies {
+            if let Some(version) = dependency["version"].as_str()
+                && !version.starts_with("file:")
+            {
+                insert_version(packages, name, version);
+            }
+            collect_v1_dependencies(&dependency["dependencies"], packages);
+        }
+    }
+
+    let lockfile: JsonValue = serde_json::from_str(content)?;
+    let mut packages = PackageVersions::new();
+    if let Some(entries) = lockfile["packages"].as_object() {
+        // lockfileVersion 2 and 3 are keyed by install location
+        for (location, entry) in entries {
+            let Some((_, name)) = location.rsplit_once("node_modules/") else {
+                continue;
+            };
+            if entry["link"].as_bool() == Some(true) {
+                continue;
+            }
+            if let Some(version) = entry["version"].as_str() {
+                insert_version(&mut packages, name, version);
+            }
+        }
+    } else {
+        collect_v1_dependencies(&lockfile["dependencies"], &mut packages);
+    }
+    Ok(packages)
+}
+
+/// Parses both the classic (v1) and the YAML based (Berry) `yarn.lock` format.
+pub fn parse_yarn_lock(content: &str) -> Result<PackageVersions> {
+    fn unquote(value: &str) -> &str {
+        value.trim().trim_matches('"')
+    }
+
+    let mut packages = PackageVersions::new();
+    let mut names: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            names.clear();
+            let Some(header) = line.strip_suffix(':') else {
+                continue;
+            };
+            for descriptor in header.split(',').map(unquote) {
+                let scope_len = usize::from(descriptor.starts_with('@'));
+                let Some(index) = descriptor[scope_len..].find('@') else {
+                    continue;
+                };
+                let (name, range) = descriptor.split_at(scope_len + index);
+                // Workspaces are linked, their version doesn't come from the lockfile
+                if range.starts_with("@workspace:") || range.starts_with("@link:") {
+                    names.clear();
+                    break;
+                }
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        } else if let Some(version) = line
+            .strip_prefix("  version")
+            .filter(|rest| rest.starts_with([' ', ':']))
+        {
+            let version = unquote(version.trim_start_matches(':'));
+            for name in &names {
+                insert_version(&mut packages, name, version);
+            }
+        }
+    }
+    Ok(packages)
+}
+
+/// Reads the lockfile in the given directory. The versions are empty when there is no
+/// lockfile.
+#[turbo_tasks::function]
+pub async fn read_locked_versions(dir: FileSystemPath) -> Result<Vc<LockedVersions>> {
+    let lockfiles: [(&str, fn(&str) -> Result<PackageVersions>); 3] = [
+        ("pnpm-lock.yaml", parse_pnpm_lock),
+        ("package-lock.json", parse_package_lock),
+        ("yarn.lock", parse_yarn_lock),
+    ];
+    for (file_name, parse) in lockfiles {
+        let path = dir.join(file_name)?;
+        let FileContent::Content(file) = &*path.read().await? else {
+            continue;
+        };
+        let packages = file
+            .content()
+            .to_str()
+            .and_then(|content| parse(&content))
+            .with_context(|| format!("failed to parse the lockfile {}", path.path));
+        return Ok(match packages {
+            Ok(packages) => LockedVersions {
+                lockfile: Some(path),
+                packages,
+            }
+            .cell(),
+            Err(err) => {
+                LockfileIssue {
+                    severity: IssueSeverity::Warning,
+                    file_path: path,
+                    title: rcstr!("Failed to read the lockfile"),
+                    description: format!("{err:#}").into(),
+                }
+                .resolved_cell()
+                .emit();
+                LockedVersions::default().cell()
+            }
+        });
+    }
+    Ok(LockedVersions::default().cell())
+}
+
+/// Emits an issue when the version installed in `package_dir` isn't one of the versions of the
+/// package in the lockfile.
+#[turbo_tasks::function]
+pub async fn check_locked_version(
+    locked_versions: Vc<LockedVersions>,
+    name: RcStr,
+    package_dir: FileSystemPath,
+) -> Result<Vc<()>> {
+    let locked_versions = locked_versions.await?;
+    let (Some(lockfile), Some(versions)) = (
+        &locked_versions.lockfile,
+        locked_versions.packages.get(&name),
+    ) else {
+        return Ok(Vc::cell(()));
+    };
+    let package_json_path = package_dir.join("package.json")?;
+    let package_json =
+        read_package_json(Vc::upcast(FileSource::new(package_json_path.clone()))).await?;
+    let Some(version) = package_json
+        .as_ref()
+        .and_then(|package_json| package_json["version"].as_str())
+    else {
+        return Ok(Vc::cell(()));
+    };
+    if !versions.contains(version) {
+        let locked = versions
+            .iter()
+            .map(|version| version.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        LockfileIssue {
+            severity: IssueSeverity::Warning,
+            file_path: package_json_path,
+            title: format!("{name}@{version} doesn't match the lockfile").into(),
+            description: format!(
+                "{name}@{version} was resolved from {}, but {} only contains {locked}. The \
+                 installed dependencies are out of date, reinstall them to match the lockfile.",
+                package_dir.path, lockfile.path
+            )
+            .into(),
+        }
+        .resolved_cell()
+        .emit();
+    }
+    Ok(Vc::cell(()))
+}
+
+#[turbo_tasks::value(shared)]
+pub struct LockfileIssue {
+    pub severity: IssueSeverity,
+    pub file_path: FileSystemPath,
+    pub title: RcStr,
+    pub description: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for LockfileIssue {
+    fn severity(&self) -> IssueSeverity {
+        self.severity
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(self.title.clone()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Resolve.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path.clone().cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(self.description.clone()).resolved_cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_package_lock, parse_pnpm_lock, parse_yarn_lock};
+
+    fn versions(packages: &super::PackageVersions, name: &str) -> Vec<String> {
+        packages
+            .get(name)
+            .map(|versions| versions.iter().map(|v| v.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_pnpm_lock() {
+        let packages = parse_pnpm_lock(
+            r#"lockfileVersion: '9.0'
+importers:
+  .:
+    dependencies:
+      react:
+        specifier: ^18.2.0
+        version: 18.2.0
+packages:
+  react@18.2.0:
+    resolution: {integrity: sha512-abc}
+  react@17.0.2:
+    resolution: {integrity: sha512-def}
+  '@types/react@18.2.1':
+    resolution: {integrity: sha512-ghi}
+  react-dom@18.2.0(react@18.2.0):
+    resolution: {integrity: sha512-jkl}
+"#,
+        )
+        .unwrap();
+        assert_eq!(versions(&packages, "react"), ["17.0.2", "18.2.0"]);
+        assert_eq!(versions(&packages, "@types/react"), ["18.2.1"]);
+        assert_eq!(versions(&packages, "react-dom"), ["18.2.0"]);
+
+        let packages = parse_pnpm_lock(
+            r#"lockfileVersion: 5.4
+packages:
+  /@babel/core/7.20.0:
+    resolution: {integrity: sha512-abc}
+  /react-dom/18.2.0_react@18.2.0:
+    resolution: {integrity: sha512-def}
+"#,
+        )
+        .unwrap();
+        assert_eq!(versions(&packages, "@babel/core"), ["7.20.0"]);
+        assert_eq!(versions(&packages, "react-dom"), ["18.2.0"]);
+    }
+
+    #[test]
+    fn test_package_lock() {
+        let packages = parse_package_lock(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": { "name": "app" },
+                    "node_modules/react": { "version": "18.2.0" },
+                    "node_modules/lib/node_modules/react": { "version": "17.0.2" },
+                    "node_modules/@scope/pkg": { "version": "1.0.0" },
+                    "node_modules/workspace": { "resolved": "packages/workspace", "link": true }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(versions(&packages, "react"), ["17.0.2", "18.2.0"]);
+        assert_eq!(versions(&packages, "@scope/pkg"), ["1.0.0"]);
+        assert!(versions(&packages, "workspace").is_empty());
+
+        let packages = parse_package_lock(
+            r#"{
+                "lockfileVersion": 1,
+                "dependencies": {
+                    "react": { "version": "18.2.0" },
+                    "lib": {
+                        "version": "1.0.0",
+                        "dependencies": { "react": { "version": "17.0.2" } }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(versions(&packages, "react"), ["17.0.2", "18.2.0"]);
+    }
+
+    #[test]
+    fn test_yarn_lock() {
+        let packages = parse_yarn_lock(
+            r#"# yarn lockfile v1
+
+
+"@babel/code-frame@^7.0.0", "@babel/code-frame@^7.10.4":
+  version "7.12.13"
+  resolved "https://registry.yarnpkg.com/@babel/code-frame/-/code-frame-7.12.13.tgz"
+  dependencies:
+    "@babel/highlight" "^7.12.13"
+
+react@^17.0.0:
+  version "17.0.2"
+
+react@^18.2.0:
+  version "18.2.0"
+"#,
+        )
+        .unwrap();
+        assert_eq!(versions(&packages, "@babel/code-frame"), ["7.12.13"]);
+        assert_eq!(versions(&packages, "react"), ["17.0.2", "18.2.0"]);
+        assert!(versions(&packages, "@babel/highlight").is_empty());
+
+        let packages = parse_yarn_lock(
+            r#"__metadata:
+  version: 6
+  cacheKey: 8
+
+"app@workspace:.":
+  version: 0.0.0-use.local
+  resolution: "app@workspace:."
+
+"react@npm:^18.2.0, react@npm:^18.0.0":
+  version: 18.2.0
+  resolution: "react@npm:18.2.0"
+  dependencies:
+    loose-envify: ^1.1.0
+"#,
+        )
+        .unwrap();
+        assert_eq!(versions(&packages, "react"), ["18.2.0"]);
+        assert!(versions(&packages, "app").is_empty());
+        assert!(versions(&packages, "__metadata").is_empty());
+    }
+}
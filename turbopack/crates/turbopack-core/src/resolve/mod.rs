@@ -20,8 +20,8 @@ use turbo_tasks::{
     FxIndexMap, FxIndexSet, NonLocalValue, ReadRef, ResolvedVc, TaskInput, TryFlatJoinIterExt,
     TryJoinIterExt, ValueToString, Vc, trace::TraceRawVcs,
 };
-use turbo_tasks_fs::{FileSystemEntryType, FileSystemPath};
-use turbo_unix_path::normalize_request;
+use turbo_tasks_fs::{FileSystem, FileSystemEntryType, FileSystemPath, ZipFileSystem};
+use turbo_unix_path::{join_path, normalize_request};
 
 use crate::{
     context::AssetContext,
@@ -37,6 +37,7 @@ use crate::{
     reference_type::ReferenceType,
     resolve::{
         alias_map::AliasKey,
+        lockfile::check_locked_version,
         node::{node_cjs_resolve_options, node_esm_resolve_options},
         options::{
             ConditionValue, ImportMapResult, ResolveInPackage, ResolveIntoPackage, ResolveModules,
@@ -46,19 +47,24 @@ use crate::{
         parse::{Request, stringify_data_uri},
         pattern::{Pattern, PatternMatch, read_matches},
         plugin::{AfterResolvePlugin, BeforeResolvePlugin},
+        pnp::{PnpResolution, pnp_manifest},
         remap::{ExportsField, ImportsField, ReplacedSubpathValueResult},
+        trace::{ResolveTraceStepKind, describe_import_map_result, emit_trace_step},
     },
     source::{OptionSource, Source, Sources},
 };
 
 mod alias_map;
+pub mod lockfile;
 pub mod node;
 pub mod options;
 pub mod origin;
 pub mod parse;
 pub mod pattern;
 pub mod plugin;
+pub mod pnp;
 pub(crate) mod remap;
+pub mod trace;
 
 pub use alias_map::{
     AliasMap, AliasMapIntoIter, AliasMapLookupIterator, AliasMatch, AliasPattern, AliasTemplate,
@@ -437,6 +443,9 @@ pub enum ExternalType {
     EcmaScriptModule,
     Global,
     Script,
+    /// A module exposed by a module federation remote. The name has the format
+    /// `remote/expose@remoteEntryUrl`.
+    FederationRemote,
 }
 
 impl Display for ExternalType {
@@ -447,6 +456,7 @@ impl Display for ExternalType {
             ExternalType::Url => write!(f, "url"),
             ExternalType::Global => write!(f, "global"),
             ExternalType::Script => write!(f, "script"),
+            ExternalType::FederationRemote => write!(f, "federation-remote"),
         }
     }
 }
@@ -1313,6 +1323,8 @@ struct FindPackageResult {
     packages: Vec<FindPackageItem>,
     // Only populated if collect_affecting_sources is true
     affecting_sources: Vec<ResolvedVc<Box<dyn Source>>>,
+    /// Explanations why a package can't be accessed, e.g. undeclared Yarn PnP dependencies
+    errors: Vec<RcStr>,
 }
 
 #[turbo_tasks::function]
@@ -1324,6 +1336,7 @@ async fn find_package(
 ) -> Result<Vc<FindPackageResult>> {
     let mut packages = vec![];
     let mut affecting_sources = vec![];
+    let mut errors = vec![];
     let options = options.await?;
     let package_name_cell = Pattern::new(package_name.clone());
 
@@ -1434,11 +1447,76 @@ async fn find_package(
                     }
                 }
             }
+            ResolveModules::Pnp(dir) => {
+                let Some(name) = package_name.as_constant_string() else {
+                    continue;
+                };
+                let Some(manifest) = *pnp_manifest(dir.clone()).await? else {
+                    continue;
+                };
+                let manifest = manifest.await?;
+                if collect_affecting_sources {
+                    affecting_sources.push(ResolvedVc::upcast(
+                        FileSource::new(manifest.path.clone()).to_resolved().await?,
+                    ));
+                }
+                // Files of packages in zip archives have paths relative to the archive, the
+                // manifest expects them relative to the root of the filesystem.
+                let issuer = match ResolvedVc::try_downcast_type::<ZipFileSystem>(lookup_path.fs) {
+                    Some(zip_fs) => join_path(&zip_fs.await?.archive.path, &lookup_path.path)
+                        .unwrap_or_default()
+                        .into(),
+                    None => lookup_path.path.clone(),
+                };
+                let root = dir.root().owned().await?;
+                let package_dir = match manifest.data.resolve(&issuer, name) {
+                    PnpResolution::NotHandled => continue,
+                    PnpResolution::Resolved(path) => root.join(&path)?,
+                    PnpResolution::Zipped {
+                        archive,
+                        path,
+                        package,
+                    } => {
+                        let archive = root.join(&archive)?;
+                        let package_dir = ZipFileSystem::new(archive.clone())
+                            .root()
+                            .owned()
+                            .await?
+                            .join(&path)?;
+                        if *package_dir.get_type().await? != FileSystemEntryType::Directory {
+                            errors.push(
+                                format!(
+                                    "{package} is stored in the zip archive {} of the Yarn cache, \
+                                     which can't be read. Run `yarn unplug {package}` to extract \
+                                     it.",
+                                    archive.path
+                                )
+                                .into(),
+                            );
+                            continue;
+                        }
+                        package_dir
+                    }
+                    PnpResolution::Error(message) => {
+                        errors.push(message.into());
+                        continue;
+                    }
+                };
+                packages.push(FindPackageItem::PackageDirectory {
+                    name: name.clone(),
+                    dir: realpath(
+                        &package_dir,
+                        collect_affecting_sources.then_some(&mut affecting_sources),
+                    )
+                    .await?,
+                });
+            }
         }
     }
     Ok(FindPackageResult::cell(FindPackageResult {
         packages,
         affecting_sources,
+        errors,
     }))
 }
 
@@ -1685,7 +1763,8 @@ async fn handle_before_resolve_plugins(
     request: Vc<Request>,
     options: Vc<ResolveOptions>,
 ) -> Result<Option<Vc<ResolveResult>>> {
-    for plugin in &options.await?.before_resolve_plugins {
+    let options_value = options.await?;
+    for plugin in &options_value.before_resolve_plugins {
         let condition = plugin.before_resolve_condition().resolve().await?;
         if !*condition.matches(request).await? {
             continue;
@@ -1695,6 +1774,15 @@ async fn handle_before_resolve_plugins(
             .before_resolve(lookup_path.clone(), reference_type.clone(), request)
             .await?
         {
+            if options_value.trace {
+                emit_trace_step(
+                    &lookup_path,
+                    request,
+                    ResolveTraceStepKind::BeforeResolvePlugin,
+                    format!("handled by a plugin: {}", result.to_string().await?),
+                )
+                .await?;
+            }
             return Ok(Some(*result));
         }
     }
@@ -1752,6 +1840,19 @@ async fn handle_after_resolve_plugins(
             )
             .await?
             {
+                if options.await?.trace {
+                    emit_trace_step(
+                        &lookup_path,
+                        request,
+                        ResolveTraceStepKind::AfterResolvePlugin,
+                        format!(
+                            "`{}` replaced by a plugin with {}",
+                            path.value_to_string().await?,
+                            new_result.to_string().await?
+                        ),
+                    )
+                    .await?;
+                }
                 let new_result = new_result.await?;
                 changed = true;
                 new_primary.extend(
@@ -1824,6 +1925,34 @@ async fn resolve_internal_inline(
                     .await?
                     .lookup(lookup_path.clone(), *request)
                     .await?;
+                if options_value.trace {
+                    emit_trace_step(
+                        &lookup_path,
+                        *request,
+                        ResolveTraceStepKind::ImportMap,
+                        describe_import_map_result(&result).await?,
+                    )
+                    .await?;
+                    if let Some((tsconfig, tsconfig_import_map)) = &options_value.tsconfig_paths {
+                        let result = tsconfig_import_map
+                            .await?
+                            .lookup(lookup_path.clone(), *request)
+                            .await?;
+                        if !matches!(result, ImportMapResult::NoEntry) {
+                            emit_trace_step(
+                                &lookup_path,
+                                *request,
+                                ResolveTraceStepKind::TsconfigPaths,
+                                format!(
+                                    "`compilerOptions.paths` of `{}`: {}",
+                                    tsconfig.value_to_string().await?,
+                                    describe_import_map_result(&result).await?
+                                ),
+                            )
+                            .await?;
+                        }
+                    }
+                }
                 if !matches!(result, ImportMapResult::NoEntry) {
                     has_alias = true;
                     let resolved_result = resolve_import_map_result(
@@ -1846,6 +1975,15 @@ async fn resolve_internal_inline(
                     {
                         return Ok(result);
                     }
+                    if options_value.trace {
+                        emit_trace_step(
+                            &lookup_path,
+                            *request,
+                            ResolveTraceStepKind::ImportMap,
+                            "the alias is unresolvable, resolving the request without it",
+                        )
+                        .await?;
+                    }
                 }
             }
         }
@@ -2101,6 +2239,18 @@ async fn resolve_internal_inline(
                     .await?
                     .lookup(lookup_path.clone(), request)
                     .await?;
+                if options_value.trace {
+                    emit_trace_step(
+                        &lookup_path,
+                        request,
+                        ResolveTraceStepKind::FallbackImportMap,
+                        format!(
+                            "the request is unresolvable, fallback: {}",
+                            describe_import_map_result(&result).await?
+                        ),
+                    )
+                    .await?;
+                }
                 let resolved_result = resolve_import_map_result(
                     &result,
                     lookup_path.clone(),
@@ -2478,6 +2628,25 @@ async fn resolve_relative_request(
     )
     .await?;
 
+    if options_value.trace {
+        let mut message = format!("probed `{}`", new_path.describe_as_string());
+        if matches.is_empty() {
+            message.push_str(", no file or directory matches");
+        }
+        for m in matches.iter() {
+            let (kind, path) = match m {
+                PatternMatch::File(_, path) => ("file", path),
+                PatternMatch::Directory(_, path) => ("directory", path),
+            };
+            write!(
+                message,
+                ", found {kind} `{}`",
+                path.value_to_string().await?
+            )?;
+        }
+        emit_trace_step(&lookup_path, request, ResolveTraceStepKind::Probe, message).await?;
+    }
+
     // This loop is necessary to 'undo' the modifications to 'new_path' that were performed above.
     // e.g. we added extensions but these shouldn't be part of the request key so remove them.
 
@@ -2721,20 +2890,75 @@ async fn resolve_module_request(
     )
     .await?;
 
+    if options_value.trace {
+        let mut message = format!("looked up `{}`", module.describe_as_string());
+        if result.packages.is_empty() {
+            message.push_str(", no package found");
+        }
+        for item in &result.packages {
+            let (kind, path) = match item {
+                FindPackageItem::PackageDirectory { dir, .. } => ("directory", dir),
+                FindPackageItem::PackageFile { file, .. } => ("file", file),
+            };
+            write!(
+                message,
+                ", found {kind} `{}`",
+                path.value_to_string().await?
+            )?;
+        }
+        for error in &result.errors {
+            write!(message, ", error: {error}")?;
+        }
+        emit_trace_step(
+            &lookup_path,
+            request,
+            ResolveTraceStepKind::Modules,
+            message,
+        )
+        .await?;
+    }
+
     if result.packages.is_empty() {
+        for error in &result.errors {
+            ResolvingIssue {
+                severity: error_severity(options).await?,
+                file_path: lookup_path.clone(),
+                request_type: "Yarn PnP request".to_string(),
+                request: request.to_resolved().await?,
+                resolve_options: options.to_resolved().await?,
+                error_message: Some(error.to_string()),
+                source: None,
+            }
+            .resolved_cell()
+            .emit();
+        }
         return Ok(*ResolveResult::unresolvable_with_affecting_sources(
             result.affecting_sources.clone(),
         ));
     }
 
+    // Singleton packages use the outermost copy, so nested node_modules folders can't introduce a
+    // second copy.
+    let mut packages = Cow::Borrowed(&result.packages);
+    if options_value
+        .singleton_packages
+        .iter()
+        .any(|name| module.is_match(name))
+    {
+        packages.to_mut().reverse();
+    }
+
     // There may be more than one package with the same name. For instance, in a
     // TypeScript project, `compilerOptions.baseUrl` can declare a path where to
     // resolve packages. A request to "foo/bar" might resolve to either
     // "[baseUrl]/foo/bar" or "[baseUrl]/node_modules/foo/bar", and we'll need to
     // try both.
-    for item in &result.packages {
+    for item in &*packages {
         match item {
             FindPackageItem::PackageDirectory { name, dir } => {
+                if let Some(locked_versions) = options_value.locked_versions {
+                    check_locked_version(*locked_versions, name.clone(), dir.clone()).await?;
+                }
                 results.push(
                     resolve_into_package(
                         path.clone(),
@@ -2831,6 +3055,7 @@ async fn resolve_into_package(
                         conditions,
                         unspecified_conditions,
                         query,
+                        ResolveTraceStepKind::ExportsField,
                     )
                     .await?,
                 );
@@ -2927,7 +3152,10 @@ async fn resolve_import_map_result(
                         ExternalType::EcmaScriptModule => {
                             node_esm_resolve_options(alias_lookup_path.root().owned().await?)
                         }
-                        ExternalType::Script | ExternalType::Url | ExternalType::Global => options,
+                        ExternalType::Script
+                        | ExternalType::Url
+                        | ExternalType::Global
+                        | ExternalType::FederationRemote => options,
                     },
                 )
                 .await?
@@ -3052,6 +3280,7 @@ async fn handle_exports_imports_field(
     conditions: &BTreeMap<RcStr, ConditionValue>,
     unspecified_conditions: &ConditionValue,
     query: RcStr,
+    trace_kind: ResolveTraceStepKind,
 ) -> Result<Vc<ResolveResult>> {
     let mut results = Vec::new();
     let mut conditions_state = FxHashMap::default();
@@ -3077,6 +3306,50 @@ async fn handle_exports_imports_field(
         }
     }
 
+    if options.await?.trace {
+        let package_json = package_json_path.value_to_string().await?;
+        let message = if results.is_empty() {
+            let active_conditions = active_conditions(conditions);
+            format!(
+                "no entry of `{package_json}` matches with the conditions [{}]",
+                active_conditions.join(", ")
+            )
+        } else {
+            results
+                .iter()
+                .map(|result| {
+                    let mut description = format!(
+                        "`{package_json}` maps to `{}`",
+                        result.result_path.describe_as_string()
+                    );
+                    if !result.conditions.is_empty() {
+                        let conditions = result
+                            .conditions
+                            .iter()
+                            .map(|(condition, value)| {
+                                if *value {
+                                    condition.to_string()
+                                } else {
+                                    format!("!{condition}")
+                                }
+                            })
+                            .collect::<Vec<_>>();
+                        description.push_str(&format!(" (conditions: {})", conditions.join(", ")));
+                    }
+                    description
+                })
+                .collect::<Vec<_>>()
+                .join("; ")
+        };
+        emit_trace_step(
+            &package_path,
+            Request::parse(req.clone()),
+            trace_kind,
+            message,
+        )
+        .await?;
+    }
+
     let mut resolved_results = Vec::new();
     for ReplacedSubpathValueResult {
         result_path,
@@ -3147,6 +3420,173 @@ async fn handle_exports_imports_field(
     ))
 }
 
+fn active_conditions(conditions: &BTreeMap<RcStr, ConditionValue>) -> Vec<RcStr> {
+    conditions
+        .iter()
+        .filter(|(_, value)| matches!(value, ConditionValue::Set))
+        .map(|(condition, _)| condition.clone())
+        .collect()
+}
+
+/// Returns the conditions of the entries of an `exports` or `imports` field matching `request`,
+/// when none of their branches applies to the active `conditions`.
+fn unmatched_entry_conditions(
+    exports_imports_field: &AliasMap<SubpathValue>,
+    request: &Pattern,
+    conditions: &BTreeMap<RcStr, ConditionValue>,
+    unspecified_conditions: &ConditionValue,
+) -> Result<Option<Vec<RcStr>>> {
+    let mut results = Vec::new();
+    let mut conditions_state = FxHashMap::default();
+    for value in exports_imports_field.lookup(request) {
+        let value = value?;
+        if value.output.add_results(
+            value.prefix,
+            value.key,
+            conditions,
+            unspecified_conditions,
+            &mut conditions_state,
+            &mut results,
+        ) {
+            return Ok(None);
+        }
+    }
+    let mut available_conditions = FxIndexSet::default();
+    for value in exports_imports_field.lookup(request) {
+        value?.output.add_conditions(&mut available_conditions);
+    }
+    Ok((!available_conditions.is_empty()).then(|| available_conditions.into_iter().collect()))
+}
+
+/// A request that matches an entry of the `exports` or `imports` field of a `package.json`, but
+/// none of the entry's branches applies to the active conditions.
+pub(crate) struct UnmatchedConditions {
+    pub package_json_path: FileSystemPath,
+    /// `exports` or `imports`
+    pub field: RcStr,
+    pub request: RcStr,
+    pub active_conditions: Vec<RcStr>,
+    pub available_conditions: Vec<RcStr>,
+}
+
+/// Finds the `exports` or `imports` entry a module or `#import` request matches without any of
+/// its branches applying. This explains failed resolves, e.g. a missing custom `source`
+/// condition. It's only looked up for requests that can't be resolved, as probing requests
+/// regularly don't match the conditions of an entry.
+pub(crate) async fn find_unmatched_conditions(
+    lookup_path: FileSystemPath,
+    request: Vc<Request>,
+    options: Vc<ResolveOptions>,
+) -> Result<Option<UnmatchedConditions>> {
+    let options_value = options.await?;
+    match &*request.await? {
+        Request::Module {
+            module,
+            path,
+            query,
+            ..
+        } => {
+            let Some((conditions, unspecified_conditions)) = options_value
+                .into_package
+                .iter()
+                .find_map(|item| match item {
+                    ResolveIntoPackage::ExportsField {
+                        conditions,
+                        unspecified_conditions,
+                    } => Some((conditions, unspecified_conditions)),
+                    _ => None,
+                })
+            else {
+                return Ok(None);
+            };
+            let mut export_path_request = path.clone();
+            export_path_request.push_front(rcstr!(".").into());
+            if !query.is_empty() {
+                export_path_request.push(query.clone().into());
+            }
+            let packages = find_package(
+                lookup_path,
+                module.clone(),
+                resolve_modules_options(options),
+                false,
+            )
+            .await?;
+            for item in &packages.packages {
+                let FindPackageItem::PackageDirectory { dir, .. } = item else {
+                    continue;
+                };
+                let package_json_path = dir.join("package.json")?;
+                let ExportsFieldResult::Some(exports_field) =
+                    &*exports_field(Vc::upcast(FileSource::new(package_json_path.clone()))).await?
+                else {
+                    continue;
+                };
+                if let Some(available_conditions) = unmatched_entry_conditions(
+                    exports_field,
+                    &export_path_request,
+                    conditions,
+                    unspecified_conditions,
+                )? {
+                    return Ok(Some(UnmatchedConditions {
+                        package_json_path,
+                        field: rcstr!("exports"),
+                        request: export_path_request.describe_as_string().into(),
+                        active_conditions: active_conditions(conditions),
+                        available_conditions,
+                    }));
+                }
+            }
+            Ok(None)
+        }
+        Request::PackageInternal {
+            path: Pattern::Constant(specifier),
+        } => {
+            let Some((conditions, unspecified_conditions)) =
+                options_value.in_package.iter().find_map(|item| match item {
+                    ResolveInPackage::ImportsField {
+                        conditions,
+                        unspecified_conditions,
+                    } => Some((conditions, unspecified_conditions)),
+                    _ => None,
+                })
+            else {
+                return Ok(None);
+            };
+            let ImportsFieldResult::Some(imports, package_json_path) =
+                &*imports_field(lookup_path).await?
+            else {
+                return Ok(None);
+            };
+            let request = Pattern::Constant(specifier.clone());
+            Ok(
+                unmatched_entry_conditions(imports, &request, conditions, unspecified_conditions)?
+                    .map(|available_conditions| UnmatchedConditions {
+                        package_json_path: package_json_path.clone(),
+                        field: rcstr!("imports"),
+                        request: specifier.clone(),
+                        active_conditions: active_conditions(conditions),
+                        available_conditions,
+                    }),
+            )
+        }
+        Request::Alternatives { requests } => {
+            for request in requests {
+                if let Some(unmatched) = Box::pin(find_unmatched_conditions(
+                    lookup_path.clone(),
+                    **request,
+                    options,
+                ))
+                .await?
+                {
+                    return Ok(Some(unmatched));
+                }
+            }
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
+}
+
 /// Resolves a `#dep` import using the containing package.json's `imports`
 /// field. The dep may be a constant string or a pattern, and the values can be
 /// static strings or conditions like `import` or `require` to handle ESM/CJS
@@ -3193,6 +3633,7 @@ async fn resolve_package_internal_with_imports_field(
         conditions,
         unspecified_conditions,
         RcStr::default(),
+        ResolveTraceStepKind::ImportsField,
     )
     .await
 }
@@ -3450,18 +3891,55 @@ mod tests {
     };
 
     use turbo_rcstr::{RcStr, rcstr};
-    use turbo_tasks::{TryJoinIterExt, Vc};
+    use turbo_tasks::{ResolvedVc, TryJoinIterExt, Vc};
     use turbo_tasks_backend::{BackendOptions, TurboTasksBackend, noop_backing_storage};
-    use turbo_tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath};
+    use turbo_tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath, ZipFileSystem};
 
+    use super::unmatched_entry_conditions;
     use crate::{
+        reference_type::ReferenceType,
         resolve::{
-            ResolveResult, ResolveResultItem, node::node_esm_resolve_options, parse::Request,
+            ResolveResult, ResolveResultItem,
+            node::{node_cjs_resolve_options, node_esm_resolve_options},
+            options::{ConditionValue, ResolutionConditions, ResolveModules},
+            parse::Request,
             pattern::Pattern,
+            remap::ExportsField,
         },
         source::Source,
     };
 
+    #[test]
+    fn test_unmatched_entry_conditions() {
+        let exports = ExportsField::try_from(&serde_json::json!({
+            ".": { "import": "./index.mjs", "require": "./index.cjs" },
+            "./source": { "source": "./src/index.ts" },
+            "./browser": { "browser": "./browser.js", "default": "./node.js" },
+        }))
+        .unwrap();
+        let conditions: ResolutionConditions = [
+            (rcstr!("import"), ConditionValue::Set),
+            (rcstr!("node"), ConditionValue::Set),
+        ]
+        .into();
+        let unmatched = |request: &str| {
+            unmatched_entry_conditions(
+                &exports,
+                &Pattern::Constant(request.into()),
+                &conditions,
+                &ConditionValue::Unset,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(unmatched("."), None);
+        assert_eq!(unmatched("./source"), Some(vec![rcstr!("source")]));
+        // `default` always applies.
+        assert_eq!(unmatched("./browser"), None);
+        // Requests without an entry aren't caused by the conditions.
+        assert_eq!(unmatched("./missing"), None);
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_explicit_js_resolves_to_ts() {
         resolve_relative_request_test(TestParams {
@@ -3709,6 +4187,168 @@ mod tests {
         .unwrap();
     }
 
+    /// Writes a zip archive with the given entries, without compression.
+    fn write_zip(path: &std::path::Path, entries: &[(&str, &str)]) {
+        let mut data = Vec::new();
+        let mut central_directory = Vec::new();
+        for (name, content) in entries {
+            let header_offset = data.len() as u32;
+            data.extend(0x04034b50u32.to_le_bytes());
+            data.extend([20, 0, 0, 0, 0, 0]);
+            data.extend([0; 8]);
+            data.extend((content.len() as u32).to_le_bytes());
+            data.extend((content.len() as u32).to_le_bytes());
+            data.extend((name.len() as u16).to_le_bytes());
+            data.extend([0, 0]);
+            data.extend(name.as_bytes());
+            data.extend(content.as_bytes());
+
+            central_directory.extend(0x02014b50u32.to_le_bytes());
+            central_directory.extend([20, 0, 20, 0, 0, 0, 0, 0]);
+            central_directory.extend([0; 8]);
+            central_directory.extend((content.len() as u32).to_le_bytes());
+            central_directory.extend((content.len() as u32).to_le_bytes());
+            central_directory.extend((name.len() as u16).to_le_bytes());
+            central_directory.extend([0; 12]);
+            central_directory.extend(header_offset.to_le_bytes());
+            central_directory.extend(name.as_bytes());
+        }
+        let central_directory_offset = data.len() as u32;
+        data.extend(&central_directory);
+        data.extend(0x06054b50u32.to_le_bytes());
+        data.extend([0; 4]);
+        data.extend((entries.len() as u16).to_le_bytes());
+        data.extend((entries.len() as u16).to_le_bytes());
+        data.extend((central_directory.len() as u32).to_le_bytes());
+        data.extend(central_directory_offset.to_le_bytes());
+        data.extend([0, 0]);
+
+        create_dir_all(path.parent().unwrap()).unwrap();
+        File::create_new(path).unwrap().write_all(&data).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_pnp_resolves_from_zip_archives() {
+        let scratch = tempfile::tempdir().unwrap();
+        {
+            let path = scratch.path();
+            File::create_new(path.join(".pnp.data.json"))
+                .unwrap()
+                .write_all(
+                    br#"{
+                        "dependencyTreeRoots": [],
+                        "packageRegistryData": [
+                            [null, [[null, {
+                                "packageLocation": "./",
+                                "packageDependencies": [["pkg", "npm:1.0.0"], ["missing", "npm:1.0.0"]]
+                            }]]],
+                            ["pkg", [["npm:1.0.0", {
+                                "packageLocation": "./.yarn/cache/pkg-npm-1.0.0-abc.zip/node_modules/pkg/",
+                                "packageDependencies": [["pkg", "npm:1.0.0"], ["dep", "npm:1.0.0"]]
+                            }]]],
+                            ["dep", [["npm:1.0.0", {
+                                "packageLocation": "./.yarn/cache/dep-npm-1.0.0-def.zip/node_modules/dep/",
+                                "packageDependencies": [["dep", "npm:1.0.0"]]
+                            }]]],
+                            ["missing", [["npm:1.0.0", {
+                                "packageLocation": "./.yarn/cache/missing-npm-1.0.0-ghi.zip/node_modules/missing/",
+                                "packageDependencies": []
+                            }]]]
+                        ]
+                    }"#,
+                )
+                .unwrap();
+            write_zip(
+                &path.join(".yarn/cache/pkg-npm-1.0.0-abc.zip"),
+                &[
+                    ("node_modules/", ""),
+                    (
+                        "node_modules/pkg/package.json",
+                        r#"{"main": "lib/main.js"}"#,
+                    ),
+                    ("node_modules/pkg/lib/main.js", "require('dep')"),
+                ],
+            );
+            write_zip(
+                &path.join(".yarn/cache/dep-npm-1.0.0-def.zip"),
+                &[("node_modules/dep/index.js", "module.exports = 'dep'")],
+            );
+        }
+
+        let path: RcStr = scratch.path().to_str().unwrap().into();
+        let tt = turbo_tasks::TurboTasks::new(TurboTasksBackend::new(
+            BackendOptions::default(),
+            noop_backing_storage(),
+        ));
+
+        tt.run_once(async move {
+            let fs = Vc::upcast::<Box<dyn FileSystem>>(DiskFileSystem::new(rcstr!("temp"), path));
+            let root = fs.root().owned().await?;
+
+            let pkg = resolve_pnp_helper(root.clone(), rcstr!("pkg")).await?;
+            let [pkg_main] = &pkg[..] else {
+                panic!("expected a single result, got {}", pkg.len());
+            };
+            assert_eq!(pkg_main.path.as_str(), "node_modules/pkg/lib/main.js");
+            let zip_fs = ResolvedVc::try_downcast_type::<ZipFileSystem>(pkg_main.fs)
+                .expect("pkg should be read from its zip archive")
+                .await?;
+            assert_eq!(
+                zip_fs.archive.path.as_str(),
+                ".yarn/cache/pkg-npm-1.0.0-abc.zip"
+            );
+
+            // Requests from inside of an archive are resolved relative to the archive location
+            let dep = resolve_pnp_helper(pkg_main.parent(), rcstr!("dep")).await?;
+            let [dep_index] = &dep[..] else {
+                panic!("expected a single result, got {}", dep.len());
+            };
+            assert_eq!(dep_index.path.as_str(), "node_modules/dep/index.js");
+            assert_ne!(dep_index.fs, pkg_main.fs);
+
+            // Archives that can't be read have to be unplugged
+            let missing = resolve_pnp_helper(root, rcstr!("missing")).await?;
+            assert!(missing.is_empty());
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[turbo_tasks::value(transparent)]
+    struct ResolvedPaths(Vec<FileSystemPath>);
+
+    #[turbo_tasks::function]
+    async fn resolve_pnp_helper(
+        lookup_path: FileSystemPath,
+        name: RcStr,
+    ) -> anyhow::Result<Vc<ResolvedPaths>> {
+        let mut options = node_cjs_resolve_options(lookup_path.root().owned().await?)
+            .owned()
+            .await?;
+        options.modules = vec![ResolveModules::Pnp(lookup_path.root().owned().await?)];
+        let result = super::resolve(
+            lookup_path,
+            ReferenceType::Undefined,
+            Request::parse(Pattern::Constant(name)),
+            options.cell(),
+        )
+        .await?;
+        let paths = result
+            .primary
+            .iter()
+            .map(async |(_, item)| {
+                let ResolveResultItem::Source(source) = item else {
+                    unreachable!()
+                };
+                Ok(source.ident().await?.path.clone())
+            })
+            .try_join()
+            .await?;
+        Ok(Vc::cell(paths))
+    }
+
     #[turbo_tasks::function]
     async fn resolve_relative_helper(
         lookup_path: FileSystemPath,
@@ -1,4 +1,8 @@
-use std::{collections::BTreeMap, future::Future, pin::Pin};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    future::Future,
+    pin::Pin,
+};
 
 use anyhow::{Result, bail};
 use bincode::{Decode, Encode};
@@ -17,9 +21,15 @@ use crate::resolve::{
     plugin::{AfterResolvePlugin, BeforeResolvePlugin},
 };
 
+/// The package versions pinned by a lockfile, see [crate::resolve::lockfile].
 #[turbo_tasks::value(shared)]
-#[derive(Hash, Debug)]
-pub struct LockedVersions {}
+#[derive(Hash, Debug, Default)]
+pub struct LockedVersions {
+    /// The lockfile the versions were read from.
+    pub lockfile: Option<FileSystemPath>,
+    /// All locked versions of each package.
+    pub packages: BTreeMap<RcStr, BTreeSet<RcStr>>,
+}
 
 #[turbo_tasks::value(transparent)]
 #[derive(Debug)]
@@ -38,6 +48,9 @@ pub enum ResolveModules {
         dir: FileSystemPath,
         excluded_extensions: ResolvedVc<ExcludedExtensions>,
     },
+    /// resolve through the Yarn Plug'n'Play manifest in that directory, for
+    /// issuers that are part of its dependency tree
+    Pnp(FileSystemPath),
 }
 
 #[derive(TraceRawVcs, Hash, PartialEq, Eq, Clone, Copy, Debug, NonLocalValue, Encode, Decode)]
@@ -643,6 +656,17 @@ pub struct ResolveOptions {
     pub collect_affecting_sources: bool,
     /// Whether to parse data URIs into modules (as opposed to keeping them as externals)
     pub parse_data_uris: bool,
+    /// Report packages resolved from node_modules with a version that isn't in the lockfile.
+    pub locked_versions: Option<ResolvedVc<LockedVersions>>,
+    /// Packages that should only be included once. They are resolved to the outermost
+    /// node_modules copy instead of the nearest one.
+    pub singleton_packages: Vec<RcStr>,
+    /// The tsconfig.json whose `compilerOptions.paths` are part of `import_map`, and an import map
+    /// of only these paths. Only used to explain resolution.
+    pub tsconfig_paths: Option<(FileSystemPath, ResolvedVc<ImportMap>)>,
+    /// Emit a [ResolveTraceStep](super::trace::ResolveTraceStep) collectible for every step of
+    /// the resolution, see [explain_resolve](super::trace::explain_resolve).
+    pub trace: bool,
 
     pub placeholder_for_future_extensions: (),
 }
@@ -0,0 +1,600 @@
+//! Resolution of bare specifiers through a Yarn Plug'n'Play manifest.
+//!
+//! Projects using PnP have no `node_modules` folder. Instead, Yarn writes a manifest
+//! (`.pnp.data.json`, or inlined into `.pnp.cjs`) that maps every package locator (name and
+//! reference) to its location on disk and to the locators of its dependencies. Requests are
+//! resolved by finding the package that contains the issuer and looking up the requested name in
+//! its declared dependencies.
+//!
+//! Packages stored in zip archives of the Yarn cache are read through a
+//! [turbo_tasks_fs::ZipFileSystem]. Unplugging them is only needed when the archive can't be
+//! read.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Deserialize;
+use turbo_rcstr::RcStr;
+use turbo_tasks::{FxIndexMap, ResolvedVc, Vc};
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+use turbo_unix_path::join_path;
+
+/// A package locator, as used by Yarn. The top-level package has neither a name nor a
+/// reference.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PnpLocator {
+    pub name: Option<RcStr>,
+    pub reference: Option<RcStr>,
+}
+
+impl PnpLocator {
+    fn top_level() -> Self {
+        PnpLocator {
+            name: None,
+            reference: None,
+        }
+    }
+}
+
+impl std::fmt::Display for PnpLocator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.name, &self.reference) {
+            (Some(name), Some(reference)) => write!(f, "{name}@{reference}"),
+            (Some(name), None) => write!(f, "{name}"),
+            _ => write!(f, "the top-level package"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PnpPackage {
+    /// The location of the package, relative to the root of the filesystem. Virtual paths are
+    /// already mapped to their real location.
+    location: RcStr,
+    /// The declared dependencies of the package. `None` is a peer dependency that isn't provided
+    /// by the ancestors.
+    dependencies: FxIndexMap<RcStr, Option<PnpLocator>>,
+}
+
+/// The parsed runtime state of a PnP manifest.
+#[derive(Debug)]
+pub struct PnpData {
+    /// The directory of the manifest, relative to the root of the filesystem.
+    base: RcStr,
+    packages: FxIndexMap<PnpLocator, PnpPackage>,
+    /// All package locations with the locators located there, longest locations first. Multiple
+    /// virtual instances of a package share the same location.
+    locations: Vec<(RcStr, Vec<PnpLocator>)>,
+    dependency_tree_roots: FxHashSet<PnpLocator>,
+    enable_top_level_fallback: bool,
+    fallback_pool: FxHashMap<RcStr, Option<PnpLocator>>,
+    fallback_exclusion_list: FxHashMap<RcStr, FxHashSet<RcStr>>,
+    ignore_pattern: Option<Regex>,
+}
+
+/// The result of resolving a package through the PnP manifest.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PnpResolution {
+    /// The issuer isn't part of the dependency tree, other resolution methods should be used.
+    NotHandled,
+    /// The directory of the package, relative to the root of the filesystem.
+    Resolved(RcStr),
+    /// The package is stored in a zip archive of the Yarn cache.
+    Zipped {
+        /// The archive, relative to the root of the filesystem.
+        archive: RcStr,
+        /// The directory of the package inside of the archive.
+        path: RcStr,
+        /// The name of the package, used to suggest unplugging it when the archive can't be
+        /// read.
+        package: RcStr,
+    },
+    /// The package can't be accessed by the issuer.
+    Error(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPnpData {
+    #[serde(default)]
+    dependency_tree_roots: Vec<RawLocator>,
+    #[serde(default)]
+    enable_top_level_fallback: bool,
+    #[serde(default)]
+    fallback_pool: Vec<(RcStr, Option<RawDependency>)>,
+    #[serde(default)]
+    fallback_exclusion_list: Vec<(RcStr, Vec<RcStr>)>,
+    #[serde(default)]
+    ignore_pattern_data: Option<String>,
+    package_registry_data: Vec<(Option<RcStr>, Vec<(Option<RcStr>, RawPackageInformation)>)>,
+}
+
+#[derive(Deserialize)]
+struct RawLocator {
+    name: RcStr,
+    reference: RcStr,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawDependency {
+    Reference(RcStr),
+    Alias(RcStr, RcStr),
+}
+
+impl RawDependency {
+    fn into_locator(self, name: &RcStr) -> PnpLocator {
+        match self {
+            RawDependency::Reference(reference) => PnpLocator {
+                name: Some(name.clone()),
+                reference: Some(reference),
+            },
+            RawDependency::Alias(name, reference) => PnpLocator {
+                name: Some(name),
+                reference: Some(reference),
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPackageInformation {
+    package_location: String,
+    #[serde(default)]
+    package_dependencies: Vec<(RcStr, Option<RawDependency>)>,
+}
+
+/// Maps a path inside of a `__virtual__` folder to the real path. Virtual paths have the form
+/// `<base>/__virtual__/<hash>/<depth>/<subpath>`, which points to `<subpath>` in the `<depth>`-th
+/// parent of `<base>`.
+fn resolve_virtual(path: &str) -> Option<String> {
+    let segments: Vec<&str> = path.split('/').collect();
+    let Some(index) = segments
+        .iter()
+        .position(|segment| *segment == "__virtual__" || *segment == "$$virtual")
+    else {
+        return Some(path.to_string());
+    };
+    let depth: usize = segments.get(index + 2)?.parse().ok()?;
+    let mut base = segments[..index].to_vec();
+    if depth > base.len() {
+        return None;
+    }
+    base.truncate(base.len() - depth);
+    base.extend(segments.iter().skip(index + 3));
+    Some(base.join("/"))
+}
+
+/// Splits a path at the first zip archive it contains into the path of the archive and the path
+/// inside of it.
+fn split_zip_path(path: &str) -> Option<(&str, &str)> {
+    let mut start = 0;
+    for segment in path.split('/') {
+        let end = start + segment.len();
+        if segment.ends_with(".zip") {
+            return Some((&path[..end], path.get(end + 1..).unwrap_or_default()));
+        }
+        start = end + 1;
+    }
+    None
+}
+
+/// Extracts the runtime state that is inlined into a `.pnp.cjs` file as a single quoted string.
+fn extract_inlined_state(code: &str) -> Result<String> {
+    let start = code
+        .find("RAW_RUNTIME_STATE")
+        .context("RAW_RUNTIME_STATE not found, the manifest might not be inlined")?;
+    let quote = code[start..]
+        .find('\'')
+        .context("RAW_RUNTIME_STATE is not a string literal")?;
+    let mut chars = code[start + quote + 1..].chars();
+    let mut state = String::new();
+    loop {
+        match chars.next().context("unterminated RAW_RUNTIME_STATE")? {
+            '\'' => return Ok(state),
+            '\\' => match chars.next().context("unterminated RAW_RUNTIME_STATE")? {
+                // A line continuation
+                '\n' => {}
+                '\r' => {
+                    if chars.as_str().starts_with('\n') {
+                        chars.next();
+                    }
+                }
+                'n' => state.push('\n'),
+                'r' => state.push('\r'),
+                't' => state.push('\t'),
+                '0' => state.push('\0'),
+                c => state.push(c),
+            },
+            c => state.push(c),
+        }
+    }
+}
+
+impl PnpData {
+    /// Parses the JSON runtime state of a manifest located in the `base` directory.
+    pub fn parse(json: &str, base: &str) -> Result<Self> {
+        let raw: RawPnpData = serde_json::from_str(json)?;
+
+        let mut packages = FxIndexMap::default();
+        let mut locations: FxIndexMap<RcStr, Vec<PnpLocator>> = FxIndexMap::default();
+        for (name, versions) in raw.package_registry_data {
+            for (reference, information) in versions {
+                let locator = PnpLocator {
+                    name: name.clone(),
+                    reference,
+                };
+                let location = join_path(base, &information.package_location)
+                    .and_then(|location| resolve_virtual(&location))
+                    .with_context(|| {
+                        format!(
+                            "the location {} of {locator} leaves the filesystem root",
+                            information.package_location
+                        )
+                    })?;
+                let location = RcStr::from(location.trim_end_matches('/'));
+                let dependencies = information
+                    .package_dependencies
+                    .into_iter()
+                    .map(|(name, dependency)| {
+                        let locator = dependency.map(|dependency| dependency.into_locator(&name));
+                        (name, locator)
+                    })
+                    .collect();
+                locations
+                    .entry(location.clone())
+                    .or_default()
+                    .push(locator.clone());
+                packages.insert(
+                    locator,
+                    PnpPackage {
+                        location,
+                        dependencies,
+                    },
+                );
+            }
+        }
+        let mut locations: Vec<_> = locations.into_iter().collect();
+        locations.sort_by_key(|(location, _)| std::cmp::Reverse(location.len()));
+
+        let ignore_pattern = raw
+            .ignore_pattern_data
+            .map(|pattern| Regex::new(&pattern))
+            .transpose()
+            .context("invalid ignorePatternData")?;
+
+        Ok(PnpData {
+            base: base.into(),
+            packages,
+            locations,
+            dependency_tree_roots: raw
+                .dependency_tree_roots
+                .into_iter()
+                .map(|root| PnpLocator {
+                    name: Some(root.name),
+                    reference: Some(root.reference),
+                })
+                .collect(),
+            enable_top_level_fallback: raw.enable_top_level_fallback,
+            fallback_pool: raw
+                .fallback_pool
+                .into_iter()
+                .map(|(name, dependency)| {
+                    let locator = dependency.map(|dependency| dependency.into_locator(&name));
+                    (name, locator)
+                })
+                .collect(),
+            fallback_exclusion_list: raw
+                .fallback_exclusion_list
+                .into_iter()
+                .map(|(name, references)| (name, references.into_iter().collect()))
+                .collect(),
+            ignore_pattern,
+        })
+    }
+
+    /// Finds the locators of the package that contains the given path.
+    fn find_locators(&self, path: &str) -> Option<&[PnpLocator]> {
+        self.locations
+            .iter()
+            .find(|(location, _)| {
+                location.is_empty()
+                    || path
+                        .strip_prefix(&**location)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .map(|(_, locators)| &**locators)
+    }
+
+    fn is_fallback_excluded(&self, locator: &PnpLocator) -> bool {
+        let (Some(name), Some(reference)) = (&locator.name, &locator.reference) else {
+            return false;
+        };
+        self.fallback_exclusion_list
+            .get(name)
+            .is_some_and(|references| references.contains(reference))
+    }
+
+    /// Looks up `name` in the dependencies of the fallback locators.
+    fn find_fallback(&self, name: &str) -> Option<&Option<PnpLocator>> {
+        std::iter::once(PnpLocator::top_level())
+            .chain(self.dependency_tree_roots.iter().cloned())
+            .filter_map(|locator| self.packages.get(&locator))
+            .find_map(|package| package.dependencies.get(name))
+            .or_else(|| self.fallback_pool.get(name))
+    }
+
+    /// Resolves the package `name` requested from a file in the `issuer` directory. Both paths
+    /// are relative to the root of the filesystem.
+    pub fn resolve(&self, issuer: &str, name: &str) -> PnpResolution {
+        if let Some(ignore_pattern) = &self.ignore_pattern {
+            let relative = if self.base.is_empty() {
+                Some(issuer)
+            } else {
+                issuer
+                    .strip_prefix(&*self.base)
+                    .and_then(|rest| rest.strip_prefix('/').or(rest.is_empty().then_some("")))
+            };
+            if relative.is_some_and(|relative| ignore_pattern.is_match(relative)) {
+                return PnpResolution::NotHandled;
+            }
+        }
+        let Some(issuers) = self.find_locators(issuer) else {
+            return PnpResolution::NotHandled;
+        };
+
+        // Virtual instances of a package share a location, so pick the first one which provides
+        // the dependency.
+        let mut dependency = None;
+        for issuer in issuers {
+            if let Some(package) = self.packages.get(issuer)
+                && let Some(locator) = package.dependencies.get(name)
+            {
+                dependency = Some(locator);
+                if locator.is_some() {
+                    break;
+                }
+            }
+        }
+        let issuer = issuers
+            .iter()
+            .find(|issuer| issuer.name.is_some())
+            .unwrap_or(&issuers[0]);
+        if dependency.is_none()
+            && self.enable_top_level_fallback
+            && !issuers
+                .iter()
+                .any(|issuer| self.is_fallback_excluded(issuer))
+        {
+            dependency = self.find_fallback(name);
+        }
+
+        let is_root = issuers
+            .iter()
+            .any(|issuer| issuer.name.is_none() || self.dependency_tree_roots.contains(issuer));
+        let locator = match dependency {
+            Some(Some(locator)) => locator,
+            Some(None) => {
+                return PnpResolution::Error(format!(
+                    "{issuer} tried to access {name} (a peer dependency) but it isn't provided by \
+                     its ancestors; this makes the require call ambiguous and unsound."
+                ));
+            }
+            None if is_root => {
+                return PnpResolution::Error(format!(
+                    "Your application tried to access {name}, but it isn't declared in your \
+                     dependencies; this makes the require call ambiguous and unsound. Add it to \
+                     the dependencies of {issuer}."
+                ));
+            }
+            None => {
+                return PnpResolution::Error(format!(
+                    "{issuer} tried to access {name}, but it isn't declared in its dependencies; \
+                     this makes the require call ambiguous and unsound. Add it to the \
+                     dependencies of {issuer} using `packageExtensions` in .yarnrc.yml."
+                ));
+            }
+        };
+
+        let Some(package) = self.packages.get(locator) else {
+            return PnpResolution::Error(format!(
+                "{locator} is a dependency of {issuer}, but it is missing from the PnP manifest. \
+                 Run `yarn install` to update it."
+            ));
+        };
+        if let Some((archive, path)) = split_zip_path(&package.location) {
+            return PnpResolution::Zipped {
+                archive: archive.into(),
+                path: path.into(),
+                package: locator.name.clone().unwrap_or_else(|| name.into()),
+            };
+        }
+        PnpResolution::Resolved(package.location.clone())
+    }
+}
+
+#[turbo_tasks::value(serialization = "none", eq = "manual", cell = "new")]
+#[derive(Debug)]
+pub struct PnpManifest {
+    /// The manifest file that was read.
+    pub path: FileSystemPath,
+    #[turbo_tasks(trace_ignore)]
+    pub data: PnpData,
+}
+
+#[turbo_tasks::value(transparent, serialization = "none", eq = "manual", cell = "new")]
+pub struct OptionPnpManifest(Option<ResolvedVc<PnpManifest>>);
+
+/// Reads the PnP manifest in the given directory, preferring `.pnp.data.json` over the state
+/// inlined into `.pnp.cjs`.
+#[turbo_tasks::function]
+pub async fn pnp_manifest(dir: FileSystemPath) -> Result<Vc<OptionPnpManifest>> {
+    for (file_name, inlined) in [(".pnp.data.json", false), (".pnp.cjs", true)] {
+        let path = dir.join(file_name)?;
+        let FileContent::Content(file) = &*path.read().await? else {
+            continue;
+        };
+        let content = file.content().to_str()?;
+        let data = if inlined {
+            extract_inlined_state(&content).and_then(|state| PnpData::parse(&state, &dir.path))
+        } else {
+            PnpData::parse(&content, &dir.path)
+        }
+        .with_context(|| format!("failed to parse the Yarn PnP manifest {}", path.path))?;
+        return Ok(Vc::cell(Some(PnpManifest { path, data }.resolved_cell())));
+    }
+    Ok(Vc::cell(None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PnpData, PnpResolution, extract_inlined_state, resolve_virtual, split_zip_path};
+
+    const MANIFEST: &str = r#"{
+        "__info": [],
+        "dependencyTreeRoots": [
+            {"name": "app", "reference": "workspace:."},
+            {"name": "lib", "reference": "workspace:packages/lib"}
+        ],
+        "enableTopLevelFallback": true,
+        "ignorePatternData": "^ignored(/|$)",
+        "fallbackExclusionList": [["app", ["workspace:."]]],
+        "fallbackPool": [["hoisted", "npm:1.0.0"]],
+        "packageRegistryData": [
+            [null, [[null, {
+                "packageLocation": "./",
+                "packageDependencies": [["react", "npm:18.2.0"], ["lib", "workspace:packages/lib"]]
+            }]]],
+            ["app", [["workspace:.", {
+                "packageLocation": "./",
+                "packageDependencies": [["react", "npm:18.2.0"], ["lib", "workspace:packages/lib"]]
+            }]]],
+            ["lib", [["workspace:packages/lib", {
+                "packageLocation": "./packages/lib/",
+                "packageDependencies": [["lodash", ["lodash-es", "npm:4.17.21"]], ["peer", null]]
+            }]]],
+            ["react", [["npm:18.2.0", {
+                "packageLocation": "./.yarn/unplugged/react-npm-18.2.0-abc/node_modules/react/",
+                "packageDependencies": [["react", "npm:18.2.0"]]
+            }]]],
+            ["lodash-es", [["npm:4.17.21", {
+                "packageLocation": "./.yarn/__virtual__/lodash-es-virtual-123/1/.yarn/cache/lodash-es-npm-4.17.21-def.zip/node_modules/lodash-es/",
+                "packageDependencies": [["lodash-es", "npm:4.17.21"]]
+            }]]],
+            ["hoisted", [["npm:1.0.0", {
+                "packageLocation": "./.yarn/unplugged/hoisted-npm-1.0.0-ghi/node_modules/hoisted/",
+                "packageDependencies": []
+            }]]]
+        ]
+    }"#;
+
+    #[test]
+    fn test_resolve_virtual() {
+        assert_eq!(resolve_virtual("a/b/c").as_deref(), Some("a/b/c"));
+        assert_eq!(
+            resolve_virtual(".yarn/__virtual__/x-virtual-1/0/cache/x.zip/node_modules/x")
+                .as_deref(),
+            Some(".yarn/cache/x.zip/node_modules/x")
+        );
+        assert_eq!(
+            resolve_virtual("app/.yarn/__virtual__/x-virtual-1/1/packages/x").as_deref(),
+            Some("app/packages/x")
+        );
+        assert_eq!(resolve_virtual(".yarn/__virtual__/x-virtual-1/3/x"), None);
+    }
+
+    #[test]
+    fn test_split_zip_path() {
+        assert_eq!(split_zip_path("a/b/c"), None);
+        assert_eq!(
+            split_zip_path(".yarn/cache/x.zip/node_modules/x"),
+            Some((".yarn/cache/x.zip", "node_modules/x"))
+        );
+        assert_eq!(split_zip_path("cache/x.zip"), Some(("cache/x.zip", "")));
+    }
+
+    #[test]
+    fn test_extract_inlined_state() {
+        let code = "#!/usr/bin/env node\nconst RAW_RUNTIME_STATE =\n'{\\\n  \"a\": \"it\\'s \
+                    \\\\\"\\\n}';\n\nfunction $$SETUP_STATE() {}\n";
+        // Line continuations don't add a newline
+        assert_eq!(
+            extract_inlined_state(code).unwrap(),
+            "{  \"a\": \"it's \\\"}"
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let data = PnpData::parse(MANIFEST, "project").unwrap();
+        assert_eq!(
+            data.resolve("project/src", "react"),
+            PnpResolution::Resolved(
+                "project/.yarn/unplugged/react-npm-18.2.0-abc/node_modules/react".into()
+            )
+        );
+        // The longest location wins
+        assert_eq!(
+            data.resolve("project/packages/lib/src", "lodash"),
+            data.resolve("project/packages/lib", "lodash")
+        );
+        assert_eq!(
+            data.resolve("project/src", "lib"),
+            PnpResolution::Resolved("project/packages/lib".into())
+        );
+        // Aliased dependencies are read from the zip archive in the cache
+        assert_eq!(
+            data.resolve("project/packages/lib", "lodash"),
+            PnpResolution::Zipped {
+                archive: "project/.yarn/cache/lodash-es-npm-4.17.21-def.zip".into(),
+                path: "node_modules/lodash-es".into(),
+                package: "lodash-es".into(),
+            }
+        );
+        // Packages can access themselves
+        assert_eq!(
+            data.resolve(
+                "project/.yarn/unplugged/react-npm-18.2.0-abc/node_modules/react/cjs",
+                "react"
+            ),
+            PnpResolution::Resolved(
+                "project/.yarn/unplugged/react-npm-18.2.0-abc/node_modules/react".into()
+            )
+        );
+        assert_eq!(data.resolve("other", "react"), PnpResolution::NotHandled);
+        assert_eq!(
+            data.resolve("project/ignored/a", "react"),
+            PnpResolution::NotHandled
+        );
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let data = PnpData::parse(MANIFEST, "").unwrap();
+        let PnpResolution::Error(message) = data.resolve("packages/lib", "peer") else {
+            panic!("expected an error");
+        };
+        assert!(message.contains("(a peer dependency)"), "{message}");
+
+        // The top-level workspace is excluded from the fallback
+        let PnpResolution::Error(message) = data.resolve("src", "hoisted") else {
+            panic!("expected an error");
+        };
+        assert!(message.starts_with("Your application tried to access hoisted"));
+
+        // Other packages fall back to the top-level dependencies and the fallback pool
+        assert_eq!(
+            data.resolve("packages/lib", "react"),
+            PnpResolution::Resolved(
+                ".yarn/unplugged/react-npm-18.2.0-abc/node_modules/react".into()
+            )
+        );
+        assert_eq!(
+            data.resolve("packages/lib", "hoisted"),
+            PnpResolution::Resolved(
+                ".yarn/unplugged/hoisted-npm-1.0.0-ghi/node_modules/hoisted".into()
+            )
+        );
+    }
+}
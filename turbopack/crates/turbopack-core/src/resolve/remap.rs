@@ -6,7 +6,7 @@ use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use turbo_rcstr::{RcStr, rcstr};
-use turbo_tasks::FxIndexMap;
+use turbo_tasks::{FxIndexMap, FxIndexSet};
 
 use crate::resolve::{
     alias_map::{AliasKey, AliasMap, AliasMapIter, AliasPattern, AliasTemplate},
@@ -332,6 +332,27 @@ impl ReplacedSubpathValue {
             ReplacedSubpathValue::Excluded => true,
         }
     }
+
+    /// Adds the names of the conditions used in the [ReplacedSubpathValue] to `target`, except
+    /// for `default`, which always applies.
+    pub fn add_conditions(&self, target: &mut FxIndexSet<RcStr>) {
+        match self {
+            ReplacedSubpathValue::Alternatives(list) => {
+                for value in list {
+                    value.add_conditions(target);
+                }
+            }
+            ReplacedSubpathValue::Conditional(list) => {
+                for (condition, value) in list {
+                    if condition != "default" {
+                        target.insert(condition.clone());
+                    }
+                    value.add_conditions(target);
+                }
+            }
+            ReplacedSubpathValue::Result(_) | ReplacedSubpathValue::Excluded => {}
+        }
+    }
 }
 
 struct ResultsIterMut<'a> {
@@ -0,0 +1,258 @@
+//! Recording of the steps taken while resolving a request, to explain why it resolved to a
+//! certain result (or didn't resolve at all).
+
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use anyhow::Result;
+use bincode::{Decode, Encode};
+use serde::Serialize;
+use turbo_rcstr::RcStr;
+use turbo_tasks::{
+    CollectiblesSource, NonLocalValue, ResolvedVc, TryJoinIterExt, ValueToString, Vc, emit,
+    trace::TraceRawVcs,
+};
+use turbo_tasks_fs::FileSystemPath;
+
+use crate::{
+    reference_type::ReferenceType,
+    resolve::{
+        ResolveResult,
+        options::{ImportMapResult, ResolveOptions},
+        parse::Request,
+        resolve,
+    },
+};
+
+/// The kind of a [ResolveTraceStep].
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    TraceRawVcs,
+    NonLocalValue,
+    Encode,
+    Decode,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ResolveTraceStepKind {
+    BeforeResolvePlugin,
+    ImportMap,
+    TsconfigPaths,
+    Modules,
+    ExportsField,
+    ImportsField,
+    Probe,
+    FallbackImportMap,
+    AfterResolvePlugin,
+}
+
+impl ResolveTraceStepKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResolveTraceStepKind::BeforeResolvePlugin => "before resolve plugin",
+            ResolveTraceStepKind::ImportMap => "import map",
+            ResolveTraceStepKind::TsconfigPaths => "tsconfig paths",
+            ResolveTraceStepKind::Modules => "modules",
+            ResolveTraceStepKind::ExportsField => "exports field",
+            ResolveTraceStepKind::ImportsField => "imports field",
+            ResolveTraceStepKind::Probe => "probe",
+            ResolveTraceStepKind::FallbackImportMap => "fallback import map",
+            ResolveTraceStepKind::AfterResolvePlugin => "after resolve plugin",
+        }
+    }
+}
+
+/// A single step of resolving `request` in `lookup_path`.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct ResolveTraceStep {
+    /// Increases with every recorded step, so steps can be put back into the order in which they
+    /// were taken.
+    pub sequence: u64,
+    pub lookup_path: FileSystemPath,
+    pub request: RcStr,
+    pub kind: ResolveTraceStepKind,
+    pub message: RcStr,
+}
+
+/// A collectible that is emitted for every step of a resolution with [ResolveOptions::trace]
+/// enabled.
+#[turbo_tasks::value_trait]
+pub trait ResolveTraceCollectible {
+    #[turbo_tasks::function]
+    fn step(self: Vc<Self>) -> Vc<ResolveTraceStep>;
+}
+
+#[turbo_tasks::value_impl]
+impl ResolveTraceCollectible for ResolveTraceStep {
+    #[turbo_tasks::function]
+    fn step(self: Vc<Self>) -> Vc<ResolveTraceStep> {
+        self
+    }
+}
+
+static NEXT_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Records a step of resolving `request`. Callers should only call this (and compute the message)
+/// when [ResolveOptions::trace] is enabled.
+pub(super) async fn emit_trace_step(
+    lookup_path: &FileSystemPath,
+    request: Vc<Request>,
+    kind: ResolveTraceStepKind,
+    message: impl Into<RcStr>,
+) -> Result<()> {
+    let step = ResolveTraceStep {
+        sequence: NEXT_SEQUENCE.fetch_add(1, Ordering::Relaxed),
+        lookup_path: lookup_path.clone(),
+        request: request.to_string().owned().await?,
+        kind,
+        message: message.into(),
+    }
+    .resolved_cell();
+    emit(ResolvedVc::upcast::<Box<dyn ResolveTraceCollectible>>(step));
+    Ok(())
+}
+
+/// Describes what an import map entry maps a request to.
+pub(super) async fn describe_import_map_result(result: &ImportMapResult) -> Result<String> {
+    Ok(match result {
+        ImportMapResult::Result(result) => {
+            format!("replaced with {}", result.to_string().await?)
+        }
+        ImportMapResult::Alias(request, lookup_path) => {
+            let mut description = format!("aliased to `{}`", request.to_string().await?);
+            if let Some(lookup_path) = lookup_path {
+                write!(
+                    description,
+                    " in `{}`",
+                    lookup_path.value_to_string().await?
+                )?;
+            }
+            description
+        }
+        ImportMapResult::External { name, ty, .. } => format!("external {ty} `{name}`"),
+        ImportMapResult::AliasExternal {
+            name,
+            ty,
+            lookup_dir,
+            ..
+        } => format!(
+            "external {ty} `{name}` if it can be resolved in `{}`",
+            lookup_dir.value_to_string().await?
+        ),
+        ImportMapResult::Alternatives(list) => {
+            let mut alternatives = Vec::with_capacity(list.len());
+            for result in list {
+                alternatives.push(Box::pin(describe_import_map_result(result)).await?);
+            }
+            format!("alternatives: {}", alternatives.join(", "))
+        }
+        ImportMapResult::NoEntry => "no entry".to_string(),
+    })
+}
+
+/// The steps of resolving a single request. Aliases, exports fields and packages lead to nested
+/// requests, which are separate groups.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TraceRawVcs, NonLocalValue, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveTraceGroup {
+    pub lookup_path: RcStr,
+    pub request: RcStr,
+    pub steps: Vec<(ResolveTraceStepKind, RcStr)>,
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveTrace {
+    /// The groups of steps, starting with the request that was explained.
+    pub groups: Vec<ResolveTraceGroup>,
+    /// A description of the final resolve result.
+    pub result: RcStr,
+}
+
+#[turbo_tasks::function(operation)]
+async fn traced_resolve_operation(
+    lookup_path: FileSystemPath,
+    reference_type: ReferenceType,
+    request: ResolvedVc<Request>,
+    options: ResolvedVc<ResolveOptions>,
+) -> Result<Vc<ResolveResult>> {
+    let mut options = options.owned().await?;
+    options.trace = true;
+    Ok(resolve(
+        lookup_path,
+        reference_type,
+        *request,
+        options.cell(),
+    ))
+}
+
+/// Resolves `request` like [resolve] and records every step taken along the way, e.g. import map
+/// lookups, the conditions of `exports` fields and the probed files.
+#[turbo_tasks::function]
+pub async fn explain_resolve(
+    lookup_path: FileSystemPath,
+    reference_type: ReferenceType,
+    request: ResolvedVc<Request>,
+    options: ResolvedVc<ResolveOptions>,
+) -> Result<Vc<ResolveTrace>> {
+    let operation = traced_resolve_operation(lookup_path.clone(), reference_type, request, options);
+    let result = operation.resolve_strongly_consistent().await?;
+    let mut steps = operation
+        .peek_collectibles::<Box<dyn ResolveTraceCollectible>>()
+        .into_iter()
+        .map(|collectible| collectible.step())
+        .try_join()
+        .await?;
+    // Collectibles are unordered
+    steps.sort_by_key(|step| step.sequence);
+
+    let root = (
+        lookup_path.value_to_string().owned().await?,
+        request.to_string().owned().await?,
+    );
+    let mut groups: Vec<ResolveTraceGroup> = Vec::new();
+    for step in steps {
+        let key = (
+            step.lookup_path.value_to_string().owned().await?,
+            step.request.clone(),
+        );
+        let group = match groups
+            .iter_mut()
+            .position(|group| group.lookup_path == key.0 && group.request == key.1)
+        {
+            Some(index) => &mut groups[index],
+            None => {
+                groups.push(ResolveTraceGroup {
+                    lookup_path: key.0,
+                    request: key.1,
+                    steps: Vec::new(),
+                });
+                groups.last_mut().unwrap()
+            }
+        };
+        group.steps.push((step.kind, step.message.clone()));
+    }
+    // The same step can be recorded multiple times, e.g. when a request is resolved for
+    // different conditions.
+    for group in &mut groups {
+        group.steps.dedup();
+    }
+    groups.sort_by_key(|group| !(group.lookup_path == root.0 && group.request == root.1));
+
+    Ok(ResolveTrace {
+        groups,
+        result: result.to_string().owned().await?,
+    }
+    .cell())
+}
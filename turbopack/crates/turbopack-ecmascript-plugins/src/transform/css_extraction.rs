@@ -0,0 +1,708 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use rustc_hash::FxHashMap;
+use swc_core::{
+    common::{DUMMY_SP, Span, SyntaxContext},
+    ecma::{
+        ast::{
+            ArrowExpr, BinExpr, BinaryOp, BlockStmtOrExpr, CallExpr, Callee, CondExpr, Decl, Expr,
+            ExprOrSpread, Id, Ident, IdentName, ImportDecl, ImportSpecifier, ImportStarAsSpecifier,
+            KeyValueProp, Lit, MemberExpr, MemberProp, Module, ModuleDecl, ModuleExportName,
+            ModuleItem, ObjectLit, ParenExpr, Pat, Program, Prop, PropName, PropOrSpread,
+            SpreadElement, Stmt, Str, TaggedTpl, Tpl, TplElement, VarDeclKind,
+        },
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+use turbo_rcstr::rcstr;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::issue::{
+    Issue, IssueExt, IssueSeverity, IssueSource, IssueStage, OptionIssueSource, OptionStyledString,
+    StyledString,
+};
+use turbopack_ecmascript::{
+    TransformContext,
+    analyzer::{ConstantNumber, ConstantValue, JsValue, graph::EvalContext},
+};
+
+/// The CSS-in-JS libraries whose styles can be extracted at build time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CssInJsLibrary {
+    /// `styled.tag` and `styled(Component)` of `styled-components`.
+    StyledComponents,
+    /// `styled.tag` and `styled(Component)` of `@emotion/styled`, and `css` of `@emotion/css`.
+    Emotion,
+}
+
+const STYLES_LOCAL: &str = "__turbopack_extracted_styles__";
+const REACT_LOCAL: &str = "__turbopack_react__";
+const PROPS_LOCAL: &str = "__turbopack_props__";
+const REF_LOCAL: &str = "__turbopack_ref__";
+const STYLED_LOCAL: &str = "__turbopack_styled__";
+
+/// Moves styles that can be evaluated at build time into a CSS module, so that they don't need to
+/// be inserted at runtime.
+///
+/// Tagged templates and object styles of `styled` become components that add the class name of the
+/// extracted styles to a component of the library without styles, which still filters the props
+/// passed to the element. `css` of `@emotion/css` becomes that class name. Interpolations are
+/// evaluated with the analyzer, they may only refer to constants of the module. When that isn't
+/// possible the styles are left to the runtime and an issue is reported.
+pub async fn extract_static_styles(
+    program: &mut Program,
+    ctx: &TransformContext<'_>,
+    library: CssInJsLibrary,
+) -> Result<()> {
+    let Program::Module(module) = program else {
+        return Ok(());
+    };
+    let imports = StyleImports::find(module, library);
+    if imports.styled.is_empty() && imports.css.is_empty() {
+        return Ok(());
+    }
+
+    let top_level_ctxt = SyntaxContext::empty().apply_mark(ctx.top_level_mark);
+    let eval_context = EvalContext::new(
+        Some(&*program),
+        ctx.unresolved_mark,
+        ctx.top_level_mark,
+        Default::default(),
+        None,
+        None,
+    );
+    let Program::Module(module) = program else {
+        unreachable!();
+    };
+    let mut extractor = StyleExtractor {
+        library,
+        imports,
+        eval_context,
+        constants: FxHashMap::default(),
+        top_level_ctxt,
+        css: String::new(),
+        class_count: 0,
+        uses_react: false,
+        dynamic: Vec::new(),
+    };
+    extractor.collect_constants(module);
+    module.visit_mut_with(&mut extractor);
+
+    // Dynamic styles are common and work fine, so they are only reported once per module.
+    if let Some(span) = extractor.dynamic.first() {
+        CssExtractionIssue {
+            file_path: ctx.file_path.clone(),
+            source: IssueSource::from_swc_offsets(ctx.source, span.lo.0, span.hi.0),
+            count: extractor.dynamic.len(),
+        }
+        .resolved_cell()
+        .emit();
+    }
+
+    if extractor.class_count == 0 {
+        return Ok(());
+    }
+    let mut imports = vec![namespace_import(
+        Ident::new(STYLES_LOCAL.into(), DUMMY_SP, top_level_ctxt),
+        &format!("data:text/css+module,{}", encode_data_uri(&extractor.css)),
+    )];
+    if extractor.uses_react {
+        imports.push(namespace_import(
+            Ident::new(REACT_LOCAL.into(), DUMMY_SP, top_level_ctxt),
+            "react",
+        ));
+    }
+    module.body.splice(0..0, imports);
+
+    Ok(())
+}
+
+/// The local bindings of the imported style functions.
+#[derive(Default)]
+struct StyleImports {
+    styled: Vec<Id>,
+    css: Vec<Id>,
+}
+
+impl StyleImports {
+    fn find(module: &Module, library: CssInJsLibrary) -> Self {
+        let mut imports = StyleImports::default();
+        for item in &module.body {
+            let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item else {
+                continue;
+            };
+            if import.type_only {
+                continue;
+            }
+            let src = import.src.value.to_string_lossy();
+            for specifier in &import.specifiers {
+                let (local, imported) = match specifier {
+                    ImportSpecifier::Default(default) => (&default.local, "default"),
+                    ImportSpecifier::Named(named) if !named.is_type_only => (
+                        &named.local,
+                        match &named.imported {
+                            Some(ModuleExportName::Ident(ident)) => ident.sym.as_str(),
+                            Some(ModuleExportName::Str(_)) => continue,
+                            None => named.local.sym.as_str(),
+                        },
+                    ),
+                    _ => continue,
+                };
+                match (library, &*src, imported) {
+                    (
+                        CssInJsLibrary::StyledComponents,
+                        "styled-components",
+                        "default" | "styled",
+                    )
+                    | (CssInJsLibrary::Emotion, "@emotion/styled", "default") => {
+                        imports.styled.push(local.to_id())
+                    }
+                    (CssInJsLibrary::Emotion, "@emotion/css", "css") => {
+                        imports.css.push(local.to_id())
+                    }
+                    _ => {}
+                }
+            }
+        }
+        imports
+    }
+}
+
+/// A value that is known at build time.
+#[derive(Clone, Debug)]
+enum StaticValue {
+    Str(String),
+    Num(f64),
+}
+
+impl StaticValue {
+    fn to_css(&self) -> String {
+        match self {
+            StaticValue::Str(str) => str.clone(),
+            StaticValue::Num(num) if num.fract() == 0.0 && num.abs() < 1e21 => {
+                format!("{}", *num as i64)
+            }
+            StaticValue::Num(num) => num.to_string(),
+        }
+    }
+}
+
+/// What the extracted styles are used for.
+enum StyleTarget {
+    /// A component created with the `styled` tag, e.g. `styled.button`.
+    Component(Box<Expr>),
+    /// The class name itself.
+    ClassName,
+}
+
+struct StyleExtractor {
+    library: CssInJsLibrary,
+    imports: StyleImports,
+    eval_context: EvalContext,
+    /// Top level constants of the module that have a static value.
+    constants: FxHashMap<Id, StaticValue>,
+    top_level_ctxt: SyntaxContext,
+    /// The CSS module with the extracted styles.
+    css: String,
+    class_count: usize,
+    uses_react: bool,
+    /// The styles that couldn't be extracted because of dynamic values.
+    dynamic: Vec<Span>,
+}
+
+impl StyleExtractor {
+    fn collect_constants(&mut self, module: &Module) {
+        for item in &module.body {
+            let decl = match item {
+                ModuleItem::Stmt(Stmt::Decl(Decl::Var(decl))) => decl,
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => match &export.decl {
+                    Decl::Var(decl) => decl,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            if decl.kind != VarDeclKind::Const {
+                continue;
+            }
+            for declarator in &decl.decls {
+                if let (Pat::Ident(ident), Some(init)) = (&declarator.name, &declarator.init)
+                    && let Some(value) = self.eval(init)
+                {
+                    self.constants.insert(ident.to_id(), value);
+                }
+            }
+        }
+    }
+
+    fn eval(&self, expr: &Expr) -> Option<StaticValue> {
+        self.static_value(self.eval_context.eval(expr))
+    }
+
+    fn static_value(&self, value: JsValue) -> Option<StaticValue> {
+        match value {
+            JsValue::Constant(ConstantValue::Num(ConstantNumber(num))) => {
+                Some(StaticValue::Num(num))
+            }
+            JsValue::Constant(constant) => constant
+                .as_str()
+                .map(|str| StaticValue::Str(str.to_string())),
+            JsValue::Variable(id) => self.constants.get(&id).cloned(),
+            JsValue::Concat(_, values) => values
+                .into_iter()
+                .map(|value| Some(self.static_value(value)?.to_css()))
+                .collect::<Option<String>>()
+                .map(StaticValue::Str),
+            JsValue::Add(_, values) => values
+                .into_iter()
+                .try_fold(None, |sum, value| {
+                    let value = self.static_value(value)?;
+                    Some(Some(match (sum, value) {
+                        (None, value) => value,
+                        (Some(StaticValue::Num(a)), StaticValue::Num(b)) => StaticValue::Num(a + b),
+                        (Some(a), b) => StaticValue::Str(a.to_css() + &b.to_css()),
+                    }))
+                })
+                .flatten(),
+            _ => None,
+        }
+    }
+
+    fn is_import(&self, expr: &Expr, imports: &[Id]) -> bool {
+        matches!(expr, Expr::Ident(ident) if imports.contains(&ident.to_id()))
+    }
+
+    /// Returns what styles passed to the `tag` are used for, if it's a supported style function.
+    fn style_target(&self, tag: &Expr) -> Option<StyleTarget> {
+        match tag {
+            // styled.button
+            Expr::Member(MemberExpr {
+                obj,
+                prop: MemberProp::Ident(_),
+                ..
+            }) if self.is_import(obj, &self.imports.styled) => {
+                Some(StyleTarget::Component(Box::new(tag.clone())))
+            }
+            // styled(Component)
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                args,
+                ..
+            }) if self.is_import(callee, &self.imports.styled)
+                && args.len() == 1
+                && args[0].spread.is_none() =>
+            {
+                Some(StyleTarget::Component(Box::new(tag.clone())))
+            }
+            // css
+            tag if self.is_import(tag, &self.imports.css) => Some(StyleTarget::ClassName),
+            _ => None,
+        }
+    }
+
+    fn template_css(&self, tpl: &Tpl) -> Option<String> {
+        let mut css = String::new();
+        for (index, quasi) in tpl.quasis.iter().enumerate() {
+            css += &quasi.cooked.as_ref()?.to_string_lossy();
+            if let Some(expr) = tpl.exprs.get(index) {
+                css += &self.eval(expr)?.to_css();
+            }
+        }
+        Some(css)
+    }
+
+    fn object_css(&self, object: &ObjectLit, css: &mut String) -> Option<()> {
+        for prop in &object.props {
+            let PropOrSpread::Prop(prop) = prop else {
+                return None;
+            };
+            let Prop::KeyValue(KeyValueProp { key, value }) = &**prop else {
+                return None;
+            };
+            let key = match key {
+                PropName::Ident(ident) => ident.sym.to_string(),
+                PropName::Str(str) => str.value.to_string_lossy().into_owned(),
+                _ => return None,
+            };
+            if let Expr::Object(nested) = &**value {
+                // Nested selectors and at-rules, e.g. `"&:hover": { ... }`
+                write!(css, "{key}{{").unwrap();
+                self.object_css(nested, css)?;
+                css.push('}');
+                continue;
+            }
+            let value = match self.eval(value)? {
+                StaticValue::Num(num) if num != 0.0 && !is_unitless_property(&key) => {
+                    format!("{}px", StaticValue::Num(num).to_css())
+                }
+                value => value.to_css(),
+            };
+            write!(css, "{}:{value};", css_property_name(&key)).unwrap();
+        }
+        Some(())
+    }
+
+    /// Adds the styles to the CSS module and returns an expression for their class name.
+    fn add_class(&mut self, styles: &str) -> Expr {
+        let class = format!("s{}", self.class_count);
+        self.class_count += 1;
+        writeln!(self.css, ".{class}{{{styles}}}").unwrap();
+        Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(Expr::Ident(self.ident(STYLES_LOCAL))),
+            prop: MemberProp::Ident(IdentName::new(class.into(), DUMMY_SP)),
+        })
+    }
+
+    fn ident(&self, name: &str) -> Ident {
+        Ident::new(name.into(), DUMMY_SP, self.top_level_ctxt)
+    }
+
+    /// A component of the library without styles, i.e. the `tag` with an empty template for
+    /// `styled-components` and `tag()` for emotion. It forwards the same props to the element as
+    /// the original component.
+    fn unstyled_component(&self, tag: Box<Expr>) -> Expr {
+        match self.library {
+            CssInJsLibrary::StyledComponents => Expr::TaggedTpl(TaggedTpl {
+                span: DUMMY_SP,
+                ctxt: Default::default(),
+                tag,
+                type_params: None,
+                tpl: Box::new(Tpl {
+                    span: DUMMY_SP,
+                    exprs: vec![],
+                    quasis: vec![TplElement {
+                        span: DUMMY_SP,
+                        tail: true,
+                        cooked: Some("".into()),
+                        raw: "".into(),
+                    }],
+                }),
+            }),
+            CssInJsLibrary::Emotion => Expr::Call(CallExpr {
+                callee: Callee::Expr(tag),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// `(styled => React.forwardRef((props, ref) => React.createElement(styled, { ...props, ref,
+    /// className })))(unstyled)` where the class name is combined with the `className` prop. The
+    /// unstyled component is created once and filters the props like the library does, e.g. it
+    /// drops `$transient` props and props that aren't valid attributes of an HTML element.
+    fn component(&mut self, tag: Box<Expr>, class_name: Expr) -> Expr {
+        self.uses_react = true;
+        let styled = self.ident(STYLED_LOCAL);
+        let props = self.ident(PROPS_LOCAL);
+        let ref_ = self.ident(REF_LOCAL);
+        let props_class_name = || {
+            Box::new(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::Ident(props.clone())),
+                prop: MemberProp::Ident(IdentName::new("className".into(), DUMMY_SP)),
+            }))
+        };
+        let class_name = Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test: props_class_name(),
+            cons: Box::new(Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: BinaryOp::Add,
+                left: Box::new(Expr::Bin(BinExpr {
+                    span: DUMMY_SP,
+                    op: BinaryOp::Add,
+                    left: Box::new(class_name.clone()),
+                    right: Box::new(str_expr(" ")),
+                })),
+                right: props_class_name(),
+            })),
+            alt: Box::new(class_name),
+        });
+        let element_props = Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: vec![
+                PropOrSpread::Spread(SpreadElement {
+                    dot3_token: DUMMY_SP,
+                    expr: Box::new(Expr::Ident(props.clone())),
+                }),
+                key_value("ref", Expr::Ident(ref_.clone())),
+                key_value("className", class_name),
+            ],
+        });
+        let render = ArrowExpr {
+            params: vec![Pat::Ident(props.into()), Pat::Ident(ref_.into())],
+            body: Box::new(BlockStmtOrExpr::Expr(Box::new(self.react_call(
+                "createElement",
+                vec![Expr::Ident(styled.clone()), element_props],
+            )))),
+            ..Default::default()
+        };
+        let create = ArrowExpr {
+            params: vec![Pat::Ident(styled.into())],
+            body: Box::new(BlockStmtOrExpr::Expr(Box::new(
+                self.react_call("forwardRef", vec![Expr::Arrow(render)]),
+            ))),
+            ..Default::default()
+        };
+        Expr::Call(CallExpr {
+            callee: Callee::Expr(Box::new(Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Arrow(create)),
+            }))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(self.unstyled_component(tag)),
+            }],
+            ..Default::default()
+        })
+    }
+
+    fn react_call(&self, name: &str, args: Vec<Expr>) -> Expr {
+        Expr::Call(CallExpr {
+            callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::Ident(self.ident(REACT_LOCAL))),
+                prop: MemberProp::Ident(IdentName::new(name.into(), DUMMY_SP)),
+            }))),
+            args: args
+                .into_iter()
+                .map(|expr| ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr),
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    /// Extracts the styles, returning the expression replacing the style function call.
+    fn extract(&mut self, target: StyleTarget, styles: &str) -> Expr {
+        let class_name = self.add_class(styles);
+        match target {
+            StyleTarget::Component(tag) => self.component(tag, class_name),
+            StyleTarget::ClassName => class_name,
+        }
+    }
+}
+
+impl VisitMut for StyleExtractor {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        expr.visit_mut_children_with(self);
+
+        let (target, styles, span) = match expr {
+            Expr::TaggedTpl(TaggedTpl { tag, tpl, span, .. }) => {
+                let Some(target) = self.style_target(tag) else {
+                    return;
+                };
+                (target, self.template_css(tpl), *span)
+            }
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                args,
+                span,
+                ..
+            }) => {
+                let [
+                    ExprOrSpread {
+                        spread: None,
+                        expr: arg,
+                    },
+                ] = &args[..]
+                else {
+                    return;
+                };
+                let Expr::Object(object) = &**arg else {
+                    return;
+                };
+                let Some(target) = self.style_target(callee) else {
+                    return;
+                };
+                let mut css = String::new();
+                (
+                    target,
+                    self.object_css(object, &mut css).map(|_| css),
+                    *span,
+                )
+            }
+            _ => return,
+        };
+        match styles {
+            Some(styles) => *expr = self.extract(target, &styles),
+            None => self.dynamic.push(span),
+        }
+    }
+}
+
+fn str_expr(value: &str) -> Expr {
+    Expr::Lit(Lit::Str(Str {
+        span: DUMMY_SP,
+        value: value.into(),
+        raw: None,
+    }))
+}
+
+fn key_value(key: &str, value: Expr) -> PropOrSpread {
+    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key: PropName::Ident(IdentName::new(key.into(), DUMMY_SP)),
+        value: Box::new(value),
+    })))
+}
+
+fn namespace_import(local: Ident, src: &str) -> ModuleItem {
+    ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+        span: DUMMY_SP,
+        specifiers: vec![ImportSpecifier::Namespace(ImportStarAsSpecifier {
+            span: DUMMY_SP,
+            local,
+        })],
+        src: Box::new(Str {
+            span: DUMMY_SP,
+            value: src.into(),
+            raw: None,
+        }),
+        type_only: false,
+        with: None,
+        phase: Default::default(),
+    }))
+}
+
+/// Percent-encodes the characters that can't be part of a data URL.
+fn encode_data_uri(data: &str) -> String {
+    let mut encoded = String::with_capacity(data.len());
+    for byte in data.bytes() {
+        if byte.is_ascii_graphic() && !matches!(byte, b'%' | b'#' | b'?' | b'"' | b'\'') {
+            encoded.push(byte as char);
+        } else {
+            write!(encoded, "%{byte:02X}").unwrap();
+        }
+    }
+    encoded
+}
+
+/// Converts the key of an object style to a CSS property, e.g. `backgroundColor` to
+/// `background-color` and `WebkitTransition` to `-webkit-transition`.
+fn css_property_name(key: &str) -> String {
+    if key.starts_with("--") {
+        return key.to_string();
+    }
+    let mut name = String::with_capacity(key.len() + 4);
+    if key.starts_with("ms") && key[2..].starts_with(|c: char| c.is_ascii_uppercase()) {
+        name.push('-');
+    }
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            name.push('-');
+            name.push(c.to_ascii_lowercase());
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
+
+/// Properties that don't get a `px` unit for numbers, the same as in `@emotion/unitless`.
+fn is_unitless_property(key: &str) -> bool {
+    matches!(
+        key,
+        "animationIterationCount"
+            | "aspectRatio"
+            | "borderImageOutset"
+            | "borderImageSlice"
+            | "borderImageWidth"
+            | "boxFlex"
+            | "boxFlexGroup"
+            | "boxOrdinalGroup"
+            | "columnCount"
+            | "columns"
+            | "flex"
+            | "flexGrow"
+            | "flexPositive"
+            | "flexShrink"
+            | "flexNegative"
+            | "flexOrder"
+            | "gridRow"
+            | "gridRowEnd"
+            | "gridRowSpan"
+            | "gridRowStart"
+            | "gridColumn"
+            | "gridColumnEnd"
+            | "gridColumnSpan"
+            | "gridColumnStart"
+            | "msGridRow"
+            | "msGridRowSpan"
+            | "msGridColumn"
+            | "msGridColumnSpan"
+            | "fontWeight"
+            | "lineHeight"
+            | "opacity"
+            | "order"
+            | "orphans"
+            | "scale"
+            | "tabSize"
+            | "widows"
+            | "zIndex"
+            | "zoom"
+            | "WebkitLineClamp"
+            | "fillOpacity"
+            | "floodOpacity"
+            | "stopOpacity"
+            | "strokeDasharray"
+            | "strokeDashoffset"
+            | "strokeMiterlimit"
+            | "strokeOpacity"
+            | "strokeWidth"
+    )
+}
+
+#[turbo_tasks::value(shared)]
+struct CssExtractionIssue {
+    file_path: FileSystemPath,
+    /// The first style that couldn't be extracted.
+    source: IssueSource,
+    /// The number of styles that couldn't be extracted.
+    count: usize,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for CssExtractionIssue {
+    fn severity(&self) -> IssueSeverity {
+        IssueSeverity::Hint
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Transform.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(if self.count == 1 {
+            rcstr!("1 style could not be extracted at build time")
+        } else {
+            format!("{} styles could not be extracted at build time", self.count).into()
+        })
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path.clone().cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(rcstr!(
+                "The styles use values that are only known at runtime, like props or imported \
+                 values, so they are inserted at runtime instead. Only literals and constants of \
+                 the same module can be extracted."
+            ))
+            .resolved_cell(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        Vc::cell(Some(self.source))
+    }
+}
@@ -23,6 +23,8 @@ use turbo_rcstr::RcStr;
 use turbo_tasks::{NonLocalValue, OperationValue, ValueDefault, Vc, trace::TraceRawVcs};
 use turbopack_ecmascript::{CustomTransformer, TransformContext};
 
+use crate::transform::css_extraction::{CssInJsLibrary, extract_static_styles};
+
 #[derive(
     Clone,
     PartialEq,
@@ -82,6 +84,10 @@ pub struct EmotionTransformConfig {
     pub auto_label: Option<EmotionLabelKind>,
     #[bincode(with_serde)]
     pub import_map: Option<IndexMap<RcStr, EmotionImportMapValue, FxBuildHasher>>,
+    /// Moves styles without runtime values into a CSS module at build time, see
+    /// [extract_static_styles].
+    #[serde(default)]
+    pub extract_static_styles: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -102,11 +108,13 @@ impl ValueDefault for EmotionTransformConfig {
 pub struct EmotionTransformer {
     #[cfg(feature = "transform_emotion")]
     config: swc_emotion::EmotionOptions,
+    extract_static_styles: bool,
 }
 
 #[cfg(feature = "transform_emotion")]
 impl EmotionTransformer {
     pub fn new(config: &EmotionTransformConfig) -> Option<Self> {
+        let extract_static_styles = config.extract_static_styles;
         let config = swc_emotion::EmotionOptions {
             // When you create a transformer structure, it is assumed that you are performing an
             // emotion transform.
@@ -134,14 +142,20 @@ impl EmotionTransformer {
             }),
         };
 
-        Some(EmotionTransformer { config })
+        Some(EmotionTransformer {
+            config,
+            extract_static_styles,
+        })
     }
 }
 
 #[cfg(not(feature = "transform_emotion"))]
 impl EmotionTransformer {
-    pub fn new(_config: &EmotionTransformConfig) -> Option<Self> {
-        None
+    pub fn new(config: &EmotionTransformConfig) -> Option<Self> {
+        // Extracting styles doesn't need the emotion transform.
+        config.extract_static_styles.then_some(EmotionTransformer {
+            extract_static_styles: true,
+        })
     }
 }
 
@@ -149,6 +163,10 @@ impl EmotionTransformer {
 impl CustomTransformer for EmotionTransformer {
     #[tracing::instrument(level = tracing::Level::TRACE, name = "emotion", skip_all)]
     async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        if self.extract_static_styles {
+            extract_static_styles(program, ctx, CssInJsLibrary::Emotion).await?;
+        }
+
         #[cfg(feature = "transform_emotion")]
         {
             let hash = {
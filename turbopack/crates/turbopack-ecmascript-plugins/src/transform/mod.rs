@@ -1,3 +1,4 @@
+pub mod css_extraction;
 pub mod directives;
 pub mod emotion;
 pub mod relay;
@@ -5,6 +5,8 @@ use swc_core::{atoms::Wtf8Atom, common::comments::NoopComments, ecma::ast::Progr
 use turbo_tasks::{ValueDefault, Vc};
 use turbopack_ecmascript::{CustomTransformer, TransformContext};
 
+use crate::transform::css_extraction::{CssInJsLibrary, extract_static_styles};
+
 #[turbo_tasks::value(shared, operation)]
 #[derive(Clone, Debug, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
@@ -16,6 +18,9 @@ pub struct StyledComponentsTransformConfig {
     pub meaningless_file_names: Vec<String>,
     pub css_prop: bool,
     pub namespace: Option<String>,
+    /// Moves styles without runtime values into a CSS module at build time, see
+    /// [extract_static_styles].
+    pub extract_static_styles: bool,
 }
 
 impl Default for StyledComponentsTransformConfig {
@@ -28,6 +33,7 @@ impl Default for StyledComponentsTransformConfig {
             meaningless_file_names: vec!["index".to_string()],
             css_prop: true,
             namespace: None,
+            extract_static_styles: false,
         }
     }
 }
@@ -49,6 +55,7 @@ impl ValueDefault for StyledComponentsTransformConfig {
 #[derive(Debug)]
 pub struct StyledComponentsTransformer {
     config: styled_components::Config,
+    extract_static_styles: bool,
 }
 
 impl StyledComponentsTransformer {
@@ -79,7 +86,10 @@ impl StyledComponentsTransformer {
                 .clone_from(meaningless_file_names);
         }
 
-        Self { config: options }
+        Self {
+            config: options,
+            extract_static_styles: config.extract_static_styles,
+        }
     }
 }
 
@@ -87,6 +97,10 @@ impl StyledComponentsTransformer {
 impl CustomTransformer for StyledComponentsTransformer {
     #[tracing::instrument(level = tracing::Level::TRACE, name = "styled_components", skip_all)]
     async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        if self.extract_static_styles {
+            extract_static_styles(program, ctx, CssInJsLibrary::StyledComponents).await?;
+        }
+
         program.mutate(styled_components::styled_components(
             Some(ctx.file_path_str),
             ctx.file_name_hash,
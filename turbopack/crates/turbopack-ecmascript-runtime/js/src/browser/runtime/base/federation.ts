@@ -0,0 +1,312 @@
+/**
+ * This file contains the module federation runtime. It will be appended to the
+ * base runtime code of builds with module federation enabled.
+ *
+ * All builds loaded into the page share a registry on `globalThis`. Remote
+ * entries register the modules their build exposes as a container, and every
+ * build offers the versions of shared packages it loaded, so that other builds
+ * can use them instead of loading their own copy.
+ */
+
+/* eslint-disable @typescript-eslint/no-unused-vars */
+
+/// <reference path="./runtime-base.ts" />
+
+type FederationExposes = Record<string, () => Promise<any>>
+
+type FederationContainer = {
+  get(expose: string): Promise<any>
+}
+
+type SharedVersion = {
+  version: string | null
+  from: string
+  exports: any
+}
+
+type SharedConfig = {
+  from: string
+  version: string | null
+  requiredVersion: string | null
+  singleton: boolean
+}
+
+type FederationRegistry = {
+  containers: Record<string, FederationContainer>
+  pendingContainers: Record<string, ((container: FederationContainer) => void)[]>
+  shareScope: Record<string, SharedVersion[]>
+}
+
+interface TurbopackFederationContext<M> extends TurbopackBrowserBaseContext<M> {
+  F: (remote: string, url: string, expose: string) => Promise<any>
+  S: (name: string, config: SharedConfig, getLocal: () => any) => any
+  X: (name: string, exposes: FederationExposes) => void
+}
+
+const federationContextPrototype =
+  browserContextPrototype as TurbopackFederationContext<unknown>
+
+const federationRegistry: FederationRegistry = (() => {
+  const globals = globalThis as { __TURBOPACK_FEDERATION__?: FederationRegistry }
+  // `||=` would be better but we need to be es2020 compatible
+  return (
+    globals.__TURBOPACK_FEDERATION__ ||
+    (globals.__TURBOPACK_FEDERATION__ = {
+      containers: {},
+      pendingContainers: {},
+      shareScope: {},
+    })
+  )
+})()
+
+/**
+ * Registers the container of a remote entry.
+ */
+function registerFederationContainer(
+  name: string,
+  exposes: FederationExposes
+) {
+  const container: FederationContainer = {
+    get(expose) {
+      const load = exposes[expose]
+      if (load == null) {
+        return Promise.reject(
+          new Error(`Module ${expose} is not exposed by remote ${name}`)
+        )
+      }
+      return load()
+    },
+  }
+  federationRegistry.containers[name] = container
+  const pending = federationRegistry.pendingContainers[name]
+  delete federationRegistry.pendingContainers[name]
+  pending?.forEach((resolve) => resolve(container))
+}
+federationContextPrototype.X = registerFederationContainer
+
+const remoteEntryLoads: Map<string, Promise<FederationContainer>> = new Map()
+
+function loadRemoteEntry(
+  remote: string,
+  url: string
+): Promise<FederationContainer> {
+  const container = federationRegistry.containers[remote]
+  if (container != null) {
+    return Promise.resolve(container)
+  }
+  let load = remoteEntryLoads.get(remote)
+  if (load != null) {
+    return load
+  }
+  load = new Promise<FederationContainer>((resolve, reject) => {
+    const pending =
+      federationRegistry.pendingContainers[remote] ||
+      (federationRegistry.pendingContainers[remote] = [])
+    pending.push(resolve)
+    const fail = () => {
+      remoteEntryLoads.delete(remote)
+      reject(new Error(`Failed to load the remote entry of ${remote} from ${url}`))
+    }
+    // The base runtime is typed for workers, which don't have a `document`.
+    const document = (globalThis as { document?: any }).document
+    if (document != null) {
+      // The remote entry registers its container when it's evaluated.
+      const script = document.createElement('script')
+      script.src = url
+      script.onerror = fail
+      document.head.appendChild(script)
+    } else if (typeof importScripts === 'function') {
+      try {
+        importScripts(url)
+      } catch {
+        fail()
+      }
+    } else {
+      fail()
+    }
+  })
+  remoteEntryLoads.set(remote, load)
+  return load
+}
+
+/**
+ * Loads a module exposed by a remote.
+ */
+async function loadFederationRemote(
+  remote: string,
+  url: string,
+  expose: string
+): Promise<any> {
+  const container = await loadRemoteEntry(remote, url)
+  return container.get(expose)
+}
+federationContextPrototype.F = loadFederationRemote
+
+/**
+ * Returns the version of a shared package to use. The first loaded version of
+ * singletons is used, with a warning when it doesn't satisfy the required
+ * version. Other packages use the highest loaded version satisfying the
+ * required version. The build's own copy is used and offered to other builds
+ * when there is no such version.
+ */
+function getFederationShared(
+  name: string,
+  config: SharedConfig,
+  getLocal: () => any
+): any {
+  const versions =
+    federationRegistry.shareScope[name] ||
+    (federationRegistry.shareScope[name] = [])
+  if (config.singleton && versions.length > 0) {
+    const loaded = versions[0]
+    if (
+      config.requiredVersion != null &&
+      (loaded.version == null ||
+        !satisfiesVersion(loaded.version, config.requiredVersion))
+    ) {
+      console.warn(
+        `Shared singleton ${name} ${loaded.version ?? '(unknown version)'} loaded by ${loaded.from} doesn't satisfy ${config.requiredVersion} required by ${config.from}`
+      )
+    }
+    return loaded.exports
+  }
+  if (!config.singleton) {
+    // Without a required version, only the build's own copy can be used.
+    let best: SharedVersion | undefined
+    let bestVersion: ParsedVersion | undefined
+    for (const candidate of versions) {
+      const version =
+        candidate.version != null ? parseVersion(candidate.version) : undefined
+      const satisfies =
+        config.requiredVersion == null
+          ? candidate.from === config.from
+          : version != null &&
+            satisfiesVersion(candidate.version!, config.requiredVersion)
+      if (
+        satisfies &&
+        (best == null ||
+          (version != null &&
+            (bestVersion == null || compareVersions(version, bestVersion) > 0)))
+      ) {
+        best = candidate
+        bestVersion = version
+      }
+    }
+    if (best != null) {
+      return best.exports
+    }
+  }
+  const exports = getLocal()
+  versions.push({ version: config.version, from: config.from, exports })
+  return exports
+}
+federationContextPrototype.S = getFederationShared
+
+type ParsedVersion = [major: number, minor: number, patch: number, prerelease: string]
+
+function parseVersion(version: string): ParsedVersion | undefined {
+  const match =
+    /^v?(\d+)(?:\.(\d+))?(?:\.(\d+))?(?:-([0-9A-Za-z.-]+))?(?:\+.*)?$/.exec(
+      version.trim()
+    )
+  if (match == null) {
+    return undefined
+  }
+  return [+match[1], +(match[2] ?? 0), +(match[3] ?? 0), match[4] ?? '']
+}
+
+function compareVersions(a: ParsedVersion, b: ParsedVersion): number {
+  for (let i = 0; i < 3; i++) {
+    if (a[i] !== b[i]) {
+      return (a[i] as number) - (b[i] as number)
+    }
+  }
+  // A release is higher than its prereleases.
+  if (a[3] === b[3]) return 0
+  if (a[3] === '') return 1
+  if (b[3] === '') return -1
+  return a[3] < b[3] ? -1 : 1
+}
+
+/**
+ * Checks a version against a semver range. Supports `||`, space separated
+ * comparator sets, `^`, `~`, `>=`, `>`, `<=`, `<`, `=`, `x` wildcards and `*`.
+ * Prereleases only satisfy ranges naming them exactly.
+ */
+function satisfiesVersion(version: string, range: string): boolean {
+  const parsed = parseVersion(version)
+  if (parsed == null) {
+    return false
+  }
+  return range.split('||').some((set) => {
+    const comparators = set.trim().split(/\s+/)
+    if (parsed[3] !== '') {
+      return comparators.some((comparator) => {
+        const exact = parseVersion(comparator.replace(/^=/, ''))
+        return exact != null && compareVersions(parsed, exact) === 0
+      })
+    }
+    return comparators.every((comparator) =>
+      satisfiesComparator(parsed, comparator)
+    )
+  })
+}
+
+function satisfiesComparator(
+  version: ParsedVersion,
+  comparator: string
+): boolean {
+  const match = /^(\^|~|>=|>|<=|<|=)?v?(.*)$/.exec(comparator)!
+  const operator = match[1] ?? ''
+  const parts = match[2].split(/[-+]/)[0].split('.')
+  // The number of leading parts that aren't wildcards, e.g. 2 for `1.2.x`.
+  let precision = 0
+  while (precision < 3 && /^\d+$/.test(parts[precision] ?? '')) {
+    precision++
+  }
+  if (precision === 0) {
+    // `*` matches every version.
+    return operator !== '<' && operator !== '>'
+  }
+  const lower: ParsedVersion = [0, 0, 0, '']
+  for (let i = 0; i < precision; i++) {
+    lower[i] = +parts[i]
+  }
+  // The lowest version with a higher part at the index, e.g. `1.3.0` for `1.2.3` and 1.
+  const next = (index: number): ParsedVersion => {
+    const result: ParsedVersion = [lower[0], lower[1], lower[2], '']
+    result[index] = (result[index] as number) + 1
+    for (let i = index + 1; i < 3; i++) {
+      result[i] = 0
+    }
+    return result
+  }
+  const compared = compareVersions(version, lower)
+  switch (operator) {
+    case '^': {
+      // The first non-zero part must not change.
+      let index = 0
+      while (index < precision - 1 && lower[index] === 0) {
+        index++
+      }
+      return compared >= 0 && compareVersions(version, next(index)) < 0
+    }
+    case '~':
+      return (
+        compared >= 0 &&
+        compareVersions(version, next(precision > 1 ? 1 : 0)) < 0
+      )
+    case '>=':
+      return compared >= 0
+    case '>':
+      return compareVersions(version, next(precision - 1)) >= 0
+    case '<=':
+      return compareVersions(version, next(precision - 1)) < 0
+    case '<':
+      return compared < 0
+    default:
+      return (
+        compared >= 0 && compareVersions(version, next(precision - 1)) < 0
+      )
+  }
+}
@@ -22,6 +22,9 @@ declare var TURBOPACK_NEXT_CHUNK_URLS: ChunkUrl[] | undefined
 // Injected by rust code
 declare var CHUNK_BASE_PATH: string
 declare var CHUNK_SUFFIX: string
+// `esm` when chunks are ES modules that export their registration instead of pushing it to the
+// global chunk list
+declare var CHUNK_FORMAT: 'script' | 'esm'
 
 interface TurbopackBrowserBaseContext<M> extends TurbopackBaseContext<M> {
   R: ResolvePathFromModule
@@ -360,12 +363,19 @@ function getPathFromScript(
   chunkScript: ChunkPath | ChunkListPath | ChunkScript | ChunkListScript
 ): ChunkPath | ChunkListPath {
   if (typeof chunkScript === 'string') {
-    return chunkScript as ChunkPath | ChunkListPath
+    // ES module chunks are identified by their `import.meta.url`
+    return CHUNK_FORMAT === 'esm'
+      ? getPathFromChunkUrl(chunkScript)
+      : (chunkScript as ChunkPath | ChunkListPath)
   }
   const chunkUrl =
     typeof TURBOPACK_NEXT_CHUNK_URLS !== 'undefined'
       ? TURBOPACK_NEXT_CHUNK_URLS.pop()!
       : chunkScript.getAttribute('src')!
+  return getPathFromChunkUrl(chunkUrl)
+}
+
+function getPathFromChunkUrl(chunkUrl: string): ChunkPath | ChunkListPath {
   const src = decodeURIComponent(chunkUrl.replace(/[?#].*$/, ''))
   const path = src.startsWith(CHUNK_BASE_PATH)
     ? src.slice(CHUNK_BASE_PATH.length)
@@ -158,7 +158,11 @@ const chunkResolvers: Map<ChunkUrl, ChunkResolver> = new Map()
       return resolver.promise
     }
 
-    if (typeof importScripts === 'function') {
+    if (CHUNK_FORMAT === 'esm' && isJs(chunkUrl)) {
+      // ES module chunks export their registration, which works the same way in documents and
+      // module workers.
+      loadEsmChunk(chunkUrl, resolver)
+    } else if (typeof importScripts === 'function') {
       // We're in a web worker
       if (isCss(chunkUrl)) {
         // ignore
@@ -230,6 +234,16 @@ const chunkResolvers: Map<ChunkUrl, ChunkResolver> = new Map()
     return resolver.promise
   }
 
+  /**
+   * Loads an ES module chunk and registers the chunk it exports.
+   */
+  function loadEsmChunk(chunkUrl: ChunkUrl, resolver: ChunkResolver) {
+    import(/* webpackIgnore: true */ chunkUrl).then(
+      (chunk: { default: ChunkRegistration }) => registerChunk(chunk.default),
+      (error) => resolver.reject(error)
+    )
+  }
+
   function fetchWebAssembly(wasmChunkPath: ChunkPath) {
     return fetch(getChunkRelativeUrl(wasmChunkPath))
   }
@@ -51,7 +51,19 @@ let BACKEND: RuntimeBackend
       }
     },
 
-    loadChunkCached(_sourceType: SourceType, _chunkUrl: ChunkUrl) {
+    loadChunkCached(_sourceType: SourceType, chunkUrl: ChunkUrl) {
+      if (CHUNK_FORMAT === 'esm' && isJs(chunkUrl)) {
+        let promise = esmChunkCache.get(chunkUrl)
+        if (promise == null) {
+          promise = import(/* webpackIgnore: true */ chunkUrl).then(
+            (chunk: { default: ChunkRegistration }) => {
+              registerChunk(chunk.default)
+            }
+          )
+          esmChunkCache.set(chunkUrl, promise)
+        }
+        return promise
+      }
       throw new Error('chunk loading is not supported')
     },
 
@@ -78,6 +90,7 @@ let BACKEND: RuntimeBackend
   }
 
   const registeredChunks: Set<ChunkPath> = new Set()
+  const esmChunkCache: Map<ChunkUrl, Promise<void>> = new Map()
   const runners: Map<ChunkPath, Set<ChunkRunner>> = new Map()
 
   /**
@@ -21,6 +21,9 @@ enum SourceType {
 
 type SourceData = ChunkPath | ModuleId
 
+// Injected by rust code. `esm` when chunks are ES modules that export their module factories.
+declare var CHUNK_FORMAT: 'script' | 'esm'
+
 process.env.TURBOPACK = '1'
 
 interface TurbopackNodeBuildContext extends TurbopackBaseContext<Module> {
@@ -56,6 +59,11 @@ function resolvePathFromModule(
     return exported as any
   }
 
+  if (exportedPath.startsWith('file:')) {
+    // Asset URLs in ES module output are already resolved against `import.meta.url`.
+    return exportedPath
+  }
+
   const strippedAssetPrefix = exportedPath.slice(ASSET_PREFIX.length)
   const resolved = path.resolve(RUNTIME_ROOT, strippedAssetPrefix)
 
@@ -63,11 +71,15 @@ function resolvePathFromModule(
 }
 nodeContextPrototype.R = resolvePathFromModule
 
-function loadRuntimeChunk(sourcePath: ChunkPath, chunkData: ChunkData): void {
+function loadRuntimeChunk(
+  sourcePath: ChunkPath,
+  chunkData: ChunkData,
+  chunkModules?: CompressedModuleFactories
+): void {
   if (typeof chunkData === 'string') {
-    loadRuntimeChunkPath(sourcePath, chunkData)
+    loadRuntimeChunkPath(sourcePath, chunkData, chunkModules)
   } else {
-    loadRuntimeChunkPath(sourcePath, chunkData.path)
+    loadRuntimeChunkPath(sourcePath, chunkData.path, chunkModules)
   }
 }
 
@@ -80,9 +92,14 @@ function clearChunkCache() {
   chunkCache.clear()
 }
 
+/**
+ * Loads a chunk of the entry's chunk group. In ES module output, the entry chunk imports the
+ * chunk itself and passes its module factories.
+ */
 function loadRuntimeChunkPath(
   sourcePath: ChunkPath,
-  chunkPath: ChunkPath
+  chunkPath: ChunkPath,
+  chunkModules?: CompressedModuleFactories
 ): void {
   if (!isJs(chunkPath)) {
     // We only support loading JS chunks in Node.js.
@@ -95,8 +112,10 @@ function loadRuntimeChunkPath(
   }
 
   try {
-    const resolved = path.resolve(RUNTIME_ROOT, chunkPath)
-    const chunkModules: CompressedModuleFactories = require(resolved)
+    if (chunkModules === undefined) {
+      const resolved = path.resolve(RUNTIME_ROOT, chunkPath)
+      chunkModules = require(resolved) as CompressedModuleFactories
+    }
     installCompressedModuleFactories(chunkModules, 0, moduleFactories)
     loadedChunks.add(chunkPath)
   } catch (cause) {
@@ -124,7 +143,21 @@ function loadChunkAsync(
   }
 
   let entry = chunkCache.get(chunkPath)
-  if (entry === undefined) {
+  if (entry === undefined && CHUNK_FORMAT === 'esm') {
+    const resolved = path.resolve(RUNTIME_ROOT, chunkPath)
+    entry = import(url.pathToFileURL(resolved).href).then(
+      (chunk: { default: CompressedModuleFactories }) => {
+        installCompressedModuleFactories(chunk.default, 0, moduleFactories)
+      },
+      (cause) => {
+        const errorMessage = `Failed to load chunk ${chunkPath} from module ${this.m.id}`
+        const error = new Error(errorMessage, { cause })
+        error.name = 'ChunkLoadError'
+        throw error
+      }
+    )
+    chunkCache.set(chunkPath, entry)
+  } else if (entry === undefined) {
     try {
       // resolve to an absolute path to simplify `require` handling
       const resolved = path.resolve(RUNTIME_ROOT, chunkPath)
@@ -288,9 +321,9 @@ function getOrInstantiateRuntimeModule(
   return instantiateRuntimeModule(chunkPath, moduleId)
 }
 
-const regexJsUrl = /\.js(?:\?[^#]*)?(?:#.*)?$/
+const regexJsUrl = /\.m?js(?:\?[^#]*)?(?:#.*)?$/
 /**
- * Checks if a given path/URL ends with .js, optionally followed by ?query or #fragment.
+ * Checks if a given path/URL ends with .js or .mjs, optionally followed by ?query or #fragment.
  */
 function isJs(chunkUrlOrPath: ChunkUrl | ChunkPath): boolean {
   return regexJsUrl.test(chunkUrlOrPath)
@@ -298,5 +331,6 @@ function isJs(chunkUrlOrPath: ChunkUrl | ChunkPath): boolean {
 
 module.exports = (sourcePath: ChunkPath) => ({
   m: (id: ModuleId) => getOrInstantiateRuntimeModule(sourcePath, id),
-  c: (chunkData: ChunkData) => loadRuntimeChunk(sourcePath, chunkData),
+  c: (chunkData: ChunkData, chunkModules?: CompressedModuleFactories) =>
+    loadRuntimeChunk(sourcePath, chunkData, chunkModules),
 })
@@ -40,6 +40,7 @@ type EsmImport = (
   moduleId: ModuleId,
   allowExportDefault: boolean
 ) => EsmNamespaceObject | Promise<EsmNamespaceObject>
+type EsmImportDeferred = (moduleId: ModuleId) => EsmNamespaceObject
 type InvokeAsyncLoader = (moduleId: ModuleId) => Promise<Exports>
 type EsmExport = (
   exportGetters: Record<string, () => any>,
@@ -119,6 +120,7 @@ interface TurbopackBaseContext<M> {
   t: RuntimeRequire
   f: ModuleContextFactory
   i: EsmImport
+  D: EsmImportDeferred
   A: InvokeAsyncLoader
   s: EsmExport
   j: DynamicExport
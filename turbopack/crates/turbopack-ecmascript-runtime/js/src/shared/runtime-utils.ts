@@ -359,6 +359,35 @@ function esmImport(
 }
 contextPrototype.i = esmImport
 
+/**
+ * Returns the namespace of a deferred import (`import defer * as ns from "..."`). The module is
+ * evaluated when the namespace is accessed for the first time.
+ */
+function esmImportDeferred(
+  this: TurbopackBaseContext<Module>,
+  id: ModuleId
+): EsmNamespaceObject {
+  let namespace: EsmNamespaceObject | undefined
+  const load = () =>
+    (namespace ??= esmImport.call(this, id) as EsmNamespaceObject)
+  return new Proxy(Object.create(null), {
+    get: (_target, prop) => Reflect.get(load(), prop),
+    // Checking for the queues of an async module evaluates it, like the proposal requires.
+    has: (_target, prop) => Reflect.has(load(), prop),
+    ownKeys: () => Reflect.ownKeys(load()),
+    getOwnPropertyDescriptor: (_target, prop) => {
+      const descriptor = Reflect.getOwnPropertyDescriptor(load(), prop)
+      // The property doesn't exist on the proxy target, so it must be configurable.
+      if (descriptor) descriptor.configurable = true
+      return descriptor
+    },
+    set: () => false,
+    defineProperty: () => false,
+    deleteProperty: () => false,
+  })
+}
+contextPrototype.D = esmImportDeferred
+
 function asyncLoader(
   this: TurbopackBaseContext<Module>,
   moduleId: ModuleId
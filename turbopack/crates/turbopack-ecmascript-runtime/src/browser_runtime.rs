@@ -5,6 +5,7 @@ use indoc::writedoc;
 use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{ResolvedVc, Vc};
 use turbopack_core::{
+    chunk::ChunkFormat,
     code_builder::{Code, CodeBuilder},
     context::AssetContext,
     environment::{ChunkLoading, Environment},
@@ -16,6 +17,13 @@ use crate::{
 };
 
 /// Returns the code for the ECMAScript runtime.
+///
+/// Chunks are registered with the `chunk_loading_global` (`TURBOPACK` by default). The module
+/// federation runtime is included when `federation` is set.
+///
+/// With [`ChunkFormat::EsModule`], the runtime is expected to be part of an ES module located at
+/// `chunk_to_output_root` relative to the output root. Chunk URLs are then resolved against
+/// `import.meta.url`.
 #[turbo_tasks::function]
 pub async fn get_browser_runtime_code(
     environment: ResolvedVc<Environment>,
@@ -24,6 +32,10 @@ pub async fn get_browser_runtime_code(
     runtime_type: RuntimeType,
     output_root_to_root_path: RcStr,
     generate_source_map: bool,
+    chunk_loading_global: RcStr,
+    federation: bool,
+    chunk_format: ChunkFormat,
+    chunk_to_output_root: RcStr,
 ) -> Result<Vc<Code>> {
     let asset_context = get_runtime_asset_context(*environment).resolve().await?;
 
@@ -44,6 +56,9 @@ pub async fn get_browser_runtime_code(
             panic!("This configuration is not supported in the browser runtime")
         }
     }
+    if federation {
+        runtime_base_code.push("browser/runtime/base/federation.ts");
+    }
 
     let chunk_loading = &*asset_context
         .compile_time_info()
@@ -81,24 +96,56 @@ pub async fn get_browser_runtime_code(
     let mut code: CodeBuilder = CodeBuilder::default();
     let relative_root_path = output_root_to_root_path;
     let chunk_base_path = chunk_base_path.await?;
-    let chunk_base_path = chunk_base_path.as_ref().map_or_else(|| "", |f| f.as_str());
     let chunk_suffix = chunk_suffix.await?;
 
     writedoc!(
         code,
         r#"
             (() => {{
-            if (!Array.isArray(globalThis.TURBOPACK)) {{
+            if (!Array.isArray(globalThis.{chunk_loading_global})) {{
                 return;
             }}
+        "#,
+    )?;
 
-            const CHUNK_BASE_PATH = {};
+    let chunk_base_path = chunk_base_path.as_ref().map_or("", |f| f.as_str());
+    match chunk_format {
+        // Chunk URLs need to be absolute to compare them with `import.meta.url`.
+        ChunkFormat::EsModule => {
+            writedoc!(
+                code,
+                r#"
+                    const CHUNK_BASE_PATH = new URL({}, import.meta.url).href;
+                "#,
+                StringifyJs(if chunk_base_path.is_empty() {
+                    chunk_to_output_root.as_str()
+                } else {
+                    chunk_base_path
+                }),
+            )?;
+        }
+        ChunkFormat::Script => {
+            writedoc!(
+                code,
+                r#"
+                    const CHUNK_BASE_PATH = {};
+                "#,
+                StringifyJs(chunk_base_path),
+            )?;
+        }
+    }
+    writedoc!(
+        code,
+        r#"
+            const CHUNK_FORMAT = {};
             const RELATIVE_ROOT_PATH = {};
-            const RUNTIME_PUBLIC_PATH = {};
+            const RUNTIME_PUBLIC_PATH = CHUNK_BASE_PATH;
         "#,
-        StringifyJs(chunk_base_path),
+        StringifyJs(match chunk_format {
+            ChunkFormat::Script => "script",
+            ChunkFormat::EsModule => "esm",
+        }),
         StringifyJs(relative_root_path.as_str()),
-        StringifyJs(chunk_base_path),
     )?;
 
     match &*chunk_suffix {
@@ -119,6 +166,14 @@ pub async fn get_browser_runtime_code(
                 StringifyJs(suffix.as_str())
             )?;
         }
+        ChunkSuffix::FromScriptSrc if matches!(chunk_format, ChunkFormat::EsModule) => {
+            writedoc!(
+                code,
+                r#"
+                    const CHUNK_SUFFIX = new URL(import.meta.url).search;
+                "#
+            )?;
+        }
         ChunkSuffix::FromScriptSrc => {
             if chunk_loading == &ChunkLoading::Edge {
                 panic!("ChunkSuffix::FromScriptSrc is not supported in Edge runtimes");
@@ -181,8 +236,8 @@ pub async fn get_browser_runtime_code(
     writedoc!(
         code,
         r#"
-            const chunksToRegister = globalThis.TURBOPACK;
-            globalThis.TURBOPACK = {{ push: registerChunk }};
+            const chunksToRegister = globalThis.{chunk_loading_global};
+            globalThis.{chunk_loading_global} = {{ push: registerChunk }};
             chunksToRegister.forEach(registerChunk);
         "#
     )?;
@@ -190,8 +245,8 @@ pub async fn get_browser_runtime_code(
         writedoc!(
             code,
             r#"
-            const chunkListsToRegister = globalThis.TURBOPACK_CHUNK_LISTS || [];
-            globalThis.TURBOPACK_CHUNK_LISTS = {{ push: registerChunkList }};
+            const chunkListsToRegister = globalThis.{chunk_loading_global}_CHUNK_LISTS || [];
+            globalThis.{chunk_loading_global}_CHUNK_LISTS = {{ push: registerChunkList }};
             chunkListsToRegister.forEach(registerChunkList);
         "#
         )?;
@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     iter,
     mem::{replace, take},
     sync::Arc,
@@ -31,7 +32,9 @@ use super::{
 use crate::{
     AnalyzeMode, SpecifiedModuleType,
     analyzer::{WellKnownObjectKind, is_unresolved},
-    references::{constant_value::parse_single_expr_lit, for_each_ident_in_pat},
+    references::{
+        constant_value::parse_single_expr_lit, esm::InlinableExport, for_each_ident_in_pat,
+    },
     utils::{AstPathRange, unparen},
 };
 
@@ -399,6 +402,9 @@ pub struct EvalContext {
     pub(crate) top_level_mark: Mark,
     pub(crate) imports: ImportMap,
     pub(crate) force_free_values: Arc<FxHashSet<Id>>,
+    /// Exports whose values can be inlined into importing modules, see
+    /// [crate::references::esm::constant_export::collect_inlinable_exports].
+    pub(crate) inlinable_exports: BTreeMap<RcStr, InlinableExport>,
 }
 
 impl EvalContext {
@@ -420,6 +426,7 @@ impl EvalContext {
                 ImportMap::analyze(m, source, comments)
             }),
             force_free_values,
+            inlinable_exports: BTreeMap::new(),
         }
     }
 
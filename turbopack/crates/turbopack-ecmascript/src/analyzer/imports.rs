@@ -1,5 +1,6 @@
 use std::{borrow::Cow, collections::BTreeMap, fmt::Display};
 
+use bincode::{Decode, Encode};
 use once_cell::sync::Lazy;
 use rustc_hash::{FxHashMap, FxHashSet};
 use swc_core::{
@@ -13,7 +14,7 @@ use swc_core::{
     },
 };
 use turbo_rcstr::{RcStr, rcstr};
-use turbo_tasks::{FxIndexMap, FxIndexSet, ResolvedVc};
+use turbo_tasks::{FxIndexMap, FxIndexSet, NonLocalValue, ResolvedVc, trace::TraceRawVcs};
 use turbopack_core::{issue::IssueSource, source::Source};
 
 use super::{JsValue, ModuleValue, top_level_await::has_top_level_await};
@@ -245,10 +246,37 @@ pub(crate) enum ImportedSymbol {
     PartEvaluation(u32),
 }
 
+/// The phase of an ESM import declaration.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Hash, TraceRawVcs, NonLocalValue, Encode, Decode,
+)]
+pub enum ImportPhase {
+    /// `import x from "..."`, the module is evaluated before the importing module.
+    #[default]
+    Evaluation,
+    /// `import defer * as ns from "..."`, the module is evaluated when the namespace is accessed
+    /// for the first time.
+    Defer,
+    /// `import source x from "..."`, imports the source representation of the module (e.g. a
+    /// `WebAssembly.Module`) without evaluating it.
+    Source,
+}
+
+impl From<swc_core::ecma::ast::ImportPhase> for ImportPhase {
+    fn from(phase: swc_core::ecma::ast::ImportPhase) -> Self {
+        match phase {
+            swc_core::ecma::ast::ImportPhase::Evaluation => ImportPhase::Evaluation,
+            swc_core::ecma::ast::ImportPhase::Defer => ImportPhase::Defer,
+            swc_core::ecma::ast::ImportPhase::Source => ImportPhase::Source,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct ImportMapReference {
     pub module_path: Wtf8Atom,
     pub imported_symbol: ImportedSymbol,
+    pub phase: ImportPhase,
     pub annotations: ImportAnnotations,
     pub issue_source: Option<IssueSource>,
 }
@@ -435,6 +463,7 @@ impl Analyzer<'_> {
         span: Span,
         module_path: Wtf8Atom,
         imported_symbol: ImportedSymbol,
+        phase: ImportPhase,
         annotations: ImportAnnotations,
     ) -> Option<usize> {
         let issue_source = self
@@ -444,6 +473,7 @@ impl Analyzer<'_> {
         let r = ImportMapReference {
             module_path,
             imported_symbol,
+            phase,
             issue_source,
             annotations,
         };
@@ -465,11 +495,16 @@ impl Visit for Analyzer<'_> {
 
         let internal_symbol = parse_with(import.with.as_deref());
 
-        if internal_symbol.is_none() {
+        let phase = ImportPhase::from(import.phase);
+
+        // Deferred and source phase imports don't evaluate the module when the importing module
+        // is evaluated.
+        if internal_symbol.is_none() && phase == ImportPhase::Evaluation {
             self.ensure_reference(
                 import.span,
                 import.src.value.clone(),
                 ImportedSymbol::ModuleEvaluation,
+                phase,
                 annotations.clone(),
             );
         }
@@ -482,6 +517,7 @@ impl Visit for Analyzer<'_> {
                 import.span,
                 import.src.value.clone(),
                 symbol,
+                phase,
                 annotations.clone(),
             );
             let i = match i {
@@ -512,6 +548,7 @@ impl Visit for Analyzer<'_> {
                 import.span,
                 import.src.value.clone(),
                 internal_symbol,
+                phase,
                 annotations,
             );
         }
@@ -526,6 +563,7 @@ impl Visit for Analyzer<'_> {
             export.span,
             export.src.value.clone(),
             ImportedSymbol::ModuleEvaluation,
+            ImportPhase::Evaluation,
             annotations.clone(),
         );
         let symbol = parse_with(export.with.as_deref());
@@ -534,6 +572,7 @@ impl Visit for Analyzer<'_> {
             export.span,
             export.src.value.clone(),
             symbol.unwrap_or(ImportedSymbol::Exports),
+            ImportPhase::Evaluation,
             annotations,
         );
         if let Some(i) = i {
@@ -558,6 +597,7 @@ impl Visit for Analyzer<'_> {
                 export.span,
                 src.value.clone(),
                 ImportedSymbol::ModuleEvaluation,
+                ImportPhase::Evaluation,
                 annotations.clone(),
             );
         }
@@ -567,8 +607,13 @@ impl Visit for Analyzer<'_> {
                 .clone()
                 .unwrap_or_else(|| get_import_symbol_from_export(spec));
 
-            let i =
-                self.ensure_reference(export.span, src.value.clone(), symbol, annotations.clone());
+            let i = self.ensure_reference(
+                export.span,
+                src.value.clone(),
+                symbol,
+                ImportPhase::Evaluation,
+                annotations.clone(),
+            );
             let i = match i {
                 Some(v) => v,
                 None => continue,
@@ -840,3 +885,89 @@ fn get_import_symbol_from_export(specifier: &ExportSpecifier) -> ImportedSymbol
         ExportSpecifier::Namespace(..) => ImportedSymbol::Exports,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{FileName, GLOBALS, SourceMap, sync::Lrc},
+        ecma::parser::parse_file_as_program,
+    };
+
+    use super::*;
+
+    fn analyze_references(code: &str) -> Vec<(String, ImportedSymbol, ImportPhase)> {
+        GLOBALS.set(&Default::default(), || {
+            let cm = Lrc::new(SourceMap::default());
+            let fm = cm.new_source_file(Lrc::new(FileName::Anon), code.to_string());
+            let program = parse_file_as_program(
+                &fm,
+                Default::default(),
+                EsVersion::latest(),
+                None,
+                &mut vec![],
+            )
+            .expect("Failed to parse");
+
+            ImportMap::analyze(&program, None, None)
+                .references()
+                .map(|r| {
+                    (
+                        r.module_path.to_string_lossy().into_owned(),
+                        r.imported_symbol.clone(),
+                        r.phase,
+                    )
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_import_phases() {
+        assert_eq!(
+            analyze_references(
+                r#"
+                import { a } from "./a.js";
+                import defer * as b from "./b.js";
+                import source c from "./c.wasm";
+                "#
+            ),
+            [
+                (
+                    "./a.js".to_string(),
+                    ImportedSymbol::ModuleEvaluation,
+                    ImportPhase::Evaluation
+                ),
+                (
+                    "./a.js".to_string(),
+                    ImportedSymbol::Symbol(atom!("a")),
+                    ImportPhase::Evaluation
+                ),
+                // Deferred and source phase imports don't evaluate the module
+                (
+                    "./b.js".to_string(),
+                    ImportedSymbol::Exports,
+                    ImportPhase::Defer
+                ),
+                (
+                    "./c.wasm".to_string(),
+                    ImportedSymbol::Symbol(atom!("default")),
+                    ImportPhase::Source
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reexports_are_evaluated() {
+        assert!(
+            analyze_references(
+                r#"
+                export * from "./a.js";
+                export { b } from "./b.js";
+                "#
+            )
+            .iter()
+            .all(|(_, _, phase)| *phase == ImportPhase::Evaluation)
+        );
+    }
+}
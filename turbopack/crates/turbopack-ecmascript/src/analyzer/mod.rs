@@ -9,7 +9,7 @@ use std::{
     sync::Arc,
 };
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use graph::VarGraph;
 use num_bigint::BigInt;
 use num_traits::identities::Zero;
@@ -1889,6 +1889,7 @@ impl JsValue {
                     WellKnownFunctionKind::RequireContextRequire(..) => ("require.context(...)".to_string(), "The require.context(...) method from webpack: https://webpack.js.org/api/module-methods/#requirecontext"),
                     WellKnownFunctionKind::RequireContextRequireKeys(..) => ("require.context(...).keys".to_string(), "The require.context(...).keys method from webpack: https://webpack.js.org/guides/dependency-management/#requirecontext"),
                     WellKnownFunctionKind::RequireContextRequireResolve(..) => ("require.context(...).resolve".to_string(), "The require.context(...).resolve method from webpack: https://webpack.js.org/guides/dependency-management/#requirecontext"),
+                    WellKnownFunctionKind::ImportMetaGlob => ("import.meta.glob".to_string(), "The import.meta.glob method from Vite: https://vite.dev/guide/features.html#glob-import"),
                     WellKnownFunctionKind::Define => ("define".to_string(), "The define method from AMD"),
                     WellKnownFunctionKind::FsReadMethod(name) => (
                         format!("fs.{name}"),
@@ -3571,6 +3572,113 @@ pub fn parse_require_context(args: &[JsValue]) -> Result<RequireContextOptions>
     })
 }
 
+#[derive(Debug, Clone)]
+pub struct ImportMetaGlobOptions {
+    /// Globs relative to the importing module. Globs starting with `!` exclude files.
+    pub patterns: Vec<RcStr>,
+    pub eager: bool,
+    /// The export of the matched modules to use instead of their namespace.
+    pub import: Option<RcStr>,
+    /// A query appended to the requests, e.g. `?raw`.
+    pub query: Option<RcStr>,
+}
+
+/// Parse the arguments passed to an import.meta.glob invocation, validate them
+/// and convert them to the appropriate rust values.
+pub fn parse_import_meta_glob(args: &[JsValue]) -> Result<ImportMetaGlobOptions> {
+    if !(1..=2).contains(&args.len()) {
+        bail!("import.meta.glob() only supports 1-2 arguments");
+    }
+
+    let patterns: Vec<RcStr> = match &args[0] {
+        JsValue::Array { items, .. } => items
+            .iter()
+            .map(|item| item.as_str().map(RcStr::from))
+            .collect::<Option<_>>(),
+        pattern => pattern.as_str().map(|pattern| vec![pattern.into()]),
+    }
+    .context(
+        "import.meta.glob(patterns, ...) requires patterns to be a constant string or an array of \
+         constant strings",
+    )?;
+    for pattern in &patterns {
+        let pattern = pattern.strip_prefix('!').unwrap_or(pattern);
+        if !pattern.starts_with("./") && !pattern.starts_with("../") {
+            bail!(
+                "import.meta.glob() only supports patterns relative to the module, starting with \
+                 `./` or `../`, got `{pattern}`"
+            );
+        }
+    }
+
+    let mut options = ImportMetaGlobOptions {
+        patterns,
+        eager: false,
+        import: None,
+        query: None,
+    };
+    let Some(arg) = args.get(1) else {
+        return Ok(options);
+    };
+    let JsValue::Object { parts, .. } = arg else {
+        bail!("import.meta.glob(..., options) requires options to be an object literal");
+    };
+    for part in parts {
+        let ObjectPart::KeyValue(key, value) = part else {
+            bail!("import.meta.glob(..., options) doesn't support spread options");
+        };
+        match key.as_str() {
+            Some("eager") => {
+                options.eager = value
+                    .as_bool()
+                    .context("import.meta.glob() requires `eager` to be a constant boolean")?;
+            }
+            Some("import") => {
+                options.import = Some(
+                    value
+                        .as_str()
+                        .context("import.meta.glob() requires `import` to be a constant string")?
+                        .into(),
+                );
+            }
+            Some("query") => {
+                let query = match value {
+                    JsValue::Object { parts, .. } => {
+                        let mut params = vec![];
+                        for part in parts {
+                            if let ObjectPart::KeyValue(key, value) = part
+                                && let (Some(key), Some(value)) = (key.as_str(), value.as_str())
+                            {
+                                params.push(format!("{key}={value}"));
+                            } else {
+                                bail!(
+                                    "import.meta.glob() requires `query` to contain constant \
+                                     strings"
+                                );
+                            }
+                        }
+                        params.join("&")
+                    }
+                    value => value
+                        .as_str()
+                        .context(
+                            "import.meta.glob() requires `query` to be a constant string or object",
+                        )?
+                        .to_string(),
+                };
+                options.query = Some(if query.starts_with('?') {
+                    query.into()
+                } else {
+                    format!("?{query}").into()
+                });
+            }
+            Some(key) => bail!("import.meta.glob() doesn't support the `{key}` option"),
+            None => bail!("import.meta.glob(..., options) requires constant option keys"),
+        }
+    }
+    Ok(options)
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct RequireContextValue(FxIndexMap<RcStr, RcStr>);
 
@@ -3615,6 +3723,7 @@ pub enum WellKnownFunctionKind {
     RequireContextRequire(RequireContextValue),
     RequireContextRequireKeys(RequireContextValue),
     RequireContextRequireResolve(RequireContextValue),
+    ImportMetaGlob,
     Define,
     FsReadMethod(Atom),
     PathToFileUrl,
@@ -3647,6 +3756,7 @@ impl WellKnownFunctionKind {
             Self::Require { .. } => Some(&["require"]),
             Self::RequireResolve => Some(&["require", "resolve"]),
             Self::RequireContext => Some(&["require", "context"]),
+            Self::ImportMetaGlob => Some(&["import", "meta", "glob"]),
             Self::Define => Some(&["define"]),
             _ => None,
         }
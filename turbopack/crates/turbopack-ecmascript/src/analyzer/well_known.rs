@@ -621,6 +621,15 @@ async fn well_known_object_member(
         WellKnownObjectKind::NodeProcessModule => {
             node_process_member(prop, compile_time_info).await?
         }
+        WellKnownObjectKind::ImportMeta => match prop.as_str() {
+            Some("glob") => JsValue::WellKnownFunction(WellKnownFunctionKind::ImportMetaGlob),
+            _ => {
+                return Ok((
+                    JsValue::member(Box::new(JsValue::WellKnownObject(kind)), Box::new(prop)),
+                    false,
+                ));
+            }
+        },
         WellKnownObjectKind::NodePreGyp => node_pre_gyp(prop),
         WellKnownObjectKind::NodeExpressApp => express(prop),
         WellKnownObjectKind::NodeProtobufLoader => protobuf_loader(prop),
@@ -0,0 +1,307 @@
+//! Module federation, i.e. builds exposing modules to other builds and consuming the modules
+//! exposed by them at runtime, while sharing packages like `react` between them.
+//!
+//! - Remotes are consumed by name through the import map. `import("app2/Button")` resolves to an
+//!   [ExternalType::FederationRemote] external, which loads the remote entry of `app2` at runtime
+//!   and gets the `./Button` module from its container.
+//! - The exposed modules are registered as a container by the remote entry, an entry module
+//!   generated by [federation_container_source]. [federation_manifest] describes it for the
+//!   consuming builds.
+//! - Shared packages are aliased to a module asking the runtime for an already loaded version that
+//!   satisfies the required version. When there is none, the build's own copy is used and offered
+//!   to the other builds.
+//!
+//! The runtime part lives in `browser/runtime/base/federation.ts` of the ECMAScript runtime.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::json;
+use turbo_rcstr::{RcStr, rcstr};
+use turbo_tasks::{FxIndexMap, ResolvedVc, TaskInput, Vc};
+use turbo_tasks_fs::{File, FileContent, FileJsonContent, FileSystemPath};
+use turbopack_core::{
+    asset::AssetContent,
+    ident::AssetIdent,
+    output::OutputAsset,
+    resolve::{
+        ExternalTraced, ExternalType, ResolveResult,
+        options::{
+            ImportMap, ImportMapResult, ImportMapping, ImportMappingReplacement,
+            ReplacedImportMapping,
+        },
+        parse::Request,
+        pattern::Pattern,
+    },
+    source::Source,
+    virtual_output::VirtualOutputAsset,
+    virtual_source::VirtualSource,
+};
+
+use crate::{
+    runtime_functions::{TURBOPACK_FEDERATION_REGISTER_CONTAINER, TURBOPACK_FEDERATION_SHARED},
+    utils::StringifyJs,
+};
+
+/// The query of the request the shared module uses to import the build's own copy of a package.
+/// It bypasses the import map alias of the shared package.
+const FALLBACK_QUERY: &str = "?__turbopack_federation_fallback__";
+
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederationOptions {
+    /// The name of the build. Other builds import the modules it exposes with it.
+    pub name: RcStr,
+    /// The modules exposed to other builds by their key, e.g. `./Button` to `./src/Button.tsx`.
+    /// Paths are relative to the project directory.
+    #[serde(default)]
+    #[bincode(with = "turbo_bincode::indexmap")]
+    pub exposes: FxIndexMap<RcStr, RcStr>,
+    /// The URLs of the remote entries of the consumed builds by their name.
+    #[serde(default)]
+    #[bincode(with = "turbo_bincode::indexmap")]
+    pub remotes: FxIndexMap<RcStr, RcStr>,
+    /// The packages shared with the other builds loaded into the same page. Only exact requests
+    /// are shared, so subpaths like `react/jsx-runtime` need their own entry.
+    #[serde(default)]
+    #[bincode(with = "turbo_bincode::indexmap")]
+    pub shared: FxIndexMap<RcStr, SharedPackageOptions>,
+    /// The URL the output directory is served from. Required when the build is consumed from
+    /// another origin, as its chunks are loaded relative to the page otherwise.
+    #[serde(default)]
+    pub public_path: Option<RcStr>,
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug, Default, Hash, TaskInput, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SharedPackageOptions {
+    /// Only a single version of the package is used in the page, the one loaded first. Builds
+    /// requiring another version get a warning.
+    pub singleton: bool,
+    /// The semver range of versions that can be used instead of the build's own copy. Defaults
+    /// to `^<version>`.
+    pub required_version: Option<RcStr>,
+    /// The version of the build's own copy. Defaults to the version in its `package.json`.
+    pub version: Option<RcStr>,
+}
+
+/// Returns the import map entries for consuming the remotes and the shared packages of the
+/// build.
+#[turbo_tasks::function]
+pub async fn federation_import_map(options: Vc<FederationOptions>) -> Result<Vc<ImportMap>> {
+    let options = options.await?;
+    let mut import_map = ImportMap::empty();
+
+    for (remote, url) in &options.remotes {
+        import_map.insert_exact_alias(
+            remote.clone(),
+            ImportMapping::External(
+                Some(format!("{remote}@{url}").into()),
+                ExternalType::FederationRemote,
+                ExternalTraced::Untraced,
+            )
+            .resolved_cell(),
+        );
+        import_map.insert_wildcard_alias(
+            format!("{remote}/"),
+            ImportMapping::External(
+                Some(format!("{remote}/*@{url}").into()),
+                ExternalType::FederationRemote,
+                ExternalTraced::Untraced,
+            )
+            .resolved_cell(),
+        );
+    }
+
+    for (package, shared) in &options.shared {
+        import_map.insert_exact_alias(
+            package.clone(),
+            ImportMapping::Dynamic(ResolvedVc::upcast(
+                FederationSharedReplacement {
+                    name: options.name.clone(),
+                    package: package.clone(),
+                    options: shared.clone(),
+                }
+                .resolved_cell(),
+            ))
+            .resolved_cell(),
+        );
+    }
+
+    Ok(import_map.cell())
+}
+
+/// Replaces a shared package with a module that negotiates the version to use at runtime.
+#[turbo_tasks::value(shared)]
+struct FederationSharedReplacement {
+    name: RcStr,
+    package: RcStr,
+    options: SharedPackageOptions,
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for FederationSharedReplacement {
+    #[turbo_tasks::function]
+    fn replace(self: ResolvedVc<Self>, _capture: Vc<Pattern>) -> Vc<ReplacedImportMapping> {
+        ReplacedImportMapping::Dynamic(ResolvedVc::upcast(self)).cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        lookup_path: FileSystemPath,
+        request: Vc<Request>,
+    ) -> Result<Vc<ImportMapResult>> {
+        if request.query().await?.as_str() == FALLBACK_QUERY {
+            return Ok(ImportMapResult::NoEntry.cell());
+        }
+        let source = shared_module_source(
+            lookup_path,
+            self.name.clone(),
+            self.package.clone(),
+            self.options.clone(),
+        )
+        .to_resolved()
+        .await?;
+        Ok(ImportMapResult::Result(ResolveResult::source(ResolvedVc::upcast(source))).cell())
+    }
+}
+
+/// Finds the version of the package installed for `lookup_path`.
+async fn installed_version(lookup_path: &FileSystemPath, package: &str) -> Result<Option<RcStr>> {
+    let mut dir = lookup_path.clone();
+    loop {
+        let package_json = dir
+            .join("node_modules")?
+            .join(package)?
+            .join("package.json")?;
+        if let FileJsonContent::Content(json) = &*package_json.read_json().await? {
+            return Ok(json["version"].as_str().map(RcStr::from));
+        }
+        if dir.is_root() {
+            return Ok(None);
+        }
+        dir = dir.parent();
+    }
+}
+
+#[turbo_tasks::function]
+async fn shared_module_source(
+    lookup_path: FileSystemPath,
+    name: RcStr,
+    package: RcStr,
+    options: SharedPackageOptions,
+) -> Result<Vc<VirtualSource>> {
+    let version = match options.version {
+        Some(version) => Some(version),
+        None => installed_version(&lookup_path, &package).await?,
+    };
+    let required_version = options
+        .required_version
+        .or_else(|| version.as_ref().map(|version| format!("^{version}").into()));
+    let config = json!({
+        "from": name,
+        "version": version,
+        "requiredVersion": required_version,
+        "singleton": options.singleton,
+    });
+    let code = format!(
+        "module.exports = {TURBOPACK_FEDERATION_SHARED}({}, {config}, () => require({}));\n",
+        StringifyJs(&package),
+        StringifyJs(&format!("{package}{FALLBACK_QUERY}")),
+    );
+    Ok(VirtualSource::new_with_ident(
+        AssetIdent::from_path(lookup_path.join("__federation_shared__.js")?)
+            .with_modifier(format!("federation shared {package}").into())
+            .to_resolved()
+            .await?,
+        AssetContent::file(FileContent::Content(File::from(code)).cell())
+            .to_resolved()
+            .await?,
+    ))
+}
+
+/// Returns the entry module of the remote entry, which registers the container with the exposed
+/// modules. They are loaded on demand.
+#[turbo_tasks::function]
+pub async fn federation_container_source(
+    project_path: FileSystemPath,
+    options: Vc<FederationOptions>,
+) -> Result<Vc<Box<dyn Source>>> {
+    let options = options.await?;
+    let mut code = format!(
+        "{TURBOPACK_FEDERATION_REGISTER_CONTAINER}({}, {{\n",
+        StringifyJs(&options.name)
+    );
+    for (key, path) in &options.exposes {
+        code += &format!(
+            "    {}: () => import({}),\n",
+            StringifyJs(key),
+            StringifyJs(path)
+        );
+    }
+    code += "});\n";
+    Ok(Vc::upcast(VirtualSource::new(
+        project_path.join("__federation_container__.js")?,
+        AssetContent::file(FileContent::Content(File::from(code)).cell()),
+    )))
+}
+
+/// Returns the manifest of the build, which tells the consuming builds where to load the remote
+/// entry from and what it exposes.
+#[turbo_tasks::function]
+pub async fn federation_manifest(
+    path: FileSystemPath,
+    options: Vc<FederationOptions>,
+    remote_entry: Vc<Box<dyn OutputAsset>>,
+) -> Result<Vc<Box<dyn OutputAsset>>> {
+    let options = options.await?;
+    let remote_entry_path = remote_entry.path().await?;
+    let remote_entry = path
+        .parent()
+        .get_path_to(&remote_entry_path)
+        .unwrap_or(remote_entry_path.path.as_str());
+    let remote_entry = match &options.public_path {
+        Some(public_path) => format!("{}/{remote_entry}", public_path.trim_end_matches('/')),
+        None => remote_entry.to_string(),
+    };
+    let manifest = json!({
+        "name": options.name,
+        "remoteEntry": remote_entry,
+        "exposes": options.exposes.keys().collect::<Vec<_>>(),
+        "shared": options
+            .shared
+            .iter()
+            .map(|(package, shared)| {
+                (
+                    package.clone(),
+                    json!({
+                        "singleton": shared.singleton,
+                        "requiredVersion": shared.required_version,
+                    }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>(),
+    });
+    Ok(Vc::upcast(VirtualOutputAsset::new(
+        path,
+        AssetContent::file(
+            FileContent::Content(File::from(serde_json::to_string_pretty(&manifest)?)).cell(),
+        ),
+    )))
+}
+
+/// The name of the global the chunks of a federated build are registered with. Every build needs
+/// its own, as module ids are only unique within a build.
+pub fn federation_chunk_loading_global(name: &str) -> RcStr {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.is_empty() {
+        rcstr!("TURBOPACK")
+    } else {
+        format!("TURBOPACK_{name}").into()
+    }
+}
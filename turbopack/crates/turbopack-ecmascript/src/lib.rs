@@ -14,13 +14,16 @@ pub mod async_chunk;
 pub mod chunk;
 pub mod code_gen;
 mod errors;
+pub mod federation;
 pub mod inlined_bytes_module;
 pub mod magic_identifier;
 pub mod manifest;
 mod merged_module;
 pub mod minify;
+pub mod minify_name_cache;
 pub mod parse;
 mod path_visitor;
+pub mod property_mangling;
 pub mod references;
 pub mod runtime_functions;
 pub mod side_effect_optimization;
@@ -126,8 +129,8 @@ pub use crate::{
     references::{AnalyzeEcmascriptModuleResult, TURBOPACK_HELPER},
     static_code::StaticEcmascriptCode,
     transform::{
-        CustomTransformer, EcmascriptInputTransform, EcmascriptInputTransforms, TransformContext,
-        TransformPlugin,
+        CoverageOptions, CustomTransformer, EcmascriptInputTransform, EcmascriptInputTransforms,
+        TransformContext, TransformPlugin,
     },
 };
 
@@ -267,6 +270,9 @@ pub struct EcmascriptOptions {
     pub enable_typeof_window_inlining: Option<TypeofWindow>,
     /// Whether to allow accessing exports info via `__webpack_exports_info__`.
     pub enable_exports_info_inlining: bool,
+    /// Whether to inline imported `const` primitives and TypeScript enum members at their use
+    /// sites. Imports whose uses are all inlined don't keep the export alive.
+    pub enable_constant_export_inlining: bool,
 
     pub inline_helpers: bool,
     /// Whether to infer side effect free modules via local analysis. Defaults to true.
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use anyhow::{Context, Result, bail};
 use bytes_str::BytesStr;
@@ -22,19 +22,33 @@ use swc_core::{
             fixer::paren_remover,
             hygiene::{self, hygiene_with_config},
         },
-        visit::VisitWith,
+        visit::{VisitMutWith, VisitWith},
     },
 };
 use tracing::instrument;
+use turbo_rcstr::RcStr;
 use turbopack_core::{
     chunk::MangleType,
     code_builder::{Code, CodeBuilder},
 };
 
-use crate::parse::{IdentCollector, generate_js_source_map};
+use crate::{
+    minify_name_cache::mangle_chunk_items,
+    parse::{IdentCollector, generate_js_source_map},
+    property_mangling::PropertyRenamer,
+};
 
 #[instrument(level = "info", name = "minify ecmascript code", skip_all)]
-pub fn minify(code: Code, source_maps: bool, mangle: Option<MangleType>) -> Result<Code> {
+pub fn minify(
+    code: Code,
+    source_maps: bool,
+    mangle: Option<MangleType>,
+    property_mangling: Option<&BTreeMap<RcStr, RcStr>>,
+    name_cache: Option<&mut BTreeMap<RcStr, BTreeMap<RcStr, RcStr>>>,
+) -> Result<Code> {
+    // The name cache replaces the mangler of the minifier, it has no use without mangling.
+    let name_cache = name_cache.filter(|_| mangle.is_some());
+
     // Pass None for the debug ID so we don't needlessly compute it for the pre-minified content, it
     // will be added by the Code object returned from this function
     let source_maps = source_maps.then(|| code.generate_source_map_ref(None));
@@ -60,7 +74,7 @@ pub fn minify(code: Code, source_maps: bool, mangle: Option<MangleType>) -> Resu
         let (program, source_map_names) =
             try_with_handler(cm.clone(), Default::default(), |handler| {
                 GLOBALS.set(&Default::default(), || {
-                    let program = match parser.parse_program() {
+                    let mut program = match parser.parse_program() {
                         Ok(program) => program,
                         Err(err) => {
                             err.into_diagnostic(handler).emit();
@@ -77,6 +91,10 @@ pub fn minify(code: Code, source_maps: bool, mangle: Option<MangleType>) -> Resu
                         Default::default()
                     };
 
+                    if let Some(mangling) = property_mangling {
+                        program.visit_mut_with(&mut PropertyRenamer { mangling });
+                    }
+
                     let unresolved_mark = Mark::new();
                     let top_level_mark = Mark::new();
 
@@ -102,7 +120,7 @@ pub fn minify(code: Code, source_maps: bool, mangle: Option<MangleType>) -> Resu
                                 keep_fnames: mangle.is_none(),
                                 ..Default::default()
                             }),
-                            mangle: mangle.map(|mangle| {
+                            mangle: mangle.filter(|_| name_cache.is_none()).map(|mangle| {
                                 let reserved = vec![atom!("AbortSignal")];
                                 match mangle {
                                     MangleType::OptimalSize => MangleOptions {
@@ -125,7 +143,14 @@ pub fn minify(code: Code, source_maps: bool, mangle: Option<MangleType>) -> Resu
                         },
                     );
 
-                    if mangle.is_none() {
+                    let mangled = match name_cache {
+                        Some(names) => {
+                            mangle_chunk_items(&mut program, unresolved_mark, names);
+                            false
+                        }
+                        None => mangle.is_some(),
+                    };
+                    if !mangled {
                         program.mutate(hygiene_with_config(hygiene::Config {
                             top_level_mark,
                             ..Default::default()
@@ -0,0 +1,410 @@
+use std::{collections::BTreeMap, mem::take};
+
+use anyhow::{Context, Result};
+use rustc_hash::{FxHashMap, FxHashSet};
+use swc_core::{
+    atoms::Atom,
+    common::{Mark, SyntaxContext},
+    ecma::{
+        ast::{
+            ArrayLit, ArrowExpr, AssignPat, AssignPatProp, BindingIdent, ClassDecl, ClassExpr,
+            Expr, FnDecl, FnExpr, Function, Id, Ident, IdentName, KeyValuePatProp, KeyValueProp,
+            Lit, ObjectPatProp, Pat, Program, Prop, PropName, WithStmt,
+        },
+        visit::{Visit, VisitMut, VisitMutWith, VisitWith},
+    },
+};
+use turbo_rcstr::RcStr;
+use turbo_tasks::{FxIndexSet, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+
+use crate::property_mangling::mangled_name;
+
+/// The mangled names of the bindings of each chunk item in a chunk, keyed by module id.
+///
+/// A binding is identified by its original name, followed by `#<n>` for the `n`th further binding
+/// with the same name in the chunk item. Names are only reused when the binding still exists, so
+/// editing a module keeps the names of its unchanged bindings and doesn't affect other modules.
+#[turbo_tasks::value(transparent)]
+pub struct MinifyNameCache(BTreeMap<RcStr, BTreeMap<RcStr, RcStr>>);
+
+#[turbo_tasks::function]
+pub async fn read_minify_name_cache(path: FileSystemPath) -> Result<Vc<MinifyNameCache>> {
+    let FileContent::Content(file) = &*path.read().await? else {
+        return Ok(Vc::cell(BTreeMap::new()));
+    };
+    let names: BTreeMap<RcStr, BTreeMap<RcStr, RcStr>> =
+        serde_json::from_str(&file.content().to_str()?)
+            .with_context(|| format!("Invalid minify name cache {}", path.path))?;
+    Ok(Vc::cell(names))
+}
+
+#[turbo_tasks::function]
+pub async fn write_minify_name_cache(
+    names: Vc<MinifyNameCache>,
+    path: FileSystemPath,
+) -> Result<Vc<()>> {
+    let names = names.await?;
+    if *read_minify_name_cache(path.clone()).await? != *names {
+        let content = serde_json::to_string_pretty(&*names)? + "\n";
+        path.write(FileContent::Content(File::from(content)).cell())
+            .as_side_effect()
+            .await?;
+    }
+    Ok(Vc::cell(()))
+}
+
+/// Renames the bindings of every module factory in the chunk, reusing the names from `names` and
+/// replacing them with the names that were used. Expects a program that went through the
+/// resolver.
+///
+/// Instead of reusing names in sibling scopes, every binding of a module factory gets its own
+/// name. That makes the output slightly larger, but a name can be kept without checking which
+/// scopes it would clash with.
+pub(crate) fn mangle_chunk_items(
+    program: &mut Program,
+    unresolved_mark: Mark,
+    names: &mut BTreeMap<RcStr, BTreeMap<RcStr, RcStr>>,
+) {
+    let previous = take(names);
+    program.visit_mut_with(&mut ChunkItemFinder {
+        unresolved_ctxt: SyntaxContext::empty().apply_mark(unresolved_mark),
+        previous: &previous,
+        names,
+    });
+}
+
+/// Finds the module factories in the array literal of a chunk, i.e. `[..., id, factory, ...]`.
+struct ChunkItemFinder<'a> {
+    unresolved_ctxt: SyntaxContext,
+    previous: &'a BTreeMap<RcStr, BTreeMap<RcStr, RcStr>>,
+    names: &'a mut BTreeMap<RcStr, BTreeMap<RcStr, RcStr>>,
+}
+
+impl VisitMut for ChunkItemFinder<'_> {
+    fn visit_mut_array_lit(&mut self, array: &mut ArrayLit) {
+        // A factory is immediately preceded by its module id. Other literals, like the chunk path
+        // or further ids of the module, can come before that.
+        let mut id: Option<RcStr> = None;
+        for elem in array.elems.iter_mut().flatten() {
+            match &mut *elem.expr {
+                Expr::Lit(Lit::Str(str)) if elem.spread.is_none() => {
+                    id = Some(str.value.to_string_lossy().into());
+                }
+                Expr::Lit(Lit::Num(num)) if elem.spread.is_none() => {
+                    id = Some(num.value.to_string().into());
+                }
+                expr @ (Expr::Arrow(_) | Expr::Fn(_)) if id.is_some() => {
+                    let id = id.take().unwrap();
+                    if let Some(names) =
+                        mangle_chunk_item(expr, self.unresolved_ctxt, self.previous.get(&id))
+                    {
+                        self.names.insert(id, names);
+                    }
+                }
+                expr => {
+                    id = None;
+                    expr.visit_mut_with(self);
+                }
+            }
+        }
+    }
+
+    // Module factories are never nested in other functions.
+    fn visit_mut_function(&mut self, _: &mut Function) {}
+
+    fn visit_mut_arrow_expr(&mut self, _: &mut ArrowExpr) {}
+}
+
+/// Renames the bindings of a single module factory. Returns `None` when the factory uses `eval`
+/// or `with`, which can access bindings by name.
+fn mangle_chunk_item(
+    factory: &mut Expr,
+    unresolved_ctxt: SyntaxContext,
+    previous: Option<&BTreeMap<RcStr, RcStr>>,
+) -> Option<BTreeMap<RcStr, RcStr>> {
+    let mut bindings = BindingCollector::default();
+    factory.visit_with(&mut bindings);
+    let mut references = ReferenceCollector {
+        bindings: &bindings.bindings,
+        unresolved_ctxt,
+        names: FxHashSet::default(),
+        uses_eval: false,
+    };
+    factory.visit_with(&mut references);
+    if references.uses_eval {
+        return None;
+    }
+
+    let mut counts = FxHashMap::<Atom, usize>::default();
+    let keys = bindings
+        .bindings
+        .iter()
+        .map(|(sym, _)| {
+            let count = counts.entry(sym.clone()).or_default();
+            *count += 1;
+            let key: RcStr = if *count == 1 {
+                sym.as_str().into()
+            } else {
+                format!("{sym}#{count}").into()
+            };
+            key
+        })
+        .collect::<Vec<_>>();
+
+    // Names that are referenced but not declared in the factory must not be shadowed.
+    let mut used = references
+        .names
+        .iter()
+        .map(|name| RcStr::from(name.as_str()))
+        .collect::<FxHashSet<_>>();
+    let mut names = BTreeMap::new();
+    let mut renames = FxHashMap::default();
+    let mut pending = Vec::new();
+    for (id, key) in bindings.bindings.iter().zip(keys) {
+        match previous.and_then(|previous| previous.get(&key)) {
+            Some(name) if Ident::verify_symbol(name).is_ok() && used.insert(name.clone()) => {
+                renames.insert(id.clone(), Atom::from(name.as_str()));
+                names.insert(key, name.clone());
+            }
+            _ => pending.push((id, key)),
+        }
+    }
+    let mut fresh = (0..)
+        .map(mangled_name)
+        .filter(|name| Ident::verify_symbol(name).is_ok());
+    for (id, key) in pending {
+        let name = fresh
+            .find(|name| !used.contains(name))
+            .expect("there are infinitely many names");
+        used.insert(name.clone());
+        renames.insert(id.clone(), Atom::from(name.as_str()));
+        names.insert(key, name);
+    }
+
+    factory.visit_mut_with(&mut BindingRenamer { renames: &renames });
+    Some(names)
+}
+
+/// Collects the bindings declared in a module factory, in the order of their declarations.
+#[derive(Default)]
+struct BindingCollector {
+    bindings: FxIndexSet<Id>,
+}
+
+impl Visit for BindingCollector {
+    fn visit_binding_ident(&mut self, ident: &BindingIdent) {
+        self.bindings.insert(ident.id.to_id());
+    }
+
+    fn visit_fn_decl(&mut self, decl: &FnDecl) {
+        self.bindings.insert(decl.ident.to_id());
+        decl.visit_children_with(self);
+    }
+
+    fn visit_class_decl(&mut self, decl: &ClassDecl) {
+        self.bindings.insert(decl.ident.to_id());
+        decl.visit_children_with(self);
+    }
+
+    fn visit_fn_expr(&mut self, expr: &FnExpr) {
+        if let Some(ident) = &expr.ident {
+            self.bindings.insert(ident.to_id());
+        }
+        expr.visit_children_with(self);
+    }
+
+    fn visit_class_expr(&mut self, expr: &ClassExpr) {
+        if let Some(ident) = &expr.ident {
+            self.bindings.insert(ident.to_id());
+        }
+        expr.visit_children_with(self);
+    }
+}
+
+/// Collects the names of identifiers that don't refer to a binding of the module factory, e.g.
+/// globals and labels.
+struct ReferenceCollector<'a> {
+    bindings: &'a FxIndexSet<Id>,
+    unresolved_ctxt: SyntaxContext,
+    names: FxHashSet<Atom>,
+    uses_eval: bool,
+}
+
+impl Visit for ReferenceCollector<'_> {
+    fn visit_ident(&mut self, ident: &Ident) {
+        if !self.bindings.contains(&ident.to_id()) {
+            if ident.ctxt == self.unresolved_ctxt && &*ident.sym == "eval" {
+                self.uses_eval = true;
+            }
+            self.names.insert(ident.sym.clone());
+        }
+    }
+
+    fn visit_with_stmt(&mut self, stmt: &WithStmt) {
+        self.uses_eval = true;
+        stmt.visit_children_with(self);
+    }
+}
+
+struct BindingRenamer<'a> {
+    renames: &'a FxHashMap<Id, Atom>,
+}
+
+impl VisitMut for BindingRenamer<'_> {
+    fn visit_mut_ident(&mut self, ident: &mut Ident) {
+        if let Some(name) = self.renames.get(&ident.to_id()) {
+            ident.sym = name.clone();
+        }
+    }
+
+    fn visit_mut_prop(&mut self, prop: &mut Prop) {
+        // `{ a }` becomes `{ a: b }`, the property keeps its name.
+        if let Prop::Shorthand(ident) = prop
+            && let Some(name) = self.renames.get(&ident.to_id())
+        {
+            let key = PropName::Ident(IdentName::new(ident.sym.clone(), ident.span));
+            ident.sym = name.clone();
+            *prop = Prop::KeyValue(KeyValueProp {
+                key,
+                value: Box::new(Expr::Ident(ident.clone())),
+            });
+            return;
+        }
+        prop.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_object_pat_prop(&mut self, prop: &mut ObjectPatProp) {
+        // `{ a = 1 } = x` becomes `{ a: b = 1 } = x`, the property keeps its name.
+        if let ObjectPatProp::Assign(AssignPatProp { span, key, value }) = prop {
+            value.visit_mut_with(self);
+            if let Some(name) = self.renames.get(&key.id.to_id()) {
+                let prop_name = PropName::Ident(IdentName::new(key.sym.clone(), key.span));
+                let mut binding = key.clone();
+                binding.id.sym = name.clone();
+                let binding = Box::new(Pat::Ident(binding));
+                *prop = ObjectPatProp::KeyValue(KeyValuePatProp {
+                    key: prop_name,
+                    value: match value.take() {
+                        Some(default) => Box::new(Pat::Assign(AssignPat {
+                            span: *span,
+                            left: binding,
+                            right: default,
+                        })),
+                        None => binding,
+                    },
+                });
+            }
+            return;
+        }
+        prop.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use turbo_rcstr::RcStr;
+    use turbopack_core::{chunk::MangleType, code_builder::CodeBuilder};
+
+    use crate::minify::minify;
+
+    fn minify_chunk(source: &str, names: &mut BTreeMap<RcStr, BTreeMap<RcStr, RcStr>>) -> String {
+        let mut code = CodeBuilder::new(false, false);
+        code.push_source(&source.to_string().into(), None);
+        let code = minify(
+            code.build(),
+            false,
+            Some(MangleType::OptimalSize),
+            None,
+            Some(names),
+        )
+        .unwrap();
+        code.source_code().to_str().unwrap().into_owned()
+    }
+
+    fn chunk(b: &str) -> String {
+        format!(
+            r#"(globalThis.TURBOPACK || (globalThis.TURBOPACK = [])).push([document.currentScript,
+"[project]/a.js", ((__turbopack_context__) => {{
+    function scale(value, factor) {{
+        const scaled = value * factor;
+        globalThis.log(scaled);
+        return scaled;
+    }}
+    __turbopack_context__.s({{ scale }});
+}}),
+"[project]/b.js", ((__turbopack_context__) => {{
+{b}
+}}),
+]);"#
+        )
+    }
+
+    const B: &str = r#"
+    function sum(first, second) {
+        const total = first + second;
+        globalThis.log(total);
+        return total;
+    }
+    __turbopack_context__.s({ sum });
+"#;
+
+    const B_EDITED: &str = r#"
+    function difference(first, second) {
+        const total = first - second;
+        globalThis.log(total);
+        return total;
+    }
+    function sum(first, second) {
+        const total = first + second;
+        globalThis.log(total);
+        return total;
+    }
+    __turbopack_context__.s({ sum, difference });
+"#;
+
+    fn chunk_item<'a>(code: &'a str, id: &str) -> &'a str {
+        let start = code.find(id).unwrap();
+        let end = code[start + id.len()..]
+            .find("\"[project]/")
+            .map_or(code.len(), |end| start + id.len() + end);
+        &code[start..end]
+    }
+
+    #[test]
+    fn names_are_stable_across_builds() {
+        let mut names = BTreeMap::new();
+        let first = minify_chunk(&chunk(B), &mut names);
+        let first_names = names.clone();
+        let second = minify_chunk(&chunk(B_EDITED), &mut names);
+
+        // The unchanged module produces the same code.
+        assert_eq!(
+            chunk_item(&first, "[project]/a.js"),
+            chunk_item(&second, "[project]/a.js")
+        );
+        assert_eq!(names["[project]/a.js"], first_names["[project]/a.js"]);
+
+        // The bindings of the edited module that still exist keep their names.
+        let b = &names["[project]/b.js"];
+        for (key, name) in &first_names["[project]/b.js"] {
+            assert_eq!(b.get(key), Some(name), "{key} was renamed");
+        }
+        assert!(b.len() > first_names["[project]/b.js"].len());
+    }
+
+    #[test]
+    fn names_are_keyed_by_the_id_before_the_factory() {
+        let mut names = BTreeMap::new();
+        minify_chunk(
+            &chunk(B).replace("document.currentScript", "\"output/chunk.js\""),
+            &mut names,
+        );
+        assert_eq!(
+            names.keys().map(|id| id.as_str()).collect::<Vec<_>>(),
+            ["[project]/a.js", "[project]/b.js"]
+        );
+    }
+}
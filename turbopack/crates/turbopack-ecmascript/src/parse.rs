@@ -49,6 +49,7 @@ use crate::{
     EcmascriptInputTransform,
     analyzer::graph::EvalContext,
     magic_identifier,
+    references::esm::constant_export::collect_inlinable_exports,
     swc_comments::ImmutableComments,
     transform::{EcmascriptInputTransforms, TransformContext},
 };
@@ -532,6 +533,9 @@ async fn parse_file_content(
                 parsed_program.mutate(explicit_resource_management());
             });
 
+            // Has to happen before the TypeScript transform removes the enum declarations
+            let inlinable_exports = collect_inlinable_exports(&parsed_program);
+
             let var_with_ts_declare = if is_typescript {
                 VarDeclWithTsDeclareCollector::collect(&parsed_program)
             } else {
@@ -585,7 +589,7 @@ async fn parse_file_content(
                 ));
             });
 
-            let eval_context = EvalContext::new(
+            let mut eval_context = EvalContext::new(
                 Some(&parsed_program),
                 unresolved_mark,
                 top_level_mark,
@@ -593,6 +597,7 @@ async fn parse_file_content(
                 Some(&comments),
                 Some(source),
             );
+            eval_context.inlinable_exports = inlinable_exports;
 
             let (comments, source_mapping_url) =
                 ImmutableComments::new_with_source_mapping_url(comments);
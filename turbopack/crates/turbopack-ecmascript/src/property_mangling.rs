@@ -0,0 +1,646 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+use swc_core::ecma::{
+    ast::{
+        AssignPat, AssignPatProp, BinExpr, BinaryOp, Decl, ExportDecl, ExportDefaultDecl,
+        ExportDefaultExpr, ExportDefaultSpecifier, ExportNamedSpecifier, ExportNamespaceSpecifier,
+        Expr, Id, IdentName, ImportNamedSpecifier, KeyValuePatProp, KeyValueProp, Lit, MemberProp,
+        ModuleExportName, ObjectPatProp, Pat, Prop, PropName, Str, SuperProp,
+    },
+    utils::find_pat_ids,
+    visit::{Visit, VisitMut, VisitMutWith, VisitWith},
+};
+use turbo_rcstr::RcStr;
+use turbo_tasks::{FxIndexSet, ResolvedVc, TryFlatJoinIterExt, TryJoinIterExt, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+use turbopack_core::{chunk::PropertyMangling, module_graph::ModuleGraph};
+
+use crate::{
+    EcmascriptModuleAsset, parse::ParseResult,
+    side_effect_optimization::locals::module::EcmascriptModuleLocalsModule,
+    tree_shake::asset::EcmascriptModulePartAsset,
+};
+
+/// Configures renaming object properties consistently across all chunks of a build.
+///
+/// Only statically known property names are renamed: member accesses, object literal and class
+/// keys, destructuring patterns and `in` checks. Properties matching the pattern must not be
+/// accessed with computed keys or be part of an API used outside of the build. Names that are
+/// imported or exported by a module, and `__esModule`, are never renamed, as they are also
+/// properties of module namespace objects and `exports`.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct PropertyManglingOptions {
+    /// A regular expression matching the names of properties to rename, e.g. `^_`.
+    pub pattern: RcStr,
+    /// A JSON file the renames are read from, so that properties keep their names across builds.
+    pub name_cache: Option<FileSystemPath>,
+}
+
+/// The format of the name cache file, compatible with the `nameCache` of terser.
+#[derive(Default, Serialize, Deserialize)]
+struct NameCache {
+    #[serde(default)]
+    props: BTreeMap<RcStr, RcStr>,
+}
+
+/// Property names that can't be renamed, even when they match the pattern.
+const RESERVED_PROPERTY_NAMES: &[&str] = &["__esModule"];
+
+#[turbo_tasks::value]
+#[derive(Debug, Default)]
+pub struct ModulePropertyNames {
+    /// The names of all properties that are accessed or defined in the module.
+    pub properties: BTreeSet<RcStr>,
+    /// The names the module imports or exports. They are properties of the module namespace
+    /// objects, which are accessed with names that aren't renamed.
+    pub module_names: BTreeSet<RcStr>,
+}
+
+#[turbo_tasks::function]
+pub async fn module_property_names(
+    module: ResolvedVc<EcmascriptModuleAsset>,
+) -> Result<Vc<ModulePropertyNames>> {
+    let parsed = module.await?.parse().await?.await?;
+    let ParseResult::Ok { program, .. } = &*parsed else {
+        return Ok(ModulePropertyNames::default().cell());
+    };
+    let mut collector = PropertyNameCollector::default();
+    program.visit_with(&mut collector);
+    Ok(collector.names.cell())
+}
+
+#[turbo_tasks::function]
+pub async fn read_property_name_cache(path: FileSystemPath) -> Result<Vc<PropertyMangling>> {
+    let FileContent::Content(file) = &*path.read().await? else {
+        return Ok(Vc::cell(BTreeMap::new()));
+    };
+    let cache: NameCache = serde_json::from_str(&file.content().to_str()?)
+        .with_context(|| format!("Invalid property name cache {}", path.path))?;
+    Ok(Vc::cell(cache.props))
+}
+
+/// Assigns short names to all properties in the module graph that match the pattern. Names from
+/// the name cache are kept, unless they clash with a property that isn't renamed.
+#[turbo_tasks::function]
+pub async fn compute_property_mangling(
+    module_graph: Vc<ModuleGraph>,
+    options: Vc<PropertyManglingOptions>,
+) -> Result<Vc<PropertyMangling>> {
+    let options = options.await?;
+    let pattern = Regex::new(&options.pattern)
+        .with_context(|| format!("Invalid property mangling pattern {}", options.pattern))?;
+
+    let graph = module_graph.read_graphs().await?;
+    let modules = graph
+        .graphs
+        .iter()
+        .flat_map(|graph| graph.iter_nodes())
+        .map(async |module| {
+            Ok(
+                if let Some(module) = ResolvedVc::try_downcast_type::<EcmascriptModuleAsset>(module)
+                {
+                    Some(module)
+                } else if let Some(locals) =
+                    ResolvedVc::try_downcast_type::<EcmascriptModuleLocalsModule>(module)
+                {
+                    Some(locals.await?.module)
+                } else if let Some(part) =
+                    ResolvedVc::try_downcast_type::<EcmascriptModulePartAsset>(module)
+                {
+                    Some(part.await?.full_module)
+                } else {
+                    None
+                },
+            )
+        })
+        .try_flat_join()
+        .await?
+        .into_iter()
+        .collect::<FxIndexSet<_>>();
+    let property_names = modules
+        .into_iter()
+        .map(|module| module_property_names(*module))
+        .try_join()
+        .await?;
+
+    let name_cache = match &options.name_cache {
+        Some(path) => read_property_name_cache(path.clone()).owned().await?,
+        None => BTreeMap::new(),
+    };
+    Ok(Vc::cell(assign_property_names(
+        &pattern,
+        property_names.iter().map(|names| &**names),
+        name_cache,
+    )?))
+}
+
+fn assign_property_names<'a>(
+    pattern: &Regex,
+    property_names: impl IntoIterator<Item = &'a ModulePropertyNames>,
+    mut mangling: BTreeMap<RcStr, RcStr>,
+) -> Result<BTreeMap<RcStr, RcStr>> {
+    let mut renamed = BTreeSet::new();
+    let mut reserved = RESERVED_PROPERTY_NAMES
+        .iter()
+        .map(|&name| RcStr::from(name))
+        .collect::<FxHashSet<_>>();
+    for names in property_names {
+        reserved.extend(names.module_names.iter().cloned());
+        for name in &names.properties {
+            if pattern.is_match(name) {
+                renamed.insert(name.clone());
+            } else {
+                reserved.insert(name.clone());
+            }
+        }
+    }
+    renamed.retain(|name| !reserved.contains(name));
+
+    let mut used = FxHashSet::default();
+    mangling.retain(|name, mangled| {
+        pattern.is_match(name)
+            && !reserved.contains(name)
+            && !pattern.is_match(mangled)
+            && !reserved.contains(mangled)
+            && used.insert(mangled.clone())
+    });
+
+    let mut names = (0..)
+        .map(mangled_name)
+        .filter(|name| !reserved.contains(name) && !pattern.is_match(name));
+    for name in renamed {
+        if mangling.contains_key(&name) {
+            continue;
+        }
+        let mangled = names
+            .find(|mangled| !used.contains(mangled))
+            .context("ran out of property names")?;
+        used.insert(mangled.clone());
+        mangling.insert(name, mangled);
+    }
+
+    Ok(mangling)
+}
+
+/// Writes the renames back to the name cache. Entries of properties that are no longer used are
+/// kept, so that they get the same name again when they come back.
+#[turbo_tasks::function]
+pub async fn write_property_name_cache(
+    mangling: Vc<PropertyMangling>,
+    path: FileSystemPath,
+) -> Result<Vc<()>> {
+    let mangling = mangling.await?;
+    if *read_property_name_cache(path.clone()).await? != *mangling {
+        let cache = NameCache {
+            props: (*mangling).clone(),
+        };
+        let content = serde_json::to_string_pretty(&cache)? + "\n";
+        path.write(FileContent::Content(File::from(content)).cell())
+            .as_side_effect()
+            .await?;
+    }
+    Ok(Vc::cell(()))
+}
+
+/// The `index`th shortest property name: `a`, ..., `_`, `aa`, `ba`, ...
+pub(crate) fn mangled_name(index: usize) -> RcStr {
+    const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ$_";
+    const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ$_0123456789";
+
+    let mut name = String::new();
+    name.push(FIRST[index % FIRST.len()] as char);
+    let mut index = index / FIRST.len();
+    while index > 0 {
+        index -= 1;
+        name.push(REST[index % REST.len()] as char);
+        index /= REST.len();
+    }
+    name.into()
+}
+
+fn str_value(str: &Str) -> RcStr {
+    str.value.to_string_lossy().into()
+}
+
+fn module_export_name(name: &ModuleExportName) -> RcStr {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.as_str().into(),
+        ModuleExportName::Str(str) => str_value(str),
+    }
+}
+
+#[derive(Default)]
+struct PropertyNameCollector {
+    names: ModulePropertyNames,
+}
+
+impl PropertyNameCollector {
+    fn insert(&mut self, name: impl Into<RcStr>) {
+        self.names.properties.insert(name.into());
+    }
+
+    fn insert_module_name(&mut self, name: impl Into<RcStr>) {
+        self.names.module_names.insert(name.into());
+    }
+}
+
+impl Visit for PropertyNameCollector {
+    fn visit_import_named_specifier(&mut self, specifier: &ImportNamedSpecifier) {
+        match &specifier.imported {
+            Some(imported) => self.insert_module_name(module_export_name(imported)),
+            None => self.insert_module_name(specifier.local.sym.as_str()),
+        }
+    }
+
+    fn visit_export_named_specifier(&mut self, specifier: &ExportNamedSpecifier) {
+        self.insert_module_name(module_export_name(&specifier.orig));
+        if let Some(exported) = &specifier.exported {
+            self.insert_module_name(module_export_name(exported));
+        }
+    }
+
+    fn visit_export_namespace_specifier(&mut self, specifier: &ExportNamespaceSpecifier) {
+        self.insert_module_name(module_export_name(&specifier.name));
+    }
+
+    fn visit_export_default_specifier(&mut self, specifier: &ExportDefaultSpecifier) {
+        self.insert_module_name(specifier.exported.sym.as_str());
+    }
+
+    fn visit_export_default_decl(&mut self, decl: &ExportDefaultDecl) {
+        self.insert_module_name("default");
+        decl.visit_children_with(self);
+    }
+
+    fn visit_export_default_expr(&mut self, expr: &ExportDefaultExpr) {
+        self.insert_module_name("default");
+        expr.visit_children_with(self);
+    }
+
+    fn visit_export_decl(&mut self, export: &ExportDecl) {
+        match &export.decl {
+            Decl::Class(class) => self.insert_module_name(class.ident.sym.as_str()),
+            Decl::Fn(function) => self.insert_module_name(function.ident.sym.as_str()),
+            Decl::Var(var) => {
+                for decl in &var.decls {
+                    for (sym, _) in find_pat_ids::<_, Id>(&decl.name) {
+                        self.insert_module_name(sym.as_str());
+                    }
+                }
+            }
+            _ => {}
+        }
+        export.visit_children_with(self);
+    }
+
+    fn visit_member_prop(&mut self, prop: &MemberProp) {
+        match prop {
+            MemberProp::Ident(ident) => {
+                self.insert(ident.sym.as_str());
+            }
+            MemberProp::Computed(computed) => {
+                if let Expr::Lit(Lit::Str(str)) = &*computed.expr {
+                    self.insert(str_value(str));
+                }
+                computed.visit_children_with(self);
+            }
+            MemberProp::PrivateName(_) => {}
+        }
+    }
+
+    fn visit_super_prop(&mut self, prop: &SuperProp) {
+        match prop {
+            SuperProp::Ident(ident) => {
+                self.insert(ident.sym.as_str());
+            }
+            SuperProp::Computed(computed) => {
+                if let Expr::Lit(Lit::Str(str)) = &*computed.expr {
+                    self.insert(str_value(str));
+                }
+                computed.visit_children_with(self);
+            }
+        }
+    }
+
+    fn visit_prop_name(&mut self, name: &PropName) {
+        match name {
+            PropName::Ident(ident) => {
+                self.insert(ident.sym.as_str());
+            }
+            PropName::Str(str) => {
+                self.insert(str_value(str));
+            }
+            _ => name.visit_children_with(self),
+        }
+    }
+
+    fn visit_prop(&mut self, prop: &Prop) {
+        if let Prop::Shorthand(ident) = prop {
+            self.insert(ident.sym.as_str());
+        }
+        prop.visit_children_with(self);
+    }
+
+    fn visit_object_pat_prop(&mut self, prop: &ObjectPatProp) {
+        if let ObjectPatProp::Assign(AssignPatProp { key, .. }) = prop {
+            self.insert(key.sym.as_str());
+        }
+        prop.visit_children_with(self);
+    }
+
+    fn visit_bin_expr(&mut self, expr: &BinExpr) {
+        if expr.op == BinaryOp::In
+            && let Expr::Lit(Lit::Str(str)) = &*expr.left
+        {
+            self.insert(str_value(str));
+        }
+        expr.visit_children_with(self);
+    }
+}
+
+/// Renames properties in the same places [PropertyNameCollector] finds them.
+pub(crate) struct PropertyRenamer<'a> {
+    pub mangling: &'a BTreeMap<RcStr, RcStr>,
+}
+
+impl PropertyRenamer<'_> {
+    fn rename_str(&self, str: &mut Str) {
+        if let Some(mangled) = self.mangling.get(&*str.value.to_string_lossy()) {
+            *str = Str {
+                span: str.span,
+                value: mangled.as_str().into(),
+                raw: None,
+            };
+        }
+    }
+
+    fn rename_ident(&self, ident: &mut IdentName) {
+        if let Some(mangled) = self.mangling.get(ident.sym.as_str()) {
+            ident.sym = mangled.as_str().into();
+        }
+    }
+}
+
+impl VisitMut for PropertyRenamer<'_> {
+    fn visit_mut_member_prop(&mut self, prop: &mut MemberProp) {
+        match prop {
+            MemberProp::Ident(ident) => self.rename_ident(ident),
+            MemberProp::Computed(computed) => {
+                if let Expr::Lit(Lit::Str(str)) = &mut *computed.expr {
+                    self.rename_str(str);
+                }
+                computed.visit_mut_children_with(self);
+            }
+            MemberProp::PrivateName(_) => {}
+        }
+    }
+
+    fn visit_mut_super_prop(&mut self, prop: &mut SuperProp) {
+        match prop {
+            SuperProp::Ident(ident) => self.rename_ident(ident),
+            SuperProp::Computed(computed) => {
+                if let Expr::Lit(Lit::Str(str)) = &mut *computed.expr {
+                    self.rename_str(str);
+                }
+                computed.visit_mut_children_with(self);
+            }
+        }
+    }
+
+    fn visit_mut_prop_name(&mut self, name: &mut PropName) {
+        match name {
+            PropName::Ident(ident) => self.rename_ident(ident),
+            PropName::Str(str) => self.rename_str(str),
+            _ => name.visit_mut_children_with(self),
+        }
+    }
+
+    fn visit_mut_prop(&mut self, prop: &mut Prop) {
+        if let Prop::Shorthand(ident) = prop
+            && let Some(mangled) = self.mangling.get(ident.sym.as_str())
+        {
+            *prop = Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(IdentName::new(mangled.as_str().into(), ident.span)),
+                value: Box::new(Expr::Ident(ident.clone())),
+            });
+            return;
+        }
+        prop.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_object_pat_prop(&mut self, prop: &mut ObjectPatProp) {
+        if let ObjectPatProp::Assign(AssignPatProp { span, key, value }) = prop
+            && let Some(mangled) = self.mangling.get(key.sym.as_str())
+        {
+            let binding = Box::new(Pat::Ident(key.clone()));
+            *prop = ObjectPatProp::KeyValue(KeyValuePatProp {
+                key: PropName::Ident(IdentName::new(mangled.as_str().into(), key.span)),
+                value: match value.take() {
+                    Some(default) => Box::new(Pat::Assign(AssignPat {
+                        span: *span,
+                        left: binding,
+                        right: default,
+                    })),
+                    None => binding,
+                },
+            });
+        }
+        prop.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_bin_expr(&mut self, expr: &mut BinExpr) {
+        if expr.op == BinaryOp::In
+            && let Expr::Lit(Lit::Str(str)) = &mut *expr.left
+        {
+            self.rename_str(str);
+        }
+        expr.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{FileName, SourceMap, sync::Lrc},
+        ecma::{
+            ast::Program,
+            codegen::{Emitter, text_writer::JsWriter},
+            parser::parse_file_as_program,
+        },
+    };
+
+    use super::*;
+
+    fn parse(cm: &Lrc<SourceMap>, code: &str) -> Program {
+        let fm = cm.new_source_file(FileName::Anon.into(), code.to_string());
+        parse_file_as_program(
+            &fm,
+            Default::default(),
+            Default::default(),
+            None,
+            &mut vec![],
+        )
+        .unwrap()
+    }
+
+    fn collect(program: &Program) -> ModulePropertyNames {
+        let mut collector = PropertyNameCollector::default();
+        program.visit_with(&mut collector);
+        collector.names
+    }
+
+    fn names(names: &[&str]) -> BTreeSet<RcStr> {
+        names.iter().map(|&name| name.into()).collect()
+    }
+
+    fn mangling(entries: &[(&str, &str)]) -> BTreeMap<RcStr, RcStr> {
+        entries
+            .iter()
+            .map(|&(name, mangled)| (name.into(), mangled.into()))
+            .collect()
+    }
+
+    #[test]
+    fn collects_properties_and_module_names() {
+        let cm = Lrc::new(SourceMap::default());
+        let program = parse(
+            &cm,
+            r#"
+            import { _imported, other as _binding } from "./a";
+            export * as _namespace from "./b";
+            export const _exported = 1;
+            const _local = { _key: 1, "_str": 2, _shorthand };
+            export { _local as _alias };
+            obj._member, obj["_computed"], "_in" in obj;
+            const { _pattern, _default = 1 } = obj;
+            "#,
+        );
+
+        let names = collect(&program);
+        assert_eq!(
+            names.properties,
+            self::names(&[
+                "_computed",
+                "_default",
+                "_in",
+                "_key",
+                "_member",
+                "_pattern",
+                "_shorthand",
+                "_str",
+            ])
+        );
+        assert_eq!(
+            names.module_names,
+            self::names(&[
+                "_alias",
+                "_exported",
+                "_imported",
+                "_local",
+                "_namespace",
+                "other",
+            ])
+        );
+    }
+
+    #[test]
+    fn reserves_module_names_and_es_module() {
+        let pattern = Regex::new("^_").unwrap();
+        let module = ModulePropertyNames {
+            properties: names(&["_a", "_b", "a", "_exported", "__esModule"]),
+            module_names: names(&["_exported"]),
+        };
+
+        let mangling = assign_property_names(&pattern, [&module], BTreeMap::new()).unwrap();
+        assert_eq!(mangling, self::mangling(&[("_a", "b"), ("_b", "c")]));
+    }
+
+    #[test]
+    fn reuses_the_name_cache() {
+        let pattern = Regex::new("^_").unwrap();
+        let module = ModulePropertyNames {
+            properties: names(&["_a", "_b", "_c", "a", "_exported"]),
+            module_names: names(&["_exported"]),
+        };
+        let name_cache = mangling(&[
+            // Clashes with a property that isn't renamed.
+            ("_a", "a"),
+            ("_b", "x"),
+            // Matches the pattern, so it would be renamed again.
+            ("_c", "_y"),
+            ("_exported", "z"),
+            // Kept for when the property is used again.
+            ("_unused", "b"),
+        ]);
+
+        let mangling = assign_property_names(&pattern, [&module], name_cache).unwrap();
+        assert_eq!(
+            mangling,
+            self::mangling(&[("_a", "c"), ("_b", "x"), ("_c", "d"), ("_unused", "b")])
+        );
+    }
+
+    #[test]
+    fn renames_properties() {
+        let cm = Lrc::new(SourceMap::default());
+        let mut program = parse(
+            &cm,
+            r#"
+            const o = { _key: 1, _short, "_str": 2 };
+            o._key, o["_str"], "_in" in o;
+            const { _pattern, _default = 1, _short: short } = o;
+            "#,
+        );
+        let mangling = mangling(&[
+            ("_key", "a"),
+            ("_short", "b"),
+            ("_str", "c"),
+            ("_in", "d"),
+            ("_pattern", "e"),
+            ("_default", "f"),
+        ]);
+
+        program.visit_mut_with(&mut PropertyRenamer {
+            mangling: &mangling,
+        });
+        // The properties are renamed in the places they are collected from.
+        let names = collect(&program);
+        assert_eq!(
+            names.properties,
+            self::names(&["a", "b", "c", "d", "e", "f"])
+        );
+
+        // Shorthand properties and patterns keep their bindings.
+        let mut code = vec![];
+        Emitter {
+            cfg: Default::default(),
+            cm: cm.clone(),
+            comments: None,
+            wr: JsWriter::new(cm.clone(), "\n", &mut code, None),
+        }
+        .emit_program(&program)
+        .unwrap();
+        let code = String::from_utf8(code).unwrap();
+        assert!(code.contains("b: _short"), "{code}");
+        assert!(code.contains("e: _pattern"), "{code}");
+        assert!(code.contains("f: _default = 1"), "{code}");
+    }
+
+    #[test]
+    fn mangled_names_are_unique() {
+        assert_eq!(mangled_name(0).as_str(), "a");
+        assert_eq!(mangled_name(53).as_str(), "_");
+        assert_eq!(mangled_name(54).as_str(), "aa");
+        assert_eq!(mangled_name(55).as_str(), "ba");
+        assert_eq!(mangled_name(54 + 54 * 64).as_str(), "aaa");
+
+        let names = (0..10_000).map(mangled_name).collect::<Vec<_>>();
+        let unique = names.iter().collect::<std::collections::HashSet<_>>();
+        assert_eq!(unique.len(), names.len());
+    }
+}
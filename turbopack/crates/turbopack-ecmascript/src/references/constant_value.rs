@@ -51,7 +51,7 @@ impl From<ConstantValueCodeGen> for CodeGen {
     }
 }
 
-fn define_env_to_expr(value: CompileTimeDefineValue) -> Expr {
+pub(crate) fn define_env_to_expr(value: CompileTimeDefineValue) -> Expr {
     match value {
         CompileTimeDefineValue::Null => {
             quote!("(\"TURBOPACK compile-time value\", null)" as Expr)
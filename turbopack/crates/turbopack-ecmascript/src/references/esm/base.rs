@@ -36,19 +36,23 @@ use turbopack_resolve::ecmascript::esm_resolve;
 
 use crate::{
     EcmascriptModuleAsset, ScopeHoistingContext, TreeShakingMode,
-    analyzer::imports::ImportAnnotations,
+    analyzer::imports::{ImportAnnotations, ImportPhase},
     chunk::{EcmascriptChunkPlaceable, EcmascriptExports},
     code_gen::{CodeGeneration, CodeGenerationHoistedStmt},
     export::Liveness,
     magic_identifier,
     references::{
         esm::{
-            EsmExport,
+            EsmExport, ImportedBindingUses,
+            constant_export::{OptionInlinableExport, inlinable_export},
             export::{all_known_export_names, is_export_missing},
         },
         util::throw_module_not_found_expr,
     },
-    runtime_functions::{TURBOPACK_EXTERNAL_IMPORT, TURBOPACK_EXTERNAL_REQUIRE, TURBOPACK_IMPORT},
+    runtime_functions::{
+        TURBOPACK_EXTERNAL_IMPORT, TURBOPACK_EXTERNAL_REQUIRE, TURBOPACK_IMPORT,
+        TURBOPACK_IMPORT_DEFERRED,
+    },
     tree_shake::{TURBOPACK_PART_IMPORT_SOURCE, asset::EcmascriptModulePartAsset},
     utils::module_id_to_lit,
 };
@@ -334,6 +338,9 @@ pub struct EsmAssetReference {
     pub import_externals: bool,
     pub tree_shaking_mode: Option<TreeShakingMode>,
     pub is_pure_import: bool,
+    pub phase: ImportPhase,
+    /// How the imported binding is used, when constant export inlining is enabled.
+    pub inline_uses: Option<ImportedBindingUses>,
 }
 
 impl EsmAssetReference {
@@ -369,6 +376,8 @@ impl EsmAssetReference {
             import_externals,
             tree_shaking_mode,
             is_pure_import: false,
+            phase: ImportPhase::Evaluation,
+            inline_uses: None,
         }
     }
 
@@ -394,6 +403,19 @@ impl EsmAssetReference {
             import_externals,
             tree_shaking_mode,
             is_pure_import: true,
+            phase: ImportPhase::Evaluation,
+            inline_uses: None,
+        }
+    }
+
+    pub fn with_phase(self, phase: ImportPhase) -> Self {
+        EsmAssetReference { phase, ..self }
+    }
+
+    pub fn with_inline_uses(self, inline_uses: Option<ImportedBindingUses>) -> Self {
+        EsmAssetReference {
+            inline_uses,
+            ..self
         }
     }
 }
@@ -404,13 +426,29 @@ impl EsmAssetReference {
     pub(crate) fn get_referenced_asset(self: Vc<Self>) -> Vc<ReferencedAsset> {
         ReferencedAsset::from_resolve_result(self.resolve_reference())
     }
+
+    /// The value uses of the imported binding can be replaced with, if constant export inlining is
+    /// enabled and the export is an inlinable constant or enum.
+    #[turbo_tasks::function]
+    pub(crate) async fn inlined_export(self: Vc<Self>) -> Result<Vc<OptionInlinableExport>> {
+        let this = self.await?;
+        if this.inline_uses.is_some()
+            && let Some(ModulePart::Export(export_name)) = &this.export_name
+            && let ReferencedAsset::Some(module) = *self.get_referenced_asset().await?
+        {
+            return Ok(inlinable_export(*module, export_name.clone()));
+        }
+        Ok(Vc::cell(None))
+    }
 }
 
 #[turbo_tasks::value_impl]
 impl ModuleReference for EsmAssetReference {
     #[turbo_tasks::function]
     async fn resolve_reference(&self) -> Result<Vc<ModuleResolveResult>> {
-        let ty = if self.annotations.module_type().is_some_and(|v| v == "json") {
+        let ty = if self.phase == ImportPhase::Source {
+            EcmaScriptModulesReferenceSubType::ImportSource
+        } else if self.annotations.module_type().is_some_and(|v| v == "json") {
             EcmaScriptModulesReferenceSubType::ImportWithType(ImportWithType::Json)
         } else if self.annotations.module_type().is_some_and(|v| v == "bytes") {
             EcmaScriptModulesReferenceSubType::ImportWithType(ImportWithType::Bytes)
@@ -480,7 +518,12 @@ impl ModuleReference for EsmAssetReference {
 impl ValueToString for EsmAssetReference {
     #[turbo_tasks::function]
     fn to_string(&self) -> Vc<RcStr> {
-        Vc::cell(format!("import {} with {}", self.request, self.annotations).into())
+        let phase = match self.phase {
+            ImportPhase::Evaluation => "",
+            ImportPhase::Defer => "defer ",
+            ImportPhase::Source => "source ",
+        };
+        Vc::cell(format!("import {phase}{} with {}", self.request, self.annotations).into())
     }
 }
 
@@ -506,23 +549,35 @@ impl ChunkableModuleReference for EsmAssetReference {
             } else {
                 Some(ChunkingType::Parallel {
                     inherit_async: true,
-                    hoisted: true,
+                    // A deferred module isn't executed before the importing module, but async
+                    // modules are still awaited by it.
+                    hoisted: self.phase != ImportPhase::Defer,
                 })
             },
         ))
     }
 
     #[turbo_tasks::function]
-    fn binding_usage(&self) -> Vc<BindingUsage> {
-        BindingUsage {
-            import: self.import_usage.clone(),
-            export: match &self.export_name {
+    async fn binding_usage(self: Vc<Self>) -> Result<Vc<BindingUsage>> {
+        let this = self.await?;
+        let import = if let Some(inline_uses) = &this.inline_uses
+            && let Some(export) = &*self.inlined_export().await?
+            && inline_uses.are_inlinable(export)
+        {
+            // Every use is replaced with the value, so the import doesn't need the export at all
+            ImportUsage::Exports(Default::default())
+        } else {
+            this.import_usage.clone()
+        };
+        Ok(BindingUsage {
+            import,
+            export: match &this.export_name {
                 Some(ModulePart::Export(export_name)) => ExportUsage::Named(export_name.clone()),
                 Some(ModulePart::Evaluation) => ExportUsage::Evaluation,
                 _ => ExportUsage::All,
             },
         }
-        .cell()
+        .cell())
     }
 }
 
@@ -624,9 +679,14 @@ impl EsmAssetReference {
                                             DUMMY_SP,
                                             ctxt.unwrap_or_default(),
                                         );
+                                        let turbopack_import = if this.phase == ImportPhase::Defer {
+                                            TURBOPACK_IMPORT_DEFERRED
+                                        } else {
+                                            TURBOPACK_IMPORT
+                                        };
                                         let mut call_expr = quote!(
                                             "$turbopack_import($id)" as Expr,
-                                            turbopack_import: Expr = TURBOPACK_IMPORT.into(),
+                                            turbopack_import: Expr = turbopack_import.into(),
                                             id: Expr = module_id_to_lit(&id),
                                         );
                                         if this.is_pure_import {
@@ -81,6 +81,38 @@ impl EsmBinding {
                 .map_or(ImportedIdent::Unresolvable, ImportedIdent::Module),
         };
 
+        if self.export.is_some()
+            && let Some(inlined) = self.reference.inlined_export().owned().await?
+            && let Some(path) = inlined.inlined_expr_path(&self.ast_path.0)
+        {
+            // Replace the read with the constant value. For enums, accesses of unknown members
+            // keep reading from the imported binding.
+            visitors.push(create_visitor!(
+                exact,
+                path,
+                visit_mut_expr,
+                |expr: &mut Expr| {
+                    use swc_core::common::Spanned;
+                    if let Some(value) = inlined.inlined_expr(expr) {
+                        *expr = value;
+                    } else if let Expr::Member(member) = expr {
+                        match &imported_ident {
+                            ImportedIdent::Module(imported_ident) => {
+                                *member.obj = imported_ident.as_expr(member.obj.span(), false);
+                            }
+                            ImportedIdent::None => {
+                                member.obj = Expr::undefined(member.obj.span());
+                            }
+                            ImportedIdent::Unresolvable => {
+                                // Do nothing, the reference will insert a throw
+                            }
+                        }
+                    }
+                }
+            ));
+            return Ok(CodeGeneration::visitors(visitors));
+        }
+
         let mut ast_path = self.ast_path.0.clone();
         loop {
             match ast_path.last() {
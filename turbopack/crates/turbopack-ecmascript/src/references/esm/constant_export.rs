@@ -0,0 +1,493 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use bincode::{Decode, Encode};
+use rustc_hash::{FxHashMap, FxHashSet};
+use swc_core::ecma::{
+    ast::{
+        Decl, ExportDecl, ExportNamedSpecifier, ExportSpecifier, Expr, Id, Ident, ImportDecl, Lit,
+        MemberExpr, MemberProp, ModuleDecl, ModuleExportName, ModuleItem, NamedExport, Pat,
+        Program, Stmt, TsEnumDecl, TsEnumMemberId, TsModuleName, UnaryExpr, UnaryOp, UpdateExpr,
+        VarDeclKind,
+    },
+    visit::{
+        AstParentKind, Visit, VisitWith,
+        fields::{ExprField, MemberExprField, PatField, UpdateExprField},
+    },
+};
+use turbo_rcstr::RcStr;
+use turbo_tasks::{NonLocalValue, ResolvedVc, Vc, trace::TraceRawVcs};
+use turbopack_core::{
+    compile_time_info::CompileTimeDefineValue,
+    module_graph::binding_usage_info::ModuleExportUsageInfo, reference::ModuleReference,
+};
+
+use crate::{
+    EcmascriptModuleAsset,
+    analyzer::imports::ImportMap,
+    chunk::{EcmascriptChunkPlaceable, EcmascriptExports},
+    parse::ParseResult,
+    references::{
+        constant_value::define_env_to_expr,
+        esm::{EsmExport, base::ReferencedAsset},
+    },
+    side_effect_optimization::locals::module::EcmascriptModuleLocalsModule,
+};
+
+/// The value of an export that importing modules can use in place of the binding.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Hash)]
+pub enum InlinableExport {
+    /// A `const` initialized with a primitive literal.
+    Constant(CompileTimeDefineValue),
+    /// A TypeScript enum with literal member values.
+    Enum(BTreeMap<RcStr, CompileTimeDefineValue>),
+}
+
+impl InlinableExport {
+    /// Returns the value to replace `expr` with. `expr` is either the imported binding itself or,
+    /// for enums, a member access on it.
+    pub(crate) fn inlined_expr(&self, expr: &Expr) -> Option<Expr> {
+        let value = match self {
+            InlinableExport::Constant(value) => value,
+            InlinableExport::Enum(members) => {
+                let Expr::Member(MemberExpr { prop, .. }) = expr else {
+                    return None;
+                };
+                members.get(&static_member_name(prop)?)?
+            }
+        };
+        Some(define_env_to_expr(value.clone()))
+    }
+
+    /// Returns the path of the expression to replace, given the path of the imported binding
+    /// (ending with `Expr::Ident`), or `None` if the binding isn't read there.
+    pub(crate) fn inlined_expr_path<'a>(
+        &self,
+        ast_path: &'a [AstParentKind],
+    ) -> Option<&'a [AstParentKind]> {
+        let [path @ .., AstParentKind::Expr(ExprField::Ident)] = ast_path else {
+            return None;
+        };
+        let path = match self {
+            InlinableExport::Constant(_) => path,
+            InlinableExport::Enum(_) => {
+                let [
+                    path @ ..,
+                    AstParentKind::Expr(ExprField::Member),
+                    AstParentKind::MemberExpr(MemberExprField::Obj),
+                ] = path
+                else {
+                    return None;
+                };
+                path
+            }
+        };
+        (!matches!(
+            path.last(),
+            None | Some(
+                AstParentKind::UpdateExpr(UpdateExprField::Arg)
+                    | AstParentKind::Pat(PatField::Expr)
+            )
+        ))
+        .then_some(path)
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionInlinableExport(Option<InlinableExport>);
+
+/// How a module uses the bindings imported through a single ESM reference.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, Hash, TraceRawVcs, NonLocalValue, Encode, Decode,
+)]
+pub struct ImportedBindingUses {
+    /// The binding is used in a way that can't be inlined, e.g. reexported, assigned to, or as a
+    /// shorthand property.
+    pub other_uses: bool,
+    /// The binding is read as a whole, i.e. not only through static member accesses.
+    pub whole_reads: bool,
+    /// The statically known members that are read from the binding.
+    pub members: BTreeSet<RcStr>,
+}
+
+impl ImportedBindingUses {
+    /// Whether every use can be replaced with the value of `export`, so that the import itself
+    /// isn't needed anymore.
+    pub fn are_inlinable(&self, export: &InlinableExport) -> bool {
+        !self.other_uses
+            && match export {
+                InlinableExport::Constant(_) => true,
+                InlinableExport::Enum(members) => {
+                    !self.whole_reads && self.members.iter().all(|m| members.contains_key(m))
+                }
+            }
+    }
+}
+
+/// Follows reexports of `export` and returns its value if it is an inlinable constant or enum.
+#[turbo_tasks::function]
+pub async fn inlinable_export(
+    module: ResolvedVc<Box<dyn EcmascriptChunkPlaceable>>,
+    export: RcStr,
+) -> Result<Vc<OptionInlinableExport>> {
+    let mut module = module;
+    let mut export = export;
+    let mut visited = FxHashSet::default();
+    while visited.insert((module, export.clone())) {
+        let EcmascriptExports::EsmExports(exports) = *module.get_exports().await? else {
+            break;
+        };
+        let exports = exports.expand_exports(ModuleExportUsageInfo::all()).await?;
+        match exports.exports.get(&export) {
+            Some(EsmExport::LocalBinding(..)) => {
+                let module = if let Some(module) =
+                    ResolvedVc::try_downcast_type::<EcmascriptModuleAsset>(module)
+                {
+                    module
+                } else if let Some(locals) =
+                    ResolvedVc::try_downcast_type::<EcmascriptModuleLocalsModule>(module)
+                {
+                    locals.await?.module
+                } else {
+                    break;
+                };
+                let parsed = module.await?.parse().await?.await?;
+                let ParseResult::Ok { eval_context, .. } = &*parsed else {
+                    break;
+                };
+                return Ok(Vc::cell(
+                    eval_context.inlinable_exports.get(&export).cloned(),
+                ));
+            }
+            Some(EsmExport::ImportedBinding(reference, name, false)) => {
+                let ReferencedAsset::Some(imported) =
+                    *ReferencedAsset::from_resolve_result(reference.resolve_reference()).await?
+                else {
+                    break;
+                };
+                module = imported;
+                export = name.clone();
+            }
+            _ => break,
+        }
+    }
+    Ok(Vc::cell(None))
+}
+
+/// Collects the exports of a module that are safe to inline into importing modules: `const`
+/// bindings initialized with a primitive literal and non-`declare` TypeScript enums whose members
+/// all have literal values.
+///
+/// This has to run before the TypeScript transform turns enums into plain objects.
+pub(crate) fn collect_inlinable_exports(program: &Program) -> BTreeMap<RcStr, InlinableExport> {
+    let Program::Module(module) = program else {
+        return BTreeMap::new();
+    };
+
+    let mut values = FxHashMap::<Id, InlinableExport>::default();
+    // Enums can be merged with other enum or namespace declarations of the same name
+    let mut merged_declarations = FxHashMap::<Id, usize>::default();
+    let mut exports = Vec::<(RcStr, Id)>::new();
+
+    for item in &module.body {
+        let decl = match item {
+            ModuleItem::Stmt(Stmt::Decl(decl)) => decl,
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => {
+                match decl {
+                    Decl::Var(var) => {
+                        for declarator in &var.decls {
+                            if let Pat::Ident(name) = &declarator.name {
+                                exports.push((name.sym.as_str().into(), name.to_id()));
+                            }
+                        }
+                    }
+                    Decl::TsEnum(decl) => {
+                        exports.push((decl.id.sym.as_str().into(), decl.id.to_id()));
+                    }
+                    _ => {}
+                }
+                decl
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+                src: None,
+                specifiers,
+                type_only: false,
+                ..
+            })) => {
+                for specifier in specifiers {
+                    if let ExportSpecifier::Named(ExportNamedSpecifier {
+                        orig: ModuleExportName::Ident(orig),
+                        exported,
+                        is_type_only: false,
+                        ..
+                    }) = specifier
+                    {
+                        let exported = match exported {
+                            Some(ModuleExportName::Ident(ident)) => ident.sym.as_str().into(),
+                            Some(ModuleExportName::Str(str)) => str.value.to_string_lossy().into(),
+                            None => orig.sym.as_str().into(),
+                        };
+                        exports.push((exported, orig.to_id()));
+                    }
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        match decl {
+            Decl::Var(var) if var.kind == VarDeclKind::Const && !var.declare => {
+                for declarator in &var.decls {
+                    if let Pat::Ident(name) = &declarator.name
+                        && let Some(value) = declarator.init.as_deref().and_then(literal_value)
+                    {
+                        values.insert(name.to_id(), InlinableExport::Constant(value));
+                    }
+                }
+            }
+            Decl::TsEnum(decl) => {
+                *merged_declarations.entry(decl.id.to_id()).or_default() += 1;
+                if !decl.declare
+                    && let Some(members) = enum_member_values(decl)
+                {
+                    values.insert(decl.id.to_id(), InlinableExport::Enum(members));
+                }
+            }
+            Decl::TsModule(decl) => {
+                if let TsModuleName::Ident(id) = &decl.id {
+                    *merged_declarations.entry(id.to_id()).or_default() += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    exports
+        .into_iter()
+        .filter(|(_, id)| merged_declarations.get(id).is_none_or(|&count| count == 1))
+        .filter_map(|(export, id)| Some((export, values.get(&id)?.clone())))
+        .collect()
+}
+
+fn number_value(value: f64) -> CompileTimeDefineValue {
+    CompileTimeDefineValue::Number(value.to_string().into())
+}
+
+fn literal_value(expr: &Expr) -> Option<CompileTimeDefineValue> {
+    Some(match expr {
+        Expr::Lit(Lit::Str(str)) => {
+            CompileTimeDefineValue::String(str.value.to_string_lossy().into())
+        }
+        Expr::Lit(Lit::Num(num)) => number_value(num.value),
+        Expr::Lit(Lit::Bool(bool)) => CompileTimeDefineValue::Bool(bool.value),
+        Expr::Lit(Lit::Null(_)) => CompileTimeDefineValue::Null,
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Minus,
+            arg,
+            ..
+        }) => {
+            let Expr::Lit(Lit::Num(num)) = &**arg else {
+                return None;
+            };
+            number_value(-num.value)
+        }
+        Expr::Paren(paren) => return literal_value(&paren.expr),
+        _ => return None,
+    })
+}
+
+/// Computes the values of all enum members, following TypeScript's auto-increment rules. Returns
+/// `None` if any member is initialized with something other than a number or string literal.
+fn enum_member_values(decl: &TsEnumDecl) -> Option<BTreeMap<RcStr, CompileTimeDefineValue>> {
+    let mut next = Some(0.0);
+    decl.members
+        .iter()
+        .map(|member| {
+            let name: RcStr = match &member.id {
+                TsEnumMemberId::Ident(ident) => ident.sym.as_str().into(),
+                TsEnumMemberId::Str(str) => str.value.to_string_lossy().into(),
+            };
+            let value = match member.init.as_deref() {
+                Some(init) => literal_value(init)?,
+                // Members without initializer are only allowed after numeric members
+                None => number_value(next?),
+            };
+            next = match &value {
+                CompileTimeDefineValue::Number(n) => Some(n.parse::<f64>().ok()? + 1.0),
+                CompileTimeDefineValue::String(_) => None,
+                _ => return None,
+            };
+            Some((name, value))
+        })
+        .collect()
+}
+
+fn static_member_name(prop: &MemberProp) -> Option<RcStr> {
+    match prop {
+        MemberProp::Ident(ident) => Some(ident.sym.as_str().into()),
+        MemberProp::Computed(computed) => match &*computed.expr {
+            Expr::Lit(Lit::Str(str)) => Some(str.value.to_string_lossy().into()),
+            _ => None,
+        },
+        MemberProp::PrivateName(_) => None,
+    }
+}
+
+/// Collects how the named imports of each ESM reference (by index) are used. This mirrors the
+/// positions [InlinableExport::inlined_expr_path] accepts.
+pub(crate) fn collect_imported_binding_uses(
+    program: &Program,
+    imports: &ImportMap,
+) -> FxHashMap<usize, ImportedBindingUses> {
+    let mut visitor = ImportedBindingUsesVisitor {
+        imports,
+        uses: FxHashMap::default(),
+    };
+    program.visit_with(&mut visitor);
+    visitor.uses
+}
+
+struct ImportedBindingUsesVisitor<'a> {
+    imports: &'a ImportMap,
+    uses: FxHashMap<usize, ImportedBindingUses>,
+}
+
+impl ImportedBindingUsesVisitor<'_> {
+    fn uses_of(&mut self, ident: &Ident) -> Option<&mut ImportedBindingUses> {
+        // Namespace imports are never inlined
+        let (index, Some(_)) = self.imports.get_binding(&ident.to_id())? else {
+            return None;
+        };
+        Some(self.uses.entry(index).or_default())
+    }
+
+    /// Marks a binding that is written to, either directly or through one of its members.
+    fn mark_write_target(&mut self, expr: &Expr) -> bool {
+        let ident = match expr {
+            Expr::Ident(ident) => ident,
+            Expr::Member(MemberExpr { obj, .. }) => match &**obj {
+                Expr::Ident(ident) => ident,
+                _ => return false,
+            },
+            _ => return false,
+        };
+        if let Some(uses) = self.uses_of(ident) {
+            uses.other_uses = true;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Visit for ImportedBindingUsesVisitor<'_> {
+    fn visit_import_decl(&mut self, _: &ImportDecl) {
+        // The import specifiers are declarations, not uses
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Ident(ident) => {
+                if let Some(uses) = self.uses_of(ident) {
+                    uses.whole_reads = true;
+                }
+                return;
+            }
+            Expr::Member(MemberExpr { obj, prop, .. }) => {
+                if let Expr::Ident(ident) = &**obj
+                    && let Some(name) = static_member_name(prop)
+                    && let Some(uses) = self.uses_of(ident)
+                {
+                    uses.members.insert(name);
+                    return;
+                }
+            }
+            Expr::Update(UpdateExpr { arg, .. })
+            | Expr::Unary(UnaryExpr {
+                op: UnaryOp::Delete,
+                arg,
+                ..
+            }) => {
+                if self.mark_write_target(arg) {
+                    return;
+                }
+            }
+            _ => {}
+        }
+        expr.visit_children_with(self);
+    }
+
+    fn visit_pat(&mut self, pat: &Pat) {
+        if let Pat::Expr(expr) = pat
+            && self.mark_write_target(expr)
+        {
+            return;
+        }
+        pat.visit_children_with(self);
+    }
+
+    fn visit_ident(&mut self, ident: &Ident) {
+        // Any identifier that isn't visited as an expression, e.g. an assignment target, a
+        // reexport or a shorthand property.
+        if let Some(uses) = self.uses_of(ident) {
+            uses.other_uses = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{FileName, SourceMap, sync::Lrc},
+        ecma::parser::{Syntax, TsSyntax, parse_file_as_program},
+    };
+
+    use super::*;
+
+    #[test]
+    fn collects_constants_and_enums() {
+        let cm = Lrc::new(SourceMap::default());
+        let fm = cm.new_source_file(
+            FileName::Anon.into(),
+            r#"
+            export const A = "a", B = -1;
+            const C = true;
+            let D = 1;
+            export { C as renamed, D };
+            export const E = Math.random();
+            export enum Direction { Up = 1, Down, Left = "left" }
+            export enum Computed { X = 1 << 2 }
+            export enum Merged { X }
+            export enum Merged { Y = 1 }
+            "#
+            .to_string(),
+        );
+        let program = parse_file_as_program(
+            &fm,
+            Syntax::Typescript(TsSyntax::default()),
+            Default::default(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+
+        let exports = collect_inlinable_exports(&program);
+        assert_eq!(
+            exports.keys().map(|k| k.as_str()).collect::<Vec<_>>(),
+            ["A", "B", "Direction", "renamed"]
+        );
+        assert_eq!(
+            exports["B"],
+            InlinableExport::Constant(CompileTimeDefineValue::Number("-1".into()))
+        );
+        assert_eq!(
+            exports["Direction"],
+            InlinableExport::Enum(BTreeMap::from([
+                ("Up".into(), CompileTimeDefineValue::Number("1".into())),
+                ("Down".into(), CompileTimeDefineValue::Number("2".into())),
+                ("Left".into(), CompileTimeDefineValue::String("left".into())),
+            ]))
+        );
+    }
+}
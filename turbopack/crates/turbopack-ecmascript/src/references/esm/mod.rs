@@ -1,5 +1,6 @@
 pub(crate) mod base;
 pub(crate) mod binding;
+pub(crate) mod constant_export;
 pub(crate) mod dynamic;
 pub(crate) mod export;
 pub(crate) mod meta;
@@ -10,6 +11,7 @@ pub(crate) mod url;
 pub use self::{
     base::EsmAssetReference,
     binding::EsmBinding,
+    constant_export::{ImportedBindingUses, InlinableExport, inlinable_export},
     dynamic::EsmAsyncAssetReference,
     export::{EsmExport, EsmExports, FoundExportType, Liveness},
     meta::{ImportMetaBinding, ImportMetaRef},
@@ -37,7 +37,7 @@ use crate::{
     references::async_module::{AsyncModule, OptionAsyncModule},
     runtime_functions::{
         TURBOPACK_EXPORT_NAMESPACE, TURBOPACK_EXPORT_VALUE, TURBOPACK_EXTERNAL_IMPORT,
-        TURBOPACK_EXTERNAL_REQUIRE, TURBOPACK_LOAD_BY_URL,
+        TURBOPACK_EXTERNAL_REQUIRE, TURBOPACK_FEDERATION_LOAD_REMOTE, TURBOPACK_LOAD_BY_URL,
     },
     utils::StringifyJs,
 };
@@ -51,6 +51,7 @@ pub enum CachedExternalType {
     EcmaScriptViaImport,
     Global,
     Script,
+    FederationRemote,
 }
 
 #[derive(
@@ -73,6 +74,7 @@ impl Display for CachedExternalType {
             CachedExternalType::EcmaScriptViaImport => write!(f, "esm_import"),
             CachedExternalType::Global => write!(f, "global"),
             CachedExternalType::Script => write!(f, "script"),
+            CachedExternalType::FederationRemote => write!(f, "federation_remote"),
         }
     }
 }
@@ -230,6 +232,31 @@ impl CachedExternalModule {
                     writeln!(code, "const mod = undefined;")?;
                 }
             }
+            CachedExternalType::FederationRemote => {
+                // Parse the request format: "remote/expose@url" or "remote@url"
+                // e.g., "app2/Button@https://app2.test.com/remoteEntry.js"
+                if let Some((module, url)) = self.request.split_once('@') {
+                    let (remote, expose) = match module.split_once('/') {
+                        Some((remote, path)) => (remote, Cow::Owned(format!("./{path}"))),
+                        None => (module, Cow::Borrowed(".")),
+                    };
+                    writeln!(
+                        code,
+                        "const mod = await {TURBOPACK_FEDERATION_LOAD_REMOTE}({}, {}, {});",
+                        StringifyJs(remote),
+                        StringifyJs(url),
+                        StringifyJs(&expose)
+                    )?;
+                } else {
+                    writeln!(
+                        code,
+                        "throw new Error('Invalid federation remote format. Expected \
+                         \"remote/expose@url\", got: {}');",
+                        StringifyJs(&self.request)
+                    )?;
+                    writeln!(code, "const mod = undefined;")?;
+                }
+            }
         }
 
         writeln!(code)?;
@@ -238,6 +265,7 @@ impl CachedExternalModule {
             writeln!(code, "module.exports = mod;")?;
         } else if self.external_type == CachedExternalType::EcmaScriptViaImport
             || self.external_type == CachedExternalType::EcmaScriptViaRequire
+            || self.external_type == CachedExternalType::FederationRemote
         {
             writeln!(code, "{TURBOPACK_EXPORT_NAMESPACE}(mod);")?;
         } else {
@@ -309,7 +337,9 @@ impl Module for CachedExternalModule {
                         )
                         .await?
                     }
-                    CachedExternalType::Global | CachedExternalType::Script => {
+                    CachedExternalType::Global
+                    | CachedExternalType::Script
+                    | CachedExternalType::FederationRemote => {
                         origin
                             .resolve_asset(
                                 Request::parse_string(self.request.clone()),
@@ -352,7 +382,8 @@ impl Module for CachedExternalModule {
     fn is_self_async(&self) -> Result<Vc<bool>> {
         Ok(Vc::cell(
             self.external_type == CachedExternalType::EcmaScriptViaImport
-                || self.external_type == CachedExternalType::Script,
+                || self.external_type == CachedExternalType::Script
+                || self.external_type == CachedExternalType::FederationRemote,
         ))
     }
 
@@ -405,6 +436,7 @@ impl EcmascriptChunkPlaceable for CachedExternalModule {
         Vc::cell(
             if self.external_type == CachedExternalType::EcmaScriptViaImport
                 || self.external_type == CachedExternalType::Script
+                || self.external_type == CachedExternalType::FederationRemote
             {
                 Some(
                     AsyncModule {
@@ -0,0 +1,385 @@
+use std::{borrow::Cow, collections::VecDeque};
+
+use anyhow::Result;
+use bincode::{Decode, Encode};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{Expr, KeyValueProp, Lit, ObjectLit, Prop, PropName, PropOrSpread},
+    quote, quote_expr,
+};
+use turbo_rcstr::RcStr;
+use turbo_tasks::{
+    FxIndexMap, NonLocalValue, ResolvedVc, ValueToString, Vc, debug::ValueDebugFormat,
+    trace::TraceRawVcs,
+};
+use turbo_tasks_fs::{
+    DirectoryEntry, FileSystemPath,
+    glob::{Glob, GlobOptions},
+};
+use turbopack_core::{
+    chunk::{ChunkableModuleReference, ChunkingContext, ChunkingType, ChunkingTypeOption},
+    environment::ChunkLoading,
+    issue::IssueSource,
+    reference::ModuleReference,
+    reference_type::EcmaScriptModulesReferenceSubType,
+    resolve::{ModuleResolveResult, RequestKey, origin::ResolveOrigin, parse::Request},
+};
+use turbopack_resolve::ecmascript::esm_resolve;
+
+use crate::{
+    analyzer::ImportMetaGlobOptions,
+    code_gen::{CodeGen, CodeGeneration, IntoCodeGenReference},
+    create_visitor,
+    references::{
+        AstPath,
+        pattern_mapping::{PatternMapping, ResolveType, SinglePatternMapping},
+    },
+    runtime_functions::TURBOPACK_IMPORT,
+};
+
+#[turbo_tasks::value]
+#[derive(Debug)]
+pub struct ImportMetaGlobMapEntry {
+    pub request: ResolvedVc<Request>,
+    pub result: ResolvedVc<ModuleResolveResult>,
+}
+
+/// The modules matched by an `import.meta.glob(..)` call, keyed by their path relative to the
+/// importing module, e.g. `./pages/index.tsx`.
+#[turbo_tasks::value(transparent)]
+pub struct ImportMetaGlobMap(
+    #[bincode(with = "turbo_bincode::indexmap")] FxIndexMap<RcStr, ImportMetaGlobMapEntry>,
+);
+
+/// Splits a relative glob into the directory it's evaluated in and the glob within that
+/// directory, e.g. `./pages/**/*.tsx` into `./pages` and `**/*.tsx`.
+fn split_glob(pattern: &str) -> (String, String) {
+    let segments: Vec<&str> = pattern.split('/').collect();
+    let glob_start = segments
+        .iter()
+        .position(|segment| segment.contains(['*', '?', '[', '{']))
+        .unwrap_or(segments.len() - 1);
+    (
+        segments[..glob_start].join("/"),
+        segments[glob_start..].join("/"),
+    )
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMetaGlobMap {
+    /// Evaluates the globs. Reading the directories through the filesystem makes the map update
+    /// when matching files are added or removed.
+    #[turbo_tasks::function]
+    pub(crate) async fn generate(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        patterns: Vec<RcStr>,
+        query: Option<RcStr>,
+        eager: bool,
+        issue_source: Option<IssueSource>,
+        is_optional: bool,
+    ) -> Result<Vc<Self>> {
+        let origin_path = origin.origin_path().owned().await?;
+        let origin_dir = origin_path.parent();
+
+        let mut excludes = vec![];
+        let mut files = vec![];
+        for pattern in &patterns {
+            if let Some(exclude) = pattern.strip_prefix('!') {
+                excludes.push(
+                    Glob::new(
+                        exclude.trim_start_matches("./").into(),
+                        GlobOptions::default(),
+                    )
+                    .await?,
+                );
+                continue;
+            }
+            let (base, glob) = split_glob(pattern);
+            let dir = origin_dir.join(&base)?;
+            let mut queue = VecDeque::from([dir
+                .read_glob(Glob::new(glob.into(), GlobOptions::default()))
+                .await?]);
+            while let Some(result) = queue.pop_front() {
+                for entry in result.results.values() {
+                    if let DirectoryEntry::File(path) = entry {
+                        files.push(path.clone());
+                    }
+                }
+                for inner in result.inner.values() {
+                    queue.push_back(inner.await?);
+                }
+            }
+        }
+
+        let subtype = if eager {
+            EcmaScriptModulesReferenceSubType::Import
+        } else {
+            EcmaScriptModulesReferenceSubType::DynamicImport
+        };
+        let mut map = FxIndexMap::default();
+        for path in files {
+            if path == origin_path {
+                continue;
+            }
+            let Some(key) = origin_dir.get_relative_path_to(&path) else {
+                continue;
+            };
+            if map.contains_key(&key)
+                || excludes
+                    .iter()
+                    .any(|exclude| exclude.matches(key.trim_start_matches("./")))
+            {
+                continue;
+            }
+            let request = Request::parse_string(
+                format!("{key}{}", query.as_deref().unwrap_or_default()).into(),
+            )
+            .to_resolved()
+            .await?;
+            let result = esm_resolve(origin, *request, subtype, is_optional, issue_source)
+                .await?
+                .to_resolved()
+                .await?;
+            map.insert(key, ImportMetaGlobMapEntry { request, result });
+        }
+        map.sort_keys();
+
+        Ok(Vc::cell(map))
+    }
+}
+
+/// A reference for `import.meta.glob()`, which is replaced with an object literal mapping the
+/// matched paths to loader functions, or to the module namespaces when `eager` is set.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct ImportMetaGlobAssetReference {
+    pub origin: ResolvedVc<Box<dyn ResolveOrigin>>,
+    pub map: ResolvedVc<ImportMetaGlobMap>,
+    pub patterns: Vec<RcStr>,
+    pub eager: bool,
+    pub import: Option<RcStr>,
+}
+
+impl ImportMetaGlobAssetReference {
+    pub async fn new(
+        origin: ResolvedVc<Box<dyn ResolveOrigin>>,
+        options: ImportMetaGlobOptions,
+        issue_source: Option<IssueSource>,
+        in_try: bool,
+    ) -> Result<Self> {
+        let map = ImportMetaGlobMap::generate(
+            *origin,
+            options.patterns.clone(),
+            options.query,
+            options.eager,
+            issue_source,
+            in_try,
+        )
+        .to_resolved()
+        .await?;
+        Ok(ImportMetaGlobAssetReference {
+            origin,
+            map,
+            patterns: options.patterns,
+            eager: options.eager,
+            import: options.import,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(&self) -> Result<Vc<ModuleResolveResult>> {
+        let mut primary = vec![];
+        let mut affecting_sources = vec![];
+        for (key, entry) in &*self.map.await? {
+            let result = entry.result.await?;
+            primary.extend(
+                result
+                    .primary
+                    .iter()
+                    .map(|(_, item)| (RequestKey::new(key.clone()), item.clone())),
+            );
+            affecting_sources.extend(result.affecting_sources.iter().copied());
+        }
+        Ok(ModuleResolveResult {
+            primary: primary.into_boxed_slice(),
+            affecting_sources: affecting_sources.into_boxed_slice(),
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> Vc<RcStr> {
+        Vc::cell(format!("import.meta.glob {}", self.patterns.join(", ")).into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(if self.eager {
+            ChunkingType::Parallel {
+                inherit_async: false,
+                hoisted: false,
+            }
+        } else {
+            ChunkingType::Async
+        }))
+    }
+}
+
+impl IntoCodeGenReference for ImportMetaGlobAssetReference {
+    fn into_code_gen_reference(
+        self,
+        path: AstPath,
+    ) -> (ResolvedVc<Box<dyn ModuleReference>>, CodeGen) {
+        let reference = self.resolved_cell();
+        (
+            ResolvedVc::upcast(reference),
+            CodeGen::ImportMetaGlobAssetReferenceCodeGen(ImportMetaGlobAssetReferenceCodeGen {
+                reference,
+                path,
+            }),
+        )
+    }
+}
+
+#[derive(
+    PartialEq, Eq, TraceRawVcs, ValueDebugFormat, NonLocalValue, Hash, Debug, Encode, Decode,
+)]
+pub struct ImportMetaGlobAssetReferenceCodeGen {
+    path: AstPath,
+    reference: ResolvedVc<ImportMetaGlobAssetReference>,
+}
+
+impl ImportMetaGlobAssetReferenceCodeGen {
+    pub async fn code_generation(
+        &self,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<CodeGeneration> {
+        let reference = self.reference.await?;
+        let resolve_type = if reference.eager
+            || matches!(
+                *chunking_context.environment().chunk_loading().await?,
+                ChunkLoading::Edge
+            ) {
+            ResolveType::ChunkItem
+        } else {
+            ResolveType::AsyncChunkLoader
+        };
+
+        let mut object = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        for (key, entry) in &*reference.map.await? {
+            let pm = PatternMapping::resolve_request(
+                *entry.request,
+                *reference.origin,
+                chunking_context,
+                *entry.result,
+                resolve_type,
+            )
+            .await?;
+            let PatternMapping::Single(pm) = &*pm else {
+                continue;
+            };
+
+            let key_expr = Expr::Lit(Lit::Str(key.as_str().into()));
+            let value = if reference.eager {
+                let namespace = match pm {
+                    SinglePatternMapping::Module(_) => quote!(
+                        "$turbopack_import($id)" as Expr,
+                        turbopack_import: Expr = TURBOPACK_IMPORT.into(),
+                        id: Expr = pm.create_id(Cow::Borrowed(&key_expr))
+                    ),
+                    _ => pm.create_require(Cow::Borrowed(&key_expr)),
+                };
+                match &reference.import {
+                    Some(name) => quote!(
+                        "$namespace[$name]" as Expr,
+                        namespace: Expr = namespace,
+                        name: Expr = Expr::Lit(Lit::Str(name.as_str().into()))
+                    ),
+                    None => namespace,
+                }
+            } else {
+                let import = pm.create_import(Cow::Borrowed(&key_expr), false);
+                match &reference.import {
+                    Some(name) => *quote_expr!(
+                        "() => $import.then((m) => m[$name])",
+                        import: Expr = import,
+                        name: Expr = Expr::Lit(Lit::Str(name.as_str().into()))
+                    ),
+                    None => *quote_expr!("() => $import", import: Expr = import),
+                }
+            };
+            object
+                .props
+                .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Str(key.as_str().into()),
+                    value: Box::new(value),
+                }))));
+        }
+
+        let visitor = create_visitor!(self.path, visit_mut_expr, |expr: &mut Expr| {
+            if let Expr::Call(_) = expr {
+                *expr = Expr::Object(object.clone());
+            }
+        });
+
+        Ok(CodeGeneration::visitors(vec![visitor]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_glob;
+    use crate::analyzer::{ConstantValue, JsValue, ObjectPart, parse_import_meta_glob};
+
+    #[test]
+    fn test_split_glob() {
+        assert_eq!(
+            split_glob("./pages/**/*.tsx"),
+            ("./pages".to_string(), "**/*.tsx".to_string())
+        );
+        assert_eq!(
+            split_glob("../shared/*/index.ts"),
+            ("../shared".to_string(), "*/index.ts".to_string())
+        );
+        assert_eq!(
+            split_glob("./config.json"),
+            (".".to_string(), "config.json".to_string())
+        );
+        assert_eq!(
+            split_glob("./{a,b}.ts"),
+            (".".to_string(), "{a,b}.ts".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_import_meta_glob() {
+        let options = parse_import_meta_glob(&[
+            JsValue::array(vec!["./pages/*.tsx".into(), "!./pages/_*.tsx".into()]),
+            JsValue::object(vec![
+                ObjectPart::KeyValue("eager".into(), JsValue::Constant(ConstantValue::True)),
+                ObjectPart::KeyValue("import".into(), "default".into()),
+                ObjectPart::KeyValue("query".into(), "raw".into()),
+            ]),
+        ])
+        .unwrap();
+        assert_eq!(options.patterns, vec!["./pages/*.tsx", "!./pages/_*.tsx"]);
+        assert!(options.eager);
+        assert_eq!(options.import.as_deref(), Some("default"));
+        assert_eq!(options.query.as_deref(), Some("?raw"));
+
+        assert!(parse_import_meta_glob(&["pages/*.tsx".into()]).is_err());
+        assert!(parse_import_meta_glob(&[JsValue::unknown_empty(true, "")]).is_err());
+    }
+}
@@ -8,6 +8,7 @@ pub mod esm;
 pub mod exports_info;
 pub mod external_module;
 pub mod ident;
+pub mod import_meta_glob;
 pub mod member;
 pub mod node;
 pub mod pattern_mapping;
@@ -108,9 +109,9 @@ use crate::{
         graph::{
             ConditionalKind, DeclUsage, Effect, EffectArg, EvalContext, VarGraph, create_graph,
         },
-        imports::{ImportAnnotations, ImportAttributes, ImportedSymbol, Reexport},
+        imports::{ImportAnnotations, ImportAttributes, ImportPhase, ImportedSymbol, Reexport},
         linker::link,
-        parse_require_context, side_effects,
+        parse_import_meta_glob, parse_require_context, side_effects,
         top_level_await::has_top_level_await,
         well_known::replace_well_known,
     },
@@ -134,10 +135,12 @@ use crate::{
         esm::{
             EsmAssetReference, EsmAsyncAssetReference, EsmBinding, EsmExports, EsmModuleItem,
             ImportMetaBinding, ImportMetaRef, UrlAssetReference, UrlRewriteBehavior,
-            base::EsmAssetReferences, export::EsmExport, module_id::EsmModuleIdAssetReference,
+            base::EsmAssetReferences, constant_export::collect_imported_binding_uses,
+            export::EsmExport, module_id::EsmModuleIdAssetReference,
         },
         exports_info::{ExportsInfoBinding, ExportsInfoRef},
         ident::IdentReplacement,
+        import_meta_glob::ImportMetaGlobAssetReference,
         member::MemberReplacement,
         node::{FilePathModuleReference, PackageJsonReference},
         raw::{DirAssetReference, FileSourceReference},
@@ -798,6 +801,12 @@ async fn analyze_ecmascript_module_internal(
         }
     }
 
+    let imported_binding_uses = if options.enable_constant_export_inlining {
+        collect_imported_binding_uses(program, &eval_context.imports)
+    } else {
+        FxHashMap::default()
+    };
+
     let span = tracing::trace_span!("esm import references");
     let import_references = async {
         let mut import_references = Vec::with_capacity(eval_context.imports.references().len());
@@ -843,6 +852,12 @@ async fn analyze_ecmascript_module_internal(
                 import_externals,
                 options.tree_shaking_mode,
             )
+            .with_phase(r.phase)
+            .with_inline_uses(
+                options
+                    .enable_constant_export_inlining
+                    .then(|| imported_binding_uses.get(&i).cloned().unwrap_or_default()),
+            )
             .resolved_cell();
 
             import_references.push(reference);
@@ -1477,7 +1492,10 @@ async fn analyze_ecmascript_module_internal(
                             // TODO move this logic into Effect creation itself and don't create new
                             // references after the fact here.
                             let original_reference = r.await?;
+                            // A deferred namespace must stay a namespace, accessing it evaluates
+                            // the module.
                             if original_reference.export_name.is_none()
+                                && original_reference.phase != ImportPhase::Defer
                                 && export.is_some()
                                 && let Some(export) = export
                             {
@@ -2188,6 +2206,38 @@ where
             );
         }
 
+        WellKnownFunctionKind::ImportMetaGlob => {
+            let args = linked_args().await?;
+            let options = match parse_import_meta_glob(args) {
+                Ok(options) => options,
+                Err(err) => {
+                    let (args, hints) = explain_args(args);
+                    handler.span_err_with_code(
+                        span,
+                        &format!(
+                            "import.meta.glob({args}) is not statically analyze-able: {}{hints}",
+                            PrettyPrintError(&err)
+                        ),
+                        DiagnosticId::Error(
+                            errors::failed_to_analyze::ecmascript::IMPORT_META_GLOB.to_string(),
+                        ),
+                    );
+                    return Ok(());
+                }
+            };
+
+            analysis.add_reference_code_gen(
+                ImportMetaGlobAssetReference::new(
+                    origin,
+                    options,
+                    Some(issue_source(source, span)),
+                    in_try,
+                )
+                .await?,
+                ast_path.to_vec().into(),
+            );
+        }
+
         WellKnownFunctionKind::FsReadMethod(name) if analysis.analyze_mode.is_tracing() => {
             let args = linked_args().await?;
             if !args.is_empty() {
@@ -0,0 +1,711 @@
+//! Code coverage instrumentation that produces counters compatible with the [Istanbul coverage
+//! format](https://github.com/istanbuljs/istanbuljs/blob/main/docs/raw-output.md), so the
+//! resulting `__coverage__` object can be consumed by `nyc`, `c8 report` or any other Istanbul
+//! reporter.
+//!
+//! Locations are looked up in the source map of the parsed file, which for TypeScript, JSX etc. is
+//! the original authored source, as Turbopack parses those directly.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Serialize;
+use swc_core::{
+    atoms::Atom,
+    common::{DUMMY_SP, SourceMap, Span, Spanned, util::take::Take},
+    ecma::{
+        ast::{
+            ArrowExpr, BinExpr, BinaryOp, BlockStmt, BlockStmtOrExpr, ClassMethod, CondExpr,
+            Constructor, Decl, DoWhileStmt, ExportDecl, Expr, ExprStmt, FnDecl, FnExpr, ForInStmt,
+            ForOfStmt, ForStmt, Function, GetterProp, Ident, IdentName, IfStmt, Lit, MethodProp,
+            ModuleDecl, ModuleItem, Pat, Program, PropName, ReturnStmt, SeqExpr, SetterProp, Stmt,
+            SwitchStmt, UpdateExpr, UpdateOp, VarDeclarator, WhileStmt,
+        },
+        utils::{ExprFactory, IsDirective, private_ident},
+        visit::{VisitMut, VisitMutWith},
+    },
+    quote,
+};
+use turbo_rcstr::RcStr;
+use turbo_tasks::ResolvedVc;
+use turbo_tasks_fs::{glob::Glob, to_sys_path};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+
+use crate::transform::TransformContext;
+
+/// Configures the [`EcmascriptInputTransform::Coverage`][super::EcmascriptInputTransform::Coverage]
+/// instrumentation.
+#[turbo_tasks::value(shared)]
+#[derive(Default, Clone, Debug)]
+pub struct CoverageOptions {
+    /// Only modules whose path matches this glob are instrumented. When `None`, all modules are
+    /// instrumented.
+    pub include: Option<ResolvedVc<Glob>>,
+    /// Modules whose path matches this glob are never instrumented, even when matching `include`.
+    /// When `None`, modules in `node_modules` are not instrumented.
+    pub exclude: Option<ResolvedVc<Glob>>,
+    /// The global variable the coverage data is collected in. Defaults to `__coverage__`, which
+    /// is where Istanbul based tooling looks for it.
+    pub coverage_variable: Option<RcStr>,
+}
+
+impl CoverageOptions {
+    /// Whether the module at `path` (relative to the root of its filesystem) should be
+    /// instrumented.
+    pub async fn matches(&self, path: &str) -> Result<bool> {
+        if let Some(include) = self.include
+            && !include.await?.matches(path)
+        {
+            return Ok(false);
+        }
+        match self.exclude {
+            Some(exclude) => Ok(!exclude.await?.matches(path)),
+            None => Ok(!path.split('/').any(|segment| segment == "node_modules")),
+        }
+    }
+}
+
+/// Value of `_coverageSchema` written by `istanbul-lib-instrument`. Reporters use it to detect
+/// coverage objects.
+const COVERAGE_SCHEMA: &str = "1a1c01bbd47fc00a2c39e90264f33305004495a9";
+
+pub(super) async fn instrument(
+    program: &mut Program,
+    ctx: &TransformContext<'_>,
+    options: &CoverageOptions,
+) -> Result<()> {
+    if !options.matches(ctx.file_path_str).await? {
+        return Ok(());
+    }
+
+    // Reporters resolve the original sources from this path, so prefer the absolute path on disk
+    let path: RcStr = match to_sys_path(ctx.file_path.clone()).await? {
+        Some(sys_path) => sys_path.to_string_lossy().into(),
+        None => ctx.file_path.to_string().into(),
+    };
+    let coverage_variable = options
+        .coverage_variable
+        .as_deref()
+        .unwrap_or("__coverage__");
+
+    let mut instrumenter = CoverageInstrumenter::new(ctx.source_map, path);
+    program.visit_mut_with(&mut instrumenter);
+    let (counters, data) = instrumenter.finish()?;
+
+    let stmt = quote!(
+        "function $cov() {
+            var global = globalThis;
+            var coverage = global[$variable] || (global[$variable] = {});
+            var data = coverage[$path];
+            if (!data || data.hash !== $hash) {
+                data = coverage[$path] = JSON.parse($data);
+            }
+            $cov = function () { return data; };
+            return data;
+        }" as Stmt,
+        cov = counters,
+        variable: Expr = Expr::Lit(coverage_variable.to_string().into()),
+        path: Expr = Expr::Lit(data.path.to_string().into()),
+        hash: Expr = Expr::Lit(data.hash.clone().into()),
+        data: Expr = Expr::Lit(serde_json::to_string(&data)?.into()),
+    );
+
+    match program {
+        Program::Module(module) => {
+            let index = module
+                .body
+                .iter()
+                .take_while(|item| match item {
+                    ModuleItem::Stmt(stmt) => stmt.directive_continue(),
+                    ModuleItem::ModuleDecl(_) => false,
+                })
+                .count();
+            module.body.insert(index, ModuleItem::Stmt(stmt));
+        }
+        Program::Script(script) => {
+            let index = script
+                .body
+                .iter()
+                .take_while(|stmt| stmt.directive_continue())
+                .count();
+            script.body.insert(index, stmt);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+#[derive(Serialize)]
+struct Location {
+    start: Position,
+    end: Position,
+}
+
+#[derive(Serialize)]
+struct FunctionMapping {
+    name: Atom,
+    decl: Location,
+    loc: Location,
+    line: usize,
+}
+
+#[derive(Serialize)]
+struct BranchMapping {
+    loc: Location,
+    #[serde(rename = "type")]
+    ty: &'static str,
+    locations: Vec<Location>,
+    line: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileCoverage {
+    path: RcStr,
+    statement_map: BTreeMap<u32, Location>,
+    fn_map: BTreeMap<u32, FunctionMapping>,
+    branch_map: BTreeMap<u32, BranchMapping>,
+    s: BTreeMap<u32, u32>,
+    f: BTreeMap<u32, u32>,
+    b: BTreeMap<u32, Vec<u32>>,
+    #[serde(rename = "_coverageSchema")]
+    coverage_schema: &'static str,
+    hash: String,
+}
+
+struct CoverageInstrumenter<'a> {
+    source_map: &'a SourceMap,
+    /// The function returning the coverage data of this module.
+    counters: Ident,
+    data: FileCoverage,
+    /// The name of the function that is visited next, taken from its declaration or the binding
+    /// it is assigned to.
+    function_name: Option<(Atom, Span)>,
+}
+
+impl<'a> CoverageInstrumenter<'a> {
+    fn new(source_map: &'a SourceMap, path: RcStr) -> Self {
+        Self {
+            source_map,
+            counters: private_ident!("__turbopack_coverage__"),
+            data: FileCoverage {
+                path,
+                statement_map: Default::default(),
+                fn_map: Default::default(),
+                branch_map: Default::default(),
+                s: Default::default(),
+                f: Default::default(),
+                b: Default::default(),
+                coverage_schema: COVERAGE_SCHEMA,
+                hash: String::new(),
+            },
+            function_name: None,
+        }
+    }
+
+    fn finish(mut self) -> Result<(Ident, FileCoverage)> {
+        // Like Istanbul, the hash identifies the coverage data layout, so that stale data of a
+        // previous version of the module is replaced instead of merged
+        self.data.hash = encode_hex(hash_xxh3_hash64(serde_json::to_string(&self.data)?));
+        Ok((self.counters, self.data))
+    }
+
+    fn location(&self, span: Span) -> Location {
+        let start = self.source_map.lookup_char_pos(span.lo);
+        let end = self.source_map.lookup_char_pos(span.hi);
+        Location {
+            start: Position {
+                line: start.line,
+                column: start.col.0,
+            },
+            end: Position {
+                line: end.line,
+                column: end.col.0,
+            },
+        }
+    }
+
+    /// Creates `cov().{counter}[index]++` or `cov().{counter}[index][branch]++`.
+    fn increment(&self, counter: &str, index: u32, branch: Option<u32>) -> Box<Expr> {
+        let mut target = Expr::from(self.counters.clone())
+            .as_call(DUMMY_SP, vec![])
+            .make_member(IdentName::new(counter.into(), DUMMY_SP))
+            .computed_member(number(index));
+        if let Some(branch) = branch {
+            target = Expr::from(target).computed_member(number(branch));
+        }
+        Box::new(Expr::Update(UpdateExpr {
+            span: DUMMY_SP,
+            op: UpdateOp::PlusPlus,
+            prefix: false,
+            arg: Box::new(target.into()),
+        }))
+    }
+
+    fn increment_stmt(&self, counter: &str, index: u32, branch: Option<u32>) -> Stmt {
+        ExprStmt {
+            span: DUMMY_SP,
+            expr: self.increment(counter, index, branch),
+        }
+        .into()
+    }
+
+    /// Registers a statement and returns its counter, or `None` if the statement isn't counted.
+    fn statement(&mut self, stmt: &Stmt) -> Option<Stmt> {
+        match stmt {
+            // Function declarations are hoisted and counted as functions instead. Blocks only
+            // contain counted statements.
+            Stmt::Decl(Decl::Fn(_)) | Stmt::Block(_) | Stmt::Empty(_) => return None,
+            Stmt::Expr(ExprStmt { expr, .. }) if matches!(&**expr, Expr::Lit(Lit::Str(_))) => {
+                return None;
+            }
+            _ => {}
+        }
+        self.statement_span(stmt.span())
+    }
+
+    fn statement_span(&mut self, span: Span) -> Option<Stmt> {
+        if span.is_dummy() {
+            return None;
+        }
+        let index = self.data.statement_map.len() as u32;
+        self.data.statement_map.insert(index, self.location(span));
+        self.data.s.insert(index, 0);
+        Some(self.increment_stmt("s", index, None))
+    }
+
+    fn function(&mut self, span: Span) -> u32 {
+        let index = self.data.fn_map.len() as u32;
+        let (name, decl) = self
+            .function_name
+            .take()
+            .unwrap_or_else(|| (format!("(anonymous_{index})").into(), span));
+        let loc = self.location(span);
+        self.data.fn_map.insert(
+            index,
+            FunctionMapping {
+                name,
+                decl: self.location(decl),
+                line: loc.start.line,
+                loc,
+            },
+        );
+        self.data.f.insert(index, 0);
+        index
+    }
+
+    fn branch(&mut self, ty: &'static str, span: Span, locations: Vec<Span>) -> u32 {
+        let index = self.data.branch_map.len() as u32;
+        let loc = self.location(span);
+        self.data.b.insert(index, vec![0; locations.len()]);
+        self.data.branch_map.insert(
+            index,
+            BranchMapping {
+                ty,
+                locations: locations.into_iter().map(|s| self.location(s)).collect(),
+                line: loc.start.line,
+                loc,
+            },
+        );
+        index
+    }
+
+    /// Registers a function, unless it has no body or was created by another transform.
+    fn function_for(&mut self, span: Span, has_body: bool) -> Option<u32> {
+        if !has_body || span.is_dummy() {
+            self.function_name = None;
+            return None;
+        }
+        Some(self.function(span))
+    }
+
+    /// Instruments the body of the function `index`, counting the function when it is entered.
+    fn function_body(&mut self, index: Option<u32>, body: &mut BlockStmt) {
+        body.visit_mut_with(self);
+        if let Some(index) = index {
+            let directives = body
+                .stmts
+                .iter()
+                .take_while(|stmt| stmt.directive_continue())
+                .count();
+            body.stmts
+                .insert(directives, self.increment_stmt("f", index, None));
+        }
+    }
+
+    /// Wraps `expr` in `(counter, expr)`.
+    fn count_expr(&self, expr: &mut Box<Expr>, index: u32, branch: u32) {
+        let seq = Expr::from(SeqExpr {
+            span: DUMMY_SP,
+            exprs: vec![self.increment("b", index, Some(branch)), expr.take()],
+        });
+        *expr = Box::new(seq.wrap_with_paren());
+    }
+}
+
+fn number(n: u32) -> Expr {
+    Expr::Lit(Lit::Num((n as f64).into()))
+}
+
+/// Wraps a single statement body (e.g. of `if (a) b();`) in a block, so counters can be inserted.
+fn ensure_block(stmt: &mut Box<Stmt>) {
+    if !stmt.is_block() {
+        let inner = (**stmt).take();
+        **stmt = BlockStmt {
+            span: inner.span(),
+            stmts: vec![inner],
+            ..Default::default()
+        }
+        .into();
+    }
+}
+
+/// Prepends a branch counter to a block created by [`ensure_block`].
+fn prepend(stmt: &mut Stmt, counter: Stmt) {
+    if let Stmt::Block(block) = stmt {
+        block.stmts.insert(0, counter);
+    }
+}
+
+fn is_logical(op: BinaryOp) -> bool {
+    matches!(
+        op,
+        BinaryOp::LogicalAnd | BinaryOp::LogicalOr | BinaryOp::NullishCoalescing
+    )
+}
+
+/// Collects the operands of a (possibly nested) logical expression. Like Istanbul, `a && b || c`
+/// is a single branch with three locations.
+fn logical_operands<'e>(expr: &'e mut Box<Expr>, operands: &mut Vec<&'e mut Box<Expr>>) {
+    if !matches!(&**expr, Expr::Bin(bin) if is_logical(bin.op)) {
+        operands.push(expr);
+    } else if let Expr::Bin(bin) = &mut **expr {
+        logical_operands(&mut bin.left, operands);
+        logical_operands(&mut bin.right, operands);
+    }
+}
+
+impl VisitMut for CoverageInstrumenter<'_> {
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        let mut new_items = Vec::with_capacity(items.len() * 2);
+        for mut item in items.take() {
+            let counter = match &item {
+                ModuleItem::Stmt(stmt) => self.statement(stmt),
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                    decl: decl @ (Decl::Var(_) | Decl::Class(_)),
+                    ..
+                })) => self.statement_span(decl.span()),
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export)) => {
+                    self.statement_span(export.span)
+                }
+                ModuleItem::ModuleDecl(_) => None,
+            };
+            item.visit_mut_with(self);
+            new_items.extend(counter.map(ModuleItem::Stmt));
+            new_items.push(item);
+        }
+        *items = new_items;
+    }
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let mut new_stmts = Vec::with_capacity(stmts.len() * 2);
+        for mut stmt in stmts.take() {
+            let counter = self.statement(&stmt);
+            stmt.visit_mut_with(self);
+            new_stmts.extend(counter);
+            new_stmts.push(stmt);
+        }
+        *stmts = new_stmts;
+    }
+
+    fn visit_mut_fn_decl(&mut self, n: &mut FnDecl) {
+        self.function_name = Some((n.ident.sym.clone(), n.ident.span));
+        n.function.visit_mut_with(self);
+    }
+
+    fn visit_mut_fn_expr(&mut self, n: &mut FnExpr) {
+        if let Some(ident) = &n.ident {
+            self.function_name = Some((ident.sym.clone(), ident.span));
+        }
+        n.function.visit_mut_with(self);
+    }
+
+    fn visit_mut_var_declarator(&mut self, n: &mut VarDeclarator) {
+        n.name.visit_mut_with(self);
+        if let Some(init) = &mut n.init {
+            if let Pat::Ident(ident) = &n.name
+                && matches!(
+                    &**init,
+                    Expr::Fn(FnExpr { ident: None, .. }) | Expr::Arrow(_)
+                )
+            {
+                self.function_name = Some((ident.sym.clone(), ident.span));
+            }
+            init.visit_mut_with(self);
+        }
+    }
+
+    fn visit_mut_method_prop(&mut self, n: &mut MethodProp) {
+        n.key.visit_mut_with(self);
+        if let PropName::Ident(ident) = &n.key {
+            self.function_name = Some((ident.sym.clone(), ident.span));
+        }
+        n.function.visit_mut_with(self);
+    }
+
+    fn visit_mut_class_method(&mut self, n: &mut ClassMethod) {
+        n.key.visit_mut_with(self);
+        if let PropName::Ident(ident) = &n.key {
+            self.function_name = Some((ident.sym.clone(), ident.span));
+        }
+        n.function.visit_mut_with(self);
+    }
+
+    fn visit_mut_function(&mut self, n: &mut Function) {
+        let index = self.function_for(n.span, n.body.is_some());
+        n.decorators.visit_mut_with(self);
+        n.params.visit_mut_with(self);
+        if let Some(body) = &mut n.body {
+            self.function_body(index, body);
+        }
+    }
+
+    fn visit_mut_constructor(&mut self, n: &mut Constructor) {
+        self.function_name = Some(("constructor".into(), n.key.span()));
+        let index = self.function_for(n.span, n.body.is_some());
+        n.params.visit_mut_with(self);
+        if let Some(body) = &mut n.body {
+            self.function_body(index, body);
+        }
+    }
+
+    fn visit_mut_getter_prop(&mut self, n: &mut GetterProp) {
+        n.key.visit_mut_with(self);
+        let index = self.function_for(n.span, n.body.is_some());
+        if let Some(body) = &mut n.body {
+            self.function_body(index, body);
+        }
+    }
+
+    fn visit_mut_setter_prop(&mut self, n: &mut SetterProp) {
+        n.key.visit_mut_with(self);
+        let index = self.function_for(n.span, n.body.is_some());
+        n.param.visit_mut_with(self);
+        if let Some(body) = &mut n.body {
+            self.function_body(index, body);
+        }
+    }
+
+    fn visit_mut_arrow_expr(&mut self, n: &mut ArrowExpr) {
+        let index = self.function_for(n.span, true);
+        n.params.visit_mut_with(self);
+        if index.is_some()
+            && let BlockStmtOrExpr::Expr(expr) = &mut *n.body
+        {
+            // Expression bodies are turned into a block, so the function counter can be inserted
+            let arg = expr.take();
+            *n.body = BlockStmtOrExpr::BlockStmt(BlockStmt {
+                span: arg.span(),
+                stmts: vec![
+                    ReturnStmt {
+                        span: arg.span(),
+                        arg: Some(arg),
+                    }
+                    .into(),
+                ],
+                ..Default::default()
+            });
+        }
+        match &mut *n.body {
+            BlockStmtOrExpr::BlockStmt(body) => self.function_body(index, body),
+            BlockStmtOrExpr::Expr(expr) => expr.visit_mut_with(self),
+        }
+    }
+
+    fn visit_mut_if_stmt(&mut self, n: &mut IfStmt) {
+        ensure_block(&mut n.cons);
+        let alt = n
+            .alt
+            .get_or_insert_with(|| Box::new(BlockStmt::default().into()));
+        ensure_block(alt);
+
+        let alt_span = if alt.span().is_dummy() {
+            n.span
+        } else {
+            alt.span()
+        };
+        let index = self.branch("if", n.span, vec![n.cons.span(), alt_span]);
+        n.test.visit_mut_with(self);
+        n.cons.visit_mut_with(self);
+        alt.visit_mut_with(self);
+        prepend(&mut n.cons, self.increment_stmt("b", index, Some(0)));
+        prepend(alt, self.increment_stmt("b", index, Some(1)));
+    }
+
+    fn visit_mut_cond_expr(&mut self, n: &mut CondExpr) {
+        n.test.visit_mut_with(self);
+        let index = self.branch("cond-expr", n.span, vec![n.cons.span(), n.alt.span()]);
+        n.cons.visit_mut_with(self);
+        n.alt.visit_mut_with(self);
+        self.count_expr(&mut n.cons, index, 0);
+        self.count_expr(&mut n.alt, index, 1);
+    }
+
+    fn visit_mut_bin_expr(&mut self, n: &mut BinExpr) {
+        if !is_logical(n.op) {
+            n.visit_mut_children_with(self);
+            return;
+        }
+        let mut operands = Vec::new();
+        logical_operands(&mut n.left, &mut operands);
+        logical_operands(&mut n.right, &mut operands);
+        let index = self.branch(
+            "binary-expr",
+            n.span,
+            operands.iter().map(|operand| operand.span()).collect(),
+        );
+        for (branch, operand) in operands.into_iter().enumerate() {
+            operand.visit_mut_with(self);
+            self.count_expr(operand, index, branch as u32);
+        }
+    }
+
+    fn visit_mut_switch_stmt(&mut self, n: &mut SwitchStmt) {
+        n.discriminant.visit_mut_with(self);
+        let index = self.branch(
+            "switch",
+            n.span,
+            n.cases.iter().map(|case| case.span).collect(),
+        );
+        for (branch, case) in n.cases.iter_mut().enumerate() {
+            case.visit_mut_with(self);
+            case.cons
+                .insert(0, self.increment_stmt("b", index, Some(branch as u32)));
+        }
+    }
+
+    fn visit_mut_for_stmt(&mut self, n: &mut ForStmt) {
+        ensure_block(&mut n.body);
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_for_in_stmt(&mut self, n: &mut ForInStmt) {
+        ensure_block(&mut n.body);
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_for_of_stmt(&mut self, n: &mut ForOfStmt) {
+        ensure_block(&mut n.body);
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_while_stmt(&mut self, n: &mut WhileStmt) {
+        ensure_block(&mut n.body);
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_do_while_stmt(&mut self, n: &mut DoWhileStmt) {
+        ensure_block(&mut n.body);
+        n.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{FileName, GLOBALS, SourceMap, sync::Lrc},
+        ecma::{
+            codegen::{Emitter, Node, text_writer::JsWriter},
+            parser::{Parser, StringInput, Syntax, lexer::Lexer},
+            visit::VisitMutWith,
+        },
+    };
+
+    use super::{CoverageInstrumenter, CoverageOptions};
+
+    fn instrument(src: &str) -> (String, serde_json::Value) {
+        GLOBALS.set(&Default::default(), || {
+            let cm: Lrc<SourceMap> = Default::default();
+            let fm = cm.new_source_file(FileName::Anon.into(), src.to_string());
+            let lexer = Lexer::new(
+                Syntax::default(),
+                Default::default(),
+                StringInput::from(&*fm),
+                None,
+            );
+            let mut program = Parser::new_from(lexer).parse_program().unwrap();
+
+            let mut instrumenter = CoverageInstrumenter::new(&cm, "/app/index.js".into());
+            program.visit_mut_with(&mut instrumenter);
+            let (_, data) = instrumenter.finish().unwrap();
+
+            let mut code = vec![];
+            program
+                .emit_with(&mut Emitter {
+                    cfg: Default::default(),
+                    cm: cm.clone(),
+                    comments: None,
+                    wr: JsWriter::new(cm.clone(), "\n", &mut code, None),
+                })
+                .unwrap();
+            (
+                String::from_utf8(code).unwrap(),
+                serde_json::to_value(&data).unwrap(),
+            )
+        })
+    }
+
+    #[test]
+    fn counts_statements_functions_and_branches() {
+        let (code, data) = instrument(
+            "function add(a, b) {\n  return a + b;\n}\nconst f = (x) => x ? 1 : 2;\nif (a && b) \
+             add(1, 2);\n",
+        );
+
+        assert_eq!(data["path"], "/app/index.js");
+        assert_eq!(data["s"].as_object().unwrap().len(), 5);
+        assert_eq!(data["fnMap"]["0"]["name"], "add");
+        assert_eq!(data["fnMap"]["1"]["name"], "f");
+        assert_eq!(
+            data["statementMap"]["0"],
+            serde_json::json!({
+                "start": { "line": 2, "column": 2 },
+                "end": { "line": 2, "column": 15 },
+            })
+        );
+
+        let branch_types = data["branchMap"]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|branch| branch["type"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(branch_types, ["cond-expr", "if", "binary-expr"]);
+        assert_eq!(data["b"]["2"], serde_json::json!([0, 0]));
+
+        assert!(code.contains("__turbopack_coverage__().f[0]++"));
+        assert!(code.contains("__turbopack_coverage__().b[1][1]++"));
+    }
+
+    #[test]
+    fn excludes_node_modules_by_default() {
+        let options = CoverageOptions::default();
+        let matches = |path: &str| {
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(options.matches(path))
+                .unwrap()
+        };
+        assert!(matches("app/page.tsx"));
+        assert!(matches("app/node_modules_utils/index.js"));
+        assert!(!matches("node_modules/react/index.js"));
+        assert!(!matches("packages/app/node_modules/lib/index.js"));
+    }
+}
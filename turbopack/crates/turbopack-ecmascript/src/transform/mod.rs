@@ -1,3 +1,5 @@
+mod coverage;
+
 use std::{fmt::Debug, hash::Hash, sync::Arc};
 
 use anyhow::Result;
@@ -25,6 +27,7 @@ use turbo_tasks::{ResolvedVc, Vc};
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{environment::Environment, source::Source};
 
+pub use self::coverage::CoverageOptions;
 use crate::runtime_functions::{TURBOPACK_MODULE, TURBOPACK_REFRESH};
 
 #[turbo_tasks::value]
@@ -51,6 +54,8 @@ pub enum EcmascriptInputTransform {
         emit_decorators_metadata: bool,
         use_define_for_class_fields: bool,
     },
+    /// Instruments the module with Istanbul compatible code coverage counters.
+    Coverage(ResolvedVc<CoverageOptions>),
 }
 
 /// The CustomTransformer trait allows you to implement your own custom SWC
@@ -255,6 +260,10 @@ impl EcmascriptInputTransform {
 
                 apply_transform(program, helpers, decorators(config))
             }
+            EcmascriptInputTransform::Coverage(options) => {
+                coverage::instrument(program, ctx, &*options.await?).await?;
+                helpers
+            }
             EcmascriptInputTransform::Plugin(transform) => {
                 // We cannot pass helpers to plugins, so we return them as is
                 transform.await?.transform(program, ctx).await?;
@@ -2,3 +2,4 @@
 #![feature(arbitrary_self_types_pointers)]
 
 pub mod process;
+pub mod svg_source;
@@ -1,3 +1,4 @@
+pub mod persistent_cache;
 pub mod svg;
 
 use std::{io::Cursor, str::FromStr};
@@ -28,7 +29,10 @@ use turbopack_core::{
     source::Source,
 };
 
-use self::svg::calculate;
+use self::svg::{
+    calculate,
+    minify::{SvgMinifyOptions, minify},
+};
 
 /// Small placeholder version of the image.
 #[derive(PartialEq, Eq, TraceRawVcs, ValueDebugFormat, NonLocalValue, Encode, Decode)]
@@ -199,29 +203,18 @@ fn load_image_internal(
     Ok((ImageBuffer::Decoded(image), format))
 }
 
+/// Computes a blur placeholder. Emits an issue and returns `None` when that fails.
 fn compute_blur_data(
     path: ResolvedVc<Box<dyn Source>>,
     image: image::DynamicImage,
     format: ImageFormat,
     options: &BlurPlaceholderOptions,
 ) -> Option<BlurPlaceholder> {
-    match compute_blur_data_internal(image, format, options)
-        .context("unable to compute blur placeholder")
-    {
-        Ok(r) => Some(r),
-        Err(err) => {
-            ImageProcessingIssue {
-                source: IssueSource::from_source_only(path),
-                message: StyledString::Text(format!("{}", PrettyPrintError(&err)).into())
-                    .resolved_cell(),
-                issue_severity: None,
-                title: None,
-            }
-            .resolved_cell()
-            .emit();
-            Some(BlurPlaceholder::fallback())
-        }
-    }
+    result_to_issue(
+        path,
+        compute_blur_data_internal(image, format, options)
+            .context("unable to compute blur placeholder"),
+    )
 }
 
 fn encode_image(image: DynamicImage, format: ImageFormat, quality: u8) -> Result<(Vec<u8>, Mime)> {
@@ -372,6 +365,15 @@ pub async fn get_meta_data(
         }
         .cell());
     }
+    let blur_placeholder = match blur_placeholder {
+        Some(blur_placeholder) => Some(blur_placeholder.await?),
+        None => None,
+    };
+    let cache_key = persistent_cache::cache_key(&bytes, extension, blur_placeholder.as_deref());
+    if let Some(meta_data) = persistent_cache::read(&cache_key).await {
+        return Ok(meta_data.cell());
+    }
+
     let Some((image_buffer, format)) = load_image(image, &bytes, extension) else {
         return Ok(ImageMetaData::fallback_value(None).cell());
     };
@@ -380,6 +382,9 @@ pub async fn get_meta_data(
         ImageBuffer::Raw(..) => Ok(ImageMetaData::fallback_value(None).cell()),
         ImageBuffer::Decoded(image_data) => {
             let (width, height) = image_data.dimensions();
+            // Results with fallback values are not cached, so the issue is reported again on
+            // the next cold start.
+            let mut cacheable = true;
             let blur_placeholder = if let Some(blur_placeholder) = blur_placeholder {
                 if matches!(
                     format,
@@ -389,12 +394,10 @@ pub async fn get_meta_data(
                         | Some(ImageFormat::WebP)
                         | Some(ImageFormat::Avif)
                 ) {
-                    compute_blur_data(
-                        image,
-                        image_data,
-                        format.unwrap(),
-                        &*blur_placeholder.await?,
-                    )
+                    let blur_data =
+                        compute_blur_data(image, image_data, format.unwrap(), &blur_placeholder);
+                    cacheable = blur_data.is_some();
+                    Some(blur_data.unwrap_or_else(BlurPlaceholder::fallback))
                 } else {
                     None
                 }
@@ -402,7 +405,7 @@ pub async fn get_meta_data(
                 None
             };
 
-            Ok(ImageMetaData {
+            let meta_data = ImageMetaData {
                 width,
                 height,
                 mime_type: if let Some(format) = format {
@@ -411,8 +414,11 @@ pub async fn get_meta_data(
                     None
                 },
                 blur_placeholder,
+            };
+            if cacheable {
+                persistent_cache::write(&cache_key, &meta_data).await;
             }
-            .cell())
+            Ok(meta_data.cell())
         }
     }
 }
@@ -482,6 +488,32 @@ pub async fn optimize(
     }
 }
 
+/// Minifies SVG markup. When the markup can't be parsed an issue is emitted and the content is
+/// returned unchanged.
+#[turbo_tasks::function]
+pub async fn optimize_svg(
+    source: ResolvedVc<Box<dyn Source>>,
+    content: ResolvedVc<FileContent>,
+) -> Result<Vc<FileContent>> {
+    let FileContent::Content(file) = &*content.await? else {
+        return Ok(*content);
+    };
+    let bytes = file.content().to_bytes();
+    let Some(svg) = result_to_issue(
+        source,
+        std::str::from_utf8(&bytes).context("Input image is not valid utf-8"),
+    ) else {
+        return Ok(*content);
+    };
+    let Some(minified) = result_to_issue(
+        source,
+        minify(svg, &SvgMinifyOptions::default()).context("Failed to optimize svg source code"),
+    ) else {
+        return Ok(*content);
+    };
+    Ok(FileContent::Content(File::from(minified).with_content_type(mime::IMAGE_SVG)).cell())
+}
+
 #[turbo_tasks::value]
 struct ImageProcessingIssue {
     message: ResolvedVc<StyledString>,
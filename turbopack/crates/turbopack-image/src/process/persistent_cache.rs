@@ -0,0 +1,207 @@
+//! A persistent on-disk cache for image processing results.
+//!
+//! Decoding images to compute their dimensions and blur placeholders dominates cold starts of
+//! image heavy applications. The results only depend on the image content and the processing
+//! options, so they are stored keyed by a content hash. The cache lives in its own directory and is
+//! versioned by [CACHE_VERSION] only, which means it survives compiler upgrades and invalidations
+//! of the turbo-tasks cache.
+
+use std::{path::PathBuf, str::FromStr, sync::Arc};
+
+use mime::Mime;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::TurboTasksApi;
+use turbo_tasks_fs::persistent_cache::{CacheKeyHasher, PersistentCache};
+
+use super::{BlurPlaceholder, BlurPlaceholderOptions, ImageMetaData};
+
+/// Needs to be increased when the format of cache entries or the way results are computed
+/// changes.
+const CACHE_VERSION: u32 = 2;
+
+/// Entries are small, mostly blur placeholders of a few hundred bytes.
+const MAX_CACHE_SIZE: u64 = 64 * 1024 * 1024;
+
+static CACHE: PersistentCache = PersistentCache::new(CACHE_VERSION, MAX_CACHE_SIZE);
+
+/// Enables the persistent image cache in the given directory for the tasks executed by
+/// `turbo_tasks`.
+pub fn init_persistent_image_cache(
+    turbo_tasks: &Arc<dyn TurboTasksApi>,
+    directory: impl Into<PathBuf>,
+) {
+    CACHE.init(turbo_tasks, directory);
+}
+
+/// Computes the cache key for an image with the given content and processing options.
+pub(super) fn cache_key(
+    bytes: &[u8],
+    extension: &str,
+    blur_placeholder: Option<&BlurPlaceholderOptions>,
+) -> String {
+    let mut hasher = CacheKeyHasher::new();
+    hasher.write(bytes);
+    hasher.write(extension);
+    match blur_placeholder {
+        Some(options) => {
+            hasher.write([1, options.quality]);
+            hasher.write(options.size.to_le_bytes());
+        }
+        None => hasher.write([0]),
+    }
+    hasher.finish()
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    width: u32,
+    height: u32,
+    mime_type: Option<String>,
+    blur_placeholder: Option<CachedBlurPlaceholder>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedBlurPlaceholder {
+    data_url: String,
+    width: u32,
+    height: u32,
+}
+
+/// Reads a cached result. Missing, unreadable and corrupted entries are treated as cache misses.
+pub(super) async fn read(key: &str) -> Option<ImageMetaData> {
+    decode(&CACHE.read(key).await?)
+}
+
+/// Stores a result in the cache. Writing is best effort, failures are ignored since the result
+/// can always be recomputed.
+pub(super) async fn write(key: &str, meta_data: &ImageMetaData) {
+    if let Some(content) = encode(meta_data) {
+        CACHE.write(key, content).await;
+    }
+}
+
+fn decode(content: &[u8]) -> Option<ImageMetaData> {
+    let entry: CacheEntry = serde_json::from_slice(content).ok()?;
+    let mime_type = match entry.mime_type {
+        Some(mime_type) => Some(Mime::from_str(&mime_type).ok()?),
+        None => None,
+    };
+    Some(ImageMetaData {
+        width: entry.width,
+        height: entry.height,
+        mime_type,
+        blur_placeholder: entry
+            .blur_placeholder
+            .map(|blur_placeholder| BlurPlaceholder {
+                data_url: blur_placeholder.data_url,
+                width: blur_placeholder.width,
+                height: blur_placeholder.height,
+            }),
+    })
+}
+
+fn encode(meta_data: &ImageMetaData) -> Option<Vec<u8>> {
+    let entry = CacheEntry {
+        width: meta_data.width,
+        height: meta_data.height,
+        mime_type: meta_data.mime_type.as_ref().map(|mime| mime.to_string()),
+        blur_placeholder: meta_data.blur_placeholder.as_ref().map(|blur_placeholder| {
+            CachedBlurPlaceholder {
+                data_url: blur_placeholder.data_url.clone(),
+                width: blur_placeholder.width,
+                height: blur_placeholder.height,
+            }
+        }),
+    };
+    serde_json::to_vec(&entry).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_backend::{BackendOptions, TurboTasksBackend, noop_backing_storage};
+
+    use super::*;
+
+    #[test]
+    fn test_cache_key() {
+        let options = BlurPlaceholderOptions {
+            quality: 70,
+            size: 8,
+        };
+        let key = cache_key(b"image", "png", Some(&options));
+        assert_eq!(key.len(), 64);
+        assert_eq!(key, cache_key(b"image", "png", Some(&options)));
+        assert_ne!(
+            cache_key(b"imagep", "ng", None),
+            cache_key(b"image", "png", None)
+        );
+        assert_ne!(key, cache_key(b"image", "png", None));
+        assert_ne!(key, cache_key(b"image", "jpg", Some(&options)));
+        assert_ne!(key, cache_key(b"other", "png", Some(&options)));
+        assert_ne!(
+            key,
+            cache_key(
+                b"image",
+                "png",
+                Some(&BlurPlaceholderOptions {
+                    quality: 80,
+                    size: 8,
+                })
+            )
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_write_and_read() {
+        let directory = tempfile::tempdir().unwrap();
+        let tt = TurboTasks::new(TurboTasksBackend::new(
+            BackendOptions::default(),
+            noop_backing_storage(),
+        ));
+        init_persistent_image_cache(&(tt.clone() as Arc<dyn TurboTasksApi>), directory.path());
+        let key = cache_key(b"image", "png", None);
+        let entry_path = directory
+            .path()
+            .join(format!("v{CACHE_VERSION}"))
+            .join(&key[..2])
+            .join(&key);
+
+        tt.run_once(async move {
+            assert!(read(&key).await.is_none());
+
+            let meta_data = ImageMetaData {
+                width: 16,
+                height: 9,
+                mime_type: Some(mime::IMAGE_PNG),
+                blur_placeholder: Some(BlurPlaceholder {
+                    data_url: "data:image/png;base64,AAAA".to_string(),
+                    width: 8,
+                    height: 5,
+                }),
+            };
+            write(&key, &meta_data).await;
+            let cached = read(&key).await.unwrap();
+            assert_eq!((cached.width, cached.height), (16, 9));
+            assert_eq!(cached.mime_type, Some(mime::IMAGE_PNG));
+            let blur_placeholder = cached.blur_placeholder.unwrap();
+            assert_eq!(blur_placeholder.data_url, "data:image/png;base64,AAAA");
+            assert_eq!((blur_placeholder.width, blur_placeholder.height), (8, 5));
+
+            // Corrupted entries are cache misses.
+            std::fs::write(&entry_path, b"{\"width\":16,")?;
+            assert!(read(&key).await.is_none());
+            std::fs::write(
+                &entry_path,
+                br#"{"width":16,"height":9,"mimeType":"not a mime type","blurPlaceholder":null}"#,
+            )?;
+            assert!(read(&key).await.is_none());
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,243 @@
+//! Generates JavaScript code for SVG modules: data urls and React components.
+
+use std::fmt::Write;
+
+use anyhow::Result;
+
+use super::minify::{SvgElement, SvgNode, parse};
+
+/// Encodes SVG markup as a `data:` url. Percent encoding is only applied to characters that need
+/// it, which results in smaller urls than base64 encoding for typical markup.
+pub fn svg_data_url(svg: &str) -> String {
+    let mut result = String::with_capacity(svg.len() + 19);
+    result.push_str("data:image/svg+xml,");
+    for c in svg.chars() {
+        match c {
+            'A'..='Z'
+            | 'a'..='z'
+            | '0'..='9'
+            | '-'
+            | '_'
+            | '.'
+            | '!'
+            | '~'
+            | '*'
+            | '\''
+            | '('
+            | ')'
+            | '/'
+            | ':'
+            | ';'
+            | '='
+            | ','
+            | '+'
+            | '?'
+            | '@'
+            | '&'
+            | '$'
+            | '['
+            | ']' => result.push(c),
+            ' ' => result.push_str("%20"),
+            c => {
+                let mut buf = [0; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    let _ = write!(result, "%{byte:02X}");
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Generates an ES module exporting a React component that renders the given SVG markup. Props
+/// passed to the component are spread onto the root `<svg>` element.
+pub fn svg_component_module(svg: &str) -> Result<String> {
+    let root = parse(svg)?;
+    let mut code = String::new();
+    writeln!(code, "import {{ createElement }} from \"react\";")?;
+    write!(code, "const SvgComponent = (props) => ")?;
+    write_element(&mut code, &root, true)?;
+    writeln!(code, ";")?;
+    writeln!(code, "export default SvgComponent;")?;
+    Ok(code)
+}
+
+fn write_element(code: &mut String, element: &SvgElement, is_root: bool) -> Result<()> {
+    write!(code, "createElement({}, {{", stringify(&element.name))?;
+    for (index, (name, value)) in element.attributes.iter().enumerate() {
+        if index > 0 {
+            code.push_str(", ");
+        }
+        let value = decode_entities(value);
+        if name == "style" {
+            write!(code, "style: ")?;
+            write_style_object(code, &value);
+        } else {
+            write!(
+                code,
+                "{}: {}",
+                stringify(&jsx_attribute_name(name)),
+                stringify(&value)
+            )?;
+        }
+    }
+    if is_root {
+        if !element.attributes.is_empty() {
+            code.push_str(", ");
+        }
+        code.push_str("...props");
+    }
+    code.push('}');
+    for child in &element.children {
+        code.push_str(", ");
+        match child {
+            SvgNode::Element(child) => write_element(code, child, false)?,
+            SvgNode::Text(text) => code.push_str(&stringify(&decode_entities(text))),
+            SvgNode::CData(data) => code.push_str(&stringify(data)),
+        }
+    }
+    code.push(')');
+    Ok(())
+}
+
+fn write_style_object(code: &mut String, style: &str) {
+    code.push('{');
+    let declarations = style.split(';').filter_map(|declaration| {
+        let (property, value) = declaration.split_once(':')?;
+        let property = property.trim();
+        (!property.is_empty()).then(|| (property, value.trim()))
+    });
+    for (index, (property, value)) in declarations.enumerate() {
+        if index > 0 {
+            code.push_str(", ");
+        }
+        // Custom properties are case sensitive and keep their name.
+        let property = if property.starts_with("--") {
+            property.to_string()
+        } else {
+            camel_case(&property.to_ascii_lowercase(), '-')
+        };
+        let _ = write!(code, "{}: {}", stringify(&property), stringify(value));
+    }
+    code.push('}');
+}
+
+/// Converts an SVG attribute name into the prop name React expects.
+fn jsx_attribute_name(name: &str) -> String {
+    match name {
+        "class" => "className".to_string(),
+        "for" => "htmlFor".to_string(),
+        _ if name.starts_with("data-") || name.starts_with("aria-") => name.to_string(),
+        _ => camel_case(&camel_case(name, ':'), '-'),
+    }
+}
+
+fn camel_case(name: &str, separator: char) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut uppercase_next = false;
+    for c in name.chars() {
+        if c == separator {
+            uppercase_next = true;
+        } else if uppercase_next {
+            result.push(c.to_ascii_uppercase());
+            uppercase_next = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Decodes the predefined XML entities and numeric character references.
+fn decode_entities(value: &str) -> String {
+    if !value.contains('&') {
+        return value.to_string();
+    }
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(index) = rest.find('&') {
+        result.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                _ => {
+                    let code = if let Some(hex) = entity
+                        .strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"))
+                    {
+                        u32::from_str_radix(hex, 16).ok()?
+                    } else {
+                        entity.strip_prefix('#')?.parse().ok()?
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end + 1))
+        });
+        if let Some((c, len)) = decoded {
+            result.push(c);
+            rest = &rest[len..];
+        } else {
+            result.push('&');
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn stringify(value: &str) -> String {
+    serde_json::to_string(value).expect("serializing a string can't fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_entities, jsx_attribute_name, svg_component_module, svg_data_url};
+
+    #[test]
+    fn test_svg_data_url() {
+        assert_eq!(
+            svg_data_url(r##"<svg viewBox="0 0 1 1"><path fill="#fff"/></svg>"##),
+            "data:image/svg+xml,%3Csvg%20viewBox=%220%200%201%201%22%3E%3Cpath%20fill=%22%23fff%\
+             22/%3E%3C/svg%3E"
+        );
+    }
+
+    #[test]
+    fn test_jsx_attribute_name() {
+        assert_eq!(jsx_attribute_name("class"), "className");
+        assert_eq!(jsx_attribute_name("stroke-width"), "strokeWidth");
+        assert_eq!(jsx_attribute_name("xlink:href"), "xlinkHref");
+        assert_eq!(jsx_attribute_name("xmlns:xlink"), "xmlnsXlink");
+        assert_eq!(jsx_attribute_name("data-name"), "data-name");
+        assert_eq!(jsx_attribute_name("viewBox"), "viewBox");
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("a &amp; b"), "a & b");
+        assert_eq!(decode_entities("&#65;&#x42;"), "AB");
+        assert_eq!(decode_entities("&unknown; &"), "&unknown; &");
+    }
+
+    #[test]
+    fn test_svg_component_module() {
+        let code = svg_component_module(
+            r#"<svg viewBox="0 0 10 10" class="icon"><path stroke-width="2" style="fill-opacity: .5; --c: red" d="M0 0"/><text>A &amp; B</text></svg>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            code,
+            r#"import { createElement } from "react";
+const SvgComponent = (props) => createElement("svg", {"viewBox": "0 0 10 10", "className": "icon", ...props}, createElement("path", {"strokeWidth": "2", style: {"fillOpacity": ".5", "--c": "red"}, "d": "M0 0"}), createElement("text", {}, "A & B"));
+export default SvgComponent;
+"#
+        );
+    }
+}
@@ -0,0 +1,692 @@
+//! A small SVG minifier modeled after the safe subset of svgo's default preset.
+//!
+//! The markup is parsed into a lightweight element tree, which is then cleaned up (comments,
+//! prolog, metadata and editor data are removed, redundant groups are collapsed and path data is
+//! shortened) and serialized again. Text content and attribute values are kept verbatim, including
+//! their entity references.
+
+use std::fmt::Write;
+
+use anyhow::{Context, Result, bail};
+
+/// Elements whose text content is significant and must not have whitespace removed.
+const TEXT_CONTENT_ELEMENTS: &[&str] = &[
+    "text", "tspan", "textPath", "style", "script", "title", "desc",
+];
+
+/// Namespace prefixes used by SVG editors to store their own data.
+const EDITOR_NAMESPACES: &[&str] = &["inkscape", "sodipodi", "sketch", "serif", "figma"];
+
+/// Attributes which can't be moved from a group to its only child without changing the
+/// rendering result or the behavior of selectors.
+const NON_COLLAPSIBLE_GROUP_ATTRIBUTES: &[&str] =
+    &["id", "class", "style", "clip-path", "mask", "filter"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvgMinifyOptions {
+    /// The number of decimal places numbers in path data and point lists are rounded to.
+    pub float_precision: u8,
+    /// Whether `<title>` and `<desc>` elements are removed. They are kept by default since they
+    /// are used by assistive technologies.
+    pub remove_descriptions: bool,
+}
+
+impl Default for SvgMinifyOptions {
+    fn default() -> Self {
+        SvgMinifyOptions {
+            float_precision: 3,
+            remove_descriptions: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SvgNode {
+    Element(SvgElement),
+    Text(String),
+    CData(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgElement {
+    pub name: String,
+    /// Attribute names and their raw (still entity encoded) values in source order.
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<SvgNode>,
+}
+
+impl SvgElement {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn has_attribute(&self, name: &str) -> bool {
+        self.attributes.iter().any(|(n, _)| n == name)
+    }
+}
+
+/// Minifies the given SVG markup.
+pub fn minify(content: &str, options: &SvgMinifyOptions) -> Result<String> {
+    let mut root = parse(content)?;
+    clean_element(&mut root, options);
+    remove_unused_xlink_namespace(&mut root);
+    let mut result = String::with_capacity(content.len());
+    serialize(&root, &mut result);
+    Ok(result)
+}
+
+/// Parses SVG markup into an element tree. Comments, processing instructions and the doctype are
+/// skipped. Fails when the root element is not an `<svg>` element.
+pub fn parse(content: &str) -> Result<SvgElement> {
+    let mut parser = Parser {
+        input: content,
+        pos: 0,
+    };
+    let root = loop {
+        parser.skip_whitespace();
+        if parser.is_eof() {
+            bail!("Source code does not contain a <svg> root element");
+        } else if parser.eat("<?") {
+            parser.take_until("?>")?;
+        } else if parser.eat("<!--") {
+            parser.take_until("-->")?;
+        } else if parser.eat("<!") {
+            parser.skip_doctype()?;
+        } else if parser.rest().starts_with('<') {
+            break parser.parse_element()?;
+        } else {
+            bail!("Unexpected text content before the root element");
+        }
+    };
+    if root.name != "svg" {
+        bail!("Expected a <svg> root element, found <{}>", root.name);
+    }
+    Ok(root)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn is_eof(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Returns everything up to `end` and advances past `end`.
+    fn take_until(&mut self, end: &str) -> Result<&'a str> {
+        let rest = self.rest();
+        let index = rest
+            .find(end)
+            .with_context(|| format!("Unexpected end of input, expected {end:?}"))?;
+        self.pos += index + end.len();
+        Ok(&rest[..index])
+    }
+
+    fn skip_doctype(&mut self) -> Result<()> {
+        // The doctype might contain an internal subset in brackets, which can contain `>`.
+        let mut depth = 0usize;
+        for (index, c) in self.rest().char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth = depth.saturating_sub(1),
+                '>' if depth == 0 => {
+                    self.pos += index + 1;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        bail!("Unexpected end of input in doctype declaration")
+    }
+
+    fn parse_name(&mut self) -> Result<&'a str> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '='))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            bail!("Expected a name at offset {}", self.pos);
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn parse_element(&mut self) -> Result<SvgElement> {
+        self.eat("<");
+        let name = self.parse_name()?.to_string();
+        let mut attributes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.eat("/>") {
+                return Ok(SvgElement {
+                    name,
+                    attributes,
+                    children: Vec::new(),
+                });
+            }
+            if self.eat(">") {
+                break;
+            }
+            if self.is_eof() {
+                bail!("Unexpected end of input in <{name}>");
+            }
+            let attr_name = self.parse_name()?.to_string();
+            self.skip_whitespace();
+            let value = if self.eat("=") {
+                self.skip_whitespace();
+                if self.eat("\"") {
+                    self.take_until("\"")?
+                } else if self.eat("'") {
+                    self.take_until("'")?
+                } else {
+                    bail!("Expected a quoted value for attribute {attr_name} of <{name}>");
+                }
+            } else {
+                ""
+            };
+            attributes.push((attr_name, value.to_string()));
+        }
+
+        let mut children = Vec::new();
+        loop {
+            if self.is_eof() {
+                bail!("Unexpected end of input, <{name}> is not closed");
+            }
+            if self.eat("</") {
+                let closing = self.parse_name()?;
+                if closing != name {
+                    bail!("Mismatched closing tag </{closing}>, expected </{name}>");
+                }
+                self.skip_whitespace();
+                if !self.eat(">") {
+                    bail!("Expected > after </{closing}");
+                }
+                return Ok(SvgElement {
+                    name,
+                    attributes,
+                    children,
+                });
+            } else if self.eat("<!--") {
+                self.take_until("-->")?;
+            } else if self.eat("<![CDATA[") {
+                children.push(SvgNode::CData(self.take_until("]]>")?.to_string()));
+            } else if self.eat("<?") {
+                self.take_until("?>")?;
+            } else if self.rest().starts_with('<') {
+                children.push(SvgNode::Element(self.parse_element()?));
+            } else {
+                let rest = self.rest();
+                let len = rest.find('<').unwrap_or(rest.len());
+                self.pos += len;
+                children.push(SvgNode::Text(rest[..len].to_string()));
+            }
+        }
+    }
+}
+
+fn is_editor_name(name: &str) -> bool {
+    let prefix = name.strip_prefix("xmlns:").unwrap_or(name);
+    let prefix = prefix.split(':').next().unwrap_or(prefix);
+    (name.contains(':') || name.starts_with("xmlns:")) && EDITOR_NAMESPACES.contains(&prefix)
+}
+
+fn is_removable_element(element: &SvgElement, options: &SvgMinifyOptions) -> bool {
+    match element.name.as_str() {
+        "metadata" => true,
+        "title" | "desc" => options.remove_descriptions,
+        "g" | "defs" => element.attributes.is_empty() && element.children.is_empty(),
+        name => is_editor_name(name),
+    }
+}
+
+fn clean_element(element: &mut SvgElement, options: &SvgMinifyOptions) {
+    element.attributes.retain(|(name, _)| !is_editor_name(name));
+
+    match element.name.as_str() {
+        "path" => {
+            for (name, value) in element.attributes.iter_mut() {
+                if name == "d"
+                    && let Some(minified) = minify_path_data(value, options.float_precision)
+                {
+                    *value = minified;
+                }
+            }
+        }
+        "polygon" | "polyline" => {
+            for (name, value) in element.attributes.iter_mut() {
+                if name == "points"
+                    && let Some(minified) = minify_number_list(value, options.float_precision)
+                {
+                    *value = minified;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let preserve_text = TEXT_CONTENT_ELEMENTS.contains(&element.name.as_str());
+    let children = std::mem::take(&mut element.children);
+    for child in children {
+        match child {
+            SvgNode::Element(mut child) => {
+                clean_element(&mut child, options);
+                if is_removable_element(&child, options) {
+                    continue;
+                }
+                if child.name == "g" {
+                    if child.attributes.is_empty() {
+                        element.children.append(&mut child.children);
+                        continue;
+                    }
+                    if let Some(collapsed) = collapse_group(&mut child) {
+                        element.children.push(SvgNode::Element(collapsed));
+                        continue;
+                    }
+                }
+                element.children.push(SvgNode::Element(child));
+            }
+            SvgNode::Text(text) => {
+                if preserve_text || !text.trim().is_empty() {
+                    element.children.push(SvgNode::Text(text));
+                }
+            }
+            SvgNode::CData(data) => element.children.push(SvgNode::CData(data)),
+        }
+    }
+}
+
+/// Moves the attributes of a group with a single child element onto that child. Returns the
+/// child when that was possible.
+fn collapse_group(group: &mut SvgElement) -> Option<SvgElement> {
+    let [SvgNode::Element(child)] = group.children.as_slice() else {
+        return None;
+    };
+    if child.has_attribute("id")
+        || group
+            .attributes
+            .iter()
+            .any(|(name, _)| NON_COLLAPSIBLE_GROUP_ATTRIBUTES.contains(&name.as_str()))
+        || group
+            .attributes
+            .iter()
+            .any(|(name, _)| name != "transform" && child.has_attribute(name))
+    {
+        return None;
+    }
+    let Some(SvgNode::Element(mut child)) = group.children.pop() else {
+        unreachable!();
+    };
+    let mut attributes = std::mem::take(&mut group.attributes);
+    for (name, value) in attributes.iter_mut() {
+        if name == "transform"
+            && let Some(index) = child.attributes.iter().position(|(n, _)| n == "transform")
+        {
+            let (_, child_transform) = child.attributes.remove(index);
+            *value = format!("{value} {child_transform}");
+        }
+    }
+    attributes.append(&mut child.attributes);
+    child.attributes = attributes;
+    Some(child)
+}
+
+fn remove_unused_xlink_namespace(root: &mut SvgElement) {
+    fn uses_xlink(element: &SvgElement) -> bool {
+        element
+            .attributes
+            .iter()
+            .any(|(name, _)| name.starts_with("xlink:"))
+            || element.children.iter().any(|child| match child {
+                SvgNode::Element(child) => uses_xlink(child),
+                _ => false,
+            })
+    }
+    if !uses_xlink(root) {
+        root.attributes.retain(|(name, _)| name != "xmlns:xlink");
+    }
+}
+
+fn serialize(element: &SvgElement, out: &mut String) {
+    out.push('<');
+    out.push_str(&element.name);
+    for (name, value) in &element.attributes {
+        // Values are stored raw, single quoted values might contain double quotes.
+        let _ = write!(out, " {name}=\"{}\"", value.replace('"', "&quot;"));
+    }
+    if element.children.is_empty() {
+        out.push_str("/>");
+        return;
+    }
+    out.push('>');
+    for child in &element.children {
+        match child {
+            SvgNode::Element(child) => serialize(child, out),
+            SvgNode::Text(text) => out.push_str(text),
+            SvgNode::CData(data) => {
+                out.push_str("<![CDATA[");
+                out.push_str(data);
+                out.push_str("]]>");
+            }
+        }
+    }
+    out.push_str("</");
+    out.push_str(&element.name);
+    out.push('>');
+}
+
+/// Formats a number with at most `precision` decimal places and without redundant zeros.
+fn format_number(value: f64, precision: u8) -> String {
+    let formatted = format!("{:.*}", precision as usize, value);
+    let formatted = if formatted.contains('.') {
+        formatted.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &formatted
+    };
+    match formatted {
+        "-0" | "" => "0".to_string(),
+        _ => {
+            if let Some(rest) = formatted.strip_prefix("0.") {
+                format!(".{rest}")
+            } else if let Some(rest) = formatted.strip_prefix("-0.") {
+                format!("-.{rest}")
+            } else {
+                formatted.to_string()
+            }
+        }
+    }
+}
+
+/// Appends a number to the output, only adding a separator when it's needed to delimit it from
+/// the previous number.
+fn push_number(out: &mut String, number: &str, previous_is_number: bool) {
+    if previous_is_number {
+        let needs_separator = if number.starts_with('-') {
+            false
+        } else if number.starts_with('.') {
+            let last_number = out
+                .rsplit(|c: char| c == ' ' || c == '-' || c.is_ascii_alphabetic())
+                .next()
+                .unwrap_or_default();
+            !last_number.contains('.')
+        } else {
+            true
+        };
+        if needs_separator {
+            out.push(' ');
+        }
+    }
+    out.push_str(number);
+}
+
+/// Reads a number (`[+-]?(\d+\.?\d*|\.\d+)([eE][+-]?\d+)?`) from the start of `s` and returns it
+/// together with its length.
+fn read_number(s: &str) -> Option<(f64, usize)> {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    if matches!(bytes.first(), Some(b'+' | b'-')) {
+        i += 1;
+    }
+    let digits_start = i;
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+    }
+    let mut has_digits = i > digits_start;
+    if i < bytes.len() && bytes[i] == b'.' {
+        i += 1;
+        let fraction_start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        has_digits |= i > fraction_start;
+    }
+    if !has_digits {
+        return None;
+    }
+    if i < bytes.len() && matches!(bytes[i], b'e' | b'E') {
+        let mut j = i + 1;
+        if j < bytes.len() && matches!(bytes[j], b'+' | b'-') {
+            j += 1;
+        }
+        let exponent_start = j;
+        while j < bytes.len() && bytes[j].is_ascii_digit() {
+            j += 1;
+        }
+        if j > exponent_start {
+            i = j;
+        }
+    }
+    Some((s[..i].parse().ok()?, i))
+}
+
+fn skip_separators(s: &str, mut pos: usize) -> usize {
+    while let Some(c) = s[pos..].chars().next() {
+        if c.is_whitespace() || c == ',' {
+            pos += c.len_utf8();
+        } else {
+            break;
+        }
+    }
+    pos
+}
+
+/// Shortens path data by rounding numbers and removing redundant separators. Commands are kept
+/// as written. Returns `None` when the path data can't be parsed, in which case it should be kept
+/// unchanged.
+pub fn minify_path_data(d: &str, precision: u8) -> Option<String> {
+    let mut out = String::with_capacity(d.len());
+    let mut pos = skip_separators(d, 0);
+    let mut command = None;
+    let mut param_index = 0usize;
+    let mut previous_is_number = false;
+    while pos < d.len() {
+        let c = d[pos..].chars().next()?;
+        if c.is_ascii_alphabetic() {
+            if !"MmZzLlHhVvCcSsQqTtAa".contains(c) {
+                return None;
+            }
+            out.push(c);
+            command = Some(c);
+            param_index = 0;
+            previous_is_number = false;
+            pos += 1;
+        } else {
+            let command = command?;
+            if matches!(command, 'Z' | 'z') {
+                return None;
+            }
+            // Arc flags are single characters and might be written without separators.
+            if matches!(command, 'A' | 'a') && matches!(param_index % 7, 3 | 4) {
+                if c != '0' && c != '1' {
+                    return None;
+                }
+                push_number(
+                    &mut out,
+                    if c == '0' { "0" } else { "1" },
+                    previous_is_number,
+                );
+                pos += 1;
+            } else {
+                let (value, len) = read_number(&d[pos..])?;
+                push_number(
+                    &mut out,
+                    &format_number(value, precision),
+                    previous_is_number,
+                );
+                pos += len;
+            }
+            param_index += 1;
+            previous_is_number = true;
+        }
+        pos = skip_separators(d, pos);
+    }
+    Some(out)
+}
+
+/// Shortens a list of numbers like the `points` attribute of `<polygon>`.
+pub fn minify_number_list(list: &str, precision: u8) -> Option<String> {
+    let mut out = String::with_capacity(list.len());
+    let mut pos = skip_separators(list, 0);
+    let mut previous_is_number = false;
+    while pos < list.len() {
+        let (value, len) = read_number(&list[pos..])?;
+        push_number(
+            &mut out,
+            &format_number(value, precision),
+            previous_is_number,
+        );
+        previous_is_number = true;
+        pos = skip_separators(list, pos + len);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SvgMinifyOptions, format_number, minify, minify_number_list, minify_path_data};
+
+    fn minify_default(svg: &str) -> String {
+        minify(svg, &SvgMinifyOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(0.5, 3), ".5");
+        assert_eq!(format_number(-0.5, 3), "-.5");
+        assert_eq!(format_number(10.0, 3), "10");
+        assert_eq!(format_number(1.23456, 3), "1.235");
+        assert_eq!(format_number(-0.0001, 3), "0");
+        assert_eq!(format_number(1e3, 3), "1000");
+    }
+
+    #[test]
+    fn test_minify_path_data() {
+        assert_eq!(
+            minify_path_data("M 10.000 20.500 L 30, -40.25 Z", 3).unwrap(),
+            "M10 20.5L30-40.25Z"
+        );
+        assert_eq!(
+            minify_path_data("M0.5 0.5 l 0.25 0.125", 3).unwrap(),
+            "M.5.5l.25.125"
+        );
+        assert_eq!(
+            minify_path_data("M1 1 a 5 5 0 0 1 10 10", 3).unwrap(),
+            "M1 1a5 5 0 0 1 10 10"
+        );
+        assert_eq!(
+            minify_path_data("M1 1a5 5 0 0110 10", 3).unwrap(),
+            "M1 1a5 5 0 0 1 10 10"
+        );
+        assert_eq!(minify_path_data("M1e2 1.23456", 2).unwrap(), "M100 1.23");
+        assert!(minify_path_data("M 1 1 X 2 2", 3).is_none());
+    }
+
+    #[test]
+    fn test_minify_number_list() {
+        assert_eq!(
+            minify_number_list("0,0 10.0,0 0.5,-0.5", 3).unwrap(),
+            "0 0 10 0 .5-.5"
+        );
+    }
+
+    #[test]
+    fn test_remove_metadata_and_comments() {
+        let svg = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Generator: Some Editor -->
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" inkscape:version="1.0" viewBox="0 0 10 10">
+  <metadata><rdf:RDF/></metadata>
+  <title>Icon</title>
+  <sodipodi:namedview/>
+  <rect width="10" height="10"/>
+</svg>"#;
+        assert_eq!(
+            minify_default(svg),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><title>Icon</title><rect width="10" height="10"/></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_remove_descriptions() {
+        let svg = r#"<svg><title>Icon</title><desc>An icon</desc><rect/></svg>"#;
+        assert_eq!(
+            minify(
+                svg,
+                &SvgMinifyOptions {
+                    remove_descriptions: true,
+                    ..Default::default()
+                }
+            )
+            .unwrap(),
+            "<svg><rect/></svg>"
+        );
+    }
+
+    #[test]
+    fn test_collapse_groups() {
+        let svg = r#"<svg><g><g fill="red"><path d="M 0 0 L 1 1"/></g></g><g/></svg>"#;
+        assert_eq!(
+            minify_default(svg),
+            r#"<svg><path fill="red" d="M0 0L1 1"/></svg>"#
+        );
+
+        let svg = r#"<svg><g transform="scale(2)"><rect transform="translate(1)"/></g></svg>"#;
+        assert_eq!(
+            minify_default(svg),
+            r#"<svg><rect transform="scale(2) translate(1)"/></svg>"#
+        );
+
+        // Groups with ids or a filter have to stay.
+        let svg = r#"<svg><g id="a"><rect/></g><g filter="url(#f)"><rect/></g></svg>"#;
+        assert_eq!(minify_default(svg), svg);
+
+        // Conflicting attributes prevent collapsing.
+        let svg = r#"<svg><g fill="red"><rect fill="blue"/></g></svg>"#;
+        assert_eq!(minify_default(svg), svg);
+    }
+
+    #[test]
+    fn test_preserve_text() {
+        let svg = r#"<svg> <text> Hello <tspan>World</tspan> </text> <style><![CDATA[ .a { fill: red } ]]></style> </svg>"#;
+        assert_eq!(
+            minify_default(svg),
+            r#"<svg><text> Hello <tspan>World</tspan> </text><style><![CDATA[ .a { fill: red } ]]></style></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_keep_used_xlink_namespace() {
+        let svg =
+            r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink"><use xlink:href="#a"/></svg>"##;
+        assert_eq!(minify_default(svg), svg);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(minify("<div></div>", &SvgMinifyOptions::default()).is_err());
+        assert!(minify("<svg><g></svg>", &SvgMinifyOptions::default()).is_err());
+        assert!(minify("<svg>", &SvgMinifyOptions::default()).is_err());
+    }
+}
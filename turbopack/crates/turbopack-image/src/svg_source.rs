@@ -0,0 +1,136 @@
+use anyhow::{Result, bail};
+use bincode::{Decode, Encode};
+use serde::Deserialize;
+use turbo_rcstr::rcstr;
+use turbo_tasks::{NonLocalValue, ResolvedVc, TaskInput, Vc, trace::TraceRawVcs};
+use turbo_tasks_fs::{File, FileContent};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    source::Source,
+};
+
+use crate::process::{
+    optimize_svg,
+    svg::codegen::{svg_component_module, svg_data_url},
+};
+
+/// How an SVG file is exposed to the importing JavaScript module.
+#[derive(
+    PartialOrd,
+    Ord,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Copy,
+    Clone,
+    TaskInput,
+    TraceRawVcs,
+    NonLocalValue,
+    Encode,
+    Decode,
+    Deserialize,
+)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum SvgImportMode {
+    /// The default export is the url of the optimized SVG file.
+    Url,
+    /// The default export is a `data:` url of the optimized SVG when it's at most `max_size`
+    /// bytes large, otherwise the url of the emitted file.
+    DataUrl { max_size: u32 },
+    /// The default export is a React component rendering the optimized SVG. Props are spread onto
+    /// the root `<svg>` element.
+    Component,
+}
+
+/// The SVG source with minified markup.
+#[turbo_tasks::value]
+pub struct OptimizedSvgSource {
+    source: ResolvedVc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value_impl]
+impl OptimizedSvgSource {
+    #[turbo_tasks::function]
+    pub fn new(source: ResolvedVc<Box<dyn Source>>) -> Vc<Self> {
+        Self::cell(OptimizedSvgSource { source })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Source for OptimizedSvgSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source.ident().with_modifier(rcstr!("optimized svg"))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for OptimizedSvgSource {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        AssetContent::file(optimize_svg(
+            *self.source,
+            self.source.content().file_content(),
+        ))
+    }
+}
+
+/// A source that transforms an SVG file into JavaScript code according to the [SvgImportMode].
+///
+/// The code imports the url of the SVG file from the inner asset `SVG` when it needs one.
+#[turbo_tasks::value]
+pub struct SvgModuleSource {
+    svg: ResolvedVc<Box<dyn Source>>,
+    mode: SvgImportMode,
+}
+
+#[turbo_tasks::value_impl]
+impl SvgModuleSource {
+    #[turbo_tasks::function]
+    pub fn new(svg: ResolvedVc<Box<dyn Source>>, mode: SvgImportMode) -> Vc<Self> {
+        Self::cell(SvgModuleSource { svg, mode })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Source for SvgModuleSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        let modifier = match self.mode {
+            SvgImportMode::Url => rcstr!("svg url"),
+            SvgImportMode::DataUrl { max_size } => format!("svg data url {max_size}").into(),
+            SvgImportMode::Component => rcstr!("svg component"),
+        };
+        self.svg
+            .ident()
+            .with_modifier(modifier)
+            .rename_as(rcstr!("*.mjs"))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for SvgModuleSource {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let content = self.svg.content().file_content().await?;
+        let FileContent::Content(file) = &*content else {
+            bail!("Input source is not a file and can't be transformed into a svg module");
+        };
+        let svg = file.content().to_str()?;
+        let code = match self.mode {
+            SvgImportMode::DataUrl { max_size } if svg.len() <= max_size as usize => format!(
+                "export default {};\n",
+                serde_json::to_string(&svg_data_url(&svg))?
+            ),
+            SvgImportMode::Url | SvgImportMode::DataUrl { .. } => {
+                "import src from \"SVG\";\nexport default src;\n".to_string()
+            }
+            SvgImportMode::Component => svg_component_module(&svg)?,
+        };
+        Ok(AssetContent::file(
+            FileContent::Content(File::from(code)).cell(),
+        ))
+    }
+}
@@ -0,0 +1,269 @@
+//! Adds `id` attributes to the headings of compiled MDX code.
+//!
+//! mdxjs doesn't support hast plugins, so instead of adding the ids before compilation, the
+//! compiled module is parsed and the props of each heading element are patched in place. Headings
+//! are always rendered through `_components.hN`, which makes them easy to find regardless of the
+//! jsx runtime.
+
+use swc_core::{
+    common::{FileName, GLOBALS, SourceMap, Span, Spanned, sync::Lrc},
+    ecma::{
+        ast::{
+            CallExpr, Callee, EsVersion, Expr, JSXElementName, JSXObject, JSXOpeningElement, Lit,
+            MemberExpr, MemberProp,
+        },
+        parser::{EsSyntax, Syntax, parse_file_as_module},
+        visit::{Visit, VisitWith},
+    },
+};
+
+use crate::metadata::MdxHeading;
+
+/// A heading element found in the compiled code, and how to attach an id to it.
+struct HeadingElement {
+    depth: u8,
+    edit: Edit,
+}
+
+enum Edit {
+    /// Insert the prop at the start of an object literal, at the given offset after `{`.
+    ObjectProp(usize),
+    /// Replace a `null` props argument.
+    ReplaceNull(usize, usize),
+    /// Insert a jsx attribute after the element name, at the given offset.
+    JsxAttribute(usize),
+}
+
+/// Adds the ids of the given headings to the compiled code. Returns `None` when the heading
+/// elements can't be matched to the headings of the document.
+pub fn add_heading_ids(code: String, headings: &[MdxHeading]) -> Option<String> {
+    if headings.is_empty() {
+        return Some(code);
+    }
+    let mut elements = find_heading_elements(&code)?;
+    elements.sort_by_key(|element| element.edit.offset());
+    if elements.len() != headings.len()
+        || elements
+            .iter()
+            .zip(headings)
+            .any(|(element, heading)| element.depth != heading.depth)
+    {
+        return None;
+    }
+
+    let mut code = code;
+    // Apply edits back to front, so earlier offsets stay valid.
+    for (element, heading) in elements.iter().zip(headings).rev() {
+        let id = serde_json::to_string(&heading.id).expect("serializing a string can't fail");
+        match element.edit {
+            Edit::ObjectProp(offset) => code.insert_str(offset, &format!("\nid: {id},")),
+            Edit::ReplaceNull(start, end) => {
+                code.replace_range(start..end, &format!("{{ id: {id} }}"))
+            }
+            Edit::JsxAttribute(offset) => code.insert_str(offset, &format!(" id={{{id}}}")),
+        }
+    }
+    Some(code)
+}
+
+impl Edit {
+    fn offset(&self) -> usize {
+        match *self {
+            Edit::ObjectProp(offset)
+            | Edit::ReplaceNull(offset, _)
+            | Edit::JsxAttribute(offset) => offset,
+        }
+    }
+}
+
+fn find_heading_elements(code: &str) -> Option<Vec<HeadingElement>> {
+    GLOBALS.set(&Default::default(), || {
+        let cm = Lrc::new(SourceMap::default());
+        let fm = cm.new_source_file(Lrc::new(FileName::Anon), code.to_string());
+        let module = parse_file_as_module(
+            &fm,
+            Syntax::Es(EsSyntax {
+                jsx: true,
+                ..Default::default()
+            }),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .ok()?;
+        let mut visitor = HeadingVisitor {
+            start_pos: fm.start_pos.0,
+            elements: Vec::new(),
+            unsupported: false,
+        };
+        module.visit_with(&mut visitor);
+        (!visitor.unsupported).then_some(visitor.elements)
+    })
+}
+
+struct HeadingVisitor {
+    start_pos: u32,
+    elements: Vec<HeadingElement>,
+    /// Set when a heading element uses a form of props that can't be patched.
+    unsupported: bool,
+}
+
+impl HeadingVisitor {
+    fn offset(&self, span: Span) -> (usize, usize) {
+        (
+            (span.lo.0 - self.start_pos) as usize,
+            (span.hi.0 - self.start_pos) as usize,
+        )
+    }
+}
+
+/// Returns the depth of a `_components.hN` element type.
+fn heading_depth(object: &str, property: &str) -> Option<u8> {
+    if object != "_components" {
+        return None;
+    }
+    match property.strip_prefix('h')?.parse() {
+        Ok(depth @ 1..=6) => Some(depth),
+        _ => None,
+    }
+}
+
+impl Visit for HeadingVisitor {
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        call.visit_children_with(self);
+
+        // Matches `_jsx(_components.hN, props)`, `_jsxDEV(...)` and
+        // `React.createElement(_components.hN, props)`.
+        let Callee::Expr(_) = &call.callee else {
+            return;
+        };
+        let [element_type, props, ..] = &call.args[..] else {
+            return;
+        };
+        let Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(prop),
+            ..
+        }) = &*element_type.expr
+        else {
+            return;
+        };
+        let Some(object) = obj.as_ident() else {
+            return;
+        };
+        let Some(depth) = heading_depth(&object.sym, &prop.sym) else {
+            return;
+        };
+        let edit = match (&*props.expr, props.spread) {
+            (Expr::Object(object), None) => Edit::ObjectProp(self.offset(object.span).0 + 1),
+            (Expr::Lit(Lit::Null(null)), None) => {
+                let (start, end) = self.offset(null.span);
+                Edit::ReplaceNull(start, end)
+            }
+            _ => {
+                self.unsupported = true;
+                return;
+            }
+        };
+        self.elements.push(HeadingElement { depth, edit });
+    }
+
+    fn visit_jsx_opening_element(&mut self, element: &JSXOpeningElement) {
+        element.visit_children_with(self);
+
+        let JSXElementName::JSXMemberExpr(member) = &element.name else {
+            return;
+        };
+        let JSXObject::Ident(object) = &member.obj else {
+            return;
+        };
+        let Some(depth) = heading_depth(&object.sym, &member.prop.sym) else {
+            return;
+        };
+        let (_, end) = self.offset(element.name.span());
+        self.elements.push(HeadingElement {
+            depth,
+            edit: Edit::JsxAttribute(end),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::add_heading_ids;
+    use crate::metadata::MdxHeading;
+
+    fn heading(depth: u8, id: &str) -> MdxHeading {
+        MdxHeading {
+            depth,
+            text: id.to_string(),
+            id: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_jsx_runtime() {
+        let code = r#"function _createMdxContent(props) {
+    const _components = { h1: "h1", h2: "h2", ...props.components };
+    return _jsxs(_Fragment, { children: [_jsx(_components.h1, {
+        children: "Intro"
+    }), "\n", _jsx(Note, { children: _jsx(_components.h2, {
+        children: "Nested"
+    }) })] });
+}
+"#;
+        assert_eq!(
+            add_heading_ids(
+                code.to_string(),
+                &[heading(1, "intro"), heading(2, "nested")]
+            )
+            .unwrap(),
+            r#"function _createMdxContent(props) {
+    const _components = { h1: "h1", h2: "h2", ...props.components };
+    return _jsxs(_Fragment, { children: [_jsx(_components.h1, {
+id: "intro",
+        children: "Intro"
+    }), "\n", _jsx(Note, { children: _jsx(_components.h2, {
+id: "nested",
+        children: "Nested"
+    }) })] });
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_classic_runtime() {
+        assert_eq!(
+            add_heading_ids(
+                "React.createElement(_components.h3, null, \"A\");".to_string(),
+                &[heading(3, "a")]
+            )
+            .unwrap(),
+            "React.createElement(_components.h3, { id: \"a\" }, \"A\");"
+        );
+    }
+
+    #[test]
+    fn test_preserved_jsx() {
+        assert_eq!(
+            add_heading_ids(
+                "const a = <_components.h2>A</_components.h2>;".to_string(),
+                &[heading(2, "a")]
+            )
+            .unwrap(),
+            "const a = <_components.h2 id={\"a\"}>A</_components.h2>;"
+        );
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let code = "_jsx(_components.h1, { children: \"A\" });".to_string();
+        assert_eq!(add_heading_ids(code.clone(), &[heading(2, "a")]), None);
+        assert_eq!(
+            add_heading_ids(code.clone(), &[heading(1, "a"), heading(1, "b")]),
+            None
+        );
+        assert_eq!(add_heading_ids(code.clone(), &[]), Some(code));
+    }
+}
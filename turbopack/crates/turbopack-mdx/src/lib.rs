@@ -2,7 +2,11 @@
 #![feature(arbitrary_self_types)]
 #![feature(arbitrary_self_types_pointers)]
 
+mod heading_ids;
+mod metadata;
+
 use anyhow::Result;
+use markdown::message::Message;
 use mdxjs::{MdxParseOptions, Options, compile};
 use serde::Deserialize;
 use turbo_rcstr::{RcStr, rcstr};
@@ -12,8 +16,8 @@ use turbopack_core::{
     asset::{Asset, AssetContent},
     ident::AssetIdent,
     issue::{
-        Issue, IssueExt, IssueSource, IssueStage, OptionIssueSource, OptionStyledString,
-        StyledString,
+        Issue, IssueExt, IssueSeverity, IssueSource, IssueStage, OptionIssueSource,
+        OptionStyledString, StyledString,
     },
     source::Source,
     source_pos::SourcePos,
@@ -45,6 +49,14 @@ pub struct MdxTransformOptions {
     pub provider_import_source: Option<RcStr>,
     /// Determines how to parse mdx contents.
     pub mdx_type: Option<MdxParseConstructs>,
+    /// Parses YAML frontmatter and exposes it as the `frontmatter` named export. Documents
+    /// without frontmatter export an empty object.
+    pub frontmatter: Option<bool>,
+    /// Adds `id` attributes with GitHub-style slugs to all headings.
+    pub heading_ids: Option<bool>,
+    /// Exposes the headings of the document as the `tableOfContents` named export, a list of
+    /// `{ depth, text, id }` objects in document order.
+    pub table_of_contents: Option<bool>,
 }
 
 impl Default for MdxTransformOptions {
@@ -56,6 +68,9 @@ impl Default for MdxTransformOptions {
             jsx_import_source: None,
             provider_import_source: None,
             mdx_type: Some(MdxParseConstructs::Commonmark),
+            frontmatter: None,
+            heading_ids: None,
+            table_of_contents: None,
         }
     }
 }
@@ -148,10 +163,11 @@ impl MdxTransformedAsset {
             None
         };
 
-        let parse_options = match transform_options.mdx_type {
+        let mut parse_options = match transform_options.mdx_type {
             Some(MdxParseConstructs::Gfm) => MdxParseOptions::gfm(),
             _ => MdxParseOptions::default(),
         };
+        parse_options.constructs.frontmatter = transform_options.frontmatter.unwrap_or(false);
 
         let options = Options {
             parse: parse_options,
@@ -170,17 +186,32 @@ impl MdxTransformedAsset {
             ..Default::default()
         };
 
-        let result = compile(&file.content().to_str()?, &options);
+        let code = file.content().to_str()?;
+        let result = compile(&code, &options).and_then(|mdx_jsx_component| {
+            apply_metadata(&code, mdx_jsx_component, &transform_options)
+        });
 
         match result {
-            Ok(mdx_jsx_component) => Ok(MdxTransformResult {
-                content: AssetContent::file(
-                    FileContent::Content(File::from(Rope::from(mdx_jsx_component))).cell(),
-                )
-                .to_resolved()
-                .await?,
+            Ok(MetadataResult {
+                code: mdx_jsx_component,
+                missing_heading_ids,
+            }) => {
+                if missing_heading_ids {
+                    MdxHeadingIdsIssue {
+                        source: IssueSource::from_source_only(self.source),
+                    }
+                    .resolved_cell()
+                    .emit();
+                }
+                Ok(MdxTransformResult {
+                    content: AssetContent::file(
+                        FileContent::Content(File::from(Rope::from(mdx_jsx_component))).cell(),
+                    )
+                    .to_resolved()
+                    .await?,
+                }
+                .cell())
             }
-            .cell()),
             Err(err) => {
                 let source = match err.place {
                     Some(p) => {
@@ -230,6 +261,54 @@ impl MdxTransformedAsset {
     }
 }
 
+struct MetadataResult {
+    code: String,
+    /// Whether heading ids were enabled but couldn't be added to the compiled code.
+    missing_heading_ids: bool,
+}
+
+/// Adds heading ids and the metadata exports enabled in the options to the compiled code.
+fn apply_metadata(
+    code: &str,
+    mut mdx_jsx_component: String,
+    transform_options: &MdxTransformOptions,
+) -> Result<MetadataResult, Message> {
+    let frontmatter = transform_options.frontmatter.unwrap_or(false);
+    let heading_ids = transform_options.heading_ids.unwrap_or(false);
+    let table_of_contents = transform_options.table_of_contents.unwrap_or(false);
+    if !frontmatter && !heading_ids && !table_of_contents {
+        return Ok(MetadataResult {
+            code: mdx_jsx_component,
+            missing_heading_ids: false,
+        });
+    }
+
+    let metadata = metadata::extract_metadata(
+        code,
+        matches!(transform_options.mdx_type, Some(MdxParseConstructs::Gfm)),
+        frontmatter,
+    )?;
+    let mut missing_heading_ids = false;
+    if heading_ids {
+        match crate::heading_ids::add_heading_ids(mdx_jsx_component.clone(), &metadata.headings) {
+            Some(code) => mdx_jsx_component = code,
+            None => missing_heading_ids = true,
+        }
+    }
+    if frontmatter {
+        mdx_jsx_component.push_str(&metadata::frontmatter_export(
+            metadata.frontmatter.as_ref(),
+        )?);
+    }
+    if table_of_contents {
+        mdx_jsx_component.push_str(&metadata::table_of_contents_export(&metadata.headings));
+    }
+    Ok(MetadataResult {
+        code: mdx_jsx_component,
+        missing_heading_ids,
+    })
+}
+
 #[turbo_tasks::value]
 struct MdxTransformResult {
     content: ResolvedVc<AssetContent>,
@@ -276,3 +355,49 @@ impl Issue for MdxIssue {
         ))
     }
 }
+
+/// Reported when the headings of the compiled code don't match the headings of the document, e.g.
+/// because a custom component renders them.
+#[turbo_tasks::value]
+struct MdxHeadingIdsIssue {
+    source: IssueSource,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for MdxHeadingIdsIssue {
+    fn severity(&self) -> IssueSeverity {
+        IssueSeverity::Warning
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.source.file_path()
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        Vc::cell(Some(self.source))
+    }
+
+    #[turbo_tasks::function]
+    fn stage(self: Vc<Self>) -> Vc<IssueStage> {
+        IssueStage::SourceTransform.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(self: Vc<Self>) -> Vc<StyledString> {
+        StyledString::Text(rcstr!("Heading ids couldn't be added")).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(rcstr!(
+                "The headings rendered by the compiled MDX code don't match the headings of the \
+                 document, so no `id` attributes were added to them. The `tableOfContents` export \
+                 still contains the ids."
+            ))
+            .resolved_cell(),
+        ))
+    }
+}
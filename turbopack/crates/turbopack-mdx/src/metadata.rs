@@ -0,0 +1,249 @@
+//! Extraction of document metadata (frontmatter and headings) from MDX source code.
+//!
+//! These replace the commonly used `remark-frontmatter`, `remark-mdx-frontmatter`,
+//! `rehype-slug` and `remark-toc`-style plugins, which would otherwise require a JavaScript
+//! loader.
+
+use markdown::{
+    Constructs, ParseOptions,
+    mdast::{Node, Yaml},
+    message::{Message, Place},
+};
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MdxHeading {
+    /// Rank of the heading, between `1` and `6`.
+    pub depth: u8,
+    /// Text content of the heading.
+    pub text: String,
+    /// A unique slug generated from the text, compatible with `github-slugger`.
+    pub id: String,
+}
+
+#[derive(Debug, Default)]
+pub struct MdxMetadata {
+    /// The raw YAML frontmatter block, if there is one.
+    pub frontmatter: Option<Yaml>,
+    /// All headings of the document in document order.
+    pub headings: Vec<MdxHeading>,
+}
+
+/// Parses the MDX source and collects frontmatter and headings. The constructs need to match the
+/// ones used for compilation, otherwise e.g. a frontmatter block could be parsed as a heading.
+pub fn extract_metadata(value: &str, gfm: bool, frontmatter: bool) -> Result<MdxMetadata, Message> {
+    let mut constructs = Constructs {
+        frontmatter,
+        ..Constructs::mdx()
+    };
+    if gfm {
+        constructs.gfm_autolink_literal = true;
+        constructs.gfm_footnote_definition = true;
+        constructs.gfm_label_start_footnote = true;
+        constructs.gfm_strikethrough = true;
+        constructs.gfm_table = true;
+        constructs.gfm_task_list_item = true;
+    }
+    let root = markdown::to_mdast(
+        value,
+        &ParseOptions {
+            constructs,
+            ..ParseOptions::mdx()
+        },
+    )?;
+
+    let mut metadata = MdxMetadata::default();
+    if let Some(Node::Yaml(yaml)) = root.children().and_then(|children| children.first()) {
+        metadata.frontmatter = Some(yaml.clone());
+    }
+    let mut slugger = Slugger::default();
+    collect_headings(&root, &mut slugger, &mut metadata.headings);
+    Ok(metadata)
+}
+
+fn collect_headings(node: &Node, slugger: &mut Slugger, headings: &mut Vec<MdxHeading>) {
+    if let Node::Heading(heading) = node {
+        let text = node.to_string();
+        headings.push(MdxHeading {
+            depth: heading.depth,
+            id: slugger.slug(&text),
+            text,
+        });
+        return;
+    }
+    if let Some(children) = node.children() {
+        for child in children {
+            collect_headings(child, slugger, headings);
+        }
+    }
+}
+
+/// Generates unique slugs the same way `github-slugger` does.
+#[derive(Default)]
+struct Slugger {
+    occurrences: FxHashMap<String, usize>,
+}
+
+impl Slugger {
+    fn slug(&mut self, text: &str) -> String {
+        let base: String = text
+            .trim()
+            .to_lowercase()
+            .chars()
+            .filter_map(|c| match c {
+                ' ' => Some('-'),
+                '-' | '_' => Some(c),
+                c if c.is_alphanumeric() => Some(c),
+                _ => None,
+            })
+            .collect();
+        // Like `github-slugger`, the counter of the base slug is incremented until the suffixed
+        // slug is unused.
+        let mut slug = base.clone();
+        while self.occurrences.contains_key(&slug) {
+            let count = self
+                .occurrences
+                .get_mut(&base)
+                .expect("the base slug is used when a suffix is needed");
+            *count += 1;
+            slug = format!("{base}-{count}");
+        }
+        self.occurrences.insert(slug.clone(), 0);
+        slug
+    }
+}
+
+/// Generates the `export const frontmatter = ...` statement. Documents without frontmatter
+/// export an empty object.
+pub fn frontmatter_export(frontmatter: Option<&Yaml>) -> Result<String, Message> {
+    let value = match frontmatter {
+        Some(yaml) if !yaml.value.trim().is_empty() => {
+            serde_yaml_ng::from_str::<serde_json::Value>(&yaml.value).map_err(|err| Message {
+                place: yaml
+                    .position
+                    .clone()
+                    .map(|position| Box::new(Place::Position(position))),
+                reason: format!("Failed to parse YAML frontmatter: {err}"),
+                rule_id: Box::new("frontmatter".to_string()),
+                source: Box::new("turbopack-mdx".to_string()),
+            })?
+        }
+        _ => serde_json::Value::Object(Default::default()),
+    };
+    Ok(format!(
+        "export const frontmatter = {};\n",
+        serde_json::to_string(&value).expect("serializing a JSON value can't fail")
+    ))
+}
+
+/// Generates the `export const tableOfContents = [...]` statement.
+pub fn table_of_contents_export(headings: &[MdxHeading]) -> String {
+    format!(
+        "export const tableOfContents = {};\n",
+        serde_json::to_string(headings).expect("serializing headings can't fail")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use markdown::mdast::Yaml;
+
+    use super::{
+        MdxHeading, Slugger, extract_metadata, frontmatter_export, table_of_contents_export,
+    };
+
+    #[test]
+    fn test_slugger() {
+        let mut slugger = Slugger::default();
+        assert_eq!(slugger.slug("Hello World"), "hello-world");
+        assert_eq!(slugger.slug("Hello World"), "hello-world-1");
+        assert_eq!(slugger.slug("Hello World"), "hello-world-2");
+        assert_eq!(slugger.slug("hello-world-1"), "hello-world-1-1");
+        assert_eq!(slugger.slug("What's `new`?"), "whats-new");
+        assert_eq!(slugger.slug("Ünïcödé  Text"), "ünïcödé--text");
+    }
+
+    #[test]
+    fn test_slugger_suffix_collisions() {
+        let mut slugger = Slugger::default();
+        assert_eq!(slugger.slug("A"), "a");
+        assert_eq!(slugger.slug("A-1"), "a-1");
+        assert_eq!(slugger.slug("A"), "a-2");
+        assert_eq!(slugger.slug("A-1"), "a-1-1");
+        assert_eq!(slugger.slug("A"), "a-3");
+    }
+
+    #[test]
+    fn test_extract_metadata() {
+        let metadata = extract_metadata(
+            "---\ntitle: Hello\ntags: [a, b]\n---\n\n# Intro\n\nSome *text*.\n\n## Getting \
+             `started`\n\n<Note>\n\n### Nested\n\n</Note>\n\n## Intro\n",
+            false,
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            metadata.headings,
+            vec![
+                MdxHeading {
+                    depth: 1,
+                    text: "Intro".into(),
+                    id: "intro".into(),
+                },
+                MdxHeading {
+                    depth: 2,
+                    text: "Getting started".into(),
+                    id: "getting-started".into(),
+                },
+                MdxHeading {
+                    depth: 3,
+                    text: "Nested".into(),
+                    id: "nested".into(),
+                },
+                MdxHeading {
+                    depth: 2,
+                    text: "Intro".into(),
+                    id: "intro-1".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            frontmatter_export(metadata.frontmatter.as_ref()).unwrap(),
+            "export const frontmatter = {\"title\":\"Hello\",\"tags\":[\"a\",\"b\"]};\n"
+        );
+    }
+
+    #[test]
+    fn test_missing_frontmatter() {
+        let metadata = extract_metadata("# Title\n", false, true).unwrap();
+        assert!(metadata.frontmatter.is_none());
+        assert_eq!(
+            frontmatter_export(None).unwrap(),
+            "export const frontmatter = {};\n"
+        );
+        assert_eq!(
+            table_of_contents_export(&metadata.headings),
+            "export const tableOfContents = [{\"depth\":1,\"text\":\"Title\",\"id\":\"title\"}];\n"
+        );
+    }
+
+    #[test]
+    fn test_disabled_frontmatter() {
+        // Without the frontmatter construct, the block is a thematic break and a setext heading.
+        let metadata = extract_metadata("---\ntitle: Hello\n---\n", false, false).unwrap();
+        assert!(metadata.frontmatter.is_none());
+        assert_eq!(metadata.headings.len(), 1);
+        assert_eq!(metadata.headings[0].text, "title: Hello");
+    }
+
+    #[test]
+    fn test_invalid_frontmatter() {
+        let err = frontmatter_export(Some(&Yaml {
+            value: "title: [".to_string(),
+            position: None,
+        }))
+        .unwrap_err();
+        assert_eq!(*err.rule_id, "frontmatter");
+    }
+}
@@ -10,7 +10,12 @@ import type { Processor } from 'postcss'
 import postcss from '@vercel/turbopack/postcss'
 // @ts-ignore
 import importedConfig from 'CONFIG'
-import { getReadEnvVariables, toPath, type TransformIpc } from './transforms'
+import {
+  getLoadedModulePaths,
+  getReadEnvVariables,
+  toPath,
+  type TransformIpc,
+} from './transforms'
 
 let processor: Processor | undefined
 
@@ -121,6 +126,7 @@ export default async function transform(
     directories,
     buildFilePaths,
     envVariables: getReadEnvVariables(),
+    loadedModulePaths: getLoadedModulePaths(),
   })
   return {
     css,
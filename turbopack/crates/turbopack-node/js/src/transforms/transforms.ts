@@ -3,6 +3,7 @@
  */
 
 import type { Ipc } from '../ipc/evaluate'
+import Module from 'module'
 import { relative, isAbsolute, join, sep } from 'path'
 import { type StructuredError } from '../ipc'
 import { type StackFrame } from '../compiled/stacktrace-parser'
@@ -14,6 +15,7 @@ export type IpcInfoMessage =
       directories?: Array<[string, string]>
       filePaths?: string[]
       buildFilePaths?: string[]
+      loadedModulePaths?: string[]
     }
   | {
       type: 'emittedError'
@@ -83,3 +85,28 @@ process.env = new Proxy(originalEnv, {
 export function getReadEnvVariables(): string[] {
   return Array.from(readEnvVars)
 }
+
+// `require.cache` would be Turbopack's module cache, Node.js keeps loaded files in `Module._cache`.
+const nodeModuleCache: Record<string, unknown> = (Module as any)._cache
+// The files of the transform runner itself are loaded before any transform runs.
+const runnerModules = new Set(Object.keys(nodeModuleCache))
+
+/**
+ * Returns the project files Node.js loaded since the transform runner started, e.g. loaders,
+ * PostCSS plugins and the configs they require. Installed packages are skipped, their versions are
+ * pinned by the lockfile.
+ */
+export function getLoadedModulePaths(): string[] {
+  const paths = []
+  for (const file of Object.keys(nodeModuleCache)) {
+    if (runnerModules.has(file) || file.split(sep).includes('node_modules')) {
+      continue
+    }
+    const relPath = relative(contextDir, file)
+    if (isAbsolute(relPath)) {
+      continue
+    }
+    paths.push(sep !== '/' ? relPath.replaceAll(sep, '/') : relPath)
+  }
+  return paths
+}
@@ -11,6 +11,7 @@ import {
 import { structuredError, type StructuredError } from '../ipc'
 import {
   fromPath,
+  getLoadedModulePaths,
   getReadEnvVariables,
   toPath,
   type TransformIpc,
@@ -25,6 +26,7 @@ export type IpcInfoMessage =
       directories?: Array<[string, string]>
       filePaths?: string[]
       buildFilePaths?: string[]
+      loadedModulePaths?: string[]
     }
   | {
       type: 'emittedError'
@@ -490,6 +492,7 @@ const transform = (
             toPath(dep),
             '**',
           ]),
+          loadedModulePaths: getLoadedModulePaths(),
         })
         if (err) return reject(err)
         if (!result.result) return reject(new Error('No result from loaders'))
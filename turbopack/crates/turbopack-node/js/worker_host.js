@@ -0,0 +1,51 @@
+// The host process of the `worker_threads` backend. It's started with `node -e` and controlled
+// with newline-delimited JSON commands on stdin. The output of the workers is multiplexed onto
+// stdout in frames of `<id> <stream> <length>\n<data>`, where stream is `o` (stdout) or `e`
+// (stderr). When a worker exits `<id> x <exit code>\n` is written.
+const { Worker } = require('node:worker_threads')
+const { createInterface } = require('node:readline')
+
+const workers = new Map()
+
+function writeFrame(id, stream, data) {
+  process.stdout.write(`${id} ${stream} ${data.length}\n`)
+  process.stdout.write(data)
+}
+
+function spawn({ id, entrypoint, port, env, maxOldGenerationSizeMb }) {
+  const worker = new Worker(entrypoint, {
+    argv: [port],
+    env,
+    stdout: true,
+    stderr: true,
+    resourceLimits:
+      maxOldGenerationSizeMb != null ? { maxOldGenerationSizeMb } : undefined,
+  })
+  worker.stdout.on('data', (chunk) => writeFrame(id, 'o', chunk))
+  worker.stderr.on('data', (chunk) => writeFrame(id, 'e', chunk))
+  worker.on('error', (err) => {
+    writeFrame(id, 'e', Buffer.from(`${(err && err.stack) || err}\n`))
+  })
+  worker.on('exit', (code) => {
+    workers.delete(id)
+    process.stdout.write(`${id} x ${code}\n`)
+  })
+  workers.set(id, worker)
+}
+
+createInterface({ input: process.stdin })
+  .on('line', (line) => {
+    const command = JSON.parse(line)
+    switch (command.type) {
+      case 'spawn':
+        spawn(command)
+        break
+      case 'terminate':
+        workers.get(command.id)?.terminate()
+        break
+      default:
+        console.error('unexpected command type', command.type)
+    }
+  })
+  // The Rust side closes stdin when the host is no longer needed.
+  .on('close', () => process.exit(0))
@@ -140,6 +140,20 @@ pub enum EnvVarTracking {
     Untracked,
 }
 
+/// How the workers of a [NodeJsPool] run the evaluated code.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, TaskInput, Clone, Copy, Hash)]
+pub enum NodeJsBackend {
+    /// Every worker is a separate Node.js process.
+    #[default]
+    ChildProcess,
+    /// Workers are `worker_threads` of a single long-lived Node.js process per working
+    /// directory. This avoids the startup time and memory overhead of a process per worker, but
+    /// the evaluated code can't rely on per-process state like `NODE_OPTIONS`. Falls back to
+    /// child processes when debugging.
+    WorkerThreads,
+}
+
 #[turbo_tasks::function(operation)]
 /// Pass the file you cared as `runtime_entries` to invalidate and reload the
 /// evaluated result automatically.
@@ -152,6 +166,7 @@ pub async fn get_evaluate_pool(
     additional_invalidation: ResolvedVc<Completion>,
     debug: bool,
     env_var_tracking: EnvVarTracking,
+    backend: NodeJsBackend,
 ) -> Result<Vc<NodeJsPool>> {
     let operation =
         emit_evaluate_pool_assets_with_effects_operation(entries, chunking_context, module_graph);
@@ -199,6 +214,7 @@ pub async fn get_evaluate_pool(
         chunking_context.root_path().owned().await?,
         available_parallelism().map_or(1, |v| v.get()),
         debug,
+        backend,
     );
     additional_invalidation.await?;
     Ok(pool.cell())
@@ -272,12 +288,21 @@ pub trait EvaluateContext {
     ) -> impl Future<Output = Result<Self::ResponseMessage>> + Send;
     fn finish(
         &self,
-        state: Self::State,
+        state: &mut Self::State,
         pool: &NodeJsPool,
     ) -> impl Future<Output = Result<()>> + Send;
 }
 
 pub async fn custom_evaluate(evaluate_context: impl EvaluateContext) -> Result<Vc<Option<RcStr>>> {
+    let (result, _) = custom_evaluate_with_state(&evaluate_context).await?;
+    Ok(Vc::cell(result.map(RcStr::from)))
+}
+
+/// Like [custom_evaluate], but also returns the state after the evaluation finished, e.g. to
+/// inspect the messages received from the evaluation.
+pub(crate) async fn custom_evaluate_with_state<C: EvaluateContext>(
+    evaluate_context: &C,
+) -> Result<(Option<String>, C::State)> {
     let pool_op = evaluate_context.pool();
     let mut state = Default::default();
 
@@ -313,15 +338,15 @@ pub async fn custom_evaluate(evaluate_context: impl EvaluateContext) -> Result<V
     // The evaluation sent an initial intermediate value without completing. We'll
     // need to spawn a new thread to continually pull data out of the process,
     // and ferry that along.
-    let result = pull_operation(&mut operation, &pool, &evaluate_context, &mut state).await?;
+    let result = pull_operation(&mut operation, &pool, evaluate_context, &mut state).await?;
 
-    evaluate_context.finish(state, &pool).await?;
+    evaluate_context.finish(&mut state, &pool).await?;
 
     if kill {
         operation.wait_or_kill().await?;
     }
 
-    Ok(Vc::cell(result.map(RcStr::from)))
+    Ok((result, state))
 }
 
 #[turbo_tasks::value]
@@ -431,6 +456,7 @@ pub async fn evaluate(
     args: Vec<ResolvedVc<JsonValue>>,
     additional_invalidation: ResolvedVc<Completion>,
     debug: bool,
+    backend: NodeJsBackend,
 ) -> Result<Vc<Option<RcStr>>> {
     custom_evaluate(BasicEvaluateContext {
         entries,
@@ -442,6 +468,7 @@ pub async fn evaluate(
         args,
         additional_invalidation,
         debug,
+        backend,
     })
     .await
 }
@@ -510,6 +537,7 @@ struct BasicEvaluateContext {
     args: Vec<ResolvedVc<JsonValue>>,
     additional_invalidation: ResolvedVc<Completion>,
     debug: bool,
+    backend: NodeJsBackend,
 }
 
 impl EvaluateContext for BasicEvaluateContext {
@@ -528,6 +556,7 @@ impl EvaluateContext for BasicEvaluateContext {
             self.additional_invalidation,
             self.debug,
             EnvVarTracking::WholeEnvTracked,
+            self.backend,
         )
     }
 
@@ -574,7 +603,7 @@ impl EvaluateContext for BasicEvaluateContext {
         bail!("BasicEvaluateContext does not support request messages")
     }
 
-    async fn finish(&self, _state: Self::State, _pool: &NodeJsPool) -> Result<()> {
+    async fn finish(&self, _state: &mut Self::State, _pool: &NodeJsPool) -> Result<()> {
         Ok(())
     }
 }
@@ -3,11 +3,15 @@ use turbo_tasks_env::ProcessEnv;
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::chunk::ChunkingContext;
 
+use crate::evaluate::NodeJsBackend;
+
 #[turbo_tasks::value]
 pub struct ExecutionContext {
     pub project_path: FileSystemPath,
     pub chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
     pub env: ResolvedVc<Box<dyn ProcessEnv>>,
+    /// How webpack loaders and PostCSS are evaluated.
+    pub backend: NodeJsBackend,
 }
 
 #[turbo_tasks::value_impl]
@@ -22,6 +26,19 @@ impl ExecutionContext {
             project_path,
             chunking_context,
             env,
+            backend: NodeJsBackend::ChildProcess,
+        }
+        .cell()
+    }
+
+    /// Returns a copy of this context that evaluates with the given `backend`.
+    #[turbo_tasks::function]
+    pub fn with_backend(&self, backend: NodeJsBackend) -> Vc<Self> {
+        ExecutionContext {
+            project_path: self.project_path.clone(),
+            chunking_context: self.chunking_context,
+            env: self.env,
+            backend,
         }
         .cell()
     }
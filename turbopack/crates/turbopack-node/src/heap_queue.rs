@@ -1,4 +1,4 @@
-use std::{collections::BinaryHeap, sync::Arc};
+use std::{collections::BinaryHeap, mem::take, sync::Arc};
 
 use parking_lot::Mutex;
 use tokio::sync::{AcquireError, Semaphore};
@@ -84,4 +84,36 @@ impl<T: Ord> HeapQueue<T> {
             }
         }
     }
+
+    /// Removes up to `limit` items matching `predicate` and returns them.
+    pub fn remove_where(
+        self: &Arc<Self>,
+        active_queues: &Mutex<Vec<Arc<Self>>>,
+        limit: usize,
+        predicate: impl Fn(&T) -> bool,
+    ) -> Vec<T> {
+        let mut heap = self.heap.lock();
+        let matching = heap.iter().filter(|item| predicate(item)).count();
+        // Items are only removed together with their permits. Concurrent `pop` calls that already
+        // acquired a permit will take other items.
+        let n = self.semaphore.forget_permits(matching.min(limit));
+        let mut removed = Vec::with_capacity(n);
+        let mut remaining = BinaryHeap::with_capacity(heap.len() - n);
+        for item in take(&mut *heap).into_vec() {
+            if removed.len() < n && predicate(&item) {
+                removed.push(item);
+            } else {
+                remaining.push(item);
+            }
+        }
+        *heap = remaining;
+        if heap.is_empty() && !removed.is_empty() {
+            // If the heap is empty, remove this queue from the active queues
+            let mut queues = active_queues.lock();
+            if let Some(pos) = queues.iter().position(|q| Arc::ptr_eq(q, self)) {
+                queues.remove(pos);
+            }
+        }
+        removed
+    }
 }
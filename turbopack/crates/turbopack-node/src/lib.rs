@@ -19,8 +19,10 @@ pub mod evaluate;
 pub mod execution_context;
 mod heap_queue;
 mod pool;
+pub mod pool_policy;
 pub mod source_map;
 pub mod transforms;
+mod worker_threads;
 
 #[turbo_tasks::function]
 async fn emit(
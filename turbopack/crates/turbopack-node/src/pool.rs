@@ -4,8 +4,8 @@ use std::{
     future::Future,
     mem::take,
     path::{Path, PathBuf},
-    process::{ExitStatus, Stdio},
-    sync::Arc,
+    process::Stdio,
+    sync::{Arc, Once},
     time::{Duration, Instant},
 };
 
@@ -22,17 +22,25 @@ use tokio::{
         Stdout, stderr, stdout,
     },
     net::{TcpListener, TcpStream},
-    process::{Child, ChildStderr, ChildStdout, Command},
+    process::{Child, Command},
     select,
-    sync::{OwnedSemaphorePermit, Semaphore},
+    sync::{Notify, OwnedSemaphorePermit, Semaphore},
     time::{sleep, timeout},
 };
+use tracing::{Instrument, field::Empty};
 use turbo_rcstr::RcStr;
 use turbo_tasks::{FxIndexSet, ResolvedVc, Vc, duration_span};
 use turbo_tasks_fs::{FileSystemPath, json::parse_json_with_source_context};
 use turbopack_ecmascript::magic_identifier::unmangle_identifiers;
 
-use crate::{AssetsForSourceMapping, heap_queue::HeapQueue, source_map::apply_source_mapping};
+use crate::{
+    AssetsForSourceMapping,
+    evaluate::NodeJsBackend,
+    heap_queue::HeapQueue,
+    pool_policy::{WORKER_IDLE, WORKER_PERMITS, pool_policy, process_rss},
+    source_map::apply_source_mapping,
+    worker_threads::{OutputStream, WorkerThread, spawn_worker_thread, stop_unused_hosts},
+};
 
 #[derive(Clone, Copy)]
 pub enum FormattingMode {
@@ -67,13 +75,51 @@ impl FormattingMode {
     }
 }
 
+/// A running Node.js worker, depending on the [NodeJsBackend].
+enum NodeJsWorker {
+    Process(Child),
+    WorkerThread(WorkerThread),
+}
+
+impl NodeJsWorker {
+    fn id(&self) -> Option<u32> {
+        match self {
+            NodeJsWorker::Process(child) => child.id(),
+            NodeJsWorker::WorkerThread(worker) => Some(worker.id()),
+        }
+    }
+
+    fn start_kill(&mut self) {
+        match self {
+            NodeJsWorker::Process(child) => {
+                let _ = child.start_kill();
+            }
+            NodeJsWorker::WorkerThread(worker) => worker.start_kill(),
+        }
+    }
+
+    /// Waits until the worker exits and returns its exit code. Processes that were terminated by
+    /// a signal have no exit code.
+    async fn wait(&mut self) -> Result<Option<i32>> {
+        Ok(match self {
+            NodeJsWorker::Process(child) => child.wait().await?.code(),
+            NodeJsWorker::WorkerThread(worker) => Some(worker.wait().await?),
+        })
+    }
+}
+
 struct NodeJsPoolProcess {
-    child: Option<Child>,
+    worker: Option<NodeJsWorker>,
     connection: TcpStream,
-    stdout_handler: OutputStreamHandler<ChildStdout, Stdout>,
-    stderr_handler: OutputStreamHandler<ChildStderr, Stderr>,
+    stdout_handler: OutputStreamHandler<OutputStream, Stdout>,
+    stderr_handler: OutputStreamHandler<OutputStream, Stderr>,
     debug: bool,
     cpu_time_invested: Duration,
+    /// When the process was last returned to the idle queue.
+    idle_since: Instant,
+    /// Limits the number of live processes across all pools. Released when the process is
+    /// dropped.
+    _worker_permit: OwnedSemaphorePermit,
 }
 
 impl Ord for NodeJsPoolProcess {
@@ -81,10 +127,10 @@ impl Ord for NodeJsPoolProcess {
         self.cpu_time_invested
             .cmp(&other.cpu_time_invested)
             .then_with(|| {
-                self.child
+                self.worker
                     .as_ref()
-                    .map(|c| c.id())
-                    .cmp(&other.child.as_ref().map(|c| c.id()))
+                    .map(|w| w.id())
+                    .cmp(&other.worker.as_ref().map(|w| w.id()))
             })
     }
 }
@@ -298,6 +344,34 @@ impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> OutputStreamHandler<R, W> {
     }
 }
 
+/// The env variables that are passed to every Node.js worker in addition to the env of the pool.
+pub(crate) fn node_base_env() -> Vec<(&'static str, String)> {
+    vec![
+        (
+            "PATH",
+            std::env::var("PATH").expect("the PATH environment variable should always be set"),
+        ),
+        #[cfg(target_family = "windows")]
+        (
+            "SystemRoot",
+            std::env::var("SystemRoot")
+                .expect("the SystemRoot environment variable should always be set"),
+        ),
+    ]
+}
+
+/// A command running `node` in `cwd` with only the [node_base_env] and piped output.
+pub(crate) fn node_command(cwd: &Path) -> Command {
+    let mut cmd = Command::new("node");
+    cmd.current_dir(cwd);
+    cmd.env_clear();
+    cmd.envs(node_base_env());
+    cmd.stderr(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+    cmd.kill_on_drop(true);
+    cmd
+}
+
 impl NodeJsPoolProcess {
     async fn new(
         cwd: &Path,
@@ -309,36 +383,37 @@ impl NodeJsPoolProcess {
         shared_stdout: SharedOutputSet,
         shared_stderr: SharedOutputSet,
         debug: bool,
+        backend: NodeJsBackend,
+        worker_permit: OwnedSemaphorePermit,
     ) -> Result<Self> {
         let guard = duration_span!("Node.js process startup");
         let listener = TcpListener::bind("127.0.0.1:0")
             .await
             .context("binding to a port")?;
         let port = listener.local_addr().context("getting port")?.port();
-        let mut cmd = Command::new("node");
-        cmd.current_dir(cwd);
-        if debug {
-            cmd.arg("--inspect-brk");
-        }
-        cmd.arg(entrypoint);
-        cmd.arg(port.to_string());
-        cmd.env_clear();
-        cmd.env(
-            "PATH",
-            std::env::var("PATH").expect("the PATH environment variable should always be set"),
-        );
-        #[cfg(target_family = "windows")]
-        cmd.env(
-            "SystemRoot",
-            std::env::var("SystemRoot")
-                .expect("the SystemRoot environment variable should always be set"),
-        );
-        cmd.envs(env);
-        cmd.stderr(Stdio::piped());
-        cmd.stdout(Stdio::piped());
-        cmd.kill_on_drop(true);
 
-        let mut child = cmd.spawn().context("spawning node pooled process")?;
+        let (mut worker, mut worker_stdout, mut worker_stderr) = match backend {
+            // Worker threads can't be debugged with `--inspect-brk` individually
+            NodeJsBackend::WorkerThreads if !debug => {
+                let (worker, stdout, stderr) = spawn_worker_thread(cwd, entrypoint, port, env)
+                    .context("spawning node worker thread")?;
+                (NodeJsWorker::WorkerThread(worker), stdout, stderr)
+            }
+            NodeJsBackend::ChildProcess | NodeJsBackend::WorkerThreads => {
+                let mut cmd = node_command(cwd);
+                if debug {
+                    cmd.arg("--inspect-brk");
+                }
+                cmd.arg(entrypoint);
+                cmd.arg(port.to_string());
+                cmd.envs(env);
+
+                let mut child = cmd.spawn().context("spawning node pooled process")?;
+                let stdout: OutputStream = Box::pin(child.stdout.take().unwrap());
+                let stderr: OutputStream = Box::pin(child.stderr.take().unwrap());
+                (NodeJsWorker::Process(child), stdout, stderr)
+            }
+        };
 
         let timeout = if debug {
             Duration::MAX
@@ -346,21 +421,14 @@ impl NodeJsPoolProcess {
             CONNECT_TIMEOUT
         };
 
-        async fn get_output(child: &mut Child) -> Result<(String, String)> {
+        async fn get_output(
+            stdout_stream: &mut OutputStream,
+            stderr_stream: &mut OutputStream,
+        ) -> Result<(String, String)> {
             let mut stdout = Vec::new();
             let mut stderr = Vec::new();
-            child
-                .stdout
-                .take()
-                .unwrap()
-                .read_to_end(&mut stdout)
-                .await?;
-            child
-                .stderr
-                .take()
-                .unwrap()
-                .read_to_end(&mut stderr)
-                .await?;
+            stdout_stream.read_to_end(&mut stdout).await?;
+            stderr_stream.read_to_end(&mut stderr).await?;
             fn clean(buffer: Vec<u8>) -> Result<String> {
                 Ok(String::from_utf8(buffer)?
                     .lines()
@@ -375,32 +443,31 @@ impl NodeJsPoolProcess {
 
         let (connection, _) = select! {
             connection = listener.accept() => connection.context("accepting connection")?,
-            status = child.wait() => {
+            status = worker.wait() => {
                 match status {
                     Ok(status) => {
-                        let (stdout, stderr) = get_output(&mut child).await?;
+                        let status = status
+                            .map_or_else(|| "a signal".to_string(), |code| format!("exit code {code}"));
+                        let (stdout, stderr) = get_output(&mut worker_stdout, &mut worker_stderr).await?;
                         bail!("node process exited before we could connect to it with {status}\nProcess output:\n{stdout}\nProcess error output:\n{stderr}");
                     }
                     Err(err) => {
-                        let _ = child.start_kill();
-                        let (stdout, stderr) = get_output(&mut child).await?;
+                        worker.start_kill();
+                        let (stdout, stderr) = get_output(&mut worker_stdout, &mut worker_stderr).await?;
                         bail!("node process exited before we could connect to it: {err:?}\nProcess output:\n{stdout}\nProcess error output:\n{stderr}");
                     },
                 }
             },
             _ = sleep(timeout) => {
-                let _ = child.start_kill();
-                let (stdout, stderr) = get_output(&mut child).await?;
+                worker.start_kill();
+                let (stdout, stderr) = get_output(&mut worker_stdout, &mut worker_stderr).await?;
                 bail!("timed out waiting for the Node.js process to connect ({timeout:?} timeout)\nProcess output:\n{stdout}\nProcess error output:\n{stderr}");
             },
         };
         connection.set_nodelay(true)?;
 
-        let child_stdout = BufReader::new(child.stdout.take().unwrap());
-        let child_stderr = BufReader::new(child.stderr.take().unwrap());
-
         let stdout_handler = OutputStreamHandler {
-            stream: child_stdout,
+            stream: BufReader::new(worker_stdout),
             shared: shared_stdout,
             assets_for_source_mapping,
             root: assets_root.clone(),
@@ -408,7 +475,7 @@ impl NodeJsPoolProcess {
             final_stream: stdout(),
         };
         let stderr_handler = OutputStreamHandler {
-            stream: child_stderr,
+            stream: BufReader::new(worker_stderr),
             shared: shared_stderr,
             assets_for_source_mapping,
             root: assets_root.clone(),
@@ -417,12 +484,14 @@ impl NodeJsPoolProcess {
         };
 
         let mut process = Self {
-            child: Some(child),
+            worker: Some(worker),
             connection,
             stdout_handler,
             stderr_handler,
             debug,
             cpu_time_invested: Duration::ZERO,
+            idle_since: Instant::now(),
+            _worker_permit: worker_permit,
         };
 
         drop(guard);
@@ -487,6 +556,16 @@ impl NodeJsPoolProcess {
         Ok(result)
     }
 
+    /// Returns why the process should be stopped instead of being reused, if it should.
+    fn recycle_reason(&self) -> Option<&'static str> {
+        let max_rss = pool_policy().max_worker_rss?;
+        // Worker threads are limited by their `resourceLimits` instead.
+        let Some(NodeJsWorker::Process(child)) = &self.worker else {
+            return None;
+        };
+        rss_recycle_reason(process_rss(child.id()?)?, max_rss)
+    }
+
     async fn send(&mut self, packet_data: Vec<u8>) -> Result<()> {
         self.connection
             .write_u32(
@@ -509,6 +588,10 @@ impl NodeJsPoolProcess {
     }
 }
 
+fn rss_recycle_reason(rss: u64, max_rss: u64) -> Option<&'static str> {
+    (rss > max_rss).then_some("memory limit exceeded")
+}
+
 #[derive(Default)]
 struct NodeJsPoolStats {
     pub total_bootup_time: Duration,
@@ -520,6 +603,10 @@ struct NodeJsPoolStats {
     pub workers: u32,
     pub booting_workers: u32,
     pub queued_tasks: u32,
+    pub total_queue_wait_time: Duration,
+    pub acquired_count: u32,
+    /// Workers that were stopped because they crashed or were recycled.
+    pub restarts: u32,
 }
 
 impl NodeJsPoolStats {
@@ -545,6 +632,24 @@ impl NodeJsPoolStats {
         self.queued_tasks += 1;
     }
 
+    fn add_queue_wait_time(&mut self, time: Duration) {
+        self.total_queue_wait_time += time;
+        self.acquired_count += 1;
+    }
+
+    fn add_restart(&mut self) {
+        self.remove_worker();
+        self.restarts += 1;
+    }
+
+    fn average_queue_wait_time(&self) -> Duration {
+        if self.acquired_count == 0 {
+            Duration::ZERO
+        } else {
+            self.total_queue_wait_time / self.acquired_count
+        }
+    }
+
     fn add_cold_process_time(&mut self, time: Duration) {
         self.total_cold_process_time += time;
         self.cold_process_count += 1;
@@ -650,6 +755,8 @@ impl Debug for NodeJsPoolStats {
             .field("bootup_count", &self.bootup_count)
             .field("cold_process_count", &self.cold_process_count)
             .field("warm_process_count", &self.warm_process_count)
+            .field("queue_wait_time", &self.average_queue_wait_time())
+            .field("restarts", &self.restarts)
             .finish()
     }
 }
@@ -676,6 +783,95 @@ type IdleProcessQueues = Mutex<Vec<Arc<HeapQueue<NodeJsPoolProcess>>>>;
 /// This is used to scale down processes globally.
 static ACTIVE_POOLS: Lazy<IdleProcessQueues> = Lazy::new(Default::default);
 
+/// Stops an idle item of any of the queues, which frees its permit. Returns `false` when there is
+/// no idle item.
+fn stop_idle_item<T: Ord>(queues: &Mutex<Vec<Arc<HeapQueue<T>>>>) -> bool {
+    let pools = queues.lock().clone();
+    pools
+        .iter()
+        .any(|pool| !pool.remove_where(queues, 1, |_| true).is_empty())
+}
+
+/// Removes the items of all queues that have been idle for longer than `idle_timeout`.
+fn remove_expired_items<T: Ord>(
+    queues: &Mutex<Vec<Arc<HeapQueue<T>>>>,
+    idle_timeout: Duration,
+    idle_since: impl Fn(&T) -> Instant,
+) -> Vec<T> {
+    let pools = queues.lock().clone();
+    pools
+        .into_iter()
+        .flat_map(|pool| {
+            pool.remove_where(queues, usize::MAX, |item| {
+                idle_since(item).elapsed() > idle_timeout
+            })
+        })
+        .collect()
+}
+
+/// Acquires one of the `reserved` permits of a pool or one of the shared `permits`. When all of
+/// them are taken, idle items of the `queues` are stopped to make room. Otherwise it waits until a
+/// permit is released or an item becomes idle.
+async fn acquire_permit<T: Ord>(
+    reserved: &Arc<Semaphore>,
+    permits: &Arc<Semaphore>,
+    idle_notify: &Notify,
+    queues: &Mutex<Vec<Arc<HeapQueue<T>>>>,
+) -> Result<OwnedSemaphorePermit> {
+    loop {
+        let idle = idle_notify.notified();
+        tokio::pin!(idle);
+        idle.as_mut().enable();
+        if let Ok(permit) = reserved.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+        if let Ok(permit) = permits.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+        if stop_idle_item(queues) {
+            continue;
+        }
+        select! {
+            permit = reserved.clone().acquire_owned() => {
+                return permit.context("acquiring reserved worker permit");
+            }
+            permit = permits.clone().acquire_owned() => {
+                return permit.context("acquiring worker permit");
+            }
+            _ = idle => {}
+        }
+    }
+}
+
+/// Acquires a permit for starting a new process of a pool with the `reserved` permits. When all
+/// permits are taken, idle processes of other pools are stopped to make room.
+async fn acquire_worker_permit(reserved: &Arc<Semaphore>) -> Result<OwnedSemaphorePermit> {
+    acquire_permit(reserved, &WORKER_PERMITS, &WORKER_IDLE, &ACTIVE_POOLS).await
+}
+
+/// Periodically stops processes that exceeded the idle timeout of the pool policy.
+fn start_idle_timeout() {
+    static STARTED: Once = Once::new();
+    let Some(idle_timeout) = pool_policy().idle_timeout else {
+        return;
+    };
+    STARTED.call_once(|| {
+        tokio::spawn(async move {
+            loop {
+                sleep(max(idle_timeout / 2, Duration::from_secs(1))).await;
+                let stopped =
+                    remove_expired_items(&ACTIVE_POOLS, idle_timeout, |process| process.idle_since);
+                for process in stopped {
+                    let _span =
+                        tracing::info_span!("stop idle Node.js worker", reason = "idle timeout")
+                            .entered();
+                    drop(process);
+                }
+            }
+        });
+    });
+}
+
 /// A pool of Node.js workers operating on [entrypoint] with specific [cwd] and
 /// [env].
 ///
@@ -685,6 +881,9 @@ static ACTIVE_POOLS: Lazy<IdleProcessQueues> = Lazy::new(Default::default);
 ///
 /// The worker will *not* use the env of the parent process by default. All env
 /// vars need to be provided to make the execution as pure as possible.
+///
+/// Depending on the [NodeJsBackend], workers are separate processes or worker threads of a
+/// shared host process.
 #[turbo_tasks::value(cell = "new", serialization = "none", eq = "manual", shared)]
 pub struct NodeJsPool {
     cwd: PathBuf,
@@ -702,11 +901,18 @@ pub struct NodeJsPool {
     /// (excludes one-off processes)
     #[turbo_tasks(trace_ignore, debug_ignore)]
     bootup_semaphore: Arc<Semaphore>,
+    /// A worker permit that only this pool uses, in addition to the ones shared by all pools. An
+    /// evaluation can wait on an evaluation in another pool while its worker holds a shared
+    /// permit, e.g. a loader importing a module that runs PostCSS. Every pool can always start one
+    /// worker, so this can't deadlock when all shared permits are taken.
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    reserved_worker_permit: Arc<Semaphore>,
     #[turbo_tasks(trace_ignore, debug_ignore)]
     shared_stdout: SharedOutputSet,
     #[turbo_tasks(trace_ignore, debug_ignore)]
     shared_stderr: SharedOutputSet,
     debug: bool,
+    backend: NodeJsBackend,
     #[turbo_tasks(trace_ignore, debug_ignore)]
     stats: Arc<Mutex<NodeJsPoolStats>>,
 }
@@ -723,7 +929,9 @@ impl NodeJsPool {
         project_dir: FileSystemPath,
         concurrency: usize,
         debug: bool,
+        backend: NodeJsBackend,
     ) -> Self {
+        start_idle_timeout();
         Self {
             cwd,
             entrypoint,
@@ -733,15 +941,39 @@ impl NodeJsPool {
             project_dir,
             concurrency_semaphore: Arc::new(Semaphore::new(if debug { 1 } else { concurrency })),
             bootup_semaphore: Arc::new(Semaphore::new(1)),
+            reserved_worker_permit: Arc::new(Semaphore::new(1)),
             idle_processes: Arc::new(HeapQueue::new()),
             shared_stdout: Arc::new(Mutex::new(FxIndexSet::default())),
             shared_stderr: Arc::new(Mutex::new(FxIndexSet::default())),
             debug,
+            backend,
             stats: Default::default(),
         }
     }
 
     async fn acquire_process(&self) -> Result<(NodeJsPoolProcess, AcquiredPermits)> {
+        let span = tracing::info_span!(
+            "acquire Node.js worker",
+            queue_wait_time = Empty,
+            fresh = Empty,
+            workers = Empty,
+            restarts = Empty
+        );
+        let start = Instant::now();
+        let result = self.acquire_process_inner().instrument(span.clone()).await;
+        let queue_wait_time = start.elapsed();
+        let mut stats = self.stats.lock();
+        stats.add_queue_wait_time(queue_wait_time);
+        span.record("queue_wait_time", queue_wait_time.as_millis() as u64);
+        span.record("workers", stats.workers);
+        span.record("restarts", stats.restarts);
+        if let Ok((_, permits)) = &result {
+            span.record("fresh", matches!(permits, AcquiredPermits::Fresh { .. }));
+        }
+        result
+    }
+
+    async fn acquire_process_inner(&self) -> Result<(NodeJsPoolProcess, AcquiredPermits)> {
         {
             self.stats.lock().add_queued_task();
         }
@@ -752,7 +984,8 @@ impl NodeJsPool {
             let permit = self.bootup_semaphore.clone().acquire_owned().await;
             let wait_time = self.stats.lock().wait_time_before_bootup();
             tokio::time::sleep(wait_time).await;
-            permit
+            let worker_permit = acquire_worker_permit(&self.reserved_worker_permit).await;
+            (permit, worker_permit)
         };
 
         select! {
@@ -760,12 +993,13 @@ impl NodeJsPool {
                 let process = idle_process_result.context("acquiring idle process permit")?;
                 Ok((process, AcquiredPermits::Idle { concurrency_permit }))
             },
-            bootup_permit = bootup => {
+            (bootup_permit, worker_permit) = bootup => {
                 let bootup_permit = bootup_permit.context("acquiring bootup permit")?;
+                let worker_permit = worker_permit?;
                 {
                     self.stats.lock().add_booting_worker();
                 }
-                let (process, bootup_time) = self.create_process().await?;
+                let (process, bootup_time) = self.create_process(worker_permit).await?;
                 // Update the worker count
                 {
                     let mut stats = self.stats.lock();
@@ -779,7 +1013,10 @@ impl NodeJsPool {
         }
     }
 
-    async fn create_process(&self) -> Result<(NodeJsPoolProcess, Duration), anyhow::Error> {
+    async fn create_process(
+        &self,
+        worker_permit: OwnedSemaphorePermit,
+    ) -> Result<(NodeJsPoolProcess, Duration), anyhow::Error> {
         let start = Instant::now();
         let process = NodeJsPoolProcess::new(
             self.cwd.as_path(),
@@ -791,6 +1028,8 @@ impl NodeJsPool {
             self.shared_stdout.clone(),
             self.shared_stderr.clone(),
             self.debug,
+            self.backend,
+            worker_permit,
         )
         .await
         .context("creating new process")?;
@@ -823,6 +1062,7 @@ impl NodeJsPool {
         for pool in pools {
             pool.reduce_to_zero(&ACTIVE_POOLS);
         }
+        stop_unused_hosts();
     }
 }
 
@@ -853,7 +1093,7 @@ impl NodeJsOperation {
 
         let result = f(process).await;
         if result.is_err() && self.allow_process_reuse {
-            self.stats.lock().remove_worker();
+            self.stats.lock().add_restart();
             self.allow_process_reuse = false;
         }
         result
@@ -887,7 +1127,9 @@ impl NodeJsOperation {
         .await
     }
 
-    pub async fn wait_or_kill(mut self) -> Result<ExitStatus> {
+    /// Stops the worker and returns its exit code, which is `None` for processes that were
+    /// terminated by a signal.
+    pub async fn wait_or_kill(mut self) -> Result<Option<i32>> {
         let mut process = self
             .process
             .take()
@@ -897,14 +1139,14 @@ impl NodeJsOperation {
             self.stats.lock().remove_worker();
         }
 
-        let mut child = process
-            .child
+        let mut worker = process
+            .worker
             .take()
             .context("Node.js operation already finished")?;
 
         // Ignore error since we are not sure if the process is still alive
-        let _ = child.start_kill();
-        let status = timeout(Duration::from_secs(30), child.wait())
+        worker.start_kill();
+        let status = timeout(Duration::from_secs(30), worker.wait())
             .await
             .context("timeout while waiting for process end")?
             .context("waiting for process end")?;
@@ -932,9 +1174,191 @@ impl Drop for NodeJsOperation {
                 }
             }
             if self.allow_process_reuse {
-                process.cpu_time_invested += elapsed;
-                self.idle_processes.push(process, &ACTIVE_POOLS);
+                if let Some(reason) = process.recycle_reason() {
+                    let _span = tracing::info_span!("stop Node.js worker", reason).entered();
+                    self.stats.lock().add_restart();
+                    drop(process);
+                } else {
+                    process.cpu_time_invested += elapsed;
+                    process.idle_since = Instant::now();
+                    self.idle_processes.push(process, &ACTIVE_POOLS);
+                    WORKER_IDLE.notify_waiters();
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An idle worker that holds its permit, ordered by `id`.
+    struct IdleWorker {
+        id: usize,
+        idle_since: Instant,
+        _permit: OwnedSemaphorePermit,
+    }
+
+    impl Ord for IdleWorker {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.id.cmp(&other.id)
+        }
+    }
+
+    impl PartialOrd for IdleWorker {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Eq for IdleWorker {}
+
+    impl PartialEq for IdleWorker {
+        fn eq(&self, other: &Self) -> bool {
+            self.id == other.id
+        }
+    }
+
+    type Queues = Mutex<Vec<Arc<HeapQueue<IdleWorker>>>>;
+
+    #[tokio::test]
+    async fn test_permits_limit_workers() {
+        let reserved = Arc::new(Semaphore::new(0));
+        let permits = Arc::new(Semaphore::new(2));
+        let idle_notify = Notify::new();
+        let queues: Queues = Default::default();
+        let acquire = || acquire_permit(&reserved, &permits, &idle_notify, &queues);
+
+        let first = acquire().await.unwrap();
+        let second = acquire().await.unwrap();
+        assert!(
+            timeout(Duration::from_millis(50), acquire()).await.is_err(),
+            "no permit is available while all workers are busy"
+        );
+
+        // An idle worker of any pool is stopped to make room.
+        let queue = Arc::new(HeapQueue::new());
+        queue.push(
+            IdleWorker {
+                id: 0,
+                idle_since: Instant::now(),
+                _permit: second,
+            },
+            &queues,
+        );
+        let third = acquire().await.unwrap();
+        assert!(queues.lock().is_empty());
+        assert_eq!(permits.available_permits(), 0);
+
+        // Waiters are woken up when a permit is released...
+        let (permit, _) = join!(acquire(), async {
+            sleep(Duration::from_millis(10)).await;
+            drop(first);
+        });
+        let fourth = permit.unwrap();
+
+        // ...or when a worker becomes idle.
+        let (permit, _) = join!(acquire(), async {
+            sleep(Duration::from_millis(10)).await;
+            queue.push(
+                IdleWorker {
+                    id: 1,
+                    idle_since: Instant::now(),
+                    _permit: fourth,
+                },
+                &queues,
+            );
+            idle_notify.notify_waiters();
+        });
+        permit.unwrap();
+        assert!(queues.lock().is_empty());
+        drop(third);
+        assert_eq!(permits.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reserved_permits_avoid_deadlocks() {
+        let permits = Arc::new(Semaphore::new(1));
+        let idle_notify = Notify::new();
+        let queues: Queues = Default::default();
+        let outer_pool = Arc::new(Semaphore::new(1));
+        let nested_pool = Arc::new(Semaphore::new(1));
+        let acquire_outer = || acquire_permit(&outer_pool, &permits, &idle_notify, &queues);
+        let acquire_nested = || acquire_permit(&nested_pool, &permits, &idle_notify, &queues);
+
+        // The busy workers of a pool use its reserved permit and all shared permits.
+        let _outer_first = acquire_outer().await.unwrap();
+        let _outer_second = acquire_outer().await.unwrap();
+        assert_eq!(permits.available_permits(), 0);
+        assert!(
+            timeout(Duration::from_millis(50), acquire_outer())
+                .await
+                .is_err(),
+            "the shared limit applies to additional workers"
+        );
+
+        // An evaluation they wait on can still start a worker in another pool.
+        let nested = timeout(Duration::from_millis(50), acquire_nested())
+            .await
+            .expect("the nested pool has no worker")
+            .unwrap();
+        assert!(
+            timeout(Duration::from_millis(50), acquire_nested())
+                .await
+                .is_err()
+        );
+
+        // A waiting pool gets its reserved permit back when its worker stops.
+        let (permit, _) = join!(acquire_nested(), async {
+            sleep(Duration::from_millis(10)).await;
+            drop(nested);
+        });
+        permit.unwrap();
+        assert_eq!(nested_pool.available_permits(), 0);
+    }
+
+    #[test]
+    fn test_idle_workers_expire() {
+        let permits = Arc::new(Semaphore::new(2));
+        let queues: Queues = Default::default();
+        let queue = Arc::new(HeapQueue::new());
+        for (id, idle_for) in [(0, 10), (1, 3)] {
+            queue.push(
+                IdleWorker {
+                    id,
+                    idle_since: Instant::now() - Duration::from_secs(idle_for),
+                    _permit: permits.clone().try_acquire_owned().unwrap(),
+                },
+                &queues,
+            );
+        }
+
+        let expired = remove_expired_items(&queues, Duration::from_secs(5), |w| w.idle_since);
+        assert_eq!(expired.iter().map(|w| w.id).collect::<Vec<_>>(), vec![0]);
+        drop(expired);
+        assert_eq!(permits.available_permits(), 1);
+        assert_eq!(queues.lock().len(), 1);
+
+        let expired = remove_expired_items(&queues, Duration::from_secs(1), |w| w.idle_since);
+        assert_eq!(expired.iter().map(|w| w.id).collect::<Vec<_>>(), vec![1]);
+        assert!(queues.lock().is_empty());
+    }
+
+    #[test]
+    fn test_rss_recycling() {
+        const MIB: u64 = 1024 * 1024;
+        assert_eq!(rss_recycle_reason(100 * MIB, 512 * MIB), None);
+        assert_eq!(rss_recycle_reason(512 * MIB, 512 * MIB), None);
+        assert_eq!(
+            rss_recycle_reason(600 * MIB, 512 * MIB),
+            Some("memory limit exceeded")
+        );
+
+        if cfg!(target_os = "linux") {
+            let rss = process_rss(std::process::id()).unwrap();
+            assert!(rss > 0);
+            assert!(rss_recycle_reason(rss, 1).is_some());
+        }
+    }
+}
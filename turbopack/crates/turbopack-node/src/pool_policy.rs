@@ -0,0 +1,199 @@
+//! Limits shared by all Node.js worker pools of the process.
+//!
+//! Every pool scales up to the available parallelism on its own, so many pools (e.g. one per
+//! PostCSS config and webpack loader rule) can spawn dozens of workers at once. The policy caps
+//! the number of live workers across all pools, by default based on the available memory, and
+//! recycles workers that grow too large or stay idle for too long.
+
+use std::{
+    sync::{Arc, OnceLock},
+    thread::available_parallelism,
+    time::Duration,
+};
+
+use anyhow::{Result, bail};
+use once_cell::sync::Lazy;
+use tokio::sync::{Notify, Semaphore};
+
+/// The memory a Node.js worker is assumed to use when deriving the number of workers from the
+/// available memory.
+const DEFAULT_WORKER_MEMORY_ESTIMATE: u64 = 512 * 1024 * 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeJsPoolPolicy {
+    /// The maximum number of Node.js workers that are alive at the same time, across all pools.
+    /// When `None`, it's derived from the available memory and parallelism. Every pool can start
+    /// one worker beyond this limit, so that evaluations waiting on other pools can't deadlock.
+    pub max_workers: Option<usize>,
+    /// The memory a single worker is expected to use, in bytes. Used to derive the maximum
+    /// number of workers from the available memory.
+    pub worker_memory_estimate: u64,
+    /// Workers whose resident set size exceeds this number of bytes after an operation are
+    /// stopped instead of being reused. Only supported on Linux.
+    pub max_worker_rss: Option<u64>,
+    /// Workers that haven't been used for this long are stopped.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl Default for NodeJsPoolPolicy {
+    fn default() -> Self {
+        Self {
+            max_workers: None,
+            worker_memory_estimate: DEFAULT_WORKER_MEMORY_ESTIMATE,
+            max_worker_rss: None,
+            idle_timeout: None,
+        }
+    }
+}
+
+static POLICY: OnceLock<NodeJsPoolPolicy> = OnceLock::new();
+
+/// Configures the limits of all Node.js worker pools. It needs to be called before the first
+/// Node.js worker is started. Setting it again is only allowed with the same policy, e.g. when a
+/// project is recreated in the same process.
+pub fn set_pool_policy(policy: NodeJsPoolPolicy) -> Result<()> {
+    if let Err(policy) = POLICY.set(policy)
+        && POLICY.get() != Some(&policy)
+    {
+        bail!("The Node.js pool policy has already been set or used");
+    }
+    Ok(())
+}
+
+pub(crate) fn pool_policy() -> &'static NodeJsPoolPolicy {
+    POLICY.get_or_init(Default::default)
+}
+
+/// A permit for every live worker. Workers hold their permit until they are stopped.
+pub(crate) static WORKER_PERMITS: Lazy<Arc<Semaphore>> = Lazy::new(|| {
+    let policy = pool_policy();
+    let max_workers = policy.max_workers.unwrap_or_else(|| {
+        derive_max_workers(
+            available_parallelism().map_or(1, |v| v.get()),
+            available_memory(),
+            policy.worker_memory_estimate,
+        )
+    });
+    Arc::new(Semaphore::new(max_workers.max(1)))
+});
+
+/// Notified whenever a worker becomes idle, which allows waiting for a worker permit to stop it.
+pub(crate) static WORKER_IDLE: Notify = Notify::const_new();
+
+/// The number of workers that fit into the available memory, bounded by twice the parallelism
+/// since pools are mostly busy at different times.
+fn derive_max_workers(
+    parallelism: usize,
+    available_memory: Option<u64>,
+    worker_memory_estimate: u64,
+) -> usize {
+    let max_workers = parallelism * 2;
+    match available_memory {
+        Some(available_memory) => {
+            let fitting_workers = available_memory / worker_memory_estimate.max(1);
+            (fitting_workers as usize).clamp(1, max_workers)
+        }
+        None => max_workers,
+    }
+}
+
+/// Parses the `MemAvailable` entry of `/proc/meminfo` into bytes.
+#[cfg(any(target_os = "linux", test))]
+fn parse_meminfo_available(meminfo: &str) -> Option<u64> {
+    parse_kb_entry(meminfo, "MemAvailable:")
+}
+
+/// Parses the `VmRSS` entry of `/proc/<pid>/status` into bytes.
+#[cfg(any(target_os = "linux", test))]
+fn parse_status_rss(status: &str) -> Option<u64> {
+    parse_kb_entry(status, "VmRSS:")
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn parse_kb_entry(content: &str, key: &str) -> Option<u64> {
+    let line = content.lines().find(|line| line.starts_with(key))?;
+    let kb: u64 = line[key.len()..]
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+/// Parses a cgroup memory limit or usage. cgroup v2 uses `max` for no limit.
+#[cfg(any(target_os = "linux", test))]
+fn parse_cgroup_value(value: &str) -> Option<u64> {
+    value.trim().parse().ok()
+}
+
+/// The memory that is available for new workers, considering the memory limit of the cgroup
+/// (e.g. of a container) the process runs in.
+#[cfg(target_os = "linux")]
+fn available_memory() -> Option<u64> {
+    let read = |path: &str| std::fs::read_to_string(path).ok();
+    let system = read("/proc/meminfo").and_then(|meminfo| parse_meminfo_available(&meminfo));
+    let cgroup = [
+        // cgroup v2
+        ("/sys/fs/cgroup/memory.max", "/sys/fs/cgroup/memory.current"),
+        // cgroup v1
+        (
+            "/sys/fs/cgroup/memory/memory.limit_in_bytes",
+            "/sys/fs/cgroup/memory/memory.usage_in_bytes",
+        ),
+    ]
+    .into_iter()
+    .find_map(|(limit, usage)| {
+        let limit = parse_cgroup_value(&read(limit)?)?;
+        let usage = parse_cgroup_value(&read(usage)?)?;
+        Some(limit.saturating_sub(usage))
+    });
+    match (system, cgroup) {
+        (Some(system), Some(cgroup)) => Some(system.min(cgroup)),
+        (system, cgroup) => system.or(cgroup),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn available_memory() -> Option<u64> {
+    None
+}
+
+/// The resident set size of a process in bytes.
+#[cfg(target_os = "linux")]
+pub(crate) fn process_rss(pid: u32) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    parse_status_rss(&status)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn process_rss(_pid: u32) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    #[test]
+    fn test_derive_max_workers() {
+        assert_eq!(derive_max_workers(8, None, GIB / 2), 16);
+        assert_eq!(derive_max_workers(8, Some(4 * GIB), GIB / 2), 8);
+        assert_eq!(derive_max_workers(8, Some(64 * GIB), GIB / 2), 16);
+        assert_eq!(derive_max_workers(8, Some(GIB / 4), GIB / 2), 1);
+    }
+
+    #[test]
+    fn test_parse_proc_files() {
+        let meminfo = "MemTotal:       16318976 kB\nMemFree:         1218976 kB\nMemAvailable:    \
+                       8159488 kB\n";
+        assert_eq!(parse_meminfo_available(meminfo), Some(8159488 * 1024));
+        let status = "Name:\tnode\nVmPeak:\t  123456 kB\nVmRSS:\t   98304 kB\n";
+        assert_eq!(parse_status_rss(status), Some(98304 * 1024));
+        assert_eq!(parse_status_rss("Name:\tnode\n"), None);
+        assert_eq!(parse_cgroup_value("2147483648\n"), Some(2 * GIB));
+        assert_eq!(parse_cgroup_value("max\n"), None);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod persistent_cache;
 pub mod postcss;
 mod util;
 pub mod webpack;
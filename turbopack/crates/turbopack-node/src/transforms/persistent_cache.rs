@@ -0,0 +1,573 @@
+//! A persistent on-disk cache for the results of webpack loaders and PostCSS.
+//!
+//! Loader results are cached by turbo-tasks too, but that cache is invalidated on every compiler
+//! upgrade, which makes the first build after an upgrade rerun expensive pipelines like Tailwind.
+//! This cache lives in its own directory and is versioned by [CACHE_VERSION] only.
+//!
+//! Entries are keyed by the loader arguments (the loader chain with its options, the resource and
+//! its content), the content of the project's lockfile (which pins the versions of installed
+//! loaders, in workspaces it is in a parent directory), the
+//! [WebpackLoaderContext::persistent_cache_inputs] (e.g. the resolved loader files) and the project
+//! files bundled into the loader runner (e.g. the PostCSS config and its imports). Each entry
+//! records the dependencies the loaders reported while running, i.e. files, directories, env
+//! variables, build dependencies and resolved requests, as well as the project files Node.js loaded
+//! for them (local and workspace loaders, PostCSS plugins, Tailwind configs and everything they
+//! require), together with hashes of their state. An entry is only used when all of them are
+//! unchanged. Checking them reads them through turbo-tasks, so a cache hit is invalidated like a
+//! loader execution would be.
+//!
+//! Without a lockfile the installed loader versions are unknown, so nothing is cached.
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_rcstr::RcStr;
+use turbo_tasks::{ResolvedVc, TryFlatJoinIterExt, TryJoinIterExt, TurboTasksApi};
+use turbo_tasks_fs::{
+    DirectoryEntry, FileContent, FileSystemPath, ReadGlobResult,
+    glob::{Glob, GlobOptions},
+    persistent_cache::{CacheKeyHasher, PersistentCache},
+};
+use turbopack_core::{
+    issue::{IssueExt, IssueSource},
+    module::Module,
+    reference_type::ReferenceType,
+    resolve::{node::node_cjs_resolve_options, parse::Request, pattern::Pattern, resolve},
+};
+
+use super::webpack::{BuildDependencyIssue, WebpackLoaderContext, WebpackResolveOptions};
+
+/// Needs to be increased when the format of cache entries or the loader runner code (e.g.
+/// `webpack-loaders.ts` or `postcss.ts`) changes.
+const CACHE_VERSION: u32 = 3;
+
+/// Loader results are mostly stylesheets and transformed modules, this keeps a few thousand of
+/// them.
+const MAX_CACHE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// The lockfiles of the supported package managers.
+const LOCKFILES: [&str; 4] = [
+    "pnpm-lock.yaml",
+    "package-lock.json",
+    "yarn.lock",
+    "bun.lock",
+];
+
+static CACHE: PersistentCache = PersistentCache::new(CACHE_VERSION, MAX_CACHE_SIZE);
+
+/// Enables the persistent loader cache in the given directory for the tasks executed by
+/// `turbo_tasks`.
+pub fn init_persistent_loader_cache(
+    turbo_tasks: &Arc<dyn TurboTasksApi>,
+    directory: impl Into<PathBuf>,
+) {
+    CACHE.init(turbo_tasks, directory);
+}
+
+/// Whether the persistent loader cache is enabled for the current task.
+pub(super) fn is_enabled() -> bool {
+    CACHE.is_enabled()
+}
+
+/// A request resolved on behalf of a loader.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedResolve {
+    pub options: WebpackResolveOptions,
+    pub lookup_path: RcStr,
+    pub request: RcStr,
+    /// The resolved path, relative to the project.
+    pub path: RcStr,
+}
+
+/// The dependencies reported by loaders while they run. All paths are relative to the project.
+#[derive(Default, Debug)]
+pub struct LoaderDependencies {
+    pub env_variables: Vec<RcStr>,
+    pub files: Vec<RcStr>,
+    pub directories: Vec<(RcStr, RcStr)>,
+    pub build_files: Vec<RcStr>,
+    /// The project files Node.js loaded while the loaders ran, outside of `node_modules`.
+    pub loaded_modules: Vec<RcStr>,
+    pub resolves: Vec<RecordedResolve>,
+}
+
+impl LoaderDependencies {
+    pub(super) fn record(
+        &mut self,
+        env_variables: &[RcStr],
+        files: &[RcStr],
+        directories: &[(RcStr, RcStr)],
+        build_files: &[RcStr],
+        loaded_modules: &[RcStr],
+    ) {
+        self.env_variables.extend_from_slice(env_variables);
+        self.files.extend_from_slice(files);
+        self.directories.extend_from_slice(directories);
+        self.build_files.extend_from_slice(build_files);
+        self.loaded_modules.extend_from_slice(loaded_modules);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    /// The JSON encoded result of the loader runner.
+    result: String,
+    /// Env variable names with a hash of their value, so that secrets aren't stored in the cache.
+    env_variables: Vec<(RcStr, Option<String>)>,
+    files: Vec<(RcStr, Option<String>)>,
+    directories: Vec<(RcStr, RcStr, String)>,
+    build_files: Vec<(RcStr, Option<String>)>,
+    loaded_modules: Vec<(RcStr, Option<String>)>,
+    resolves: Vec<RecordedResolve>,
+}
+
+fn content_hash(content: impl AsRef<[u8]>) -> String {
+    let mut hasher = CacheKeyHasher::new();
+    hasher.write(content);
+    hasher.finish()
+}
+
+/// Hashes the content of a file. Returns `None` when the file doesn't exist.
+async fn file_hash(path: &FileSystemPath) -> Result<Option<String>> {
+    Ok(match &*path.read().await? {
+        FileContent::Content(file) => Some(content_hash(&*file.content().to_bytes())),
+        FileContent::NotFound => None,
+    })
+}
+
+/// Hashes the lockfile of the project, which is in `cwd` or, for workspaces, in one of its parent
+/// directories. Returns `None` when there is no lockfile.
+async fn lockfile_hash(cwd: &FileSystemPath) -> Result<Option<String>> {
+    let mut dir = cwd.clone();
+    loop {
+        let mut hasher = CacheKeyHasher::new();
+        let mut has_lockfile = false;
+        for lockfile in LOCKFILES {
+            let hash = file_hash(&dir.join(lockfile)?).await?;
+            has_lockfile |= hash.is_some();
+            hasher.write_option(hash);
+        }
+        if has_lockfile {
+            hasher.write(dir.path.as_str());
+            return Ok(Some(hasher.finish()));
+        }
+        if dir.is_root() {
+            return Ok(None);
+        }
+        dir = dir.parent();
+    }
+}
+
+/// Hashes the paths and contents of all files in `directory` matching `glob`.
+async fn directory_hash(directory: &FileSystemPath, glob: &RcStr) -> Result<String> {
+    let mut entries = Vec::new();
+    let mut queue: Vec<ResolvedVc<ReadGlobResult>> = vec![
+        directory
+            .read_glob(Glob::new(glob.clone(), GlobOptions::default()))
+            .to_resolved()
+            .await?,
+    ];
+    while let Some(result) = queue.pop() {
+        let result = result.await?;
+        for entry in result.results.values() {
+            match entry {
+                DirectoryEntry::File(path) => entries.push((path.clone(), true)),
+                DirectoryEntry::Directory(path)
+                | DirectoryEntry::Symlink(path)
+                | DirectoryEntry::Other(path) => entries.push((path.clone(), false)),
+                DirectoryEntry::Error(_) => {}
+            }
+        }
+        queue.extend(result.inner.values().copied());
+    }
+    let mut entries = entries
+        .into_iter()
+        .map(async |(path, is_file)| {
+            let hash = if is_file {
+                file_hash(&path).await?
+            } else {
+                None
+            };
+            Ok((path.path, hash))
+        })
+        .try_join()
+        .await?;
+    entries.sort();
+
+    let mut hasher = CacheKeyHasher::new();
+    for (path, hash) in entries {
+        hasher.write(path.as_str());
+        hasher.write_option(hash);
+    }
+    Ok(hasher.finish())
+}
+
+async fn env_variable_hash(context: &WebpackLoaderContext, name: &RcStr) -> Result<Option<String>> {
+    let value = context.env.read(name.clone()).await?;
+    Ok(value.as_ref().map(|value| content_hash(value.as_str())))
+}
+
+async fn files_with_hashes(
+    context: &WebpackLoaderContext,
+    files: &[RcStr],
+) -> Result<Vec<(RcStr, Option<String>)>> {
+    let mut files = files
+        .iter()
+        .map(async |file| Ok((file.clone(), file_hash(&context.cwd.join(file)?).await?)))
+        .try_join()
+        .await?;
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Resolves the `loaders` in `cwd` like the loader runner does, so that their files can be part of
+/// the cache key. Returns nothing when the cache is disabled. Loaders that can't be resolved are
+/// skipped, the loader runner reports them.
+pub(super) async fn resolve_loader_files(
+    cwd: &FileSystemPath,
+    loaders: impl IntoIterator<Item = RcStr>,
+) -> Result<Vec<FileSystemPath>> {
+    if !is_enabled() {
+        return Ok(Vec::new());
+    }
+    let options = node_cjs_resolve_options(cwd.root().owned().await?);
+    let files = loaders
+        .into_iter()
+        .map(async |loader| {
+            let result = resolve(
+                cwd.clone(),
+                ReferenceType::Undefined,
+                Request::parse(Pattern::Constant(loader)),
+                options,
+            );
+            Ok(match *result.first_source().await? {
+                Some(source) => Some(source.ident().path().owned().await?),
+                None => None,
+            })
+        })
+        .try_flat_join()
+        .await?;
+    Ok(files)
+}
+
+/// Whether `path` is part of an installed package, whose version is pinned by the lockfile.
+fn is_in_node_modules(path: &str) -> bool {
+    path.split('/').any(|segment| segment == "node_modules")
+}
+
+/// Hashes the project files that are bundled into the loader runner, e.g. the PostCSS config and
+/// the files it imports. Installed packages are covered by the lockfile.
+async fn bundled_project_files_hash(context: &WebpackLoaderContext) -> Result<String> {
+    let graph = context.module_graph.read_graphs().await?;
+    let paths = graph
+        .graphs
+        .iter()
+        .flat_map(|graph| graph.iter_nodes())
+        .map(async |module| module.ident().path().owned().await)
+        .try_join()
+        .await?;
+    let mut files = paths
+        .into_iter()
+        .filter(|path| path.fs == context.cwd.fs && !is_in_node_modules(&path.path))
+        .map(async |path| Ok((path.path.clone(), file_hash(&path).await?)))
+        .try_join()
+        .await?;
+    files.sort();
+    files.dedup();
+
+    let mut hasher = CacheKeyHasher::new();
+    for (path, hash) in files {
+        hasher.write(path.as_str());
+        hasher.write_option(hash);
+    }
+    Ok(hasher.finish())
+}
+
+/// Computes the cache key for the evaluation of `context`. Returns `None` when the cache is
+/// disabled or the evaluation can't be cached.
+pub(super) async fn cache_key(context: &WebpackLoaderContext) -> Result<Option<String>> {
+    if !is_enabled() {
+        return Ok(None);
+    }
+    let Some(lockfile_hash) = lockfile_hash(&context.cwd).await? else {
+        return Ok(None);
+    };
+    let mut hasher = CacheKeyHasher::new();
+    hasher.write(lockfile_hash);
+
+    for input in &context.persistent_cache_inputs {
+        hasher.write(input.path.as_str());
+        hasher.write_option(file_hash(input).await?);
+    }
+    hasher.write(bundled_project_files_hash(context).await?);
+    for arg in context.args.iter().try_join().await? {
+        hasher.write(serde_json::to_string(&*arg)?);
+    }
+    hasher.write(context.cwd.path.as_str());
+
+    Ok(Some(hasher.finish()))
+}
+
+/// Reads an entry. Missing, unreadable and corrupted entries are treated as cache misses.
+async fn read_entry(key: &str) -> Option<CacheEntry> {
+    serde_json::from_slice(&CACHE.read(key).await?).ok()
+}
+
+async fn write_entry(key: &str, entry: &CacheEntry) {
+    if let Ok(content) = serde_json::to_vec(entry) {
+        CACHE.write(key, content).await;
+    }
+}
+
+/// Reads a cached result and checks that the recorded dependencies are unchanged.
+pub(super) async fn read(key: &str, context: &WebpackLoaderContext) -> Result<Option<RcStr>> {
+    let Some(entry) = read_entry(key).await else {
+        return Ok(None);
+    };
+
+    for (name, hash) in &entry.env_variables {
+        if env_variable_hash(context, name).await? != *hash {
+            return Ok(None);
+        }
+    }
+    for (file, hash) in entry
+        .files
+        .iter()
+        .chain(&entry.build_files)
+        .chain(&entry.loaded_modules)
+    {
+        if file_hash(&context.cwd.join(file)?).await? != *hash {
+            return Ok(None);
+        }
+    }
+    for (dir, glob, hash) in &entry.directories {
+        if directory_hash(&context.cwd.join(dir)?, glob).await? != *hash {
+            return Ok(None);
+        }
+    }
+    for resolve in &entry.resolves {
+        let path = context
+            .resolve_request(
+                resolve.options.clone(),
+                &resolve.lookup_path,
+                resolve.request.clone(),
+            )
+            .await;
+        if !matches!(path, Ok(path) if path == resolve.path) {
+            return Ok(None);
+        }
+    }
+
+    // Report the build dependencies again, like running the loaders would.
+    for (build_file, _) in &entry.build_files {
+        BuildDependencyIssue {
+            source: IssueSource::from_source_only(context.context_source_for_issue),
+            path: context.cwd.join(build_file)?,
+        }
+        .resolved_cell()
+        .emit();
+    }
+
+    Ok(Some(entry.result.into()))
+}
+
+/// Stores a result in the cache together with the current state of its dependencies. Writing is
+/// best effort, failures are ignored since the result can always be recomputed.
+pub(super) async fn write(
+    key: &str,
+    result: &str,
+    dependencies: &LoaderDependencies,
+    context: &WebpackLoaderContext,
+) -> Result<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    // Loaded files outside of the filesystem root can't be checked when reading the entry.
+    if dependencies
+        .loaded_modules
+        .iter()
+        .any(|file| context.cwd.join(file).is_err())
+    {
+        return Ok(());
+    }
+
+    let mut env_variables = dependencies
+        .env_variables
+        .iter()
+        .map(async |name| Ok((name.clone(), env_variable_hash(context, name).await?)))
+        .try_join()
+        .await?;
+    env_variables.sort();
+    env_variables.dedup();
+    let directories = dependencies
+        .directories
+        .iter()
+        .map(async |(dir, glob)| {
+            Ok((
+                dir.clone(),
+                glob.clone(),
+                directory_hash(&context.cwd.join(dir)?, glob).await?,
+            ))
+        })
+        .try_join()
+        .await?;
+    let entry = CacheEntry {
+        result: result.to_string(),
+        env_variables,
+        files: files_with_hashes(context, &dependencies.files).await?,
+        directories,
+        build_files: files_with_hashes(context, &dependencies.build_files).await?,
+        loaded_modules: files_with_hashes(context, &dependencies.loaded_modules).await?,
+        resolves: dependencies.resolves.clone(),
+    };
+    write_entry(key, &entry).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use turbo_rcstr::rcstr;
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_backend::{
+        BackendOptions, NoopBackingStorage, TurboTasksBackend, noop_backing_storage,
+    };
+    use turbo_tasks_fs::{DiskFileSystem, FileSystem};
+
+    use super::*;
+
+    /// A fresh turbo-tasks instance with the cache enabled, it reads files from disk again.
+    fn turbo_tasks(cache_dir: &Path) -> Arc<TurboTasks<TurboTasksBackend<NoopBackingStorage>>> {
+        let tt = TurboTasks::new(TurboTasksBackend::new(
+            BackendOptions {
+                storage_mode: None,
+                ..Default::default()
+            },
+            noop_backing_storage(),
+        ));
+        init_persistent_loader_cache(&(tt.clone() as Arc<dyn TurboTasksApi>), cache_dir);
+        tt
+    }
+
+    fn entry() -> CacheEntry {
+        CacheEntry {
+            result: r#"{"source":".a{color:red}"}"#.to_string(),
+            env_variables: vec![(rcstr!("NODE_ENV"), Some(content_hash("production")))],
+            files: vec![(rcstr!("src/missing.css"), None)],
+            directories: vec![(rcstr!("src"), rcstr!("**/*.css"), content_hash("src"))],
+            build_files: vec![],
+            loaded_modules: vec![(
+                rcstr!("postcss.config.js"),
+                Some(content_hash("module.exports = {}")),
+            )],
+            resolves: vec![],
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_write_and_read_entry() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let key = content_hash("key");
+
+        let tt = turbo_tasks(cache_dir.path());
+        tt.run_once({
+            let key = key.clone();
+            async move {
+                assert!(read_entry(&key).await.is_none());
+                write_entry(&key, &entry()).await;
+                let read = read_entry(&key).await.expect("the entry was written");
+                assert_eq!(serde_json::to_value(read)?, serde_json::to_value(entry())?);
+                Ok(())
+            }
+        })
+        .await
+        .unwrap();
+
+        // A partially written or otherwise corrupted entry is a cache miss.
+        fs::write(
+            cache_dir
+                .path()
+                .join(format!("v{CACHE_VERSION}"))
+                .join(&key[..2])
+                .join(&key),
+            r#"{"result":"#,
+        )
+        .unwrap();
+        tt.run_once(async move {
+            assert!(read_entry(&key).await.is_none());
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    /// Writes an entry keyed by the lockfile hash in one build and reads it in the next one.
+    async fn build_with_lockfile(project_dir: &Path, cache_dir: &Path) -> (Option<String>, bool) {
+        let project_dir: RcStr = project_dir.to_str().unwrap().into();
+        turbo_tasks(cache_dir)
+            .run_once(async move {
+                let root = DiskFileSystem::new(rcstr!("project"), project_dir)
+                    .root()
+                    .owned()
+                    .await?;
+                let Some(key) = lockfile_hash(&root.join("packages/app")?).await? else {
+                    return Ok((None, false));
+                };
+                let hit = read_entry(&key).await.is_some();
+                if !hit {
+                    write_entry(&key, &entry()).await;
+                }
+                Ok((Some(key), hit))
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_changed_lockfile_invalidates_entries() {
+        let project_dir = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(project_dir.path().join("packages/app")).unwrap();
+
+        // Without a lockfile nothing is cached.
+        assert_eq!(
+            build_with_lockfile(project_dir.path(), cache_dir.path()).await,
+            (None, false)
+        );
+
+        // The lockfile of a workspace is in one of the parent directories.
+        let lockfile = project_dir.path().join("pnpm-lock.yaml");
+        fs::write(&lockfile, "lockfileVersion: '9.0'\n").unwrap();
+        let (first_key, hit) = build_with_lockfile(project_dir.path(), cache_dir.path()).await;
+        assert!(first_key.is_some());
+        assert!(!hit);
+        assert_eq!(
+            build_with_lockfile(project_dir.path(), cache_dir.path()).await,
+            (first_key.clone(), true)
+        );
+
+        fs::write(
+            &lockfile,
+            "lockfileVersion: '9.0'\npackages:\n  postcss@8.5.0: {}\n",
+        )
+        .unwrap();
+        let (changed_key, hit) = build_with_lockfile(project_dir.path(), cache_dir.path()).await;
+        assert!(changed_key.is_some());
+        assert_ne!(changed_key, first_key);
+        assert!(!hit);
+
+        // A lockfile closer to the loader's directory takes precedence.
+        fs::write(
+            project_dir.path().join("packages/app/package-lock.json"),
+            "{}",
+        )
+        .unwrap();
+        let (nested_key, _) = build_with_lockfile(project_dir.path(), cache_dir.path()).await;
+        assert_ne!(nested_key, changed_key);
+    }
+}
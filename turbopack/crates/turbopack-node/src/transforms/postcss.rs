@@ -205,20 +205,22 @@ async fn config_changed(
     .completed())
 }
 
-#[turbo_tasks::function]
-async fn extra_configs_changed(
-    asset_context: Vc<Box<dyn AssetContext>>,
-    postcss_config_path: FileSystemPath,
-) -> Result<Vc<Completion>> {
+/// The Tailwind configs next to a PostCSS config, which the PostCSS config might load.
+fn tailwind_config_paths(postcss_config_path: &FileSystemPath) -> Result<[FileSystemPath; 3]> {
     let parent_path = postcss_config_path.parent();
-
-    let config_paths = [
+    Ok([
         parent_path.join("tailwind.config.js")?,
         parent_path.join("tailwind.config.mjs")?,
         parent_path.join("tailwind.config.ts")?,
-    ];
+    ])
+}
 
-    let configs = config_paths
+#[turbo_tasks::function]
+async fn extra_configs_changed(
+    asset_context: Vc<Box<dyn AssetContext>>,
+    postcss_config_path: FileSystemPath,
+) -> Result<Vc<Completion>> {
+    let configs = tailwind_config_paths(&postcss_config_path)?
         .into_iter()
         .map(|path| async move {
             Ok(
@@ -450,6 +452,7 @@ impl PostCssTransformedAsset {
             project_path,
             chunking_context,
             env,
+            backend,
         } = &*self.execution_context.await?;
 
         // For this postcss transform, there is no guarantee that looking up for the
@@ -494,6 +497,11 @@ impl PostCssTransformedAsset {
             .to_resolved()
             .await?;
 
+        // The config isn't reported as a dependency by the loader runner, so it needs to be part
+        // of the persistent cache key.
+        let mut persistent_cache_inputs = vec![config_path.clone()];
+        persistent_cache_inputs.extend(tailwind_config_paths(&config_path)?);
+
         let postcss_executor =
             postcss_executor(*evaluate_context, project_path.clone(), config_path).module();
 
@@ -531,6 +539,8 @@ impl PostCssTransformedAsset {
                 ResolvedVc::cell(source_map.into()),
             ],
             additional_invalidation: config_changed,
+            persistent_cache_inputs,
+            backend: *backend,
         })
         .await?;
 
@@ -53,13 +53,16 @@ use crate::{
     debug::should_debug,
     embed_js::embed_file_path,
     evaluate::{
-        EnvVarTracking, EvaluateContext, EvaluateEntries, EvaluationIssue, custom_evaluate,
-        get_evaluate_entries, get_evaluate_pool,
+        EnvVarTracking, EvaluateContext, EvaluateEntries, EvaluationIssue, NodeJsBackend,
+        custom_evaluate_with_state, get_evaluate_entries, get_evaluate_pool,
     },
     execution_context::ExecutionContext,
     pool::{FormattingMode, NodeJsPool},
     source_map::{StackFrame, StructuredError},
-    transforms::util::{EmittedAsset, emitted_assets_to_virtual_sources},
+    transforms::{
+        persistent_cache::{self, LoaderDependencies, RecordedResolve},
+        util::{EmittedAsset, emitted_assets_to_virtual_sources},
+    },
 };
 
 #[serde_as]
@@ -223,6 +226,7 @@ impl WebpackLoadersProcessedAsset {
             project_path,
             chunking_context,
             env,
+            backend,
         } = &*transform.execution_context.await?;
         let source_content = self.source.content();
         let AssetContent::File(file) = *source_content.await? else {
@@ -286,6 +290,12 @@ impl WebpackLoadersProcessedAsset {
                 ResolvedVc::cell(transform.source_maps.into()),
             ],
             additional_invalidation: Completion::immutable().to_resolved().await?,
+            persistent_cache_inputs: persistent_cache::resolve_loader_files(
+                &project_path,
+                loaders.iter().map(|loader| loader.loader.clone()),
+            )
+            .await?,
+            backend: *backend,
         })
         .await?;
 
@@ -336,7 +346,26 @@ impl WebpackLoadersProcessedAsset {
 pub(crate) async fn evaluate_webpack_loader(
     webpack_loader_context: WebpackLoaderContext,
 ) -> Result<Vc<Option<RcStr>>> {
-    custom_evaluate(webpack_loader_context).await
+    let cache_key = persistent_cache::cache_key(&webpack_loader_context).await?;
+    if let Some(cache_key) = &cache_key
+        && let Some(result) = persistent_cache::read(cache_key, &webpack_loader_context).await?
+    {
+        return Ok(Vc::cell(Some(result)));
+    }
+
+    let (result, state) = custom_evaluate_with_state(&webpack_loader_context).await?;
+    if let (Some(cache_key), Some(result)) = (&cache_key, &result)
+        && !state.reported_problems
+    {
+        persistent_cache::write(
+            cache_key,
+            result,
+            &state.dependencies,
+            &webpack_loader_context,
+        )
+        .await?;
+    }
+    Ok(Vc::cell(result.map(RcStr::from)))
 }
 
 #[derive(Deserialize, Debug, PartialEq, Eq, Encode, Decode)]
@@ -384,6 +413,8 @@ pub enum InfoMessage {
         directories: Vec<(RcStr, RcStr)>,
         #[serde(default)]
         build_file_paths: Vec<RcStr>,
+        #[serde(default)]
+        loaded_module_paths: Vec<RcStr>,
     },
     EmittedError {
         severity: IssueSeverity,
@@ -395,7 +426,17 @@ pub enum InfoMessage {
 }
 
 #[derive(
-    Debug, Clone, TaskInput, Hash, PartialEq, Eq, Deserialize, TraceRawVcs, Encode, Decode,
+    Debug,
+    Clone,
+    TaskInput,
+    Hash,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+    Encode,
+    Decode,
 )]
 #[serde(rename_all = "camelCase")]
 pub struct WebpackResolveOptions {
@@ -442,13 +483,76 @@ pub struct WebpackLoaderContext {
     pub resolve_options_context: Option<ResolvedVc<ResolveOptionsContext>>,
     pub args: Vec<ResolvedVc<JsonValue>>,
     pub additional_invalidation: ResolvedVc<Completion>,
+    /// Files the result depends on that are not reported by the loaders, e.g. the config of the
+    /// loader runner. Their content is part of the key of the persistent loader cache.
+    pub persistent_cache_inputs: Vec<FileSystemPath>,
+    pub backend: NodeJsBackend,
+}
+
+impl WebpackLoaderContext {
+    /// Resolves a request of a loader and returns the path of the result relative to the
+    /// project.
+    pub(super) async fn resolve_request(
+        &self,
+        webpack_options: WebpackResolveOptions,
+        lookup_path: &str,
+        request: RcStr,
+    ) -> Result<RcStr> {
+        let Some(resolve_options_context) = self.resolve_options_context else {
+            bail!("Resolve options are not available in this context");
+        };
+        let lookup_path = self.cwd.join(lookup_path)?;
+        let request = Request::parse(Pattern::Constant(request));
+        let options = resolve_options(lookup_path.clone(), *resolve_options_context);
+
+        let options = apply_webpack_resolve_options(options, webpack_options);
+
+        let resolved = resolve(
+            lookup_path.clone(),
+            ReferenceType::Undefined,
+            request,
+            options,
+        );
+
+        if let Some(source) = *resolved.first_source().await? {
+            if let Some(path) = self
+                .cwd
+                .get_relative_path_to(&*source.ident().path().await?)
+            {
+                Ok(path)
+            } else {
+                bail!(
+                    "Resolving {} in {} ends up on a different filesystem",
+                    request.to_string().await?,
+                    lookup_path.value_to_string().await?
+                );
+            }
+        } else {
+            bail!(
+                "Unable to resolve {} in {}",
+                request.to_string().await?,
+                lookup_path.value_to_string().await?
+            );
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct WebpackLoaderState {
+    logs: Vec<LogInfo>,
+    /// The dependencies reported by the loaders, which are recorded in the persistent loader
+    /// cache.
+    dependencies: LoaderDependencies,
+    /// Whether errors or warnings were reported. Such results are not cached persistently, as
+    /// the issues wouldn't be reported again on a cache hit.
+    reported_problems: bool,
 }
 
 impl EvaluateContext for WebpackLoaderContext {
     type InfoMessage = InfoMessage;
     type RequestMessage = RequestMessage;
     type ResponseMessage = ResponseMessage;
-    type State = Vec<LogInfo>;
+    type State = WebpackLoaderState;
 
     fn pool(&self) -> OperationVc<crate::pool::NodeJsPool> {
         get_evaluate_pool(
@@ -463,6 +567,7 @@ impl EvaluateContext for WebpackLoaderContext {
             // vars only. So the runtime code tracks which env vars are read and send a dependency
             // message for them.
             EnvVarTracking::Untracked,
+            self.backend,
         )
     }
 
@@ -503,7 +608,15 @@ impl EvaluateContext for WebpackLoaderContext {
                 file_paths,
                 directories,
                 build_file_paths,
+                loaded_module_paths,
             } => {
+                state.dependencies.record(
+                    &env_variables,
+                    &file_paths,
+                    &directories,
+                    &build_file_paths,
+                    &loaded_module_paths,
+                );
                 // We only process these dependencies to help with tracking, so if it is disabled
                 // dont bother.
                 if turbo_tasks::turbo_tasks().is_tracking_dependencies() {
@@ -549,6 +662,7 @@ impl EvaluateContext for WebpackLoaderContext {
                 }
             }
             InfoMessage::EmittedError { error, severity } => {
+                state.reported_problems = true;
                 EvaluateEmittedErrorIssue {
                     source: IssueSource::from_source_only(self.context_source_for_issue),
                     error,
@@ -561,7 +675,7 @@ impl EvaluateContext for WebpackLoaderContext {
                 .emit();
             }
             InfoMessage::Log { logs } => {
-                state.extend(logs);
+                state.logs.extend(logs);
             }
         }
         Ok(())
@@ -569,7 +683,7 @@ impl EvaluateContext for WebpackLoaderContext {
 
     async fn request(
         &self,
-        _state: &mut Self::State,
+        state: &mut Self::State,
         data: Self::RequestMessage,
         _pool: &NodeJsPool,
     ) -> Result<Self::ResponseMessage> {
@@ -579,57 +693,33 @@ impl EvaluateContext for WebpackLoaderContext {
                 lookup_path,
                 request,
             } => {
-                let Some(resolve_options_context) = self.resolve_options_context else {
-                    bail!("Resolve options are not available in this context");
-                };
-                let lookup_path = self.cwd.join(&lookup_path)?;
-                let request = Request::parse(Pattern::Constant(request));
-                let options = resolve_options(lookup_path.clone(), *resolve_options_context);
-
-                let options = apply_webpack_resolve_options(options, webpack_options);
-
-                let resolved = resolve(
-                    lookup_path.clone(),
-                    ReferenceType::Undefined,
+                let path = self
+                    .resolve_request(webpack_options.clone(), &lookup_path, request.clone())
+                    .await?;
+                state.dependencies.resolves.push(RecordedResolve {
+                    options: webpack_options,
+                    lookup_path,
                     request,
-                    options,
-                );
-
-                if let Some(source) = *resolved.first_source().await? {
-                    if let Some(path) = self
-                        .cwd
-                        .get_relative_path_to(&*source.ident().path().await?)
-                    {
-                        Ok(ResponseMessage::Resolve { path })
-                    } else {
-                        bail!(
-                            "Resolving {} in {} ends up on a different filesystem",
-                            request.to_string().await?,
-                            lookup_path.value_to_string().await?
-                        );
-                    }
-                } else {
-                    bail!(
-                        "Unable to resolve {} in {}",
-                        request.to_string().await?,
-                        lookup_path.value_to_string().await?
-                    );
-                }
+                    path: path.clone(),
+                });
+                Ok(ResponseMessage::Resolve { path })
             }
             RequestMessage::TrackFileRead { file } => {
                 // Ignore result, we read on the JS side again to prevent some IPC overhead. Still
                 // await the read though to cover at least one class of race conditions.
                 let _ = &*self.cwd.join(&file)?.read().await?;
+                state.dependencies.files.push(file);
                 Ok(ResponseMessage::TrackFileRead {})
             }
         }
     }
 
-    async fn finish(&self, state: Self::State, pool: &NodeJsPool) -> Result<()> {
-        let has_errors = state.iter().any(|log| log.log_type == LogType::Error);
-        let has_warnings = state.iter().any(|log| log.log_type == LogType::Warn);
+    async fn finish(&self, state: &mut Self::State, pool: &NodeJsPool) -> Result<()> {
+        let has_errors = state.logs.iter().any(|log| log.log_type == LogType::Error);
+        let has_warnings = state.logs.iter().any(|log| log.log_type == LogType::Warn);
         if has_errors || has_warnings {
-            let logs = state
+            state.reported_problems = true;
+            let logs = take(&mut state.logs)
                 .into_iter()
                 .filter(|log| {
                     matches!(
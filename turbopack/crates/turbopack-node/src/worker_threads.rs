@@ -0,0 +1,307 @@
+//! The `worker_threads` backend of the Node.js pool.
+//!
+//! Instead of a process per worker, a single long-lived host process per working directory runs
+//! the workers as `worker_threads`. Workers connect to the pool with the same socket protocol as
+//! processes do, so only starting, stopping and collecting the output of workers differs. The
+//! host multiplexes the stdout and stderr of its workers onto its own stdout, which is split up
+//! again here.
+
+use std::{
+    io::Cursor,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    },
+};
+
+use anyhow::{Context, Result, bail};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader},
+    process::{Child, ChildStdout},
+    sync::{
+        mpsc::{UnboundedSender, unbounded_channel},
+        oneshot,
+    },
+};
+use tokio_util::io::StreamReader;
+use turbo_rcstr::RcStr;
+
+use crate::{
+    pool::{node_base_env, node_command},
+    pool_policy::pool_policy,
+};
+
+const HOST_SCRIPT: &str = include_str!("../js/worker_host.js");
+
+/// The stdout or stderr of a worker.
+pub(crate) type OutputStream = Pin<Box<dyn AsyncRead + Send + Sync>>;
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum HostCommand<'a> {
+    #[serde(rename_all = "camelCase")]
+    Spawn {
+        id: u32,
+        entrypoint: &'a Path,
+        port: u16,
+        env: FxHashMap<&'a str, &'a str>,
+        max_old_generation_size_mb: Option<u64>,
+    },
+    Terminate {
+        id: u32,
+    },
+}
+
+struct WorkerRoute {
+    stdout: UnboundedSender<Vec<u8>>,
+    stderr: UnboundedSender<Vec<u8>>,
+    exit: oneshot::Sender<i32>,
+}
+
+type WorkerRoutes = Arc<Mutex<FxHashMap<u32, WorkerRoute>>>;
+
+struct WorkerThreadHost {
+    // Kept to kill the host when it's dropped
+    #[allow(dead_code)]
+    child: Child,
+    commands: UnboundedSender<Vec<u8>>,
+    routes: WorkerRoutes,
+    /// Set when the stdout of the host closed, i.e. it exited.
+    closed: Arc<AtomicBool>,
+    next_id: AtomicU32,
+}
+
+/// The running hosts by working directory.
+static HOSTS: Lazy<Mutex<FxHashMap<PathBuf, Arc<WorkerThreadHost>>>> = Lazy::new(Default::default);
+
+impl WorkerThreadHost {
+    fn start(cwd: &Path) -> Result<Self> {
+        let mut cmd = node_command(cwd);
+        cmd.arg("-e").arg(HOST_SCRIPT);
+        cmd.stdin(std::process::Stdio::piped());
+        // The host only writes to stderr when it fails itself.
+        cmd.stderr(std::process::Stdio::inherit());
+        let mut child = cmd.spawn().context("spawning Node.js worker thread host")?;
+
+        let mut stdin = child.stdin.take().unwrap();
+        let (commands, mut command_receiver) = unbounded_channel::<Vec<u8>>();
+        tokio::spawn(async move {
+            while let Some(command) = command_receiver.recv().await {
+                if stdin.write_all(&command).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let routes = WorkerRoutes::default();
+        let closed = Arc::new(AtomicBool::new(false));
+        tokio::spawn(route_output(
+            child.stdout.take().unwrap(),
+            routes.clone(),
+            closed.clone(),
+        ));
+
+        Ok(Self {
+            child,
+            commands,
+            routes,
+            closed,
+            next_id: AtomicU32::new(1),
+        })
+    }
+
+    fn send(&self, command: HostCommand) -> Result<()> {
+        let mut line = serde_json::to_vec(&command)?;
+        line.push(b'\n');
+        if self.commands.send(line).is_err() {
+            bail!("the Node.js worker thread host exited");
+        }
+        Ok(())
+    }
+
+    fn spawn(
+        self: &Arc<Self>,
+        entrypoint: &Path,
+        port: u16,
+        env: &FxHashMap<RcStr, RcStr>,
+    ) -> Result<(WorkerThread, OutputStream, OutputStream)> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (stdout, stdout_receiver) = unbounded_channel();
+        let (stderr, stderr_receiver) = unbounded_channel();
+        let (exit, exit_receiver) = oneshot::channel();
+        self.routes.lock().insert(
+            id,
+            WorkerRoute {
+                stdout,
+                stderr,
+                exit,
+            },
+        );
+
+        // Workers don't inherit the env of the host, so it's passed completely.
+        let base_env = node_base_env();
+        let worker_env = base_env
+            .iter()
+            .map(|(k, v)| (*k, v.as_str()))
+            .chain(env.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+            .collect();
+        let result = self.send(HostCommand::Spawn {
+            id,
+            entrypoint,
+            port,
+            env: worker_env,
+            max_old_generation_size_mb: pool_policy()
+                .max_worker_rss
+                .map(|bytes| bytes / (1024 * 1024)),
+        });
+        if let Err(err) = result {
+            self.routes.lock().remove(&id);
+            return Err(err);
+        }
+
+        fn output_stream(
+            mut receiver: tokio::sync::mpsc::UnboundedReceiver<Vec<u8>>,
+        ) -> OutputStream {
+            Box::pin(StreamReader::new(futures::stream::poll_fn(move |cx| {
+                receiver
+                    .poll_recv(cx)
+                    .map(|chunk| chunk.map(|chunk| Ok::<_, std::io::Error>(Cursor::new(chunk))))
+            })))
+        }
+
+        Ok((
+            WorkerThread {
+                id,
+                host: self.clone(),
+                exit: exit_receiver,
+                exit_code: None,
+            },
+            output_stream(stdout_receiver),
+            output_stream(stderr_receiver),
+        ))
+    }
+}
+
+/// Reads the frames written by the host and forwards them to the workers.
+async fn route_output(stdout: ChildStdout, routes: WorkerRoutes, closed: Arc<AtomicBool>) {
+    let mut reader = BufReader::new(stdout);
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if !matches!(reader.read_line(&mut header).await, Ok(n) if n > 0) {
+            break;
+        }
+        let mut parts = header.trim_end().splitn(3, ' ');
+        let (Some(Ok(id)), Some(stream), Some(value)) = (
+            parts.next().map(str::parse::<u32>),
+            parts.next(),
+            parts.next(),
+        ) else {
+            break;
+        };
+        match stream {
+            "o" | "e" => {
+                let Ok(len) = value.parse::<usize>() else {
+                    break;
+                };
+                let mut data = vec![0; len];
+                if reader.read_exact(&mut data).await.is_err() {
+                    break;
+                }
+                // Output of workers whose streams were dropped already is discarded.
+                if let Some(route) = routes.lock().get(&id) {
+                    let sender = if stream == "o" {
+                        &route.stdout
+                    } else {
+                        &route.stderr
+                    };
+                    let _ = sender.send(data);
+                }
+            }
+            "x" => {
+                // Dropping the route closes the output streams of the worker.
+                if let Some(route) = routes.lock().remove(&id) {
+                    let _ = route.exit.send(value.parse().unwrap_or(1));
+                }
+            }
+            _ => break,
+        }
+    }
+    closed.store(true, Ordering::Release);
+    routes.lock().clear();
+}
+
+/// A worker running as a `worker_threads` worker in a host process. It's terminated when
+/// dropped.
+pub(crate) struct WorkerThread {
+    id: u32,
+    host: Arc<WorkerThreadHost>,
+    exit: oneshot::Receiver<i32>,
+    exit_code: Option<i32>,
+}
+
+impl WorkerThread {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Requests the termination of the worker. Use [WorkerThread::wait] to wait for it.
+    pub fn start_kill(&self) {
+        let _ = self.host.send(HostCommand::Terminate { id: self.id });
+    }
+
+    /// Waits until the worker exits and returns its exit code.
+    pub async fn wait(&mut self) -> Result<i32> {
+        if let Some(exit_code) = self.exit_code {
+            return Ok(exit_code);
+        }
+        let exit_code = (&mut self.exit)
+            .await
+            .context("the Node.js worker thread host exited")?;
+        self.exit_code = Some(exit_code);
+        Ok(exit_code)
+    }
+}
+
+impl Drop for WorkerThread {
+    fn drop(&mut self) {
+        if self.exit_code.is_none() {
+            self.start_kill();
+        }
+    }
+}
+
+/// Starts a worker for `entrypoint` in the host for `cwd`, which is started if needed. Returns
+/// the worker with its stdout and stderr.
+pub(crate) fn spawn_worker_thread(
+    cwd: &Path,
+    entrypoint: &Path,
+    port: u16,
+    env: &FxHashMap<RcStr, RcStr>,
+) -> Result<(WorkerThread, OutputStream, OutputStream)> {
+    let host = {
+        let mut hosts = HOSTS.lock();
+        match hosts.get(cwd) {
+            Some(host) if !host.closed.load(Ordering::Acquire) => host.clone(),
+            _ => {
+                let host = Arc::new(WorkerThreadHost::start(cwd)?);
+                hosts.insert(cwd.to_path_buf(), host.clone());
+                host
+            }
+        }
+    };
+    host.spawn(entrypoint, port, env)
+}
+
+/// Stops the hosts that don't run any workers.
+pub(crate) fn stop_unused_hosts() {
+    HOSTS
+        .lock()
+        .retain(|_, host| !host.routes.lock().is_empty());
+}
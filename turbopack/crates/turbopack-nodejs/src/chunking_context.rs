@@ -6,9 +6,10 @@ use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
     asset::Asset,
     chunk::{
-        Chunk, ChunkGroupResult, ChunkItem, ChunkType, ChunkableModule, ChunkingConfig,
-        ChunkingConfigs, ChunkingContext, EntryChunkGroupResult, EvaluatableAssets, MinifyType,
-        ModuleId, SourceMapSourceType, SourceMapsType,
+        Chunk, ChunkFormat, ChunkGroupResult, ChunkItem, ChunkType, ChunkableModule,
+        ChunkingConfig, ChunkingConfigs, ChunkingContext, EntryChunkGroupResult, EvaluatableAssets,
+        MinifyType, ModuleId, OptionPropertyMangling, PropertyMangling, SourceMapSourceType,
+        SourceMapsType,
         availability_info::AvailabilityInfo,
         chunk_group::{MakeChunkGroupResult, make_chunk_group},
         module_id_strategies::{DevModuleIdStrategy, ModuleIdStrategy},
@@ -66,6 +67,15 @@ impl NodeJsChunkingContextBuilder {
         self
     }
 
+    /// Renames object properties consistently in all chunks when minifying.
+    pub fn property_mangling(
+        mut self,
+        property_mangling: Option<ResolvedVc<PropertyMangling>>,
+    ) -> Self {
+        self.chunking_context.property_mangling = property_mangling;
+        self
+    }
+
     pub fn source_maps(mut self, source_maps: SourceMapsType) -> Self {
         self.chunking_context.source_maps_type = source_maps;
         self
@@ -95,6 +105,11 @@ impl NodeJsChunkingContextBuilder {
         self
     }
 
+    pub fn chunk_format(mut self, chunk_format: ChunkFormat) -> Self {
+        self.chunking_context.chunk_format = chunk_format;
+        self
+    }
+
     pub fn runtime_type(mut self, runtime_type: RuntimeType) -> Self {
         self.chunking_context.runtime_type = runtime_type;
         self
@@ -193,6 +208,10 @@ pub struct NodeJsChunkingContext {
     enable_dynamic_chunk_content_loading: bool,
     /// Whether to minify resulting chunks
     minify_type: MinifyType,
+    /// Object properties to rename when minifying
+    property_mangling: Option<ResolvedVc<PropertyMangling>>,
+    /// The format JavaScript chunks are emitted in. ES module chunks use the `.mjs` extension.
+    chunk_format: ChunkFormat,
     /// Whether to generate source maps
     source_maps_type: SourceMapsType,
     /// Whether to use manifest chunks for lazy compilation
@@ -242,6 +261,8 @@ impl NodeJsChunkingContext {
                 environment,
                 runtime_type,
                 minify_type: MinifyType::NoMinify,
+                property_mangling: None,
+                chunk_format: ChunkFormat::Script,
                 source_maps_type: SourceMapsType::Full,
                 manifest_chunks: false,
                 source_map_source_type: SourceMapSourceType::TurbopackUri,
@@ -349,6 +370,16 @@ impl ChunkingContext for NodeJsChunkingContext {
         self.minify_type.cell()
     }
 
+    #[turbo_tasks::function]
+    fn property_mangling(&self) -> Vc<OptionPropertyMangling> {
+        Vc::cell(self.property_mangling)
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_format(&self) -> Vc<ChunkFormat> {
+        self.chunk_format.cell()
+    }
+
     #[turbo_tasks::function]
     async fn asset_url(&self, ident: FileSystemPath, tag: Option<RcStr>) -> Result<Vc<RcStr>> {
         let asset_path = ident.to_string();
@@ -391,6 +422,11 @@ impl ChunkingContext for NodeJsChunkingContext {
         extension: RcStr,
     ) -> Result<Vc<FileSystemPath>> {
         let root_path = self.chunk_root_path.clone();
+        let extension = match (self.chunk_format, extension.as_str()) {
+            (ChunkFormat::EsModule, ".js") => rcstr!(".mjs"),
+            (ChunkFormat::EsModule, ".js.map") => rcstr!(".mjs.map"),
+            _ => extension,
+        };
         let name = ident
             .output_name(self.root_path.clone(), prefix, extension)
             .owned()
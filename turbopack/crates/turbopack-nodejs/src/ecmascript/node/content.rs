@@ -3,7 +3,7 @@ use turbo_tasks::{ResolvedVc, Vc};
 use turbo_tasks_fs::{File, FileContent};
 use turbopack_core::{
     asset::AssetContent,
-    chunk::{ChunkingContext, MinifyType},
+    chunk::{ChunkFormat, ChunkingContext, MinifyType},
     code_builder::{Code, CodeBuilder},
     output::OutputAsset,
     source_map::{GenerateSourceMap, SourceMapAsset},
@@ -53,7 +53,10 @@ impl EcmascriptBuildNodeChunkContent {
 
         let mut code = CodeBuilder::new(true, *self.chunking_context.debug_ids_enabled().await?);
 
-        write!(code, "module.exports = [")?;
+        match *self.chunking_context.chunk_format().await? {
+            ChunkFormat::Script => write!(code, "module.exports = [")?,
+            ChunkFormat::EsModule => write!(code, "export default [")?,
+        }
 
         let content = self.content.await?;
         let chunk_items = content.chunk_item_code_and_ids().await?;
@@ -70,7 +73,17 @@ impl EcmascriptBuildNodeChunkContent {
         let mut code = code.build();
 
         if let MinifyType::Minify { mangle } = *self.chunking_context.minify_type().await? {
-            code = minify(code, source_maps, mangle)?;
+            let property_mangling = match *self.chunking_context.property_mangling().await? {
+                Some(property_mangling) => Some(property_mangling.await?),
+                None => None,
+            };
+            code = minify(
+                code,
+                source_maps,
+                mangle,
+                property_mangling.as_deref(),
+                None,
+            )?;
         }
 
         Ok(code.cell())
@@ -7,20 +7,35 @@ use turbo_tasks::{ResolvedVc, ValueToString, Vc};
 use turbo_tasks_fs::{File, FileContent, FileSystemPath};
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::{ChunkingContext, EvaluatableAssets, ModuleChunkItemIdExt},
+    chunk::{ChunkFormat, ChunkingContext, EvaluatableAssets, ModuleChunkItemIdExt},
     code_builder::{Code, CodeBuilder},
-    module_graph::ModuleGraph,
+    module_graph::{ModuleGraph, binding_usage_info::ModuleExportUsageInfo},
     output::{
         OutputAsset, OutputAssets, OutputAssetsReference, OutputAssetsReferences,
         OutputAssetsWithReferenced,
     },
     source_map::{GenerateSourceMap, SourceMapAsset},
 };
-use turbopack_ecmascript::{chunk::EcmascriptChunkPlaceable, utils::StringifyJs};
+use turbopack_ecmascript::{
+    chunk::{EcmascriptChunkPlaceable, EcmascriptExports},
+    utils::StringifyJs,
+};
 
 use super::runtime::EcmascriptBuildNodeRuntimeChunk;
 use crate::NodeJsChunkingContext;
 
+#[turbo_tasks::value(transparent)]
+struct OptionExportNames(Option<Vec<RcStr>>);
+
+/// Whether `name` can be used as is in an `export { binding as name }` specifier.
+fn is_identifier_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
 /// An Ecmascript chunk that loads a list of parallel chunks, then instantiates
 /// runtime entries.
 #[turbo_tasks::value(shared)]
@@ -86,30 +101,79 @@ impl EcmascriptBuildNodeEntryChunk {
         };
 
         let mut code = CodeBuilder::default();
-
-        writedoc!(
-            code,
-            r#"
-                var R=require({})({})
-            "#,
-            StringifyJs(&*runtime_relative_path),
-            StringifyJs(chunk_public_path),
-        )?;
-
+        let chunk_format = *this.chunking_context.chunk_format().await?;
         let other_chunks = this.other_chunks.await?;
-        for other_chunk in &*other_chunks {
-            let other_chunk_path = &*other_chunk.path().await?;
-            if let Some(other_chunk_public_path) = output_root.get_path_to(other_chunk_path) {
+
+        if matches!(chunk_format, ChunkFormat::EsModule) {
+            writedoc!(
+                code,
+                r#"
+                    import __turbopack_runtime__ from {};
+                "#,
+                StringifyJs(&*runtime_relative_path),
+            )?;
+            let mut registrations = vec![];
+            for other_chunk in &*other_chunks {
+                let other_chunk_path = &*other_chunk.path().await?;
+                if !other_chunk_path.has_extension(".mjs") {
+                    continue;
+                }
+                let (Some(other_chunk_public_path), Some(specifier)) = (
+                    output_root.get_path_to(other_chunk_path),
+                    chunk_directory.get_relative_path_to(other_chunk_path),
+                ) else {
+                    continue;
+                };
+                let binding = format!("__turbopack_chunk_{}__", registrations.len());
                 writedoc!(
                     code,
-                    // TODO(WEB-1112) This should call `require()` directly, perhaps as an argument
-                    // to `loadChunk`.
                     r#"
-                        R.c({})
+                        import {binding} from {};
+                    "#,
+                    StringifyJs(&specifier),
+                )?;
+                registrations.push((binding, other_chunk_public_path.to_string()));
+            }
+            writedoc!(
+                code,
+                r#"
+                    var R=__turbopack_runtime__({})
+                "#,
+                StringifyJs(chunk_public_path),
+            )?;
+            for (binding, other_chunk_public_path) in registrations {
+                writedoc!(
+                    code,
+                    r#"
+                        R.c({}, {binding})
                     "#,
                     StringifyJs(&other_chunk_public_path)
                 )?;
             }
+        } else {
+            writedoc!(
+                code,
+                r#"
+                    var R=require({})({})
+                "#,
+                StringifyJs(&*runtime_relative_path),
+                StringifyJs(chunk_public_path),
+            )?;
+
+            for other_chunk in &*other_chunks {
+                let other_chunk_path = &*other_chunk.path().await?;
+                if let Some(other_chunk_public_path) = output_root.get_path_to(other_chunk_path) {
+                    writedoc!(
+                        code,
+                        // TODO(WEB-1112) This should call `require()` directly, perhaps as an
+                        // argument to `loadChunk`.
+                        r#"
+                            R.c({})
+                        "#,
+                        StringifyJs(&other_chunk_public_path)
+                    )?;
+                }
+            }
         }
 
         let evaluatable_assets = this.evaluatable_assets.await?;
@@ -136,17 +200,79 @@ impl EcmascriptBuildNodeEntryChunk {
             .chunk_item_id(Vc::upcast(*this.chunking_context))
             .await?;
 
-        writedoc!(
-            code,
-            r#"
-                module.exports=R.m({}).exports
-            "#,
-            StringifyJs(&*runtime_module_id),
-        )?;
+        match chunk_format {
+            ChunkFormat::Script => writedoc!(
+                code,
+                r#"
+                    module.exports=R.m({}).exports
+                "#,
+                StringifyJs(&*runtime_module_id),
+            )?,
+            ChunkFormat::EsModule => {
+                // Awaiting the exports also waits for async modules, e.g. ones importing ESM
+                // externals.
+                writedoc!(
+                    code,
+                    r#"
+                        const __turbopack_exports__=await R.m({}).exports
+                    "#,
+                    StringifyJs(&*runtime_module_id),
+                )?;
+                match &*self.esm_export_names().await? {
+                    Some(names) => {
+                        let mut specifiers = vec![];
+                        for (i, name) in names.iter().enumerate() {
+                            let binding = format!("__turbopack_export_{i}__");
+                            writedoc!(
+                                code,
+                                r#"
+                                    var {binding}=__turbopack_exports__[{}]
+                                "#,
+                                StringifyJs(name),
+                            )?;
+                            if is_identifier_name(name) {
+                                specifiers.push(format!("{binding} as {name}"));
+                            } else {
+                                specifiers.push(format!("{binding} as {}", StringifyJs(name)));
+                            }
+                        }
+                        writedoc!(
+                            code,
+                            r#"
+                                export {{ {} }}
+                            "#,
+                            specifiers.join(", "),
+                        )?;
+                    }
+                    None => writedoc!(
+                        code,
+                        r#"
+                            export default __turbopack_exports__
+                        "#,
+                    )?,
+                }
+            }
+        }
 
         Ok(Code::cell(code.build()))
     }
 
+    /// The names the exported module exports, which the chunk re-exports when it's an ES module.
+    /// `None` when they are not statically known, e.g. for CommonJS modules, in which case the
+    /// exports object becomes the default export.
+    #[turbo_tasks::function]
+    async fn esm_export_names(&self) -> Result<Vc<OptionExportNames>> {
+        let EcmascriptExports::EsmExports(exports) = *self.exported_module.get_exports().await?
+        else {
+            return Ok(Vc::cell(None));
+        };
+        let expanded = exports.expand_exports(ModuleExportUsageInfo::all()).await?;
+        if !expanded.dynamic_exports.is_empty() {
+            return Ok(Vc::cell(None));
+        }
+        Ok(Vc::cell(Some(expanded.exports.keys().cloned().collect())))
+    }
+
     #[turbo_tasks::function]
     fn runtime_chunk(&self) -> Vc<EcmascriptBuildNodeRuntimeChunk> {
         EcmascriptBuildNodeRuntimeChunk::new(*self.chunking_context)
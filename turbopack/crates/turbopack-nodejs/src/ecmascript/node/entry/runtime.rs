@@ -7,7 +7,7 @@ use turbo_tasks::{ResolvedVc, ValueToString, Vc};
 use turbo_tasks_fs::{File, FileContent, FileSystem, FileSystemPath};
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::ChunkingContext,
+    chunk::{ChunkFormat, ChunkingContext},
     code_builder::{Code, CodeBuilder},
     ident::AssetIdent,
     output::{OutputAsset, OutputAssetsReference, OutputAssetsWithReferenced},
@@ -52,6 +52,22 @@ impl EcmascriptBuildNodeRuntimeChunk {
         let mut code = CodeBuilder::default();
         let asset_prefix = this.chunking_context.asset_prefix().await?;
         let asset_prefix = asset_prefix.as_deref().unwrap_or("/");
+        let chunk_format = *this.chunking_context.chunk_format().await?;
+
+        if matches!(chunk_format, ChunkFormat::EsModule) {
+            // The runtime is written against CommonJS globals, provide them in the ES module
+            // scope. `module.exports` is re-exported as the default export below.
+            writedoc!(
+                code,
+                r#"
+                    import {{ createRequire as __turbopack_create_require__ }} from "node:module";
+                    import {{ fileURLToPath as __turbopack_file_url_to_path__ }} from "node:url";
+                    const require = __turbopack_create_require__(import.meta.url);
+                    const __filename = __turbopack_file_url_to_path__(import.meta.url);
+                    const module = {{ exports: {{}} }};
+                "#,
+            )?;
+        }
 
         writedoc!(
             code,
@@ -59,10 +75,15 @@ impl EcmascriptBuildNodeRuntimeChunk {
                 const RUNTIME_PUBLIC_PATH = {};
                 const RELATIVE_ROOT_PATH = {};
                 const ASSET_PREFIX = {};
+                const CHUNK_FORMAT = {};
             "#,
             StringifyJs(runtime_public_path),
             StringifyJs(output_root_to_root_path.as_str()),
             StringifyJs(asset_prefix),
+            StringifyJs(match chunk_format {
+                ChunkFormat::Script => "script",
+                ChunkFormat::EsModule => "esm",
+            }),
         )?;
 
         match *this.chunking_context.runtime_type().await? {
@@ -87,6 +108,15 @@ impl EcmascriptBuildNodeRuntimeChunk {
             }
         }
 
+        if matches!(chunk_format, ChunkFormat::EsModule) {
+            writedoc!(
+                code,
+                r#"
+                    export default module.exports;
+                "#,
+            )?;
+        }
+
         Ok(Code::cell(code.build()))
     }
 
@@ -14,6 +14,7 @@ use turbopack_core::resolve::{
 use crate::{
     resolve_options_context::ResolveOptionsContext,
     typescript::{apply_tsconfig_resolve_options, tsconfig, tsconfig_resolve_options},
+    url_imports::UrlImportsResolvePlugin,
 };
 
 #[turbo_tasks::function]
@@ -149,18 +150,19 @@ async fn base_resolve_options(
     };
     Ok(ResolveOptions {
         extensions,
-        modules: if let Some(environment) = emulating {
-            if *environment.resolve_node_modules().await? {
-                vec![ResolveModules::Nested(
-                    root.clone(),
-                    vec![rcstr!("node_modules")],
-                )]
-            } else {
-                Vec::new()
-            }
-        } else {
+        modules: {
             let mut mods = Vec::new();
-            if let Some(dir) = &opt.enable_node_modules {
+            if let Some(dir) = &opt.enable_pnp {
+                mods.push(ResolveModules::Pnp(dir.clone()));
+            }
+            if let Some(environment) = emulating {
+                if *environment.resolve_node_modules().await? {
+                    mods.push(ResolveModules::Nested(
+                        root.clone(),
+                        vec![rcstr!("node_modules")],
+                    ));
+                }
+            } else if let Some(dir) = &opt.enable_node_modules {
                 mods.push(ResolveModules::Nested(
                     dir.clone(),
                     vec![rcstr!("node_modules")],
@@ -202,9 +204,21 @@ async fn base_resolve_options(
         import_map: Some(import_map),
         resolved_map: opt.resolved_map,
         after_resolve_plugins: opt.after_resolve_plugins.clone(),
-        before_resolve_plugins: opt.before_resolve_plugins.clone(),
+        before_resolve_plugins: {
+            let mut plugins = opt.before_resolve_plugins.clone();
+            if let Some(url_imports) = opt.url_imports {
+                plugins.push(ResolvedVc::upcast(
+                    UrlImportsResolvePlugin::new(*url_imports)
+                        .to_resolved()
+                        .await?,
+                ));
+            }
+            plugins
+        },
         loose_errors: opt.loose_errors,
         collect_affecting_sources: opt.collect_affecting_sources,
+        locked_versions: opt.locked_versions,
+        singleton_packages: opt.singleton_packages.clone(),
         ..Default::default()
     }
     .cell())
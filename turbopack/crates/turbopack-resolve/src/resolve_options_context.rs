@@ -6,11 +6,13 @@ use turbopack_core::{
     condition::ContextCondition,
     environment::Environment,
     resolve::{
-        options::{ImportMap, ResolvedMap},
+        options::{ImportMap, LockedVersions, ResolvedMap},
         plugin::{AfterResolvePlugin, BeforeResolvePlugin},
     },
 };
 
+use crate::url_imports::UrlImportsOptions;
+
 #[turbo_tasks::value(shared)]
 #[derive(Default, Clone)]
 pub struct ResolveOptionsContext {
@@ -24,6 +26,9 @@ pub struct ResolveOptionsContext {
     /// Enable resolving of the node_modules folder when within the provided
     /// directory
     pub enable_node_modules: Option<FileSystemPath>,
+    /// Enable resolving through the Yarn Plug'n'Play manifest (`.pnp.data.json` or `.pnp.cjs`)
+    /// in the provided directory, if there is one. It takes precedence over node_modules.
+    pub enable_pnp: Option<FileSystemPath>,
     /// A specific path to a tsconfig.json file to use for resolving modules. If `None`, one will
     /// be looked up through the filesystem
     pub tsconfig_path: Option<FileSystemPath>,
@@ -63,6 +68,12 @@ pub struct ResolveOptionsContext {
     pub loose_errors: bool,
     /// Collect affecting sources for each resolve result.  Useful for tracing.
     pub collect_affecting_sources: bool,
+    /// Report packages whose installed version doesn't match these locked versions.
+    pub locked_versions: Option<ResolvedVc<LockedVersions>>,
+    /// Packages that should only be included once, see `ResolveOptions::singleton_packages`.
+    pub singleton_packages: Vec<RcStr>,
+    /// Fetch allowed `https://` imports at build time instead of treating them as externals.
+    pub url_imports: Option<ResolvedVc<UrlImportsOptions>>,
 
     pub placeholder_for_future_extensions: (),
 }
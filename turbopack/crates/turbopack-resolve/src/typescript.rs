@@ -238,6 +238,8 @@ pub async fn read_from_tsconfigs<T>(
 pub struct TsConfigResolveOptions {
     base_url: Option<FileSystemPath>,
     import_map: Option<ResolvedVc<ImportMap>>,
+    /// The tsconfig.json the `import_map` was read from.
+    tsconfig: Option<FileSystemPath>,
     is_module_resolution_nodenext: bool,
 }
 
@@ -365,6 +367,7 @@ pub async fn tsconfig_resolve_options(
     Ok(TsConfigResolveOptions {
         base_url,
         import_map,
+        tsconfig: Some(tsconfig),
         is_module_resolution_nodenext,
     }
     .cell())
@@ -403,6 +406,10 @@ pub async fn apply_tsconfig_resolve_options(
                 .to_resolved()
                 .await?,
         );
+        resolve_options.tsconfig_paths = tsconfig_resolve_options
+            .tsconfig
+            .clone()
+            .map(|tsconfig| (tsconfig, tsconfig_import_map));
     }
     resolve_options.enable_typescript_with_output_extension =
         tsconfig_resolve_options.is_module_resolution_nodenext;
@@ -1,9 +1,9 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{ResolvedVc, Vc};
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
+    chunk::{ChunkFormat, ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
     ident::AssetIdent,
     module::{Module, ModuleSideEffects},
     module_graph::ModuleGraph,
@@ -154,17 +154,32 @@ impl ChunkItem for StaticUrlJsChunkItem {
 impl EcmascriptChunkItem for StaticUrlJsChunkItem {
     #[turbo_tasks::function]
     async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
-        Ok(EcmascriptChunkItemContent {
-            inner_code: format!(
+        let asset_path = self.static_asset.path().owned().await?;
+        let inner_code = match *self.chunking_context.chunk_format().await? {
+            ChunkFormat::Script => format!(
                 "{TURBOPACK_EXPORT_VALUE}({path});",
                 path = StringifyJs(
                     &self
                         .chunking_context
-                        .asset_url(self.static_asset.path().owned().await?, self.tag.clone())
+                        .asset_url(asset_path, self.tag.clone())
                         .await?
                 )
-            )
-            .into(),
+            ),
+            // ES module chunks are emitted into the chunk root, so the asset can be located
+            // relative to the chunk that references it.
+            ChunkFormat::EsModule => {
+                let chunk_root_path = self.chunking_context.chunk_root_path().await?;
+                let Some(path) = chunk_root_path.get_relative_path_to(&asset_path) else {
+                    bail!("asset {asset_path} is not in the same filesystem as {chunk_root_path}");
+                };
+                format!(
+                    "{TURBOPACK_EXPORT_VALUE}(new URL({path}, import.meta.url).href);",
+                    path = StringifyJs(&path)
+                )
+            }
+        };
+        Ok(EcmascriptChunkItemContent {
+            inner_code: inner_code.into(),
             ..Default::default()
         }
         .cell())
@@ -21,12 +21,15 @@ use turbo_tasks_backend::{BackendOptions, TurboTasksBackend, noop_backing_storag
 use turbo_tasks_env::CommandLineProcessEnv;
 use turbo_tasks_fs::{
     DiskFileSystem, FileContent, FileSystem, FileSystemEntryType, FileSystemPath,
+    glob::{Glob, GlobOptions},
     json::parse_json_with_source_context,
 };
 use turbo_unix_path::sys_to_unix;
 use turbopack::{
     ModuleAssetContext,
-    module_options::{EcmascriptOptionsContext, ModuleOptionsContext, TypescriptTransformOptions},
+    module_options::{
+        EcmascriptOptionsContext, ModuleOptionsContext, SvgImportMode, TypescriptTransformOptions,
+    },
 };
 use turbopack_core::{
     chunk::{ChunkingConfig, MangleType, MinifyType},
@@ -46,11 +49,11 @@ use turbopack_core::{
     },
 };
 use turbopack_css::chunk::CssChunkType;
-use turbopack_ecmascript::{TreeShakingMode, chunk::EcmascriptChunkType};
+use turbopack_ecmascript::{CoverageOptions, TreeShakingMode, chunk::EcmascriptChunkType};
 use turbopack_ecmascript_runtime::RuntimeType;
 use turbopack_node::{
     debug::should_debug,
-    evaluate::{evaluate, get_evaluate_entries},
+    evaluate::{NodeJsBackend, evaluate, get_evaluate_entries},
 };
 use turbopack_nodejs::NodeJsChunkingContext;
 use turbopack_resolve::resolve_options_context::ResolveOptionsContext;
@@ -251,6 +254,14 @@ struct TestOptions {
     minify: bool,
     #[serde(default)]
     production_chunking: bool,
+    #[serde(default)]
+    svg: Option<SvgImportMode>,
+    /// Evaluates the test in a worker thread instead of a child process.
+    #[serde(default)]
+    worker_threads: bool,
+    /// Instruments the modules of the test with code coverage counters.
+    #[serde(default)]
+    coverage: bool,
 }
 
 fn default_tree_shaking_mode() -> Option<TreeShakingMode> {
@@ -270,6 +281,9 @@ impl Default for TestOptions {
             scope_hoisting: default_true(),
             minify: false,
             production_chunking: false,
+            svg: None,
+            worker_threads: false,
+            coverage: false,
         }
     }
 }
@@ -417,8 +431,24 @@ async fn run_test_operation(prepared_test: ResolvedVc<PreparedTest>) -> Result<V
                 import_externals: true,
                 enable_exports_info_inlining: true,
                 infer_module_side_effects: true,
+                enable_coverage: if options.coverage {
+                    Some(
+                        CoverageOptions {
+                            include: Some(
+                                Glob::new(rcstr!("**/input/**"), GlobOptions::default())
+                                    .to_resolved()
+                                    .await?,
+                            ),
+                            ..Default::default()
+                        }
+                        .resolved_cell(),
+                    )
+                } else {
+                    None
+                },
                 ..Default::default()
             },
+            enable_svg: options.svg,
             environment: Some(env),
             tree_shaking_mode: options.tree_shaking_mode,
             rules: vec![(
@@ -558,6 +588,11 @@ async fn run_test_operation(prepared_test: ResolvedVc<PreparedTest>) -> Result<V
         vec![],
         Completion::immutable(),
         should_debug("execution_test"),
+        if options.worker_threads {
+            NodeJsBackend::WorkerThreads
+        } else {
+            NodeJsBackend::ChildProcess
+        },
     )
     .await?;
 
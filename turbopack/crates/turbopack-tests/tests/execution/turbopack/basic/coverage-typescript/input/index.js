@@ -0,0 +1,40 @@
+import { distance } from './math.ts'
+
+it('should report coverage locations of the original TypeScript source', () => {
+  expect(distance({ x: 0, y: 0 }, { x: 3, y: 4 })).toBe(5)
+
+  const [path, data] = Object.entries(globalThis.__coverage__).find(([path]) =>
+    path.endsWith('math.ts')
+  )
+  expect(data.path).toBe(path)
+
+  const functions = Object.entries(data.fnMap).map(([id, fn]) => ({
+    name: fn.name,
+    decl: fn.decl.start,
+    calls: data.f[id],
+  }))
+  expect(functions).toEqual([
+    { name: 'distance', decl: { line: 6, column: 16 }, calls: 1 },
+    { name: 'unused', decl: { line: 12, column: 16 }, calls: 0 },
+  ])
+
+  // Locations are in the authored source, including the type annotations
+  const statements = Object.entries(data.statementMap).map(([id, loc]) => ({
+    loc,
+    calls: data.s[id],
+  }))
+  expect(statements).toEqual([
+    {
+      loc: { start: { line: 7, column: 2 }, end: { line: 7, column: 30 } },
+      calls: 1,
+    },
+    {
+      loc: { start: { line: 8, column: 2 }, end: { line: 8, column: 30 } },
+      calls: 1,
+    },
+    {
+      loc: { start: { line: 9, column: 2 }, end: { line: 9, column: 37 } },
+      calls: 1,
+    },
+  ])
+})
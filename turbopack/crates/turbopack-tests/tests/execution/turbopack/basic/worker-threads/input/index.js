@@ -0,0 +1,5 @@
+import { isMainThread } from 'esm-external/node:worker_threads'
+
+it('should evaluate in a worker thread', () => {
+  expect(isMainThread).toBe(false)
+})
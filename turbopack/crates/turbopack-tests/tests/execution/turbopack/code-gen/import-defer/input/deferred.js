@@ -0,0 +1,5 @@
+import { log } from './log.js'
+
+log.push('deferred.js')
+
+export const value = 'deferred'
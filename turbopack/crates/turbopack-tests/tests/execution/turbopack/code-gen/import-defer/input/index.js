@@ -0,0 +1,13 @@
+import { log } from './log.js'
+import defer * as deferred from './deferred.js'
+
+it('should evaluate a deferred module on the first namespace access', () => {
+  expect(log).toEqual([])
+
+  expect(deferred.value).toBe('deferred')
+  expect(log).toEqual(['deferred.js'])
+
+  // The module is only evaluated once
+  expect(Object.keys(deferred)).toContain('value')
+  expect(log).toEqual(['deferred.js'])
+})
@@ -0,0 +1,13 @@
+import Icon from './icon.svg'
+
+it('exposes svgs as react components', () => {
+  const element = Icon({ className: 'large', 'aria-hidden': true })
+  expect(element.type).toBe('svg')
+  expect(element.props.viewBox).toBe('0 0 24 24')
+  expect(element.props.className).toBe('large')
+  expect(element.props['aria-hidden']).toBe(true)
+
+  const path = element.children.find((child) => child.type === 'path')
+  expect(path.props.strokeWidth).toBe('2')
+  expect(path.props.style).toEqual({ fillOpacity: '0.5' })
+})
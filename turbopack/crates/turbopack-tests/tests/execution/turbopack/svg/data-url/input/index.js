@@ -0,0 +1,15 @@
+import small from './small.svg'
+import large from './large.svg'
+
+it('inlines small svgs as minified data urls', () => {
+  expect(small).toMatch(/^data:image\/svg\+xml,/)
+  const markup = decodeURIComponent(small.slice('data:image/svg+xml,'.length))
+  expect(markup).toMatch(/^<svg/)
+  expect(markup).not.toContain('<!--')
+  expect(markup).not.toContain('\n')
+})
+
+it('emits large svgs as files', () => {
+  expect(large).not.toMatch(/^data:/)
+  expect(large).toMatch(/\.svg$/)
+})
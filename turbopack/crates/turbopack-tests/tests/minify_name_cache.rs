@@ -0,0 +1,262 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use dunce::canonicalize;
+use turbo_rcstr::{RcStr, rcstr};
+use turbo_tasks::{ResolvedVc, TurboTasks, Vc, apply_effects};
+use turbo_tasks_backend::{BackendOptions, TurboTasksBackend, noop_backing_storage};
+use turbo_tasks_fs::{DiskFileSystem, FileSystem};
+use turbopack::{ModuleAssetContext, module_options::ModuleOptionsContext};
+use turbopack_browser::{BrowserChunkingContext, ContentHashing};
+use turbopack_core::{
+    asset::Asset,
+    chunk::{
+        ChunkingContext, EvaluatableAsset, MangleType, MinifyType,
+        availability_info::AvailabilityInfo,
+    },
+    compile_time_info::CompileTimeInfo,
+    context::AssetContext,
+    environment::{BrowserEnvironment, Environment, ExecutionEnvironment},
+    file_source::FileSource,
+    ident::Layer,
+    module::Module,
+    module_graph::{
+        ModuleGraph,
+        chunk_group_info::{ChunkGroup, ChunkGroupEntry},
+    },
+    output::OutputAsset,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+};
+use turbopack_ecmascript_runtime::RuntimeType;
+use turbopack_resolve::resolve_options_context::ResolveOptionsContext;
+
+const INDEX: &str = r#"import { distance } from "./math.js";
+import { format } from "./format.js";
+
+const origin = { x: 0, y: 0 };
+const target = { x: 3, y: 4 };
+console.log(format(distance(origin, target)));
+import("./lazy.js").then((lazy) => lazy.greet("world"));
+"#;
+
+const MATH: &str = r#"export function distance(from, to) {
+  const horizontal = to.x - from.x;
+  const vertical = to.y - from.y;
+  return Math.sqrt(horizontal * horizontal + vertical * vertical);
+}
+"#;
+
+const FORMAT: &str = r#"export function format(value) {
+  const rounded = Math.round(value * 100) / 100;
+  return rounded === 1 ? rounded + " unit" : rounded + " units";
+}
+"#;
+
+const FORMAT_EDITED: &str = r#"export function format(value, precision) {
+  const factor = 10 ** precision;
+  const rounded = Math.round(value * factor) / factor;
+  return rounded === 1 ? rounded + " unit" : rounded + " units";
+}
+"#;
+
+const LAZY: &str = r#"export function greet(name) {
+  const greeting = "the lazy chunk greets " + name;
+  console.log(greeting);
+  return greeting;
+}
+"#;
+
+/// Builds the project twice, each time with a fresh turbo-tasks instance like separate
+/// `turbopack build` runs, and edits a module in between. The second build reads the mangled names
+/// the first one wrote, so only the edited module and the chunk containing it change.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn editing_a_module_keeps_the_minified_names_of_the_others() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = canonicalize(dir.path()).unwrap();
+    fs::create_dir_all(root.join("input")).unwrap();
+    fs::write(root.join("input/index.js"), INDEX).unwrap();
+    fs::write(root.join("input/math.js"), MATH).unwrap();
+    fs::write(root.join("input/format.js"), FORMAT).unwrap();
+    fs::write(root.join("input/lazy.js"), LAZY).unwrap();
+
+    let first = build(&root).await;
+    let first_names = read_files(&root.join("names"));
+
+    fs::write(root.join("input/format.js"), FORMAT_EDITED).unwrap();
+    fs::remove_dir_all(root.join("output")).unwrap();
+    let second = build(&root).await;
+    let second_names = read_files(&root.join("names"));
+
+    // The modules that weren't edited keep all of their names, the edited one keeps the names of
+    // its unchanged bindings.
+    for binding in ["horizontal", "origin", "greeting"] {
+        assert_eq!(
+            module_names(&first_names, binding),
+            module_names(&second_names, binding),
+            "the names of the module with the binding {binding} changed"
+        );
+    }
+    assert_eq!(
+        module_names(&first_names, "rounded")["rounded"],
+        module_names(&second_names, "rounded")["rounded"]
+    );
+
+    // The content hashes in the file names only match when the contents do.
+    let lazy_chunk = |output: &BTreeMap<String, String>| {
+        output
+            .iter()
+            .find(|(path, content)| path.ends_with(".js") && content.contains("the lazy chunk"))
+            .map(|(path, content)| (path.clone(), content.clone()))
+            .expect("the lazy chunk is missing")
+    };
+    assert_eq!(lazy_chunk(&first), lazy_chunk(&second));
+    let changed = second
+        .keys()
+        .filter(|path| first.get(*path) != second.get(*path))
+        .collect::<Vec<_>>();
+    assert!(
+        !changed.is_empty() && changed.len() < second.len(),
+        "only the chunks containing format.js should change: {changed:#?}"
+    );
+}
+
+/// Returns the mangled names of the module in the name cache that has a binding named `binding`.
+fn module_names(name_cache: &BTreeMap<String, String>, binding: &str) -> BTreeMap<String, String> {
+    name_cache
+        .values()
+        .flat_map(|content| {
+            serde_json::from_str::<BTreeMap<String, BTreeMap<String, String>>>(content)
+                .unwrap()
+                .into_values()
+        })
+        .find(|names| names.contains_key(binding))
+        .unwrap_or_else(|| panic!("no module has the binding {binding}: {name_cache:#?}"))
+}
+
+async fn build(root: &Path) -> BTreeMap<String, String> {
+    let tt = TurboTasks::new(TurboTasksBackend::new(
+        BackendOptions {
+            storage_mode: None,
+            // The name cache is read and written by the same build.
+            dependency_tracking: true,
+            ..Default::default()
+        },
+        noop_backing_storage(),
+    ));
+    let root_path: RcStr = root.to_str().unwrap().into();
+    tt.run_once(async move {
+        let build_op = build_operation(root_path);
+        build_op.read_strongly_consistent().await?;
+        apply_effects(build_op).await?;
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    let output = read_files(&root.join("output"));
+    assert!(!output.is_empty(), "nothing was emitted");
+    output
+}
+
+/// Reads all files in the directory, keyed by their path relative to it.
+fn read_files(dir: &Path) -> BTreeMap<String, String> {
+    fn read(dir: &Path, prefix: &str, files: &mut BTreeMap<String, String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let entry = entry.unwrap();
+            let name = format!("{prefix}{}", entry.file_name().to_str().unwrap());
+            if entry.file_type().unwrap().is_dir() {
+                read(&entry.path(), &format!("{name}/"), files);
+            } else {
+                files.insert(name, fs::read_to_string(entry.path()).unwrap());
+            }
+        }
+    }
+
+    let mut files = BTreeMap::new();
+    read(dir, "", &mut files);
+    files
+}
+
+#[turbo_tasks::function(operation)]
+async fn build_operation(root: RcStr) -> Result<()> {
+    let project_fs = DiskFileSystem::new(rcstr!("project"), root);
+    let root_path = project_fs.root().owned().await?;
+    let output_path = root_path.join("output")?;
+
+    let env = Environment::new(ExecutionEnvironment::Browser(
+        BrowserEnvironment {
+            dom: true,
+            web_worker: false,
+            service_worker: false,
+            browserslist_query: rcstr!("Chrome 102"),
+        }
+        .resolved_cell(),
+    ))
+    .to_resolved()
+    .await?;
+
+    let asset_context: Vc<Box<dyn AssetContext>> = Vc::upcast(ModuleAssetContext::new(
+        Default::default(),
+        CompileTimeInfo::new(*env),
+        ModuleOptionsContext {
+            environment: Some(env),
+            ..Default::default()
+        }
+        .cell(),
+        ResolveOptionsContext::default().cell(),
+        Layer::new(rcstr!("test")),
+    ));
+
+    let entry_module = asset_context
+        .process(
+            Vc::upcast(FileSource::new(root_path.join("input/index.js")?)),
+            ReferenceType::Entry(EntryReferenceSubType::Undefined),
+        )
+        .module();
+    let entry = Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(entry_module)
+        .await?
+        .context("The entry module is not evaluatable")?
+        .to_resolved()
+        .await?;
+    let entry_modules = vec![ResolvedVc::upcast(entry)];
+
+    let module_graph = ModuleGraph::from_modules(
+        Vc::cell(vec![ChunkGroupEntry::Entry(entry_modules.clone())]),
+        false,
+        true,
+    );
+
+    let chunking_context: Vc<Box<dyn ChunkingContext>> = Vc::upcast(
+        BrowserChunkingContext::builder(
+            root_path.clone(),
+            output_path.clone(),
+            rcstr!(".."),
+            output_path.clone(),
+            output_path.clone(),
+            output_path.clone(),
+            env,
+            RuntimeType::Production,
+        )
+        .minify_type(MinifyType::Minify {
+            mangle: Some(MangleType::OptimalSize),
+        })
+        .use_content_hashing(ContentHashing::Direct { length: 16 })
+        .minify_name_cache(Some(root_path.join("names")?))
+        .build(),
+    );
+
+    let chunk_group = chunking_context.evaluated_chunk_group_assets(
+        entry_module.ident(),
+        ChunkGroup::Entry(entry_modules),
+        module_graph,
+        AvailabilityInfo::root(),
+    );
+    for asset in chunk_group.expand_all_assets().await?.iter() {
+        asset.content().write(asset.path().owned().await?).await?;
+    }
+
+    Ok(())
+}
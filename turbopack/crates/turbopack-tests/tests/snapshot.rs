@@ -29,8 +29,9 @@ use turbopack_browser::BrowserChunkingContext;
 use turbopack_core::{
     asset::Asset,
     chunk::{
-        ChunkingConfig, ChunkingContext, ChunkingContextExt, EvaluatableAsset, EvaluatableAssetExt,
-        EvaluatableAssets, MinifyType, SourceMapSourceType, availability_info::AvailabilityInfo,
+        ChunkFormat, ChunkingConfig, ChunkingContext, ChunkingContextExt, EvaluatableAsset,
+        EvaluatableAssetExt, EvaluatableAssets, MinifyType, SourceMapSourceType,
+        availability_info::AvailabilityInfo,
     },
     compile_time_defines,
     compile_time_info::{CompileTimeDefineValue, CompileTimeInfo, DefinableNameSegment},
@@ -52,7 +53,9 @@ use turbopack_core::{
     source::Source,
 };
 use turbopack_ecmascript::{
-    AnalyzeMode, EcmascriptInputTransform, TreeShakingMode, chunk::EcmascriptChunkType,
+    AnalyzeMode, EcmascriptInputTransform, TreeShakingMode,
+    chunk::EcmascriptChunkType,
+    federation::{FederationOptions, federation_container_source, federation_import_map},
 };
 use turbopack_ecmascript_plugins::transform::{
     emotion::{EmotionTransformConfig, EmotionTransformer},
@@ -88,6 +91,8 @@ struct SnapshotOptions {
     #[serde(default)]
     remove_unused_exports: bool,
     #[serde(default)]
+    constant_export_inlining: bool,
+    #[serde(default)]
     scope_hoisting: bool,
     #[serde(default)]
     production_chunking: bool,
@@ -95,6 +100,15 @@ struct SnapshotOptions {
     enable_debug_ids: bool,
     #[serde(default)]
     source_map_source_type: SourceMapSourceType,
+    /// Builds the entry with module federation, including the container of the exposed modules.
+    /// Only supported with the browser runtime.
+    #[serde(default)]
+    federation: Option<FederationOptions>,
+    #[serde(default)]
+    chunk_format: ChunkFormat,
+    /// Extracts static styles of emotion and styled-components into CSS modules.
+    #[serde(default)]
+    extract_static_styles: bool,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -123,10 +137,14 @@ impl Default for SnapshotOptions {
             tree_shaking_mode: None,
             remove_unused_imports: false,
             remove_unused_exports: false,
+            constant_export_inlining: false,
             scope_hoisting: false,
             production_chunking: false,
             enable_debug_ids: false,
             source_map_source_type: SourceMapSourceType::default(),
+            federation: None,
+            chunk_format: ChunkFormat::default(),
+            extract_static_styles: false,
         }
     }
 }
@@ -335,16 +353,28 @@ async fn run_test_operation(resource: RcStr) -> Result<Vc<FileSystemPath>> {
             preprocess: ResolvedVc::cell(vec![]),
             main: ResolvedVc::cell(vec![
                 EcmascriptInputTransform::Plugin(ResolvedVc::cell(Box::new(
-                    EmotionTransformer::new(&EmotionTransformConfig::default())
-                        .expect("Should be able to create emotion transformer"),
+                    EmotionTransformer::new(&EmotionTransformConfig {
+                        extract_static_styles: options.extract_static_styles,
+                        ..Default::default()
+                    })
+                    .expect("Should be able to create emotion transformer"),
                 ) as _)),
                 EcmascriptInputTransform::Plugin(ResolvedVc::cell(Box::new(
-                    StyledComponentsTransformer::new(&StyledComponentsTransformConfig::default()),
+                    StyledComponentsTransformer::new(&StyledComponentsTransformConfig {
+                        extract_static_styles: options.extract_static_styles,
+                        ..Default::default()
+                    }),
                 ) as _)),
             ]),
             postprocess: ResolvedVc::cell(vec![]),
         }],
     );
+    let federation = options.federation.map(FederationOptions::resolved_cell);
+    let import_map = match federation {
+        Some(federation) => Some(federation_import_map(*federation).to_resolved().await?),
+        None => None,
+    };
+
     let asset_context: Vc<Box<dyn AssetContext>> = Vc::upcast(ModuleAssetContext::new(
         Default::default(),
         compile_time_info,
@@ -360,6 +390,7 @@ async fn run_test_operation(resource: RcStr) -> Result<Vc<FileSystemPath>> {
                 ignore_dynamic_requests: true,
                 infer_module_side_effects: true,
                 enable_exports_info_inlining: true,
+                enable_constant_export_inlining: options.constant_export_inlining,
                 ..Default::default()
             },
             environment: Some(env),
@@ -393,6 +424,7 @@ async fn run_test_operation(resource: RcStr) -> Result<Vc<FileSystemPath>> {
                 }
                 .resolved_cell(),
             )],
+            import_map,
             ..Default::default()
         }
         .cell(),
@@ -413,9 +445,23 @@ async fn run_test_operation(resource: RcStr) -> Result<Vc<FileSystemPath>> {
     let (evaluatable_assets, entry_modules) = if let Some(ecmascript) =
         Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(entry_module).await?
     {
-        let evaluatable_assets = runtime_entries
+        let mut evaluatable_assets = runtime_entries
             .unwrap_or_else(EvaluatableAssets::empty)
             .with_entry(ecmascript);
+        if let Some(federation) = federation {
+            let container = asset_context
+                .process(
+                    federation_container_source(project_path.clone(), *federation),
+                    ReferenceType::Entry(EntryReferenceSubType::Undefined),
+                )
+                .module();
+            let Some(container) =
+                Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(container).await?
+            else {
+                bail!("The module federation container is not chunkable");
+            };
+            evaluatable_assets = evaluatable_assets.with_entry(container);
+        }
         (
             evaluatable_assets,
             evaluatable_assets
@@ -485,7 +531,12 @@ async fn run_test_operation(resource: RcStr) -> Result<Vc<FileSystemPath>> {
                     .then(|| binding_usage.unwrap()),
             )
             .debug_ids(options.enable_debug_ids)
-            .source_map_source_type(options.source_map_source_type);
+            .source_map_source_type(options.source_map_source_type)
+            .chunk_format(options.chunk_format);
+
+            if let Some(federation) = federation {
+                builder = builder.federation(Some(federation.await?.name.clone()));
+            }
 
             if options.production_chunking {
                 builder = builder
@@ -503,6 +554,9 @@ async fn run_test_operation(resource: RcStr) -> Result<Vc<FileSystemPath>> {
             Vc::upcast(builder.build())
         }
         Runtime::NodeJs => {
+            if federation.is_some() {
+                bail!("Module federation is only supported with the browser runtime");
+            }
             let mut builder = NodeJsChunkingContext::builder(
                 project_root,
                 project_path.clone(),
@@ -526,7 +580,8 @@ async fn run_test_operation(resource: RcStr) -> Result<Vc<FileSystemPath>> {
                     .then(|| binding_usage.unwrap()),
             )
             .debug_ids(options.enable_debug_ids)
-            .source_map_source_type(options.source_map_source_type);
+            .source_map_source_type(options.source_map_source_type)
+            .chunk_format(options.chunk_format);
 
             if options.production_chunking {
                 builder = builder
@@ -563,7 +618,10 @@ async fn run_test_operation(resource: RcStr) -> Result<Vc<FileSystemPath>> {
                             // `expected` expects a completely flat output directory.
                             chunk_root_path
                                 .join(entry_module.ident().path().await?.file_stem().unwrap())?
-                                .with_extension("entry.js"),
+                                .with_extension(match options.chunk_format {
+                                    ChunkFormat::Script => "entry.js",
+                                    ChunkFormat::EsModule => "entry.mjs",
+                                }),
                             evaluatable_assets,
                             module_graph,
                             OutputAssets::empty(),
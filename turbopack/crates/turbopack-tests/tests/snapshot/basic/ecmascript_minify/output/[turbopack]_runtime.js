@@ -1,4 +1,5 @@
 const RUNTIME_PUBLIC_PATH = "output/[turbopack]_runtime.js";
 const RELATIVE_ROOT_PATH = "../../../../../../..";
 const ASSET_PREFIX = "/";
+const CHUNK_FORMAT = "script";
 // Dummy runtime
\ No newline at end of file
@@ -0,0 +1,15 @@
+export const VERSION = '1.0.0'
+export const MAX_RETRIES = 3
+
+export enum Color {
+  Red = 'red',
+  Green = 'green',
+}
+
+export enum Direction {
+  Up = 1,
+  Down,
+  Left = 'left',
+}
+
+export const random = Math.random()
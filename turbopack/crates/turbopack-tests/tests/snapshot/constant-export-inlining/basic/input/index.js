@@ -0,0 +1,12 @@
+import { VERSION, MAX_RETRIES, Color, Direction, random } from './constants.ts'
+
+// Inlined, the exports are removed as they aren't used otherwise
+console.log(VERSION, MAX_RETRIES)
+console.log(Color.Red, Color.Green)
+
+// Known members are inlined, the reverse mapping is read from the enum, so it's still exported
+console.log(Direction.Up, Direction.Left)
+console.log(Direction[1])
+
+// Not a constant
+console.log(random)
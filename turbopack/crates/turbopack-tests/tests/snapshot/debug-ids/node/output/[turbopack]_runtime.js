@@ -1,6 +1,7 @@
 const RUNTIME_PUBLIC_PATH = "output/[turbopack]_runtime.js";
 const RELATIVE_ROOT_PATH = "../../../../../../..";
 const ASSET_PREFIX = "/";
+const CHUNK_FORMAT = "script";
 /**
  * This file contains runtime types and functions that are shared between all
  * TurboPack ECMAScript runtimes.
@@ -616,16 +617,20 @@ nodeContextPrototype.c = moduleCache;
     if (typeof exportedPath !== 'string') {
         return exported;
     }
+    if (exportedPath.startsWith('file:')) {
+        // Asset URLs in ES module output are already resolved against `import.meta.url`.
+        return exportedPath;
+    }
     const strippedAssetPrefix = exportedPath.slice(ASSET_PREFIX.length);
     const resolved = path.resolve(RUNTIME_ROOT, strippedAssetPrefix);
     return url.pathToFileURL(resolved).href;
 }
 nodeContextPrototype.R = resolvePathFromModule;
-function loadRuntimeChunk(sourcePath, chunkData) {
+function loadRuntimeChunk(sourcePath, chunkData, chunkModules) {
     if (typeof chunkData === 'string') {
-        loadRuntimeChunkPath(sourcePath, chunkData);
+        loadRuntimeChunkPath(sourcePath, chunkData, chunkModules);
     } else {
-        loadRuntimeChunkPath(sourcePath, chunkData.path);
+        loadRuntimeChunkPath(sourcePath, chunkData.path, chunkModules);
     }
 }
 const loadedChunks = new Set();
@@ -635,7 +640,10 @@ const chunkCache = new Map();
 function clearChunkCache() {
     chunkCache.clear();
 }
-function loadRuntimeChunkPath(sourcePath, chunkPath) {
+/**
+ * Loads a chunk of the entry's chunk group. In ES module output, the entry chunk imports the
+ * chunk itself and passes its module factories.
+ */ function loadRuntimeChunkPath(sourcePath, chunkPath, chunkModules) {
     if (!isJs(chunkPath)) {
         // We only support loading JS chunks in Node.js.
         // This branch can be hit when trying to load a CSS chunk.
@@ -645,8 +653,10 @@ function loadRuntimeChunkPath(sourcePath, chunkPath) {
         return;
     }
     try {
-        const resolved = path.resolve(RUNTIME_ROOT, chunkPath);
-        const chunkModules = require(resolved);
+        if (chunkModules === undefined) {
+            const resolved = path.resolve(RUNTIME_ROOT, chunkPath);
+            chunkModules = require(resolved);
+        }
         installCompressedModuleFactories(chunkModules, 0, moduleFactories);
         loadedChunks.add(chunkPath);
     } catch (cause) {
@@ -669,7 +679,20 @@ function loadChunkAsync(chunkData) {
         return unsupportedLoadChunk;
     }
     let entry = chunkCache.get(chunkPath);
-    if (entry === undefined) {
+    if (entry === undefined && CHUNK_FORMAT === 'esm') {
+        const resolved = path.resolve(RUNTIME_ROOT, chunkPath);
+        entry = import(url.pathToFileURL(resolved).href).then((chunk)=>{
+            installCompressedModuleFactories(chunk.default, 0, moduleFactories);
+        }, (cause)=>{
+            const errorMessage = `Failed to load chunk ${chunkPath} from module ${this.m.id}`;
+            const error = new Error(errorMessage, {
+                cause
+            });
+            error.name = 'ChunkLoadError';
+            throw error;
+        });
+        chunkCache.set(chunkPath, entry);
+    } else if (entry === undefined) {
         try {
             // resolve to an absolute path to simplify `require` handling
             const resolved = path.resolve(RUNTIME_ROOT, chunkPath);
@@ -780,15 +803,15 @@ function getOrInstantiateRuntimeModule(chunkPath, moduleId) {
     }
     return instantiateRuntimeModule(chunkPath, moduleId);
 }
-const regexJsUrl = /\.js(?:\?[^#]*)?(?:#.*)?$/;
+const regexJsUrl = /\.m?js(?:\?[^#]*)?(?:#.*)?$/;
 /**
- * Checks if a given path/URL ends with .js, optionally followed by ?query or #fragment.
+ * Checks if a given path/URL ends with .js or .mjs, optionally followed by ?query or #fragment.
  */ function isJs(chunkUrlOrPath) {
     return regexJsUrl.test(chunkUrlOrPath);
 }
 module.exports = (sourcePath)=>({
         m: (id)=>getOrInstantiateRuntimeModule(sourcePath, id),
-        c: (chunkData)=>loadRuntimeChunk(sourcePath, chunkData)
+        c: (chunkData, chunkModules)=>loadRuntimeChunk(sourcePath, chunkData, chunkModules)
     });
 
 
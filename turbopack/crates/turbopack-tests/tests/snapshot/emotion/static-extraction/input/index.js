@@ -0,0 +1,32 @@
+import styled from '@emotion/styled'
+import { css } from '@emotion/css'
+
+const primary = 'rebeccapurple'
+const gap = 4
+
+// Templates can interpolate constants of the module.
+const Button = styled.button`
+  color: ${primary};
+  padding: ${gap}px;
+`
+
+// Numbers in object styles get a px unit, except for unitless properties and 0.
+const Card = styled('section')({
+  marginTop: 8,
+  lineHeight: 1.5,
+  zIndex: 2,
+  opacity: 0,
+  '&:hover': { backgroundColor: primary },
+})
+
+// `css` becomes the class name of the extracted styles.
+const highlight = css`
+  outline: 1px solid ${primary};
+`
+
+// Props are only known at runtime, so these styles are left to emotion.
+const Dynamic = styled.div`
+  color: ${(props) => props.color};
+`
+
+console.log(Button, Card, highlight, Dynamic)
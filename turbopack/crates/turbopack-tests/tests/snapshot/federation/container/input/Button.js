@@ -0,0 +1,5 @@
+import { version } from 'shared-lib'
+
+export default function Button() {
+  return `button ${version}`
+}
@@ -0,0 +1,5 @@
+import { version } from 'shared-lib'
+
+console.log(version)
+
+import('app2/Widget').then((widget) => console.log(widget))
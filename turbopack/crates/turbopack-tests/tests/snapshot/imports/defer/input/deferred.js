@@ -0,0 +1,3 @@
+console.log('evaluated deferred.js')
+
+export const value = 'deferred'
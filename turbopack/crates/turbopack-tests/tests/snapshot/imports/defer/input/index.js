@@ -0,0 +1,4 @@
+import defer * as deferred from './deferred.js'
+
+console.log('before access')
+console.log(deferred.value)
@@ -0,0 +1 @@
+export default 'Hello from an async chunk!'
@@ -0,0 +1,5 @@
+import { message } from './message.js'
+
+console.log(message)
+
+import('./async.js').then(({ default: value }) => console.log(value))
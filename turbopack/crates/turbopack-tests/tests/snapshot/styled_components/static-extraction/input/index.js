@@ -0,0 +1,16 @@
+import styled from 'styled-components'
+
+const size = 12
+
+const Title = styled.h1`
+  font-size: ${size}px;
+`
+
+const Link = styled.a({ textDecoration: 'none', fontWeight: 700 })
+
+// Props are only known at runtime, so these styles are left to styled-components.
+const Badge = styled.span`
+  color: ${(props) => (props.$active ? 'green' : 'gray')};
+`
+
+console.log(Title, Link, Badge)
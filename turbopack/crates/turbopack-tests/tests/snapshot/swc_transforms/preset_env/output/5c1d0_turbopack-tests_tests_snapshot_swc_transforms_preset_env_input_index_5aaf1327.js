@@ -6,10 +6,10 @@
 if (!Array.isArray(globalThis.TURBOPACK)) {
     return;
 }
-
 const CHUNK_BASE_PATH = "";
+const CHUNK_FORMAT = "script";
 const RELATIVE_ROOT_PATH = "../../../../../../..";
-const RUNTIME_PUBLIC_PATH = "";
+const RUNTIME_PUBLIC_PATH = CHUNK_BASE_PATH;
 const CHUNK_SUFFIX = "";
 /**
  * This file contains runtime types and functions that are shared between all
@@ -1212,9 +1212,13 @@ browserContextPrototype.b = getWorkerBlobURL;
 }
 function getPathFromScript(chunkScript) {
     if (typeof chunkScript === 'string') {
-        return chunkScript;
+        // ES module chunks are identified by their `import.meta.url`
+        return CHUNK_FORMAT === 'esm' ? getPathFromChunkUrl(chunkScript) : chunkScript;
     }
     var chunkUrl = typeof TURBOPACK_NEXT_CHUNK_URLS !== 'undefined' ? TURBOPACK_NEXT_CHUNK_URLS.pop() : chunkScript.getAttribute('src');
+    return getPathFromChunkUrl(chunkUrl);
+}
+function getPathFromChunkUrl(chunkUrl) {
     var src = decodeURIComponent(chunkUrl.replace(/[?#].*$/, ''));
     var path = src.startsWith(CHUNK_BASE_PATH) ? src.slice(CHUNK_BASE_PATH.length) : src;
     return path;
@@ -1624,7 +1628,11 @@ var BACKEND;
             // `resolver.resolve()` in this branch.
             return resolver.promise;
         }
-        if (typeof importScripts === 'function') {
+        if (CHUNK_FORMAT === 'esm' && isJs(chunkUrl)) {
+            // ES module chunks export their registration, which works the same way in documents and
+            // module workers.
+            loadEsmChunk(chunkUrl, resolver);
+        } else if (typeof importScripts === 'function') {
             // We're in a web worker
             if (isCss(chunkUrl)) {
             // ignore
@@ -1704,6 +1712,15 @@ var BACKEND;
         resolver.loadingStarted = true;
         return resolver.promise;
     }
+    /**
+   * Loads an ES module chunk and registers the chunk it exports.
+   */ function loadEsmChunk(chunkUrl, resolver) {
+        import(/* webpackIgnore: true */ chunkUrl).then(function(chunk) {
+            return registerChunk(chunk.default);
+        }, function(error) {
+            return resolver.reject(error);
+        });
+    }
     function fetchWebAssembly(wasmChunkPath) {
         return fetch(getChunkRelativeUrl(wasmChunkPath));
     }
@@ -40,6 +40,9 @@ use crate::{
 pub struct WebAssemblyModuleAsset {
     source: ResolvedVc<WebAssemblySource>,
     asset_context: ResolvedVc<Box<dyn AssetContext>>,
+    /// Whether the module is imported with `import source`, i.e. it exports the compiled
+    /// `WebAssembly.Module` instead of instantiating it.
+    source_phase: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -52,6 +55,21 @@ impl WebAssemblyModuleAsset {
         Self::cell(WebAssemblyModuleAsset {
             source,
             asset_context,
+            source_phase: false,
+        })
+    }
+
+    /// The module of an `import source` of the WebAssembly source, which default exports the
+    /// compiled `WebAssembly.Module`.
+    #[turbo_tasks::function]
+    pub fn new_source_phase(
+        source: ResolvedVc<WebAssemblySource>,
+        asset_context: ResolvedVc<Box<dyn AssetContext>>,
+    ) -> Vc<Self> {
+        Self::cell(WebAssemblyModuleAsset {
+            source,
+            asset_context,
+            source_phase: true,
         })
     }
 
@@ -64,7 +82,7 @@ impl WebAssemblyModuleAsset {
     async fn loader_as_module(&self) -> Result<Vc<Box<dyn Module>>> {
         let query = &self.source.ident().await?.query;
 
-        let loader_source = if query == "?module" {
+        let loader_source = if self.source_phase || query == "?module" {
             compiling_loader_source(*self.source)
         } else {
             instantiating_loader_source(*self.source)
@@ -125,7 +143,11 @@ impl Module for WebAssemblyModuleAsset {
         Ok(self
             .source
             .ident()
-            .with_modifier(rcstr!("wasm module"))
+            .with_modifier(if self.source_phase {
+                rcstr!("wasm module source")
+            } else {
+                rcstr!("wasm module")
+            })
             .with_layer(self.asset_context.into_trait_ref().await?.layer()))
     }
 
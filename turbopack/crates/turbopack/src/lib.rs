@@ -16,7 +16,7 @@ use anyhow::{Result, bail};
 use module_options::{ModuleOptions, ModuleOptionsContext, ModuleRuleEffect, ModuleType};
 use tracing::{Instrument, field::Empty};
 use turbo_rcstr::{RcStr, rcstr};
-use turbo_tasks::{ResolvedVc, TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks::{ResolvedVc, TryJoinIterExt, ValueToString, Vc, fxindexmap};
 use turbo_tasks_fs::FileSystemPath;
 pub use turbopack_core::condition;
 use turbopack_core::{
@@ -56,6 +56,7 @@ use turbopack_ecmascript::{
     },
     tree_shake::asset::EcmascriptModulePartAsset,
 };
+use turbopack_image::svg_source::{OptimizedSvgSource, SvgImportMode, SvgModuleSource};
 use turbopack_json::JsonModuleAsset;
 use turbopack_resolve::{
     resolve::resolve_options, resolve_options_context::ResolveOptionsContext,
@@ -277,6 +278,27 @@ async fn apply_module_type(
         ModuleType::InlinedBytesJs => {
             ResolvedVc::upcast(InlinedBytesJsModule::new(*source).to_resolved().await?)
         }
+        ModuleType::Svg { mode } => {
+            let optimized_source: ResolvedVc<Box<dyn Source>> =
+                ResolvedVc::upcast(OptimizedSvgSource::new(*source).to_resolved().await?);
+            let static_asset = StaticUrlJsModule::new(*optimized_source, None)
+                .to_resolved()
+                .await?;
+            if *mode == SvgImportMode::Url {
+                ResolvedVc::upcast(static_asset)
+            } else {
+                module_asset_context
+                    .process(
+                        Vc::upcast(SvgModuleSource::new(*optimized_source, *mode)),
+                        ReferenceType::Internal(ResolvedVc::cell(fxindexmap!(
+                            rcstr!("SVG") => ResolvedVc::upcast(static_asset)
+                        ))),
+                    )
+                    .module()
+                    .to_resolved()
+                    .await?
+            }
+        }
         ModuleType::WebAssembly { source_ty } => ResolvedVc::upcast(
             WebAssemblyModuleAsset::new(
                 WebAssemblySource::new(*source, *source_ty),
@@ -711,6 +733,34 @@ async fn process_default_internal(
         return Ok(ProcessResult::Unknown(current_source).cell());
     };
 
+    if matches!(
+        reference_type,
+        ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::ImportSource)
+    ) {
+        // Only WebAssembly modules have a source representation.
+        let ModuleType::WebAssembly { source_ty } = module_type else {
+            ModuleIssue::new(
+                *ident,
+                rcstr!("Unsupported source phase import"),
+                rcstr!("Only WebAssembly modules can be imported with `import source`"),
+                Some(IssueSource::from_source_only(current_source)),
+            )
+            .to_resolved()
+            .await?
+            .emit();
+            return Ok(ProcessResult::Ignore.cell());
+        };
+        return Ok(ProcessResult::Module(ResolvedVc::upcast(
+            WebAssemblyModuleAsset::new_source_phase(
+                WebAssemblySource::new(*current_source, source_ty),
+                Vc::upcast(module_asset_context),
+            )
+            .to_resolved()
+            .await?,
+        ))
+        .cell());
+    }
+
     let module = apply_module_type(
         current_source,
         module_asset_context,
@@ -1066,6 +1116,7 @@ pub async fn replace_external(
         }
         ExternalType::Global => CachedExternalType::Global,
         ExternalType::Script => CachedExternalType::Script,
+        ExternalType::FederationRemote => CachedExternalType::FederationRemote,
         ExternalType::Url => {
             // we don't want to wrap url externals.
             return Ok(None);
@@ -2,6 +2,7 @@ pub(crate) mod custom_module_type;
 pub mod match_mode;
 pub mod module_options_context;
 pub mod module_rule;
+pub mod native_loaders;
 pub mod rule_condition;
 pub mod transition_rule;
 
@@ -34,7 +35,10 @@ use turbopack_node::{
 use turbopack_resolve::resolve_options_context::ResolveOptionsContext;
 use turbopack_wasm::source::WebAssemblySourceType;
 
-use crate::evaluate_context::{config_tracing_module_context, node_evaluate_asset_context};
+use crate::{
+    evaluate_context::{config_tracing_module_context, node_evaluate_asset_context},
+    module_options::native_loaders::native_loaders_transform,
+};
 
 #[turbo_tasks::function]
 fn package_import_map_from_import_mapping(
@@ -203,11 +207,13 @@ impl ModuleOptions {
                     enable_types,
                     ref enable_typescript_transform,
                     ref enable_decorators,
+                    enable_coverage,
                     ignore_dynamic_requests,
                     import_externals,
                     esm_url_rewrite_behavior,
                     enable_typeof_window_inlining,
                     enable_exports_info_inlining,
+                    enable_constant_export_inlining,
                     source_maps: ecmascript_source_maps,
                     inline_helpers,
                     infer_module_side_effects,
@@ -215,6 +221,7 @@ impl ModuleOptions {
                 },
             enable_mdx,
             enable_mdx_rs,
+            enable_svg,
             css:
                 CssOptionsContext {
                     enable_raw_css,
@@ -285,12 +292,18 @@ impl ModuleOptions {
             analyze_mode,
             enable_typeof_window_inlining,
             enable_exports_info_inlining,
+            enable_constant_export_inlining,
             inline_helpers,
             infer_module_side_effects,
             ..Default::default()
         };
         let ecmascript_options_vc = ecmascript_options.resolved_cell();
 
+        // Instrument before downleveling, so that counters match the authored syntax
+        if let Some(options) = enable_coverage {
+            postprocess.push(EcmascriptInputTransform::Coverage(options));
+        }
+
         if let Some(environment) = environment {
             postprocess.push(EcmascriptInputTransform::PresetEnv(environment));
         }
@@ -613,27 +626,40 @@ impl ModuleOptions {
                 let mut all_rule_condition = RuleCondition::All(rule_conditions);
                 all_rule_condition.flatten();
                 if !matches!(all_rule_condition, RuleCondition::False) {
+                    // Common loaders that only turn a file into a JavaScript module are
+                    // replaced by in-process implementations when their options allow it.
+                    let transform = if let Some(transform) = native_loaders_transform(
+                        &rule.loaders.await?,
+                        rule.rename_as.clone(),
+                        &execution_context.await?.project_path,
+                    )
+                    .await?
+                    {
+                        transform
+                    } else {
+                        ResolvedVc::upcast(
+                            WebpackLoaders::new(
+                                node_evaluate_asset_context(
+                                    *execution_context,
+                                    Some(import_map),
+                                    None,
+                                    Layer::new(rcstr!("webpack_loaders")),
+                                    false,
+                                ),
+                                *execution_context,
+                                *rule.loaders,
+                                rule.rename_as.clone(),
+                                resolve_options_context,
+                                matches!(ecmascript_source_maps, SourceMapsType::Full),
+                            )
+                            .to_resolved()
+                            .await?,
+                        )
+                    };
                     rules.push(ModuleRule::new(
                         all_rule_condition,
                         vec![ModuleRuleEffect::SourceTransforms(ResolvedVc::cell(vec![
-                            ResolvedVc::upcast(
-                                WebpackLoaders::new(
-                                    node_evaluate_asset_context(
-                                        *execution_context,
-                                        Some(import_map),
-                                        None,
-                                        Layer::new(rcstr!("webpack_loaders")),
-                                        false,
-                                    ),
-                                    *execution_context,
-                                    *rule.loaders,
-                                    rule.rename_as.clone(),
-                                    resolve_options_context,
-                                    matches!(ecmascript_source_maps, SourceMapsType::Full),
-                                )
-                                .to_resolved()
-                                .await?,
-                            ),
+                            transform,
                         ]))],
                     ));
                 }
@@ -773,6 +799,19 @@ impl ModuleOptions {
             ]);
         }
 
+        if let Some(mode) = enable_svg {
+            rules.push(ModuleRule::new(
+                RuleCondition::all(vec![
+                    RuleCondition::ResourcePathEndsWith(".svg".to_string()),
+                    // `url()` in CSS and `new URL()` keep referencing the file itself.
+                    RuleCondition::not(RuleCondition::ReferenceType(ReferenceType::Url(
+                        UrlReferenceSubType::Undefined,
+                    ))),
+                ]),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Svg { mode })],
+            ));
+        }
+
         if enable_mdx || enable_mdx_rs.is_some() {
             let (jsx_runtime, jsx_import_source, development) = if let Some(enable_jsx) = enable_jsx
             {
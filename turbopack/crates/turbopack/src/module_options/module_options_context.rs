@@ -14,8 +14,10 @@ use turbopack_core::{
     environment::Environment, resolve::options::ImportMapping,
 };
 use turbopack_ecmascript::{
-    AnalyzeMode, TreeShakingMode, TypeofWindow, references::esm::UrlRewriteBehavior,
+    AnalyzeMode, CoverageOptions, TreeShakingMode, TypeofWindow,
+    references::esm::UrlRewriteBehavior,
 };
+pub use turbopack_image::svg_source::SvgImportMode;
 pub use turbopack_mdx::MdxTransformOptions;
 use turbopack_node::{
     execution_context::ExecutionContext,
@@ -189,6 +191,9 @@ pub struct ModuleOptionsContext {
     // however we might want to unify them in the future.
     pub enable_mdx: bool,
     pub enable_mdx_rs: Option<ResolvedVc<MdxTransformOptions>>,
+    /// Minifies `.svg` files and exposes them as url, data url or React component. When unset,
+    /// they are plain static assets.
+    pub enable_svg: Option<SvgImportMode>,
 
     pub environment: Option<ResolvedVc<Environment>>,
     pub execution_context: Option<ResolvedVc<ExecutionContext>>,
@@ -230,6 +235,8 @@ pub struct EcmascriptOptionsContext {
     pub enable_types: bool,
     pub enable_typescript_transform: Option<ResolvedVc<TypescriptTransformOptions>>,
     pub enable_decorators: Option<ResolvedVc<DecoratorsOptions>>,
+    /// Instruments modules with Istanbul compatible code coverage counters.
+    pub enable_coverage: Option<ResolvedVc<CoverageOptions>>,
     pub esm_url_rewrite_behavior: Option<UrlRewriteBehavior>,
     /// References to externals from ESM imports should use `import()` and make
     /// async modules.
@@ -244,6 +251,9 @@ pub struct EcmascriptOptionsContext {
     /// Whether to allow accessing exports info via `__webpack_exports_info__`.
     pub enable_exports_info_inlining: bool,
 
+    /// Whether to inline exported constants and TypeScript enum members into importing modules.
+    pub enable_constant_export_inlining: bool,
+
     // TODO should this be a part of Environment instead?
     pub inline_helpers: bool,
 
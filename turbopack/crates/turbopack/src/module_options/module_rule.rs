@@ -9,6 +9,7 @@ use turbopack_core::{
 };
 use turbopack_css::CssModuleAssetType;
 use turbopack_ecmascript::{EcmascriptInputTransforms, EcmascriptOptions};
+use turbopack_image::svg_source::SvgImportMode;
 use turbopack_wasm::source::WebAssemblySourceType;
 
 use crate::module_options::{CustomModuleType, RuleCondition, match_mode::MatchMode};
@@ -148,6 +149,10 @@ pub enum ModuleType {
         tag: Option<RcStr>,
     },
     InlinedBytesJs,
+    /// An SVG file that is minified and exposed as url, data url or React component.
+    Svg {
+        mode: SvgImportMode,
+    },
     WebAssembly {
         source_ty: WebAssemblySourceType,
     },
@@ -0,0 +1,479 @@
+//! In-process replacements for common webpack loaders.
+//!
+//! Running a loader through `turbopack-node` costs a round trip to a Node.js worker for every
+//! file. Loaders that only convert a file into a JavaScript module (`raw-loader`, `url-loader`,
+//! `@svgr/webpack` and `yaml-loader`) are replaced by a [SourceTransform] implemented in Rust when
+//! the configured options are understood. Otherwise the loader runs in Node.js and a
+//! [NativeLoaderFallbackIssue] explains why.
+
+use anyhow::{Context, Result, bail};
+use bincode::{Decode, Encode};
+use data_encoding::BASE64;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use turbo_rcstr::{RcStr, rcstr};
+use turbo_tasks::{NonLocalValue, ResolvedVc, TaskInput, Vc, trace::TraceRawVcs};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    issue::{Issue, IssueExt, IssueSeverity, IssueStage, OptionStyledString, StyledString},
+    source::Source,
+    source_transform::SourceTransform,
+};
+use turbopack_image::process::{optimize_svg, svg::codegen::svg_component_module};
+use turbopack_node::transforms::webpack::WebpackLoaderItem;
+
+/// A webpack loader that has an in-process implementation.
+#[derive(
+    Clone, Debug, PartialEq, Eq, Hash, TaskInput, TraceRawVcs, NonLocalValue, Encode, Decode,
+)]
+pub enum NativeLoader {
+    /// `raw-loader`: exports the content of the file as a string.
+    Raw { es_module: bool },
+    /// `url-loader` without a `limit`: exports a base64 `data:` url of the file.
+    Url {
+        mimetype: Option<RcStr>,
+        es_module: bool,
+    },
+    /// `@svgr/webpack`: exports a React component rendering the SVG.
+    Svgr { svgo: bool },
+    /// `yaml-loader`: exports the parsed YAML document.
+    Yaml,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum NativeLoaderMatch {
+    /// The loader has no in-process implementation.
+    Unknown,
+    Supported(NativeLoader),
+    /// The loader has an in-process implementation, but its options can't be handled by it.
+    Unsupported {
+        loader: &'static str,
+        reason: String,
+    },
+}
+
+/// Returns the name of the package a loader request refers to, e.g. `@svgr/webpack` for
+/// `@svgr/webpack` or `/project/node_modules/@svgr/webpack/lib/index.js`. Returns `None` for
+/// relative and absolute paths outside of `node_modules`.
+fn loader_package_name(loader: &str) -> Option<&str> {
+    let request = match loader.rfind("node_modules/") {
+        Some(index) => &loader[index + "node_modules/".len()..],
+        None if loader.starts_with('.') || loader.starts_with('/') || loader.contains('\\') => {
+            return None;
+        }
+        None => loader,
+    };
+    let end = if request.starts_with('@') {
+        let scope_end = request.find('/')?;
+        request[scope_end + 1..]
+            .find('/')
+            .map_or(request.len(), |index| scope_end + 1 + index)
+    } else {
+        request.find('/').unwrap_or(request.len())
+    };
+    Some(&request[..end])
+}
+
+fn bool_option(options: &JsonMap<String, JsonValue>, key: &str, default: bool) -> Result<bool> {
+    match options.get(key) {
+        None => Ok(default),
+        Some(JsonValue::Bool(value)) => Ok(*value),
+        Some(value) => bail!("`{key}` must be a boolean, but is `{value}`"),
+    }
+}
+
+fn check_option_keys(options: &JsonMap<String, JsonValue>, supported: &[&str]) -> Result<()> {
+    if let Some(key) = options
+        .keys()
+        .find(|key| !supported.contains(&key.as_str()))
+    {
+        bail!("the option `{key}` is not supported");
+    }
+    Ok(())
+}
+
+fn native_loader_from_options(
+    loader: &'static str,
+    options: &JsonMap<String, JsonValue>,
+) -> Result<NativeLoader> {
+    Ok(match loader {
+        "raw-loader" => {
+            check_option_keys(options, &["esModule"])?;
+            NativeLoader::Raw {
+                es_module: bool_option(options, "esModule", true)?,
+            }
+        }
+        "url-loader" => {
+            check_option_keys(options, &["limit", "mimetype", "encoding", "esModule"])?;
+            match options.get("limit") {
+                None | Some(JsonValue::Bool(true)) => {}
+                Some(_) => bail!("a `limit` requires falling back to another loader"),
+            }
+            match options.get("encoding") {
+                None | Some(JsonValue::Bool(true)) => {}
+                Some(JsonValue::String(encoding)) if encoding == "base64" => {}
+                Some(value) => bail!("the encoding `{value}` is not supported"),
+            }
+            let mimetype = match options.get("mimetype") {
+                None => None,
+                Some(JsonValue::String(mimetype)) => Some(mimetype.as_str().into()),
+                Some(value) => bail!("`mimetype` must be a string, but is `{value}`"),
+            };
+            NativeLoader::Url {
+                mimetype,
+                es_module: bool_option(options, "esModule", true)?,
+            }
+        }
+        "@svgr/webpack" => {
+            check_option_keys(options, &["svgo"])?;
+            NativeLoader::Svgr {
+                svgo: bool_option(options, "svgo", true)?,
+            }
+        }
+        "yaml-loader" => {
+            check_option_keys(options, &["asJSON"])?;
+            NativeLoader::Yaml
+        }
+        _ => unreachable!("{loader} is not a native loader"),
+    })
+}
+
+fn match_native_loader(item: &WebpackLoaderItem) -> NativeLoaderMatch {
+    let Some(name) = loader_package_name(&item.loader) else {
+        return NativeLoaderMatch::Unknown;
+    };
+    let Some(loader) = ["raw-loader", "url-loader", "@svgr/webpack", "yaml-loader"]
+        .into_iter()
+        .find(|loader| *loader == name.split('?').next().unwrap_or(name))
+    else {
+        return NativeLoaderMatch::Unknown;
+    };
+    if item.loader.contains('?') {
+        return NativeLoaderMatch::Unsupported {
+            loader,
+            reason: "options passed as a query string are not supported".to_string(),
+        };
+    }
+    match native_loader_from_options(loader, &item.options) {
+        Ok(native_loader) => NativeLoaderMatch::Supported(native_loader),
+        Err(err) => NativeLoaderMatch::Unsupported {
+            loader,
+            reason: err.to_string(),
+        },
+    }
+}
+
+/// Returns an in-process [SourceTransform] replacing `loaders`, if there is one. Only a single
+/// loader can be replaced, as each of them turns a file into a JavaScript module.
+///
+/// When the loader has an in-process implementation that doesn't support the configured options,
+/// a [NativeLoaderFallbackIssue] is emitted and `None` is returned, so the loader runs in Node.js.
+pub async fn native_loaders_transform(
+    loaders: &[WebpackLoaderItem],
+    rename_as: Option<RcStr>,
+    project_path: &FileSystemPath,
+) -> Result<Option<ResolvedVc<Box<dyn SourceTransform>>>> {
+    let [item] = loaders else {
+        return Ok(None);
+    };
+    Ok(match match_native_loader(item) {
+        NativeLoaderMatch::Unknown => None,
+        NativeLoaderMatch::Supported(loader) => Some(ResolvedVc::upcast(
+            NativeLoaderTransform::new(loader, rename_as)
+                .to_resolved()
+                .await?,
+        )),
+        NativeLoaderMatch::Unsupported { loader, reason } => {
+            NativeLoaderFallbackIssue {
+                file_path: project_path.clone(),
+                loader: loader.into(),
+                reason: reason.into(),
+            }
+            .resolved_cell()
+            .emit();
+            None
+        }
+    })
+}
+
+#[turbo_tasks::value]
+pub struct NativeLoaderTransform {
+    loader: NativeLoader,
+    rename_as: Option<RcStr>,
+}
+
+#[turbo_tasks::value_impl]
+impl NativeLoaderTransform {
+    #[turbo_tasks::function]
+    pub fn new(loader: NativeLoader, rename_as: Option<RcStr>) -> Vc<Self> {
+        NativeLoaderTransform { loader, rename_as }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for NativeLoaderTransform {
+    #[turbo_tasks::function]
+    fn transform(
+        self: ResolvedVc<Self>,
+        source: ResolvedVc<Box<dyn Source>>,
+    ) -> Vc<Box<dyn Source>> {
+        Vc::upcast(
+            NativeLoaderProcessedSource {
+                transform: self,
+                source,
+            }
+            .cell(),
+        )
+    }
+}
+
+#[turbo_tasks::value]
+struct NativeLoaderProcessedSource {
+    transform: ResolvedVc<NativeLoaderTransform>,
+    source: ResolvedVc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Source for NativeLoaderProcessedSource {
+    /// Like for loaders running in Node.js, the ident is renamed according to the rule. Without a
+    /// rename it becomes a JavaScript file, as that's what all native loaders generate.
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<Vc<AssetIdent>> {
+        let rename_as = self
+            .transform
+            .await?
+            .rename_as
+            .clone()
+            .unwrap_or_else(|| rcstr!("*.js"));
+        Ok(self.source.ident().rename_as(rename_as))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for NativeLoaderProcessedSource {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let loader = &self.transform.await?.loader;
+        let content = match loader {
+            NativeLoader::Svgr { svgo: true } => {
+                optimize_svg(*self.source, self.source.content().file_content())
+            }
+            _ => self.source.content().file_content(),
+        };
+        let FileContent::Content(file) = &*content.await? else {
+            return Ok(AssetContent::file(FileContent::NotFound.cell()));
+        };
+        let code = match loader {
+            NativeLoader::Raw { es_module } => {
+                let text = file
+                    .content()
+                    .to_str()
+                    .context("raw-loader can only load UTF-8 text files")?;
+                default_export(&serde_json::to_string(&text)?, *es_module)
+            }
+            NativeLoader::Url {
+                mimetype,
+                es_module,
+            } => {
+                let mimetype = match mimetype {
+                    Some(mimetype) => mimetype.to_string(),
+                    None => match file.content_type() {
+                        Some(mimetype) => mimetype.to_string(),
+                        None => {
+                            mimetype_from_extension(self.source.ident().path().await?.extension())
+                                .to_string()
+                        }
+                    },
+                };
+                let url = format!(
+                    "data:{mimetype};base64,{}",
+                    BASE64.encode(&file.content().to_bytes())
+                );
+                default_export(&serde_json::to_string(&url)?, *es_module)
+            }
+            NativeLoader::Svgr { .. } => svg_component_module(&file.content().to_str()?)?,
+            NativeLoader::Yaml => {
+                let document: JsonValue = serde_yaml_ng::from_str(&file.content().to_str()?)
+                    .context("Unable to parse YAML")?;
+                default_export(&serde_json::to_string(&document)?, true)
+            }
+        };
+        Ok(AssetContent::file(
+            FileContent::Content(File::from(code)).cell(),
+        ))
+    }
+}
+
+fn default_export(value: &str, es_module: bool) -> String {
+    if es_module {
+        format!("export default {value};\n")
+    } else {
+        format!("module.exports = {value};\n")
+    }
+}
+
+/// The mime type `url-loader` would use for files of common types.
+fn mimetype_from_extension(extension: &str) -> &'static str {
+    match extension.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "eot" => "application/vnd.ms-fontobject",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "txt" => "text/plain",
+        "css" => "text/css",
+        "html" => "text/html",
+        "json" => "application/json",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Emitted when a webpack loader with an in-process implementation has to run in Node.js
+/// because of its options.
+#[turbo_tasks::value(shared)]
+pub struct NativeLoaderFallbackIssue {
+    pub file_path: FileSystemPath,
+    pub loader: RcStr,
+    pub reason: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for NativeLoaderFallbackIssue {
+    fn severity(&self) -> IssueSeverity {
+        IssueSeverity::Info
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Code(self.loader.clone()),
+            StyledString::Text(rcstr!(" runs in Node.js")),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Config.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path.clone().cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(
+                format!(
+                    "{} has a faster built-in implementation, which can't be used because {}. \
+                     Remove the unsupported options to use it.",
+                    self.loader, self.reason
+                )
+                .into(),
+            )
+            .resolved_cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn item(loader: &str, options: JsonValue) -> WebpackLoaderItem {
+        WebpackLoaderItem {
+            loader: loader.into(),
+            options: options.as_object().unwrap().clone(),
+        }
+    }
+
+    #[test]
+    fn test_loader_package_name() {
+        assert_eq!(loader_package_name("raw-loader"), Some("raw-loader"));
+        assert_eq!(
+            loader_package_name("raw-loader/dist/cjs.js"),
+            Some("raw-loader")
+        );
+        assert_eq!(loader_package_name("@svgr/webpack"), Some("@svgr/webpack"));
+        assert_eq!(
+            loader_package_name("/app/node_modules/@svgr/webpack/lib/index.js"),
+            Some("@svgr/webpack")
+        );
+        assert_eq!(
+            loader_package_name("/app/node_modules/.pnpm/node_modules/yaml-loader/index.js"),
+            Some("yaml-loader")
+        );
+        assert_eq!(loader_package_name("./loaders/raw-loader.js"), None);
+        assert_eq!(loader_package_name("/app/loaders/raw-loader.js"), None);
+    }
+
+    #[test]
+    fn test_match_supported_loaders() {
+        assert_eq!(
+            match_native_loader(&item("raw-loader", json!({}))),
+            NativeLoaderMatch::Supported(NativeLoader::Raw { es_module: true })
+        );
+        assert_eq!(
+            match_native_loader(&item("url-loader", json!({ "mimetype": "image/png" }))),
+            NativeLoaderMatch::Supported(NativeLoader::Url {
+                mimetype: Some(rcstr!("image/png")),
+                es_module: true,
+            })
+        );
+        assert_eq!(
+            match_native_loader(&item("@svgr/webpack", json!({ "svgo": false }))),
+            NativeLoaderMatch::Supported(NativeLoader::Svgr { svgo: false })
+        );
+        assert_eq!(
+            match_native_loader(&item("yaml-loader", json!({ "asJSON": true }))),
+            NativeLoaderMatch::Supported(NativeLoader::Yaml)
+        );
+        assert_eq!(
+            match_native_loader(&item("babel-loader", json!({}))),
+            NativeLoaderMatch::Unknown
+        );
+    }
+
+    #[test]
+    fn test_match_unsupported_options() {
+        let NativeLoaderMatch::Unsupported { loader, reason } =
+            match_native_loader(&item("url-loader", json!({ "limit": 8192 })))
+        else {
+            panic!("expected url-loader with a limit to be unsupported");
+        };
+        assert_eq!(loader, "url-loader");
+        assert!(reason.contains("limit"));
+
+        assert!(matches!(
+            match_native_loader(&item("@svgr/webpack", json!({ "icon": true }))),
+            NativeLoaderMatch::Unsupported { .. }
+        ));
+        assert!(matches!(
+            match_native_loader(&item("raw-loader", json!({ "esModule": "yes" }))),
+            NativeLoaderMatch::Unsupported { .. }
+        ));
+        assert!(matches!(
+            match_native_loader(&item("raw-loader?esModule=false", json!({}))),
+            NativeLoaderMatch::Unsupported { .. }
+        ));
+    }
+}